@@ -62,6 +62,52 @@ impl McpApps {
     }
 }
 
+/// MCP 工具过滤：按应用记录被禁用的工具名
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct McpToolFilters {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub claude: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codex: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gemini: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub opencode: Vec<String>,
+}
+
+impl McpToolFilters {
+    /// 获取指定应用禁用的工具
+    pub fn for_app(&self, app: &AppType) -> &[String] {
+        match app {
+            AppType::Claude => &self.claude,
+            AppType::Codex => &self.codex,
+            AppType::Gemini => &self.gemini,
+            AppType::OpenCode => &self.opencode,
+        }
+    }
+
+    /// 设置指定应用禁用的工具（去重并排序，保证写入 live 配置时顺序稳定）
+    pub fn set_for(&mut self, app: &AppType, mut tools: Vec<String>) {
+        tools.retain(|t| !t.trim().is_empty());
+        tools.sort();
+        tools.dedup();
+        match app {
+            AppType::Claude => self.claude = tools,
+            AppType::Codex => self.codex = tools,
+            AppType::Gemini => self.gemini = tools,
+            AppType::OpenCode => self.opencode = tools,
+        }
+    }
+
+    /// 检查是否没有任何过滤
+    pub fn is_empty(&self) -> bool {
+        self.claude.is_empty()
+            && self.codex.is_empty()
+            && self.gemini.is_empty()
+            && self.opencode.is_empty()
+    }
+}
+
 /// Skill 应用启用状态（标记 Skill 应用到哪些客户端）
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct SkillApps {
@@ -127,6 +173,46 @@ impl SkillApps {
     }
 }
 
+/// 共享提示词的应用启用状态（标记写入哪些应用的提示词文件）
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct PromptApps {
+    #[serde(default)]
+    pub claude: bool,
+    #[serde(default)]
+    pub codex: bool,
+    #[serde(default)]
+    pub gemini: bool,
+    #[serde(default)]
+    pub opencode: bool,
+}
+
+impl PromptApps {
+    /// 检查指定应用是否启用
+    pub fn is_enabled_for(&self, app: &AppType) -> bool {
+        match app {
+            AppType::Claude => self.claude,
+            AppType::Codex => self.codex,
+            AppType::Gemini => self.gemini,
+            AppType::OpenCode => self.opencode,
+        }
+    }
+
+    /// 设置指定应用的启用状态
+    pub fn set_enabled_for(&mut self, app: &AppType, enabled: bool) {
+        match app {
+            AppType::Claude => self.claude = enabled,
+            AppType::Codex => self.codex = enabled,
+            AppType::Gemini => self.gemini = enabled,
+            AppType::OpenCode => self.opencode = enabled,
+        }
+    }
+
+    /// 检查是否所有应用都未启用
+    pub fn is_empty(&self) -> bool {
+        !self.claude && !self.codex && !self.gemini && !self.opencode
+    }
+}
+
 /// 已安装的 Skill（v3.10.0+ 统一结构）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -156,6 +242,47 @@ pub struct InstalledSkill {
     pub apps: SkillApps,
     /// 安装时间（Unix 时间戳）
     pub installed_at: i64,
+    /// 安装时解析到的上游提交 SHA（用于检测更新）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
+    /// 安装内容的 SHA-256 哈希（用于检测本地修改）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// 安装前安全扫描的发现项（用户确认后安装）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scan_findings: Vec<SkillFinding>,
+    /// 项目作用域：非空时只同步到这些项目内的应用 Skills 目录（如 `.claude/skills`），
+    /// 不再写入用户级目录
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+}
+
+/// Skill 扫描发现项的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillFindingSeverity {
+    /// 提示信息（如包含脚本文件）
+    Info,
+    /// 需要用户确认的风险
+    Warning,
+    /// 阻止安装（如符号链接指向 Skill 目录之外）
+    Critical,
+}
+
+/// Skill 安装前扫描的单个发现项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillFinding {
+    pub severity: SkillFindingSeverity,
+    /// 机器可读的规则代码（如 "pipe-to-shell"）
+    pub code: String,
+    /// 相对于 Skill 目录的文件路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// 命中的行号（从 1 开始）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub message: String,
 }
 
 /// 未管理的 Skill（在应用目录中发现但未被 CC Switch 管理）
@@ -171,6 +298,9 @@ pub struct UnmanagedSkill {
     pub description: Option<String>,
     /// 在哪些应用目录中发现（如 ["claude", "codex"]）
     pub found_in: Vec<String>,
+    /// 在哪些项目目录中发现（仅存在于用户级目录时为空）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
 }
 
 /// MCP 服务器定义（v3.7.0 统一结构）
@@ -188,6 +318,19 @@ pub struct McpServer {
     pub docs: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+    /// 最近一次健康检查发现的工具列表
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<crate::mcp::McpToolInfo>,
+    /// 按应用禁用的工具
+    #[serde(
+        default,
+        rename = "disabledTools",
+        skip_serializing_if = "McpToolFilters::is_empty"
+    )]
+    pub disabled_tools: McpToolFilters,
+    /// 项目作用域：非空时仅写入这些项目目录的配置，而非用户级全局配置
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
 }
 
 /// MCP 配置：单客户端维度（v3.6.x 及以前，保留用于向后兼容）
@@ -673,6 +816,8 @@ impl MultiAppConfig {
             enabled: true, // 自动启用
             created_at: Some(timestamp),
             updated_at: Some(timestamp),
+            sort_index: None,
+            projects: Vec::new(),
         };
 
         // 插入到对应的应用配置中
@@ -786,6 +931,9 @@ impl MultiAppConfig {
                             homepage,
                             docs,
                             tags,
+                            tools: Vec::new(),
+                            disabled_tools: McpToolFilters::default(),
+                            projects: Vec::new(),
                         },
                     );
                 }
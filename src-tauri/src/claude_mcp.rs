@@ -365,14 +365,83 @@ pub fn validate_command_in_path(cmd: &str) -> Result<bool, AppError> {
     Ok(false)
 }
 
+type ServersMap = std::collections::HashMap<String, Value>;
+
 /// 读取 ~/.claude.json 中的 mcpServers 映射
 pub fn read_mcp_servers_map() -> Result<std::collections::HashMap<String, Value>, AppError> {
+    read_mcp_servers_map_from(&user_config_path())
+}
+
+/// 读取项目级 `.mcp.json` 中的 mcpServers 映射
+pub fn read_project_mcp_servers_map(
+    project_dir: &Path,
+) -> Result<std::collections::HashMap<String, Value>, AppError> {
+    read_mcp_servers_map_from(&project_dir.join(".mcp.json"))
+}
+
+/// 读取 ~/.claude.json 中按项目记录的 MCP 服务器（`projects[path].mcpServers`）
+///
+/// 仅返回包含 MCP 服务器的项目。
+pub fn read_local_project_mcp_servers() -> Result<Vec<(String, ServersMap)>, AppError> {
     let path = user_config_path();
     if !path.exists() {
-        return Ok(std::collections::HashMap::new());
+        return Ok(Vec::new());
+    }
+
+    let root = read_json_value(&path)?;
+    let Some(projects) = root.get("projects").and_then(|v| v.as_object()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut out: Vec<(String, ServersMap)> = projects
+        .iter()
+        .filter_map(|(dir, project)| {
+            let servers = project.get("mcpServers")?.as_object()?;
+            if servers.is_empty() {
+                return None;
+            }
+            let map = servers
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            Some((dir.clone(), map))
+        })
+        .collect();
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(out)
+}
+
+/// 列出 ~/.claude.json 中记录过的项目目录（仅返回仍存在的目录）
+pub fn list_known_project_dirs() -> Result<Vec<String>, AppError> {
+    let path = user_config_path();
+    if !path.exists() {
+        return Ok(Vec::new());
     }
 
     let root = read_json_value(&path)?;
+    let mut dirs: Vec<String> = root
+        .get("projects")
+        .and_then(|v| v.as_object())
+        .map(|projects| {
+            projects
+                .keys()
+                .filter(|dir| Path::new(dir).is_dir())
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn read_mcp_servers_map_from(
+    path: &Path,
+) -> Result<std::collections::HashMap<String, Value>, AppError> {
+    if !path.exists() {
+        return Ok(std::collections::HashMap::new());
+    }
+
+    let root = read_json_value(path)?;
     let servers = root
         .get("mcpServers")
         .and_then(|v| v.as_object())
@@ -388,18 +457,50 @@ pub fn set_mcp_servers_map(
     servers: &std::collections::HashMap<String, Value>,
 ) -> Result<(), AppError> {
     let path = user_config_path();
-    let mut root = if path.exists() {
-        read_json_value(&path)?
-    } else {
-        serde_json::json!({})
-    };
 
-    // 构建 mcpServers 对象：移除 UI 辅助字段（enabled/source），仅保留实际 MCP 规范
     // 检测目标路径是否为 WSL，若是则跳过 cmd /c 包装
     let is_wsl_target = is_wsl_path(&path);
     if is_wsl_target {
         log::info!("检测到 WSL 路径，跳过 cmd /c 包装: {}", path.display());
     }
+    write_mcp_servers_map(&path, servers, !is_wsl_target)
+}
+
+/// 将 MCP 服务器映射写入项目级 `.mcp.json`
+///
+/// `.mcp.json` 通常随仓库共享，因此不做 Windows cmd /c 包装；映射为空时删除该文件。
+pub fn set_project_mcp_servers_map(
+    project_dir: &Path,
+    servers: &std::collections::HashMap<String, Value>,
+) -> Result<(), AppError> {
+    let path = project_dir.join(".mcp.json");
+    if servers.is_empty() {
+        let root = read_json_value(&path)?;
+        let only_servers = root
+            .as_object()
+            .is_some_and(|obj| obj.keys().all(|k| k == "mcpServers"));
+        if only_servers {
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| AppError::io(&path, e))?;
+            }
+            return Ok(());
+        }
+    }
+    write_mcp_servers_map(&path, servers, false)
+}
+
+fn write_mcp_servers_map(
+    path: &Path,
+    servers: &std::collections::HashMap<String, Value>,
+    wrap_windows_commands: bool,
+) -> Result<(), AppError> {
+    let mut root = if path.exists() {
+        read_json_value(path)?
+    } else {
+        serde_json::json!({})
+    };
+
+    // 构建 mcpServers 对象：移除 UI 辅助字段（enabled/source），仅保留实际 MCP 规范
     let mut out: Map<String, Value> = Map::new();
     for (id, spec) in servers.iter() {
         let mut obj = if let Some(map) = spec.as_object() {
@@ -427,7 +528,7 @@ pub fn set_mcp_servers_map(
         obj.remove("docs");
 
         // Windows 平台自动包装 npx/npm 等命令为 cmd /c 格式（WSL 路径除外）
-        if !is_wsl_target {
+        if wrap_windows_commands {
             wrap_command_for_windows(&mut obj);
         }
 
@@ -437,11 +538,11 @@ pub fn set_mcp_servers_map(
     {
         let obj = root
             .as_object_mut()
-            .ok_or_else(|| AppError::Config("~/.claude.json 根必须是对象".into()))?;
+            .ok_or_else(|| AppError::Config(format!("{} 根必须是对象", path.display())))?;
         obj.insert("mcpServers".into(), Value::Object(out));
     }
 
-    write_json_value(&path, &root)?;
+    write_json_value(path, &root)?;
     Ok(())
 }
 
@@ -6,9 +6,10 @@ use std::collections::HashMap;
 use serde::Serialize;
 use tauri::State;
 
-use crate::app_config::AppType;
+use crate::app_config::{AppType, McpApps};
 use crate::claude_mcp;
-use crate::services::McpService;
+use crate::mcp::{McpCatalogEntry, McpCatalogSource};
+use crate::services::{McpCatalogService, McpService};
 use crate::store::AppState;
 
 /// 获取 Claude MCP 状态
@@ -114,6 +115,9 @@ pub async fn upsert_mcp_server_in_config(
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         }
     };
 
@@ -204,3 +208,141 @@ pub async fn import_mcp_from_apps(state: State<'_, AppState>) -> Result<usize, S
     total += McpService::import_from_opencode(&state).unwrap_or(0);
     Ok(total)
 }
+
+/// 从项目配置导入 MCP 服务器（未指定目录时扫描已知项目）
+#[tauri::command]
+pub async fn import_mcp_from_projects(
+    state: State<'_, AppState>,
+    project_dirs: Option<Vec<String>>,
+) -> Result<usize, String> {
+    McpService::import_from_projects(&state, &project_dirs.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// 获取可作为 MCP 项目作用域的已知项目目录
+#[tauri::command]
+pub async fn get_mcp_known_projects() -> Result<Vec<String>, String> {
+    McpService::list_known_projects().map_err(|e| e.to_string())
+}
+
+/// 测试 MCP 服务器：实际启动/连接并完成 initialize + tools/list 握手
+///
+/// 传入 `id` 时测试已保存的服务器，成功后缓存发现的工具列表；
+/// 否则测试 `spec`（用于编辑表单中的未保存配置）。
+#[tauri::command]
+pub async fn test_mcp_server(
+    state: State<'_, AppState>,
+    id: Option<String>,
+    spec: Option<serde_json::Value>,
+    timeout_secs: Option<u64>,
+) -> Result<crate::mcp::McpHealthReport, String> {
+    let (spec, saved_id) = match (spec, id) {
+        (Some(spec), _) => (spec, None),
+        (None, Some(id)) => {
+            let spec = McpService::get_all_servers(&state)
+                .map_err(|e| e.to_string())?
+                .get(&id)
+                .map(|s| s.server.clone())
+                .ok_or_else(|| format!("MCP 服务器不存在: {id}"))?;
+            (spec, Some(id))
+        }
+        (None, None) => return Err("缺少 id 或 spec 参数".to_string()),
+    };
+
+    let timeout = std::time::Duration::from_secs(
+        timeout_secs
+            .unwrap_or(crate::mcp::DEFAULT_TIMEOUT_SECS)
+            .clamp(1, 120),
+    );
+    // 与写入 live 配置一致：启动前解析 env/headers 中的 ${secret:name}
+    let spec = crate::secret_store::resolve_value(&spec).map_err(|e| e.to_string())?;
+    let report = crate::mcp::check_server(&spec, timeout).await;
+
+    if let Some(id) = saved_id.filter(|_| report.ok) {
+        McpService::save_discovered_tools(&state, &id, report.tools.clone())
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(report)
+}
+
+/// 设置 MCP 服务器在指定应用中禁用的工具
+#[tauri::command]
+pub async fn set_mcp_disabled_tools(
+    state: State<'_, AppState>,
+    server_id: String,
+    app: String,
+    tools: Vec<String>,
+) -> Result<(), String> {
+    let app_ty = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    McpService::set_disabled_tools(&state, &server_id, app_ty, tools).map_err(|e| e.to_string())
+}
+
+// ========== MCP 目录（Catalog）==========
+
+/// 获取 MCP 目录来源列表
+#[tauri::command]
+pub async fn get_mcp_catalog_sources(
+    state: State<'_, AppState>,
+) -> Result<Vec<McpCatalogSource>, String> {
+    state
+        .db
+        .get_mcp_catalog_sources()
+        .map_err(|e| e.to_string())
+}
+
+/// 添加或更新 MCP 目录来源
+#[tauri::command]
+pub async fn save_mcp_catalog_source(
+    state: State<'_, AppState>,
+    source: McpCatalogSource,
+) -> Result<bool, String> {
+    let source = McpCatalogService::normalize_source(source).map_err(|e| e.to_string())?;
+    state
+        .db
+        .save_mcp_catalog_source(&source)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 删除 MCP 目录来源
+#[tauri::command]
+pub async fn remove_mcp_catalog_source(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<bool, String> {
+    state
+        .db
+        .delete_mcp_catalog_source(&id)
+        .map_err(|e| e.to_string())
+}
+
+/// 搜索 MCP 目录（关键字匹配名称/描述，标签需全部命中）
+#[tauri::command]
+pub async fn search_mcp_catalog(
+    state: State<'_, AppState>,
+    query: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<Vec<McpCatalogEntry>, String> {
+    let sources = state
+        .db
+        .get_mcp_catalog_sources()
+        .map_err(|e| e.to_string())?;
+    McpCatalogService::search(sources, query, tags.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 从目录安装 MCP 服务器
+///
+/// `inputs` 为条目声明的输入值（敏感值可填写 `${secret:name}` 引用）；
+/// `id` 为空时使用条目建议的 id。
+#[tauri::command]
+pub async fn install_mcp_from_catalog(
+    state: State<'_, AppState>,
+    entry: McpCatalogEntry,
+    inputs: HashMap<String, String>,
+    apps: McpApps,
+    id: Option<String>,
+) -> Result<McpServer, String> {
+    McpCatalogService::install(&state, &entry, &inputs, apps, id).map_err(|e| e.to_string())
+}
@@ -13,6 +13,8 @@ mod plugin;
 mod prompt;
 mod provider;
 mod proxy;
+mod secret;
+mod session_manager;
 mod settings;
 pub mod skill;
 mod stream_check;
@@ -31,6 +33,8 @@ pub use plugin::*;
 pub use prompt::*;
 pub use provider::*;
 pub use proxy::*;
+pub use secret::*;
+pub use session_manager::*;
 pub use settings::*;
 pub use skill::*;
 pub use stream_check::*;
@@ -1,10 +1,13 @@
 use indexmap::IndexMap;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 use tauri::State;
 
 use crate::app_config::AppType;
-use crate::prompt::Prompt;
+use crate::prompt::{
+    Prompt, PromptDedupGroup, PromptFileDrift, PromptPreview, PromptRevision, SharedPrompt,
+};
 use crate::services::PromptService;
 use crate::store::AppState;
 
@@ -23,7 +26,7 @@ pub async fn upsert_prompt(
     id: String,
     prompt: Prompt,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<Vec<String>, String> {
     let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
     PromptService::upsert_prompt(&state, app_type, &id, prompt).map_err(|e| e.to_string())
 }
@@ -43,11 +46,61 @@ pub async fn enable_prompt(
     app: String,
     id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<Vec<String>, String> {
     let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
     PromptService::enable_prompt(&state, app_type, &id).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn disable_prompt(
+    app: String,
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    PromptService::disable_prompt(&state, app_type, &id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reorder_prompts(
+    app: String,
+    ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    PromptService::reorder_prompts(&state, app_type, &ids).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_prompt_projects(
+    app: String,
+    id: String,
+    projects: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Prompt, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    PromptService::set_projects(&state, app_type, &id, projects).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn import_prompts_from_projects(
+    app: String,
+    project_dirs: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    PromptService::import_from_projects(&state, app_type, &project_dirs).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn check_prompt_drift(
+    app: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<PromptFileDrift>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    PromptService::check_drift(&state, app_type).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn import_prompt_from_file(
     app: String,
@@ -62,3 +115,104 @@ pub async fn get_current_prompt_file_content(app: String) -> Result<Option<Strin
     let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
     PromptService::get_current_file_content(app_type).map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn preview_prompt(
+    app: String,
+    id: Option<String>,
+    content: Option<String>,
+    project: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<PromptPreview, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    PromptService::preview(&state, app_type, id.as_deref(), content, project.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_prompt_variables(
+    state: State<'_, AppState>,
+) -> Result<BTreeMap<String, String>, String> {
+    PromptService::get_variables(&state).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_prompt_variables(
+    variables: BTreeMap<String, String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    PromptService::set_variables(&state, variables).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_prompt_revisions(
+    app: String,
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<PromptRevision>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    PromptService::list_revisions(&state, app_type, &id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn diff_prompt_revisions(
+    from: i64,
+    to: i64,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    PromptService::diff_revisions(&state, from, to).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_prompt_revision(
+    app: String,
+    revision_id: i64,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    PromptService::restore_revision(&state, app_type, revision_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_shared_prompts(
+    state: State<'_, AppState>,
+) -> Result<IndexMap<String, SharedPrompt>, String> {
+    PromptService::get_shared_prompts(&state).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn upsert_shared_prompt(
+    prompt: SharedPrompt,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    PromptService::upsert_shared_prompt(&state, prompt).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_shared_prompt(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    PromptService::delete_shared_prompt(&state, &id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn toggle_shared_prompt_app(
+    id: String,
+    app: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    PromptService::toggle_shared_prompt_app(&state, &id, app_type, enabled)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn preview_prompt_dedup(
+    state: State<'_, AppState>,
+) -> Result<Vec<PromptDedupGroup>, String> {
+    PromptService::preview_dedup(&state).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn dedup_prompts_into_shared(state: State<'_, AppState>) -> Result<usize, String> {
+    PromptService::dedup_into_shared(&state).map_err(|e| e.to_string())
+}
@@ -4,6 +4,7 @@ use tauri::State;
 use crate::app_config::AppType;
 use crate::error::AppError;
 use crate::provider::Provider;
+use crate::proxy::log_codes::usg as log_usg;
 use crate::services::{EndpointLatency, ProviderService, ProviderSortUpdate, SpeedtestService};
 use crate::store::AppState;
 use std::str::FromStr;
@@ -119,14 +120,30 @@ pub fn import_default_config(state: State<'_, AppState>, app: String) -> Result<
 #[allow(non_snake_case)]
 #[tauri::command]
 pub async fn queryProviderUsage(
+    app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     #[allow(non_snake_case)] providerId: String, // 使用 camelCase 匹配前端
     app: String,
 ) -> Result<crate::provider::UsageResult, String> {
     let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
-    ProviderService::query_usage(state.inner(), app_type, &providerId)
+    let result = ProviderService::query_usage(state.inner(), app_type.clone(), &providerId)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+
+    if let Err(e) = crate::services::usage_monitor::UsageMonitorService::record_result(
+        &state.db,
+        Some(&app_handle),
+        app_type.as_str(),
+        &providerId,
+        &result,
+    ) {
+        log::warn!(
+            "[{}] 记录用量查询结果失败: {e}",
+            log_usg::USAGE_QUERY_FAILED
+        );
+    }
+
+    Ok(result)
 }
 
 /// 测试用量脚本（使用当前编辑器中的脚本，不保存）
@@ -162,6 +179,12 @@ pub async fn testUsageScript(
     .map_err(|e| e.to_string())
 }
 
+/// 获取内置用量查询模板列表
+#[tauri::command]
+pub fn get_usage_templates() -> Vec<crate::services::provider::UsageTemplateInfo> {
+    crate::services::provider::list_usage_templates()
+}
+
 /// 读取当前生效的配置内容
 #[tauri::command]
 pub fn read_live_provider_settings(app: String) -> Result<serde_json::Value, String> {
@@ -0,0 +1,57 @@
+use crate::secret_store::{SecretInfo, SecretStore, SecretStoreStatus};
+
+/// 获取密钥库状态（不含任何密钥明文）
+#[tauri::command]
+pub fn get_secret_store_status() -> Result<SecretStoreStatus, String> {
+    SecretStore::open_default()
+        .status()
+        .map_err(|e| e.to_string())
+}
+
+/// 初始化密钥库：提供口令时使用口令模式，否则使用系统密钥环
+#[tauri::command]
+pub fn init_secret_store(passphrase: Option<String>) -> Result<(), String> {
+    SecretStore::open_default()
+        .init(passphrase.as_deref().filter(|p| !p.is_empty()))
+        .map_err(|e| e.to_string())
+}
+
+/// 使用口令解锁密钥库
+#[tauri::command]
+pub fn unlock_secret_store(passphrase: String) -> Result<(), String> {
+    SecretStore::open_default()
+        .unlock(&passphrase)
+        .map_err(|e| e.to_string())
+}
+
+/// 锁定密钥库（清除内存中的主密钥）
+#[tauri::command]
+pub fn lock_secret_store() -> Result<(), String> {
+    SecretStore::open_default()
+        .lock()
+        .map_err(|e| e.to_string())
+}
+
+/// 列出密钥名称
+#[tauri::command]
+pub fn list_secrets() -> Result<Vec<SecretInfo>, String> {
+    SecretStore::open_default()
+        .list()
+        .map_err(|e| e.to_string())
+}
+
+/// 新增或更新密钥
+#[tauri::command]
+pub fn set_secret(name: String, value: String) -> Result<(), String> {
+    SecretStore::open_default()
+        .set(name.trim(), &value)
+        .map_err(|e| e.to_string())
+}
+
+/// 删除密钥
+#[tauri::command]
+pub fn delete_secret(name: String) -> Result<bool, String> {
+    SecretStore::open_default()
+        .delete(&name)
+        .map_err(|e| e.to_string())
+}
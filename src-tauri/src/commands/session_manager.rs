@@ -1,12 +1,36 @@
 #![allow(non_snake_case)]
 
+use std::collections::HashMap;
+
+use tauri::State;
+
 use crate::session_manager;
+use crate::store::AppState;
 
 #[tauri::command]
-pub async fn list_sessions() -> Result<Vec<session_manager::SessionMeta>, String> {
-    let sessions = tauri::async_runtime::spawn_blocking(session_manager::scan_sessions)
+pub async fn list_sessions(
+    state: State<'_, AppState>,
+) -> Result<Vec<session_manager::SessionMeta>, String> {
+    let mut sessions = tauri::async_runtime::spawn_blocking(session_manager::scan_sessions)
         .await
         .map_err(|e| format!("Failed to scan sessions: {e}"))?;
+
+    // 附加代理记录的会话成本（统计失败不影响列表展示）
+    match state.db.get_session_stats(None, None) {
+        Ok(stats) => {
+            let stats = crate::services::usage_stats::merge_stats_by_local_session(&stats);
+            let by_session: HashMap<&str, _> =
+                stats.iter().map(|s| (s.session_id.as_str(), s)).collect();
+            for session in &mut sessions {
+                if let Some(stat) = by_session.get(session.session_id.as_str()) {
+                    session.total_cost = Some(stat.total_cost.clone());
+                    session.request_count = Some(stat.request_count);
+                }
+            }
+        }
+        Err(e) => log::warn!("Failed to load session usage stats: {e}"),
+    }
+
     Ok(sessions)
 }
 
@@ -24,6 +48,199 @@ pub async fn get_session_messages(
     .map_err(|e| format!("Failed to load session messages: {e}"))?
 }
 
+/// 全文检索会话消息
+///
+/// `refresh` 为 true 时检索前先增量更新索引；否则距上次更新超过间隔时才增量更新。
+#[tauri::command]
+pub async fn search_sessions(
+    state: State<'_, AppState>,
+    query: session_manager::search::SessionSearchQuery,
+    refresh: Option<bool>,
+) -> Result<Vec<session_manager::search::SessionSearchResult>, String> {
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if refresh.unwrap_or(false) {
+            session_manager::search::refresh_index(&db, false)?;
+        } else {
+            session_manager::search::ensure_index(&db)?;
+        }
+        session_manager::search::search(&db, &query)
+    })
+    .await
+    .map_err(|e| format!("Failed to search sessions: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+/// 更新会话检索索引，`rebuild` 为 true 时清空后全量重建
+#[tauri::command]
+pub async fn refresh_session_index(
+    state: State<'_, AppState>,
+    rebuild: Option<bool>,
+) -> Result<session_manager::search::SessionIndexStats, String> {
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        session_manager::search::refresh_index(&db, rebuild.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Failed to refresh session index: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+/// 将会话接力到另一应用（claude / codex）并在原项目目录中启动
+#[tauri::command]
+pub async fn handoff_session(
+    providerId: String,
+    sourcePath: String,
+    targetApp: String,
+    mode: Option<session_manager::handoff::HandoffMode>,
+    launch: Option<bool>,
+) -> Result<session_manager::handoff::HandoffResult, String> {
+    let target = preferred_session_terminal();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = session_manager::handoff::handoff_session(
+            &providerId,
+            &sourcePath,
+            &targetApp,
+            mode.unwrap_or_default(),
+        )?;
+        if launch.unwrap_or(true) {
+            session_manager::terminal::launch_terminal(
+                &target,
+                &result.command,
+                result.cwd.as_deref(),
+                None,
+            )?;
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Failed to hand off session: {e}"))?
+}
+
+/// 导出会话为 Markdown / HTML / 分享包并写入指定文件
+#[tauri::command]
+pub async fn export_session(
+    providerId: String,
+    sourcePath: String,
+    format: session_manager::export::SessionExportFormat,
+    options: Option<session_manager::export::SessionExportOptions>,
+    filePath: String,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let messages = session_manager::load_transcript(&providerId, &sourcePath)?;
+        let meta = session_manager::find_session(&providerId, &sourcePath).unwrap_or_else(|| {
+            session_manager::SessionMeta {
+                provider_id: providerId.clone(),
+                session_id: std::path::Path::new(&sourcePath)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                title: None,
+                summary: None,
+                project_dir: None,
+                created_at: None,
+                last_active_at: None,
+                source_path: Some(sourcePath.clone()),
+                resume_command: None,
+                total_cost: None,
+                request_count: None,
+            }
+        });
+        let content = session_manager::export::export_session(
+            &meta,
+            &messages,
+            format,
+            &options.unwrap_or_default(),
+        )?;
+        crate::config::atomic_write(std::path::Path::new(&filePath), &content)
+            .map_err(|e| e.to_string())?;
+        Ok(filePath)
+    })
+    .await
+    .map_err(|e| format!("Failed to export session: {e}"))?
+}
+
+/// 统计会话的对话轮数、工具调用、失败次数、涉及文件、时长与模型
+#[tauri::command]
+pub async fn get_session_analytics(
+    query: Option<session_manager::analytics::SessionAnalyticsQuery>,
+) -> Result<session_manager::analytics::SessionAnalyticsReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        session_manager::analytics::analyze_sessions(&query.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Failed to analyze sessions: {e}"))
+}
+
+/// 统计各应用、各项目的会话磁盘占用
+#[tauri::command]
+pub async fn get_session_disk_usage(
+) -> Result<session_manager::housekeeping::SessionDiskUsage, String> {
+    tauri::async_runtime::spawn_blocking(session_manager::housekeeping::disk_usage)
+        .await
+        .map_err(|e| format!("Failed to compute session disk usage: {e}"))
+}
+
+/// 归档超过指定天数未活动的会话
+#[tauri::command]
+pub async fn archive_sessions(
+    olderThanDays: u32,
+    providerId: Option<String>,
+) -> Result<session_manager::housekeeping::HousekeepingResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        session_manager::housekeeping::archive_sessions(olderThanDays, providerId.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Failed to archive sessions: {e}"))?
+}
+
+/// 删除选中的会话（移入回收站）
+#[tauri::command]
+pub async fn delete_sessions(
+    sessions: Vec<session_manager::housekeeping::SessionRef>,
+) -> Result<session_manager::housekeeping::HousekeepingResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        session_manager::housekeeping::delete_sessions(&sessions)
+    })
+    .await
+    .map_err(|e| format!("Failed to delete sessions: {e}"))?
+}
+
+/// 列出归档或回收站中的会话
+#[tauri::command]
+pub async fn list_stored_sessions(
+    store: session_manager::housekeeping::SessionStore,
+) -> Result<Vec<session_manager::housekeeping::StoredSessionEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        session_manager::housekeeping::list_stored_sessions(store)
+    })
+    .await
+    .map_err(|e| format!("Failed to list stored sessions: {e}"))
+}
+
+/// 从归档或回收站恢复会话
+#[tauri::command]
+pub async fn restore_stored_session(
+    bundlePath: String,
+) -> Result<session_manager::housekeeping::StoredSession, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        session_manager::housekeeping::restore_session(&bundlePath)
+    })
+    .await
+    .map_err(|e| format!("Failed to restore session: {e}"))?
+}
+
+/// 清空会话回收站
+#[tauri::command]
+pub async fn empty_session_trash(olderThanDays: Option<u32>) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        session_manager::housekeeping::empty_trash(olderThanDays)
+    })
+    .await
+    .map_err(|e| format!("Failed to empty session trash: {e}"))?
+}
+
 #[tauri::command]
 pub async fn launch_session_terminal(
     command: String,
@@ -34,15 +251,7 @@ pub async fn launch_session_terminal(
     let cwd = cwd.clone();
     let custom_config = custom_config.clone();
 
-    // Read preferred terminal from global settings
-    let preferred = crate::settings::get_preferred_terminal();
-    // Map global setting terminal names to session terminal names
-    // Global uses "iterm2", session terminal uses "iterm"
-    let target = match preferred.as_deref() {
-        Some("iterm2") => "iterm".to_string(),
-        Some(t) => t.to_string(),
-        None => "terminal".to_string(), // Default to Terminal.app on macOS
-    };
+    let target = preferred_session_terminal();
 
     tauri::async_runtime::spawn_blocking(move || {
         session_manager::terminal::launch_terminal(
@@ -57,3 +266,13 @@ pub async fn launch_session_terminal(
 
     Ok(true)
 }
+
+/// 读取全局设置中的首选终端，并映射为会话终端名称
+fn preferred_session_terminal() -> String {
+    // Global uses "iterm2", session terminal uses "iterm"
+    match crate::settings::get_preferred_terminal().as_deref() {
+        Some("iterm2") => "iterm".to_string(),
+        Some(t) => t.to_string(),
+        None => "terminal".to_string(), // Default to Terminal.app on macOS
+    }
+}
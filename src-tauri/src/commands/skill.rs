@@ -6,7 +6,9 @@
 
 use crate::app_config::{AppType, InstalledSkill, UnmanagedSkill};
 use crate::error::format_skill_error;
-use crate::services::skill::{DiscoverableSkill, Skill, SkillRepo, SkillService};
+use crate::secret_store::SecretStore;
+use crate::services::skill::{DiscoverableSkill, Skill, SkillRepo, SkillService, SkillUpdateInfo};
+use crate::services::skill_scan::SkillScanReport;
 use crate::store::AppState;
 use std::sync::Arc;
 use tauri::State;
@@ -38,10 +40,12 @@ pub fn get_installed_skills(app_state: State<'_, AppState>) -> Result<Vec<Instal
 /// 参数：
 /// - skill: 从发现列表获取的技能信息
 /// - current_app: 当前选中的应用，安装后默认启用该应用
+/// - accept_risks: 用户已查看扫描报告并确认风险项
 #[tauri::command]
 pub async fn install_skill_unified(
     skill: DiscoverableSkill,
     current_app: String,
+    accept_risks: Option<bool>,
     service: State<'_, SkillServiceState>,
     app_state: State<'_, AppState>,
 ) -> Result<InstalledSkill, String> {
@@ -49,7 +53,26 @@ pub async fn install_skill_unified(
 
     service
         .0
-        .install(&app_state.db, &skill, &app_type)
+        .install(
+            &app_state.db,
+            &skill,
+            &app_type,
+            accept_risks.unwrap_or(false),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 安装前扫描 Skill（SKILL.md 校验、脚本与可疑模式、超大文件、越界符号链接）
+#[tauri::command]
+pub async fn scan_skill(
+    skill: DiscoverableSkill,
+    service: State<'_, SkillServiceState>,
+    app_state: State<'_, AppState>,
+) -> Result<SkillScanReport, String> {
+    service
+        .0
+        .scan_available(&app_state.db, &skill)
         .await
         .map_err(|e| e.to_string())
 }
@@ -61,6 +84,51 @@ pub fn uninstall_skill_unified(id: String, app_state: State<'_, AppState>) -> Re
     Ok(true)
 }
 
+/// 检查已安装 Skills 的上游更新（对比安装时的提交与分支最新提交）
+#[tauri::command]
+pub async fn check_skill_updates(
+    service: State<'_, SkillServiceState>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<SkillUpdateInfo>, String> {
+    service
+        .0
+        .check_updates(&app_state.db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 将 Skill 更新到上游最新提交（保留旧版本用于回滚）
+///
+/// 本地修改过的 Skill 需要传入 `force` 才会被覆盖。
+#[tauri::command]
+pub async fn update_skill(
+    id: String,
+    accept_risks: Option<bool>,
+    force: Option<bool>,
+    service: State<'_, SkillServiceState>,
+    app_state: State<'_, AppState>,
+) -> Result<InstalledSkill, String> {
+    service
+        .0
+        .update(
+            &app_state.db,
+            &id,
+            accept_risks.unwrap_or(false),
+            force.unwrap_or(false),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 回滚 Skill 到更新前的版本
+#[tauri::command]
+pub fn rollback_skill(
+    id: String,
+    app_state: State<'_, AppState>,
+) -> Result<InstalledSkill, String> {
+    SkillService::rollback(&app_state.db, &id).map_err(|e| e.to_string())
+}
+
 /// 切换 Skill 的应用启用状态
 #[tauri::command]
 pub fn toggle_skill_app(
@@ -74,6 +142,16 @@ pub fn toggle_skill_app(
     Ok(true)
 }
 
+/// 设置 Skill 的项目作用域（空列表表示用户级目录）
+#[tauri::command]
+pub fn set_skill_projects(
+    id: String,
+    projects: Vec<String>,
+    app_state: State<'_, AppState>,
+) -> Result<InstalledSkill, String> {
+    SkillService::set_projects(&app_state.db, &id, projects).map_err(|e| e.to_string())
+}
+
 /// 扫描未管理的 Skills
 #[tauri::command]
 pub fn scan_unmanaged_skills(
@@ -139,17 +217,28 @@ pub async fn get_skills_for_app(
 #[tauri::command]
 pub async fn install_skill(
     directory: String,
+    accept_risks: Option<bool>,
     service: State<'_, SkillServiceState>,
     app_state: State<'_, AppState>,
 ) -> Result<bool, String> {
-    install_skill_for_app("claude".to_string(), directory, service, app_state).await
+    install_skill_for_app(
+        "claude".to_string(),
+        directory,
+        accept_risks,
+        service,
+        app_state,
+    )
+    .await
 }
 
 /// 安装指定应用的技能（兼容旧 API）
+///
+/// `accept_risks` 表示用户已查看扫描报告并确认风险项
 #[tauri::command]
 pub async fn install_skill_for_app(
     app: String,
     directory: String,
+    accept_risks: Option<bool>,
     service: State<'_, SkillServiceState>,
     app_state: State<'_, AppState>,
 ) -> Result<bool, String> {
@@ -183,7 +272,12 @@ pub async fn install_skill_for_app(
 
     service
         .0
-        .install(&app_state.db, &skill, &app_type)
+        .install(
+            &app_state.db,
+            &skill,
+            &app_type,
+            accept_risks.unwrap_or(false),
+        )
         .await
         .map_err(|e| e.to_string())?;
 
@@ -220,19 +314,45 @@ pub fn uninstall_skill_for_app(
 
 // ========== 仓库管理命令 ==========
 
-/// 获取技能仓库列表
+/// 获取技能仓库列表（不返回访问令牌）
 #[tauri::command]
 pub fn get_skill_repos(app_state: State<'_, AppState>) -> Result<Vec<SkillRepo>, String> {
-    app_state.db.get_skill_repos().map_err(|e| e.to_string())
+    let repos = app_state.db.get_skill_repos().map_err(|e| e.to_string())?;
+    Ok(repos
+        .into_iter()
+        .map(|repo| SkillRepo {
+            token: None,
+            ..repo
+        })
+        .collect())
 }
 
 /// 添加技能仓库
+///
+/// 访问令牌存入密钥库，数据库中只保存引用；未填写令牌时保留已保存的令牌。
 #[tauri::command]
-pub fn add_skill_repo(repo: SkillRepo, app_state: State<'_, AppState>) -> Result<bool, String> {
+pub fn add_skill_repo(
+    repo: SkillRepo,
+    app: tauri::AppHandle,
+    app_state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let mut repo = SkillService::normalize_repo(repo).map_err(|e| e.to_string())?;
+    if repo.token.is_none() {
+        repo.token = app_state
+            .db
+            .get_skill_repos()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|r| r.owner == repo.owner && r.name == repo.name)
+            .and_then(|r| r.token);
+    }
+    SkillService::store_repo_token(&mut repo, &SecretStore::open_default())
+        .map_err(|e| e.to_string())?;
     app_state
         .db
         .save_skill_repo(&repo)
         .map_err(|e| e.to_string())?;
+    SkillService::start_local_watcher(app);
     Ok(true)
 }
 
@@ -241,12 +361,25 @@ pub fn add_skill_repo(repo: SkillRepo, app_state: State<'_, AppState>) -> Result
 pub fn remove_skill_repo(
     owner: String,
     name: String,
+    app: tauri::AppHandle,
     app_state: State<'_, AppState>,
 ) -> Result<bool, String> {
+    let existing = app_state
+        .db
+        .get_skill_repos()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|r| r.owner == owner && r.name == name);
     app_state
         .db
         .delete_skill_repo(&owner, &name)
         .map_err(|e| e.to_string())?;
+    if let Some(repo) = existing {
+        if let Err(e) = SkillService::delete_repo_token(&repo, &SecretStore::open_default()) {
+            log::warn!("删除仓库 {owner}/{name} 的访问令牌失败: {e}");
+        }
+    }
+    SkillService::start_local_watcher(app);
     Ok(true)
 }
 
@@ -255,10 +388,23 @@ pub fn remove_skill_repo(
 pub fn install_skills_from_zip(
     file_path: String,
     current_app: String,
+    accept_risks: Option<bool>,
     app_state: State<'_, AppState>,
 ) -> Result<Vec<InstalledSkill>, String> {
     let app_type = parse_app_type(&current_app)?;
     let path = std::path::Path::new(&file_path);
 
-    SkillService::install_from_zip(&app_state.db, path, &app_type).map_err(|e| e.to_string())
+    SkillService::install_from_zip(
+        &app_state.db,
+        path,
+        &app_type,
+        accept_risks.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 安装前扫描 ZIP 文件中的 Skills
+#[tauri::command]
+pub fn scan_skills_zip(file_path: String) -> Result<Vec<SkillScanReport>, String> {
+    SkillService::scan_zip(std::path::Path::new(&file_path)).map_err(|e| e.to_string())
 }
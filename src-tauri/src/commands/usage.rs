@@ -1,6 +1,8 @@
 //! 使用统计相关命令
 
+use crate::database::UsageQueryRecord;
 use crate::error::AppError;
+use crate::services::budget::{BudgetAlertConfig, BudgetAlertService, BudgetStatus};
 use crate::services::usage_stats::*;
 use crate::store::AppState;
 use tauri::State;
@@ -37,6 +39,30 @@ pub fn get_model_stats(state: State<'_, AppState>) -> Result<Vec<ModelStats>, Ap
     state.db.get_model_stats()
 }
 
+/// 获取会话统计（按 session_id 聚合）
+#[tauri::command]
+pub fn get_session_usage_stats(
+    state: State<'_, AppState>,
+    start_date: Option<i64>,
+    end_date: Option<i64>,
+) -> Result<Vec<SessionUsageStats>, AppError> {
+    state.db.get_session_stats(start_date, end_date)
+}
+
+/// 获取项目统计（关联本地会话的项目目录后聚合）
+#[tauri::command]
+pub async fn get_project_usage_stats(
+    state: State<'_, AppState>,
+    start_date: Option<i64>,
+    end_date: Option<i64>,
+) -> Result<Vec<ProjectUsageStats>, AppError> {
+    let session_stats = state.db.get_session_stats(start_date, end_date)?;
+    let sessions = tauri::async_runtime::spawn_blocking(crate::session_manager::scan_sessions)
+        .await
+        .map_err(|e| AppError::Message(format!("扫描会话失败: {e}")))?;
+    Ok(aggregate_project_stats(&session_stats, &sessions))
+}
+
 /// 获取请求日志列表
 #[tauri::command]
 pub fn get_request_logs(
@@ -150,6 +176,46 @@ pub fn check_provider_limits(
     state.db.check_provider_limits(&provider_id, &app_type)
 }
 
+/// 获取预算告警配置
+#[tauri::command]
+pub fn get_budget_alert_config(state: State<'_, AppState>) -> Result<BudgetAlertConfig, AppError> {
+    state.db.get_budget_alert_config()
+}
+
+/// 保存预算告警配置
+#[tauri::command]
+pub fn save_budget_alert_config(
+    state: State<'_, AppState>,
+    config: BudgetAlertConfig,
+) -> Result<(), AppError> {
+    if let Some(budget) = config.global_monthly_budget_usd.as_deref() {
+        if !budget.trim().is_empty() && budget.trim().parse::<f64>().is_err() {
+            return Err(AppError::InvalidInput(format!("无效的月度预算: {budget}")));
+        }
+    }
+    state.db.save_budget_alert_config(&config)
+}
+
+/// 获取全局月度预算状态（含消费速率预测）
+#[tauri::command]
+pub fn get_budget_status(state: State<'_, AppState>) -> Result<BudgetStatus, AppError> {
+    BudgetAlertService::get_status(&state.db)
+}
+
+/// 获取用量脚本查询历史（余额趋势）
+#[tauri::command]
+pub fn get_usage_query_history(
+    state: State<'_, AppState>,
+    provider_id: String,
+    app_type: String,
+    start_date: Option<i64>,
+    end_date: Option<i64>,
+) -> Result<Vec<UsageQueryRecord>, AppError> {
+    state
+        .db
+        .get_usage_query_history(&provider_id, &app_type, start_date, end_date)
+}
+
 /// 删除模型定价
 #[tauri::command]
 pub fn delete_model_pricing(state: State<'_, AppState>, model_id: String) -> Result<(), AppError> {
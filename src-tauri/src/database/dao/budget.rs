@@ -0,0 +1,84 @@
+//! 预算告警数据访问对象
+//!
+//! 提供预算告警配置的读写，以及按周期去重的告警触发记录。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use crate::services::budget::BudgetAlertConfig;
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, NaiveTime, TimeZone};
+use rusqlite::params;
+
+impl Database {
+    /// 获取预算告警配置
+    pub fn get_budget_alert_config(&self) -> Result<BudgetAlertConfig, AppError> {
+        match self.get_setting("budget_alert_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析预算告警配置失败: {e}"))),
+            None => Ok(BudgetAlertConfig::default()),
+        }
+    }
+
+    /// 保存预算告警配置
+    pub fn save_budget_alert_config(&self, config: &BudgetAlertConfig) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化预算告警配置失败: {e}")))?;
+        self.set_setting("budget_alert_config", &json)
+    }
+
+    /// 记录一次阈值触发
+    ///
+    /// 同一 scope/period/threshold 只会记录一次，返回 true 表示本次为首次触发。
+    pub fn try_record_budget_alert(
+        &self,
+        scope_key: &str,
+        period: &str,
+        threshold: u32,
+        fired_at: i64,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO budget_alert_log (scope_key, period, threshold, fired_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![scope_key, period, threshold as i64, fired_at],
+            )
+            .map_err(|e| AppError::Database(format!("记录预算告警失败: {e}")))?;
+        Ok(inserted > 0)
+    }
+
+    /// 获取 `now` 所在月份（本地时区）所有请求的总消费
+    ///
+    /// 按月份起止时间戳做范围查询，以便使用 `created_at` 索引。
+    pub fn get_global_monthly_usage(&self, now: DateTime<Local>) -> Result<f64, AppError> {
+        let (start, end) = local_month_range(now);
+        let conn = lock_conn!(self.conn);
+        let usage: f64 = conn
+            .query_row(
+                "SELECT COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0)
+                 FROM proxy_request_logs
+                 WHERE created_at >= ?1 AND created_at < ?2",
+                params![start, end],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Database(format!("查询本月消费失败: {e}")))?;
+        Ok(usage)
+    }
+}
+
+/// `now` 所在月份（本地时区）的起止时间戳 `[start, end)`
+fn local_month_range(now: DateTime<Local>) -> (i64, i64) {
+    let today = now.date_naive();
+    let first = today.with_day(1).unwrap_or(today);
+    let next = first.checked_add_months(Months::new(1)).unwrap_or(first);
+    (local_midnight(first), local_midnight(next))
+}
+
+/// 本地时区某日零点的时间戳（零点不存在时取 UTC 零点近似）
+fn local_midnight(date: NaiveDate) -> i64 {
+    let midnight = date.and_time(NaiveTime::MIN);
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|t| t.timestamp())
+        .unwrap_or_else(|| midnight.and_utc().timestamp())
+}
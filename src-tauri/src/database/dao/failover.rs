@@ -1,6 +1,9 @@
 //! 故障转移队列 DAO
 //!
 //! 管理代理模式下的故障转移队列（基于 providers 表的 in_failover_queue 字段）
+//!
+//! 队列默认按界面排序（sort_index）排列；被降级到队尾的供应商记录在 failover_index 中，
+//! 按降级先后排在其余供应商之后，不改动界面排序。
 
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
@@ -17,7 +20,7 @@ pub struct FailoverQueueItem {
 }
 
 impl Database {
-    /// 获取故障转移队列（按 sort_index 排序）
+    /// 获取故障转移队列（未降级的按 sort_index 排序，降级的按降级先后排在队尾）
     pub fn get_failover_queue(&self, app_type: &str) -> Result<Vec<FailoverQueueItem>, AppError> {
         let conn = lock_conn!(self.conn);
 
@@ -26,7 +29,8 @@ impl Database {
                 "SELECT id, name, sort_index
                  FROM providers
                  WHERE app_type = ?1 AND in_failover_queue = 1
-                 ORDER BY COALESCE(sort_index, 999999), id ASC",
+                 ORDER BY failover_index IS NOT NULL, failover_index,
+                          COALESCE(sort_index, 999999), id ASC",
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -62,7 +66,8 @@ impl Database {
         let conn = lock_conn!(self.conn);
 
         conn.execute(
-            "UPDATE providers SET in_failover_queue = 1 WHERE id = ?1 AND app_type = ?2",
+            "UPDATE providers SET in_failover_queue = 1, failover_index = NULL
+             WHERE id = ?1 AND app_type = ?2",
             rusqlite::params![provider_id, app_type],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -80,7 +85,8 @@ impl Database {
 
         // 1. 从队列中移除
         conn.execute(
-            "UPDATE providers SET in_failover_queue = 0 WHERE id = ?1 AND app_type = ?2",
+            "UPDATE providers SET in_failover_queue = 0, failover_index = NULL
+             WHERE id = ?1 AND app_type = ?2",
             rusqlite::params![provider_id, app_type],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -97,12 +103,59 @@ impl Database {
         Ok(())
     }
 
+    /// 将供应商移至故障转移队列末尾
+    ///
+    /// 仅对已在队列中的供应商生效，返回是否发生了移动。界面排序（sort_index）保持不变。
+    pub fn move_to_failover_queue_end(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let updated = conn
+            .execute(
+                "UPDATE providers
+                 SET failover_index = (
+                     SELECT COALESCE(MAX(failover_index), 0) + 1 FROM providers
+                     WHERE app_type = ?2 AND in_failover_queue = 1
+                 )
+                 WHERE id = ?1 AND app_type = ?2 AND in_failover_queue = 1",
+                rusqlite::params![provider_id, app_type],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(updated > 0)
+    }
+
+    /// 撤销 [`Self::move_to_failover_queue_end`] 的降级，恢复按界面排序的位置
+    ///
+    /// 返回是否发生了恢复（未被降级的供应商不受影响）。
+    pub fn restore_failover_position(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let updated = conn
+            .execute(
+                "UPDATE providers SET failover_index = NULL
+                 WHERE id = ?1 AND app_type = ?2 AND in_failover_queue = 1
+                   AND failover_index IS NOT NULL",
+                rusqlite::params![provider_id, app_type],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(updated > 0)
+    }
+
     /// 清空故障转移队列
     pub fn clear_failover_queue(&self, app_type: &str) -> Result<(), AppError> {
         let conn = lock_conn!(self.conn);
 
         conn.execute(
-            "UPDATE providers SET in_failover_queue = 0 WHERE app_type = ?1",
+            "UPDATE providers SET in_failover_queue = 0, failover_index = NULL WHERE app_type = ?1",
             [app_type],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -144,3 +197,57 @@ impl Database {
         Ok(available)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::database::{lock_conn, Database};
+    use crate::error::AppError;
+
+    #[test]
+    fn test_move_to_failover_queue_end_keeps_sort_index() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, sort_index) in [("a", Some(0)), ("b", None), ("c", Some(1))] {
+                conn.execute(
+                    "INSERT INTO providers (id, app_type, name, settings_config, sort_index, in_failover_queue)
+                     VALUES (?1, 'claude', ?1, '{}', ?2, 1)",
+                    rusqlite::params![id, sort_index],
+                )
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            }
+        }
+        let order = |db: &Database| -> Result<Vec<String>, AppError> {
+            Ok(db
+                .get_failover_queue("claude")?
+                .into_iter()
+                .map(|item| item.provider_id)
+                .collect())
+        };
+        assert_eq!(order(&db)?, ["a", "c", "b"]);
+
+        // 未设置 sort_index 的供应商不会排到降级项之后
+        assert!(db.move_to_failover_queue_end("claude", "a")?);
+        assert_eq!(order(&db)?, ["c", "b", "a"]);
+        assert!(db.move_to_failover_queue_end("claude", "c")?);
+        assert_eq!(order(&db)?, ["b", "a", "c"]);
+
+        // 界面排序不受影响
+        let queue = db.get_failover_queue("claude")?;
+        let a = queue.iter().find(|item| item.provider_id == "a").unwrap();
+        assert_eq!(a.sort_index, Some(0));
+
+        // 重新加入队列后恢复按界面排序
+        db.remove_from_failover_queue("claude", "a")?;
+        db.add_to_failover_queue("claude", "a")?;
+        assert_eq!(order(&db)?, ["a", "b", "c"]);
+
+        assert!(!db.move_to_failover_queue_end("claude", "missing")?);
+
+        // 余额恢复后撤销降级
+        assert!(db.restore_failover_position("claude", "c")?);
+        assert_eq!(order(&db)?, ["a", "c", "b"]);
+        assert!(!db.restore_failover_position("claude", "c")?);
+        Ok(())
+    }
+}
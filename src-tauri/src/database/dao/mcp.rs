@@ -1,10 +1,11 @@
 //! MCP 服务器数据访问对象
 //!
-//! 提供 MCP 服务器的 CRUD 操作。
+//! 提供 MCP 服务器与目录来源的 CRUD 操作。
 
 use crate::app_config::{McpApps, McpServer};
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
+use crate::mcp::{McpCatalogSource, McpCatalogSourceKind};
 use indexmap::IndexMap;
 use rusqlite::params;
 
@@ -13,7 +14,7 @@ impl Database {
     pub fn get_all_mcp_servers(&self) -> Result<IndexMap<String, McpServer>, AppError> {
         let conn = lock_conn!(self.conn);
         let mut stmt = conn.prepare(
-            "SELECT id, name, server_config, description, homepage, docs, tags, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode
+            "SELECT id, name, server_config, description, homepage, docs, tags, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, tools, disabled_tools, projects
              FROM mcp_servers
              ORDER BY name ASC, id ASC"
         ).map_err(|e| AppError::Database(e.to_string()))?;
@@ -31,9 +32,15 @@ impl Database {
                 let enabled_codex: bool = row.get(8)?;
                 let enabled_gemini: bool = row.get(9)?;
                 let enabled_opencode: bool = row.get(10)?;
+                let tools_str: String = row.get(11)?;
+                let disabled_tools_str: String = row.get(12)?;
+                let projects_str: String = row.get(13)?;
 
                 let server = serde_json::from_str(&server_config_str).unwrap_or_default();
                 let tags = serde_json::from_str(&tags_str).unwrap_or_default();
+                let tools = serde_json::from_str(&tools_str).unwrap_or_default();
+                let disabled_tools = serde_json::from_str(&disabled_tools_str).unwrap_or_default();
+                let projects = serde_json::from_str(&projects_str).unwrap_or_default();
 
                 Ok((
                     id.clone(),
@@ -51,6 +58,9 @@ impl Database {
                         homepage,
                         docs,
                         tags,
+                        tools,
+                        disabled_tools,
+                        projects,
                     },
                 ))
             })
@@ -70,8 +80,9 @@ impl Database {
         conn.execute(
             "INSERT OR REPLACE INTO mcp_servers (
                 id, name, server_config, description, homepage, docs, tags,
-                enabled_claude, enabled_codex, enabled_gemini, enabled_opencode
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                enabled_claude, enabled_codex, enabled_gemini, enabled_opencode,
+                tools, disabled_tools, projects
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             params![
                 server.id,
                 server.name,
@@ -87,6 +98,14 @@ impl Database {
                 server.apps.codex,
                 server.apps.gemini,
                 server.apps.opencode,
+                serde_json::to_string(&server.tools)
+                    .map_err(|e| AppError::Database(format!("Failed to serialize tools: {e}")))?,
+                serde_json::to_string(&server.disabled_tools).map_err(|e| AppError::Database(
+                    format!("Failed to serialize disabled tools: {e}")
+                ))?,
+                serde_json::to_string(&server.projects).map_err(|e| AppError::Database(
+                    format!("Failed to serialize projects: {e}")
+                ))?,
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -100,4 +119,102 @@ impl Database {
             .map_err(|e| AppError::Database(e.to_string()))?;
         Ok(())
     }
+
+    // ========== MCP 目录来源 ==========
+
+    /// 获取所有 MCP 目录来源
+    pub fn get_mcp_catalog_sources(&self) -> Result<Vec<McpCatalogSource>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, kind, location, branch, enabled FROM mcp_catalog_sources
+                 ORDER BY name ASC, id ASC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                let kind: String = row.get(2)?;
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    kind,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, bool>(5)?,
+                ))
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut sources = Vec::new();
+        for row in rows {
+            let (id, name, kind, location, branch, enabled) =
+                row.map_err(|e| AppError::Database(e.to_string()))?;
+            let Ok(kind) = serde_json::from_value::<McpCatalogSourceKind>(serde_json::json!(kind))
+            else {
+                log::warn!("跳过未知类型的 MCP 目录来源 '{id}': {kind}");
+                continue;
+            };
+            sources.push(McpCatalogSource {
+                id,
+                name,
+                kind,
+                location,
+                branch,
+                enabled,
+            });
+        }
+        Ok(sources)
+    }
+
+    /// 保存 MCP 目录来源
+    pub fn save_mcp_catalog_source(&self, source: &McpCatalogSource) -> Result<(), AppError> {
+        let kind = serde_json::to_value(source.kind)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT OR REPLACE INTO mcp_catalog_sources (id, name, kind, location, branch, enabled)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                source.id,
+                source.name,
+                kind,
+                source.location,
+                source.branch,
+                source.enabled
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 删除 MCP 目录来源
+    pub fn delete_mcp_catalog_source(&self, id: &str) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let affected = conn
+            .execute("DELETE FROM mcp_catalog_sources WHERE id = ?1", params![id])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(affected > 0)
+    }
+
+    /// 初始化默认的 MCP 目录来源（启动时调用，补充缺失的默认来源）
+    pub fn init_default_mcp_catalog_sources(&self) -> Result<usize, AppError> {
+        let existing: std::collections::HashSet<String> = self
+            .get_mcp_catalog_sources()?
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+
+        let mut count = 0;
+        for source in crate::services::mcp_catalog::default_catalog_sources() {
+            if !existing.contains(&source.id) {
+                self.save_mcp_catalog_source(&source)?;
+                count += 1;
+                log::info!("补充默认 MCP 目录来源: {}", source.id);
+            }
+        }
+        Ok(count)
+    }
 }
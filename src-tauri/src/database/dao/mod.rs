@@ -2,16 +2,20 @@
 //!
 //! Database access operations for each domain
 
+pub mod budget;
 pub mod failover;
 pub mod mcp;
 pub mod prompts;
 pub mod providers;
 pub mod proxy;
+pub mod session_index;
 pub mod settings;
 pub mod skills;
 pub mod stream_check;
 pub mod universal_providers;
+pub mod usage_history;
 
 // 所有 DAO 方法都通过 Database impl 提供，无需单独导出
 // 导出 FailoverQueueItem 供外部使用
 pub use failover::FailoverQueueItem;
+pub use usage_history::UsageQueryRecord;
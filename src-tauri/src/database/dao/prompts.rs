@@ -1,10 +1,11 @@
 //! 提示词数据访问对象
 //!
-//! 提供提示词（Prompt）的 CRUD 操作。
+//! 提供提示词（Prompt）、跨应用共享提示词（SharedPrompt）与版本历史的 CRUD 操作。
 
+use crate::app_config::PromptApps;
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
-use crate::prompt::Prompt;
+use crate::prompt::{Prompt, PromptRevision, PromptRevisionSource, SharedPrompt};
 use indexmap::IndexMap;
 use rusqlite::params;
 
@@ -14,9 +15,9 @@ impl Database {
         let conn = lock_conn!(self.conn);
         let mut stmt = conn
             .prepare(
-                "SELECT id, name, content, description, enabled, created_at, updated_at
+                "SELECT id, name, content, description, enabled, created_at, updated_at, sort_index, projects
              FROM prompts WHERE app_type = ?1
-             ORDER BY created_at ASC, id ASC",
+             ORDER BY COALESCE(sort_index, 999999), created_at ASC, id ASC",
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -29,6 +30,8 @@ impl Database {
                 let enabled: bool = row.get(4)?;
                 let created_at: Option<i64> = row.get(5)?;
                 let updated_at: Option<i64> = row.get(6)?;
+                let sort_index: Option<usize> = row.get(7)?;
+                let projects: String = row.get(8)?;
 
                 Ok((
                     id.clone(),
@@ -40,6 +43,8 @@ impl Database {
                         enabled,
                         created_at,
                         updated_at,
+                        sort_index,
+                        projects: parse_projects(projects),
                     },
                 ))
             })
@@ -58,8 +63,9 @@ impl Database {
         let conn = lock_conn!(self.conn);
         conn.execute(
             "INSERT OR REPLACE INTO prompts (
-                id, app_type, name, content, description, enabled, created_at, updated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                id, app_type, name, content, description, enabled, created_at, updated_at,
+                sort_index, projects
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 prompt.id,
                 app_type,
@@ -69,6 +75,8 @@ impl Database {
                 prompt.enabled,
                 prompt.created_at,
                 prompt.updated_at,
+                prompt.sort_index,
+                serde_json::to_string(&prompt.projects).unwrap_or_else(|_| "[]".to_string()),
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -85,4 +93,254 @@ impl Database {
         .map_err(|e| AppError::Database(e.to_string()))?;
         Ok(())
     }
+
+    /// 获取所有共享提示词
+    pub fn get_shared_prompts(&self) -> Result<IndexMap<String, SharedPrompt>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, content, description, enabled_claude, enabled_codex,
+                        enabled_gemini, enabled_opencode, overrides, sort_index, projects,
+                        created_at, updated_at
+             FROM shared_prompts
+             ORDER BY COALESCE(sort_index, 999999), created_at ASC, id ASC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let prompt_iter = stmt
+            .query_map([], |row| {
+                let id: String = row.get(0)?;
+                let overrides: String = row.get(8)?;
+                let projects: String = row.get(10)?;
+                Ok((
+                    id.clone(),
+                    SharedPrompt {
+                        id,
+                        name: row.get(1)?,
+                        content: row.get(2)?,
+                        description: row.get(3)?,
+                        apps: PromptApps {
+                            claude: row.get(4)?,
+                            codex: row.get(5)?,
+                            gemini: row.get(6)?,
+                            opencode: row.get(7)?,
+                        },
+                        overrides: serde_json::from_str(&overrides).unwrap_or_default(),
+                        sort_index: row.get(9)?,
+                        projects: parse_projects(projects),
+                        created_at: row.get(11)?,
+                        updated_at: row.get(12)?,
+                    },
+                ))
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut prompts = IndexMap::new();
+        for prompt_res in prompt_iter {
+            let (id, prompt) = prompt_res.map_err(|e| AppError::Database(e.to_string()))?;
+            prompts.insert(id, prompt);
+        }
+        Ok(prompts)
+    }
+
+    /// 保存共享提示词
+    pub fn save_shared_prompt(&self, prompt: &SharedPrompt) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        write_shared_prompt(&conn, prompt)
+    }
+
+    /// 将多个应用中的提示词合并为共享提示词（同一事务）
+    ///
+    /// 保存共享提示词、删除 `merged` 中的 (应用, 提示词 id)，并将它们的版本历史
+    /// 转移到 `revision_app` 下的共享提示词，避免留下无主的版本记录。
+    pub fn merge_prompts_into_shared(
+        &self,
+        shared: &SharedPrompt,
+        revision_app: &str,
+        merged: &[(&str, &str)],
+    ) -> Result<(), AppError> {
+        let mut conn = lock_conn!(self.conn);
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        write_shared_prompt(&tx, shared)?;
+        for (app_type, prompt_id) in merged {
+            tx.execute(
+                "DELETE FROM prompts WHERE id = ?1 AND app_type = ?2",
+                params![prompt_id, app_type],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+            tx.execute(
+                "UPDATE prompt_revisions SET app_type = ?1, prompt_id = ?2
+                 WHERE app_type = ?3 AND prompt_id = ?4",
+                params![revision_app, shared.id, app_type, prompt_id],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 删除共享提示词
+    pub fn delete_shared_prompt(&self, id: &str) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute("DELETE FROM shared_prompts WHERE id = ?1", params![id])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    // ========== 版本历史 ==========
+
+    /// 记录一个提示词版本，并只保留最近 `keep` 个版本
+    pub fn add_prompt_revision(
+        &self,
+        app_type: &str,
+        prompt_id: &str,
+        content: &str,
+        source: PromptRevisionSource,
+        created_at: i64,
+        keep: usize,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO prompt_revisions (app_type, prompt_id, content, source, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![app_type, prompt_id, content, source.as_str(), created_at],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM prompt_revisions WHERE app_type = ?1 AND prompt_id = ?2 AND id NOT IN (
+                SELECT id FROM prompt_revisions WHERE app_type = ?1 AND prompt_id = ?2
+                ORDER BY id DESC LIMIT ?3
+            )",
+            params![app_type, prompt_id, keep as i64],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 获取提示词的所有版本（新版本在前）
+    pub fn get_prompt_revisions(
+        &self,
+        app_type: &str,
+        prompt_id: &str,
+    ) -> Result<Vec<PromptRevision>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, app_type, prompt_id, content, source, created_at
+             FROM prompt_revisions WHERE app_type = ?1 AND prompt_id = ?2
+             ORDER BY id DESC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![app_type, prompt_id], revision_from_row)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut revisions = Vec::new();
+        for row in rows {
+            if let Some(revision) = row.map_err(|e| AppError::Database(e.to_string()))? {
+                revisions.push(revision);
+            }
+        }
+        Ok(revisions)
+    }
+
+    /// 获取提示词的最新版本
+    pub fn get_latest_prompt_revision(
+        &self,
+        app_type: &str,
+        prompt_id: &str,
+    ) -> Result<Option<PromptRevision>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let result = conn.query_row(
+            "SELECT id, app_type, prompt_id, content, source, created_at
+             FROM prompt_revisions WHERE app_type = ?1 AND prompt_id = ?2
+             ORDER BY id DESC LIMIT 1",
+            params![app_type, prompt_id],
+            revision_from_row,
+        );
+        match result {
+            Ok(revision) => Ok(revision),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::Database(e.to_string())),
+        }
+    }
+
+    /// 按 id 获取提示词版本
+    pub fn get_prompt_revision(&self, id: i64) -> Result<Option<PromptRevision>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let result = conn.query_row(
+            "SELECT id, app_type, prompt_id, content, source, created_at
+             FROM prompt_revisions WHERE id = ?1",
+            params![id],
+            revision_from_row,
+        );
+        match result {
+            Ok(revision) => Ok(revision),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::Database(e.to_string())),
+        }
+    }
+
+    /// 删除提示词的所有版本
+    pub fn delete_prompt_revisions(&self, app_type: &str, prompt_id: &str) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "DELETE FROM prompt_revisions WHERE app_type = ?1 AND prompt_id = ?2",
+            params![app_type, prompt_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn write_shared_prompt(conn: &rusqlite::Connection, prompt: &SharedPrompt) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO shared_prompts (
+            id, name, content, description, enabled_claude, enabled_codex, enabled_gemini,
+            enabled_opencode, overrides, sort_index, projects, created_at, updated_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            prompt.id,
+            prompt.name,
+            prompt.content,
+            prompt.description,
+            prompt.apps.claude,
+            prompt.apps.codex,
+            prompt.apps.gemini,
+            prompt.apps.opencode,
+            serde_json::to_string(&prompt.overrides).unwrap_or_else(|_| "{}".to_string()),
+            prompt.sort_index,
+            serde_json::to_string(&prompt.projects).unwrap_or_else(|_| "[]".to_string()),
+            prompt.created_at,
+            prompt.updated_at,
+        ],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// 解析项目作用域 JSON，损坏时视为用户级
+fn parse_projects(raw: String) -> Vec<String> {
+    serde_json::from_str(&raw).unwrap_or_default()
+}
+
+/// 读取版本行；未知来源的版本跳过
+fn revision_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Option<PromptRevision>> {
+    let id: i64 = row.get(0)?;
+    let source: String = row.get(4)?;
+    let Ok(source) = serde_json::from_value::<PromptRevisionSource>(serde_json::json!(source))
+    else {
+        log::warn!("跳过未知来源的提示词版本 {id}: {source}");
+        return Ok(None);
+    };
+    Ok(Some(PromptRevision {
+        id,
+        app_type: row.get(1)?,
+        prompt_id: row.get(2)?,
+        content: row.get(3)?,
+        source,
+        created_at: row.get(5)?,
+    }))
 }
@@ -0,0 +1,218 @@
+//! 会话全文索引数据访问对象
+//!
+//! - `session_index_files`：已索引的会话文件及其大小/修改时间，用于增量更新
+//! - `session_index_messages`：每行一条会话消息
+//! - `session_index_fts`：以 `session_index_messages` 为外部内容的 FTS5 索引，由触发器同步
+
+use std::collections::HashMap;
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use crate::session_manager::search::{IndexedSessionFile, SessionSearchQuery, SessionSearchRow};
+use crate::session_manager::SessionMessage;
+use rusqlite::params;
+use rusqlite::types::Value;
+
+impl Database {
+    /// 获取已索引文件的 (大小, 修改时间)，按 source_path 索引
+    pub fn get_session_index_files(&self) -> Result<HashMap<String, (i64, i64)>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt =
+            conn.prepare("SELECT source_path, file_size, file_mtime FROM session_index_files")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+        })?;
+
+        let mut files = HashMap::new();
+        for row in rows {
+            let (path, stamp) = row?;
+            files.insert(path, stamp);
+        }
+        Ok(files)
+    }
+
+    /// 用最新内容替换一个会话文件的索引
+    pub fn replace_session_index(
+        &self,
+        file: &IndexedSessionFile,
+        messages: &[SessionMessage],
+    ) -> Result<(), AppError> {
+        let mut conn = lock_conn!(self.conn);
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM session_index_messages WHERE source_path = ?1",
+            params![file.source_path],
+        )?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO session_index_messages (source_path, seq, role, ts, content)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (seq, message) in messages.iter().enumerate() {
+                stmt.execute(params![
+                    file.source_path,
+                    seq as i64,
+                    message.role,
+                    message.ts,
+                    message.content
+                ])?;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO session_index_files (
+                source_path, provider_id, session_id, title, project_dir, resume_command,
+                created_at, last_active_at, file_size, file_mtime, indexed_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                file.source_path,
+                file.provider_id,
+                file.session_id,
+                file.title,
+                file.project_dir,
+                file.resume_command,
+                file.created_at,
+                file.last_active_at,
+                file.file_size,
+                file.file_mtime,
+                file.indexed_at
+            ],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 移除一个会话文件的索引
+    pub fn remove_session_index(&self, source_path: &str) -> Result<(), AppError> {
+        let mut conn = lock_conn!(self.conn);
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM session_index_messages WHERE source_path = ?1",
+            params![source_path],
+        )?;
+        tx.execute(
+            "DELETE FROM session_index_files WHERE source_path = ?1",
+            params![source_path],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 清空会话索引（用于重建）
+    pub fn clear_session_index(&self) -> Result<(), AppError> {
+        let mut conn = lock_conn!(self.conn);
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM session_index_messages", [])?;
+        tx.execute("DELETE FROM session_index_files", [])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 检索消息，返回命中的消息行（含所属会话信息）
+    ///
+    /// 不少于 3 个字符的关键词走 FTS5 trigram 索引并按相关度排序；
+    /// 全部关键词都更短时退化为 LIKE 匹配，按时间倒序。
+    pub fn search_session_index(
+        &self,
+        query: &SessionSearchQuery,
+        terms: &[String],
+        limit: usize,
+    ) -> Result<Vec<SessionSearchRow>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        let match_expr = terms
+            .iter()
+            .filter(|term| term.chars().count() >= 3)
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let has_match = !match_expr.is_empty();
+        if has_match {
+            conditions.push("session_index_fts MATCH ?");
+            values.push(Value::Text(match_expr));
+        }
+        for term in terms.iter().filter(|term| term.chars().count() < 3) {
+            conditions.push("m.content LIKE ? ESCAPE '\\'");
+            values.push(Value::Text(format!("%{}%", escape_like(term))));
+        }
+        if let Some(provider_id) = &query.provider_id {
+            conditions.push("s.provider_id = ?");
+            values.push(Value::Text(provider_id.clone()));
+        }
+        if let Some(project_dir) = &query.project_dir {
+            conditions.push("s.project_dir = ?");
+            values.push(Value::Text(project_dir.clone()));
+        }
+        if let Some(role) = &query.role {
+            conditions.push("m.role = ?");
+            values.push(Value::Text(role.clone()));
+        }
+        if let Some(start) = query.start_date {
+            conditions.push("COALESCE(m.ts, s.last_active_at) >= ?");
+            values.push(Value::Integer(start));
+        }
+        if let Some(end) = query.end_date {
+            conditions.push("COALESCE(m.ts, s.last_active_at) <= ?");
+            values.push(Value::Integer(end));
+        }
+        if conditions.is_empty() {
+            conditions.push("1 = 1");
+        }
+        values.push(Value::Integer(limit as i64));
+
+        let (from, order_by) = if has_match {
+            (
+                "session_index_fts JOIN session_index_messages m ON m.id = session_index_fts.rowid",
+                "bm25(session_index_fts), s.last_active_at DESC",
+            )
+        } else {
+            (
+                "session_index_messages m",
+                "COALESCE(m.ts, s.last_active_at) DESC",
+            )
+        };
+        let sql = format!(
+            "SELECT s.provider_id, s.session_id, s.source_path, s.title, s.project_dir,
+                    s.resume_command, s.last_active_at, m.role, m.content, m.seq, m.ts
+             FROM {from}
+             JOIN session_index_files s ON s.source_path = m.source_path
+             WHERE {}
+             ORDER BY {order_by}
+             LIMIT ?",
+            conditions.join(" AND ")
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(values), |row| {
+            Ok(SessionSearchRow {
+                provider_id: row.get(0)?,
+                session_id: row.get(1)?,
+                source_path: row.get(2)?,
+                title: row.get(3)?,
+                project_dir: row.get(4)?,
+                resume_command: row.get(5)?,
+                last_active_at: row.get(6)?,
+                role: row.get(7)?,
+                content: row.get(8)?,
+                seq: row.get(9)?,
+                ts: row.get(10)?,
+            })
+        })?;
+
+        let mut hits = Vec::new();
+        for row in rows {
+            hits.push(row?);
+        }
+        Ok(hits)
+    }
+}
+
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
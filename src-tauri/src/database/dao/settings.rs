@@ -204,4 +204,27 @@ impl Database {
             .map_err(|e| AppError::Database(format!("序列化日志配置失败: {e}")))?;
         self.set_setting("log_config", &json)
     }
+
+    // --- 提示词模板变量 ---
+
+    /// 获取用户自定义的提示词模板变量
+    pub fn get_prompt_variables(
+        &self,
+    ) -> Result<std::collections::BTreeMap<String, String>, AppError> {
+        match self.get_setting("prompt_variables")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析提示词模板变量失败: {e}"))),
+            None => Ok(Default::default()),
+        }
+    }
+
+    /// 更新用户自定义的提示词模板变量
+    pub fn set_prompt_variables(
+        &self,
+        variables: &std::collections::BTreeMap<String, String>,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(variables)
+            .map_err(|e| AppError::Database(format!("序列化提示词模板变量失败: {e}")))?;
+        self.set_setting("prompt_variables", &json)
+    }
 }
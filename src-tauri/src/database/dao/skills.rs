@@ -6,10 +6,10 @@
 //! - Skills 使用统一的 id 主键，支持四应用启用标志
 //! - 实际文件存储在 ~/.cc-switch/skills/，同步到各应用目录
 
-use crate::app_config::{InstalledSkill, SkillApps};
+use crate::app_config::{InstalledSkill, SkillApps, SkillFinding};
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
-use crate::services::skill::SkillRepo;
+use crate::services::skill::{SkillRepo, SkillSourceKind};
 use indexmap::IndexMap;
 use rusqlite::params;
 
@@ -22,7 +22,8 @@ impl Database {
         let mut stmt = conn
             .prepare(
                 "SELECT id, name, description, directory, repo_owner, repo_name, repo_branch,
-                        readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at
+                        readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at,
+                        commit_sha, content_hash, scan_findings, projects
                  FROM skills ORDER BY name ASC",
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -45,6 +46,10 @@ impl Database {
                         opencode: row.get(11)?,
                     },
                     installed_at: row.get(12)?,
+                    commit_sha: row.get(13)?,
+                    content_hash: row.get(14)?,
+                    scan_findings: parse_findings(row.get(15)?),
+                    projects: parse_projects(row.get(16)?),
                 })
             })
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -63,7 +68,8 @@ impl Database {
         let mut stmt = conn
             .prepare(
                 "SELECT id, name, description, directory, repo_owner, repo_name, repo_branch,
-                        readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at
+                        readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at,
+                        commit_sha, content_hash, scan_findings, projects
                  FROM skills WHERE id = ?1",
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -85,6 +91,10 @@ impl Database {
                     opencode: row.get(11)?,
                 },
                 installed_at: row.get(12)?,
+                commit_sha: row.get(13)?,
+                content_hash: row.get(14)?,
+                scan_findings: parse_findings(row.get(15)?),
+                projects: parse_projects(row.get(16)?),
             })
         });
 
@@ -101,8 +111,9 @@ impl Database {
         conn.execute(
             "INSERT OR REPLACE INTO skills
              (id, name, description, directory, repo_owner, repo_name, repo_branch,
-              readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+              readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at,
+              commit_sha, content_hash, scan_findings, projects)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
             params![
                 skill.id,
                 skill.name,
@@ -117,6 +128,10 @@ impl Database {
                 skill.apps.gemini,
                 skill.apps.opencode,
                 skill.installed_at,
+                skill.commit_sha,
+                skill.content_hash,
+                serde_json::to_string(&skill.scan_findings).unwrap_or_else(|_| "[]".to_string()),
+                serde_json::to_string(&skill.projects).unwrap_or_else(|_| "[]".to_string()),
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -159,34 +174,68 @@ impl Database {
         let conn = lock_conn!(self.conn);
         let mut stmt = conn
             .prepare(
-                "SELECT owner, name, branch, enabled FROM skill_repos ORDER BY owner ASC, name ASC",
+                "SELECT owner, name, branch, enabled, kind, url, subpath, token FROM skill_repos
+                 ORDER BY owner ASC, name ASC",
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
 
         let repo_iter = stmt
             .query_map([], |row| {
-                Ok(SkillRepo {
-                    owner: row.get(0)?,
-                    name: row.get(1)?,
-                    branch: row.get(2)?,
-                    enabled: row.get(3)?,
-                })
+                let kind: String = row.get(4)?;
+                Ok((
+                    SkillRepo {
+                        owner: row.get(0)?,
+                        name: row.get(1)?,
+                        branch: row.get(2)?,
+                        enabled: row.get(3)?,
+                        kind: SkillSourceKind::default(),
+                        url: row.get(5)?,
+                        subpath: row.get(6)?,
+                        token: row.get(7)?,
+                    },
+                    kind,
+                ))
             })
             .map_err(|e| AppError::Database(e.to_string()))?;
 
         let mut repos = Vec::new();
         for repo_res in repo_iter {
-            repos.push(repo_res.map_err(|e| AppError::Database(e.to_string()))?);
+            let (mut repo, kind) = repo_res.map_err(|e| AppError::Database(e.to_string()))?;
+            let Ok(kind) = serde_json::from_value::<SkillSourceKind>(serde_json::json!(kind))
+            else {
+                log::warn!(
+                    "跳过未知类型的 Skill 仓库 '{}/{}': {kind}",
+                    repo.owner,
+                    repo.name
+                );
+                continue;
+            };
+            repo.kind = kind;
+            repos.push(repo);
         }
         Ok(repos)
     }
 
     /// 保存 Skill 仓库
     pub fn save_skill_repo(&self, repo: &SkillRepo) -> Result<(), AppError> {
+        let kind = serde_json::to_value(repo.kind)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
         let conn = lock_conn!(self.conn);
         conn.execute(
-            "INSERT OR REPLACE INTO skill_repos (owner, name, branch, enabled) VALUES (?1, ?2, ?3, ?4)",
-            params![repo.owner, repo.name, repo.branch, repo.enabled],
+            "INSERT OR REPLACE INTO skill_repos (owner, name, branch, enabled, kind, url, subpath, token)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                repo.owner,
+                repo.name,
+                repo.branch,
+                repo.enabled,
+                kind,
+                repo.url,
+                repo.subpath,
+                repo.token
+            ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
         Ok(())
@@ -232,3 +281,13 @@ impl Database {
         Ok(count)
     }
 }
+
+/// 解析扫描发现项列（损坏的数据视为无发现项）
+fn parse_findings(raw: String) -> Vec<SkillFinding> {
+    serde_json::from_str(&raw).unwrap_or_default()
+}
+
+/// 解析项目作用域列
+fn parse_projects(raw: String) -> Vec<String> {
+    serde_json::from_str(&raw).unwrap_or_default()
+}
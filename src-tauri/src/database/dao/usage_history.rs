@@ -0,0 +1,190 @@
+//! 用量查询历史 DAO
+//!
+//! 记录每次用量脚本查询的结果，用于余额趋势展示与低余额判断。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use crate::provider::UsageResult;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// 用量查询历史记录（每个套餐一条）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageQueryRecord {
+    pub provider_id: String,
+    pub app_type: String,
+    pub success: bool,
+    pub plan_name: Option<String>,
+    pub total: Option<f64>,
+    pub used: Option<f64>,
+    pub remaining: Option<f64>,
+    pub unit: Option<String>,
+    pub is_valid: Option<bool>,
+    pub error: Option<String>,
+    pub queried_at: i64,
+}
+
+impl Database {
+    /// 保存一次用量查询结果
+    ///
+    /// 成功时每个套餐写入一行，失败时写入一行错误记录。
+    pub fn save_usage_query_result(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+        result: &UsageResult,
+        queried_at: i64,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        let sql = "INSERT INTO usage_query_history
+            (provider_id, app_type, success, plan_name, total, used, remaining, unit, is_valid, error, queried_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)";
+
+        match result.data.as_deref() {
+            Some(items) if result.success && !items.is_empty() => {
+                for item in items {
+                    conn.execute(
+                        sql,
+                        params![
+                            provider_id,
+                            app_type,
+                            true,
+                            item.plan_name,
+                            item.total,
+                            item.used,
+                            item.remaining,
+                            item.unit,
+                            item.is_valid,
+                            item.invalid_message,
+                            queried_at,
+                        ],
+                    )
+                    .map_err(|e| AppError::Database(format!("保存用量查询记录失败: {e}")))?;
+                }
+            }
+            _ => {
+                conn.execute(
+                    sql,
+                    params![
+                        provider_id,
+                        app_type,
+                        result.success,
+                        Option::<String>::None,
+                        Option::<f64>::None,
+                        Option::<f64>::None,
+                        Option::<f64>::None,
+                        Option::<String>::None,
+                        Option::<bool>::None,
+                        result.error,
+                        queried_at,
+                    ],
+                )
+                .map_err(|e| AppError::Database(format!("保存用量查询记录失败: {e}")))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 获取用量查询历史（按时间升序，便于绘制趋势图）
+    pub fn get_usage_query_history(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+        start_date: Option<i64>,
+        end_date: Option<i64>,
+    ) -> Result<Vec<UsageQueryRecord>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn.prepare(
+            "SELECT provider_id, app_type, success, plan_name, total, used, remaining,
+                    unit, is_valid, error, queried_at
+             FROM usage_query_history
+             WHERE provider_id = ?1 AND app_type = ?2
+               AND (?3 IS NULL OR queried_at >= ?3)
+               AND (?4 IS NULL OR queried_at <= ?4)
+             ORDER BY queried_at ASC, id ASC",
+        )?;
+
+        let rows = stmt.query_map(
+            params![provider_id, app_type, start_date, end_date],
+            |row| {
+                Ok(UsageQueryRecord {
+                    provider_id: row.get(0)?,
+                    app_type: row.get(1)?,
+                    success: row.get(2)?,
+                    plan_name: row.get(3)?,
+                    total: row.get(4)?,
+                    used: row.get(5)?,
+                    remaining: row.get(6)?,
+                    unit: row.get(7)?,
+                    is_valid: row.get(8)?,
+                    error: row.get(9)?,
+                    queried_at: row.get(10)?,
+                })
+            },
+        )?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            records.push(row?);
+        }
+        Ok(records)
+    }
+
+    /// 获取最近一次查询时间（秒）
+    pub fn get_last_usage_query_at(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+    ) -> Result<Option<i64>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let last = conn
+            .query_row(
+                "SELECT MAX(queried_at) FROM usage_query_history
+                 WHERE provider_id = ?1 AND app_type = ?2",
+                params![provider_id, app_type],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .flatten();
+        Ok(last)
+    }
+
+    /// 获取最近一次成功查询中最小的剩余额度
+    pub fn get_latest_min_remaining(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+    ) -> Result<Option<f64>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let remaining = conn
+            .query_row(
+                "SELECT MIN(remaining) FROM usage_query_history
+                 WHERE provider_id = ?1 AND app_type = ?2 AND success = 1
+                   AND queried_at = (
+                       SELECT MAX(queried_at) FROM usage_query_history
+                       WHERE provider_id = ?1 AND app_type = ?2 AND success = 1
+                   )",
+                params![provider_id, app_type],
+                |row| row.get::<_, Option<f64>>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .flatten();
+        Ok(remaining)
+    }
+
+    /// 清理指定时间之前的查询历史，返回删除条数
+    pub fn prune_usage_query_history(&self, before: i64) -> Result<usize, AppError> {
+        let conn = lock_conn!(self.conn);
+        let deleted = conn
+            .execute(
+                "DELETE FROM usage_query_history WHERE queried_at < ?1",
+                params![before],
+            )
+            .map_err(|e| AppError::Database(format!("清理用量查询历史失败: {e}")))?;
+        Ok(deleted)
+    }
+}
@@ -32,6 +32,7 @@ impl Database {
                 meta TEXT NOT NULL DEFAULT '{}',
                 is_current BOOLEAN NOT NULL DEFAULT 0,
                 in_failover_queue BOOLEAN NOT NULL DEFAULT 0,
+                failover_index INTEGER,
                 PRIMARY KEY (id, app_type)
             )",
             [],
@@ -58,7 +59,9 @@ impl Database {
             id TEXT PRIMARY KEY, name TEXT NOT NULL, server_config TEXT NOT NULL,
             description TEXT, homepage TEXT, docs TEXT, tags TEXT NOT NULL DEFAULT '[]',
             enabled_claude BOOLEAN NOT NULL DEFAULT 0, enabled_codex BOOLEAN NOT NULL DEFAULT 0,
-            enabled_gemini BOOLEAN NOT NULL DEFAULT 0, enabled_opencode BOOLEAN NOT NULL DEFAULT 0
+            enabled_gemini BOOLEAN NOT NULL DEFAULT 0, enabled_opencode BOOLEAN NOT NULL DEFAULT 0,
+            tools TEXT NOT NULL DEFAULT '[]', disabled_tools TEXT NOT NULL DEFAULT '{}',
+            projects TEXT NOT NULL DEFAULT '[]'
         )",
             [],
         )
@@ -68,6 +71,7 @@ impl Database {
         conn.execute("CREATE TABLE IF NOT EXISTS prompts (
             id TEXT NOT NULL, app_type TEXT NOT NULL, name TEXT NOT NULL, content TEXT NOT NULL,
             description TEXT, enabled BOOLEAN NOT NULL DEFAULT 1, created_at INTEGER, updated_at INTEGER,
+            sort_index INTEGER, projects TEXT NOT NULL DEFAULT '[]',
             PRIMARY KEY (id, app_type)
         )", []).map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -86,7 +90,11 @@ impl Database {
             enabled_codex BOOLEAN NOT NULL DEFAULT 0,
             enabled_gemini BOOLEAN NOT NULL DEFAULT 0,
             enabled_opencode BOOLEAN NOT NULL DEFAULT 0,
-            installed_at INTEGER NOT NULL DEFAULT 0
+            installed_at INTEGER NOT NULL DEFAULT 0,
+            commit_sha TEXT,
+            content_hash TEXT,
+            scan_findings TEXT NOT NULL DEFAULT '[]',
+            projects TEXT NOT NULL DEFAULT '[]'
         )",
             [],
         )
@@ -96,7 +104,8 @@ impl Database {
         conn.execute(
             "CREATE TABLE IF NOT EXISTS skill_repos (
             owner TEXT NOT NULL, name TEXT NOT NULL, branch TEXT NOT NULL DEFAULT 'main',
-            enabled BOOLEAN NOT NULL DEFAULT 1, PRIMARY KEY (owner, name)
+            enabled BOOLEAN NOT NULL DEFAULT 1, kind TEXT NOT NULL DEFAULT 'github',
+            url TEXT, subpath TEXT, token TEXT, PRIMARY KEY (owner, name)
         )",
             [],
         )
@@ -241,6 +250,100 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+        // 17. Budget Alert Log 表（预算告警去重，每个周期每个阈值只触发一次）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS budget_alert_log (
+            scope_key TEXT NOT NULL, period TEXT NOT NULL, threshold INTEGER NOT NULL,
+            fired_at INTEGER NOT NULL, PRIMARY KEY (scope_key, period, threshold)
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 18. Usage Query History 表（用量脚本查询结果时间序列，每个套餐一行）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS usage_query_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, provider_id TEXT NOT NULL, app_type TEXT NOT NULL,
+            success INTEGER NOT NULL, plan_name TEXT, total REAL, used REAL, remaining REAL,
+            unit TEXT, is_valid INTEGER, error TEXT, queried_at INTEGER NOT NULL
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_usage_query_history_provider
+             ON usage_query_history(app_type, provider_id, queried_at)",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 19. MCP Catalog Sources 表（MCP 服务器目录来源）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS mcp_catalog_sources (
+            id TEXT PRIMARY KEY, name TEXT NOT NULL, kind TEXT NOT NULL, location TEXT NOT NULL,
+            branch TEXT, enabled BOOLEAN NOT NULL DEFAULT 1
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 20. Shared Prompts 表（跨应用共享的提示词）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS shared_prompts (
+            id TEXT PRIMARY KEY, name TEXT NOT NULL, content TEXT NOT NULL, description TEXT,
+            enabled_claude BOOLEAN NOT NULL DEFAULT 0, enabled_codex BOOLEAN NOT NULL DEFAULT 0,
+            enabled_gemini BOOLEAN NOT NULL DEFAULT 0, enabled_opencode BOOLEAN NOT NULL DEFAULT 0,
+            overrides TEXT NOT NULL DEFAULT '{}', sort_index INTEGER,
+            projects TEXT NOT NULL DEFAULT '[]', created_at INTEGER, updated_at INTEGER
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 21. Prompt Revisions 表（提示词版本历史）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS prompt_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, app_type TEXT NOT NULL, prompt_id TEXT NOT NULL,
+            content TEXT NOT NULL, source TEXT NOT NULL, created_at INTEGER NOT NULL
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_prompt_revisions_prompt
+             ON prompt_revisions(app_type, prompt_id, id)",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 22. Session Index 表（会话全文检索，可随时重建的缓存，不参与 SQL 导出）
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS session_index_files (
+            source_path TEXT PRIMARY KEY, provider_id TEXT NOT NULL, session_id TEXT NOT NULL,
+            title TEXT, project_dir TEXT, resume_command TEXT,
+            created_at INTEGER, last_active_at INTEGER,
+            file_size INTEGER NOT NULL, file_mtime INTEGER NOT NULL, indexed_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS session_index_messages (
+            id INTEGER PRIMARY KEY, source_path TEXT NOT NULL, seq INTEGER NOT NULL,
+            role TEXT NOT NULL, ts INTEGER, content TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_index_messages_source
+            ON session_index_messages(source_path);
+        CREATE VIRTUAL TABLE IF NOT EXISTS session_index_fts USING fts5(
+            content, content='session_index_messages', content_rowid='id', tokenize='trigram'
+        );
+        CREATE TRIGGER IF NOT EXISTS session_index_messages_ai AFTER INSERT ON session_index_messages BEGIN
+            INSERT INTO session_index_fts(rowid, content) VALUES (new.id, new.content);
+        END;
+        CREATE TRIGGER IF NOT EXISTS session_index_messages_ad AFTER DELETE ON session_index_messages BEGIN
+            INSERT INTO session_index_fts(session_index_fts, rowid, content)
+            VALUES ('delete', old.id, old.content);
+        END;",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
         // 尝试添加 live_takeover_active 列到 proxy_config 表
         let _ = conn.execute(
             "ALTER TABLE proxy_config ADD COLUMN live_takeover_active INTEGER NOT NULL DEFAULT 0",
@@ -294,6 +397,49 @@ impl Database {
             "in_failover_queue",
             "BOOLEAN NOT NULL DEFAULT 0",
         )?;
+        // 故障转移队列的降级顺序（与界面排序 sort_index 分开）
+        Self::add_column_if_missing(conn, "providers", "failover_index", "INTEGER")?;
+
+        // 确保 MCP 工具清单、工具过滤与项目作用域列存在（对于已存在的数据库）
+        Self::add_column_if_missing(conn, "mcp_servers", "tools", "TEXT NOT NULL DEFAULT '[]'")?;
+        Self::add_column_if_missing(
+            conn,
+            "mcp_servers",
+            "disabled_tools",
+            "TEXT NOT NULL DEFAULT '{}'",
+        )?;
+        Self::add_column_if_missing(
+            conn,
+            "mcp_servers",
+            "projects",
+            "TEXT NOT NULL DEFAULT '[]'",
+        )?;
+
+        // 确保提示词排序与项目作用域列存在（对于已存在的数据库）
+        Self::add_column_if_missing(conn, "prompts", "sort_index", "INTEGER")?;
+        Self::add_column_if_missing(conn, "prompts", "projects", "TEXT NOT NULL DEFAULT '[]'")?;
+
+        // 确保 Skill 版本信息列存在（对于已存在的数据库）
+        Self::add_column_if_missing(conn, "skills", "commit_sha", "TEXT")?;
+        Self::add_column_if_missing(conn, "skills", "content_hash", "TEXT")?;
+        Self::add_column_if_missing(
+            conn,
+            "skills",
+            "scan_findings",
+            "TEXT NOT NULL DEFAULT '[]'",
+        )?;
+        Self::add_column_if_missing(conn, "skills", "projects", "TEXT NOT NULL DEFAULT '[]'")?;
+
+        // 确保 Skill 仓库来源列存在（对于已存在的数据库）
+        Self::add_column_if_missing(
+            conn,
+            "skill_repos",
+            "kind",
+            "TEXT NOT NULL DEFAULT 'github'",
+        )?;
+        Self::add_column_if_missing(conn, "skill_repos", "url", "TEXT")?;
+        Self::add_column_if_missing(conn, "skill_repos", "subpath", "TEXT")?;
+        Self::add_column_if_missing(conn, "skill_repos", "token", "TEXT")?;
 
         // 删除旧的 failover_queue 表（如果存在）
         let _ = conn.execute("DROP INDEX IF EXISTS idx_failover_queue_order", []);
@@ -624,3 +624,192 @@ fn schema_model_pricing_is_seeded_on_init() {
         gemini_count
     );
 }
+
+#[test]
+fn prompt_revisions_keep_latest_versions() {
+    use crate::prompt::PromptRevisionSource;
+
+    let db = Database::memory().expect("create memory db");
+    for (i, source) in [
+        PromptRevisionSource::Import,
+        PromptRevisionSource::Ui,
+        PromptRevisionSource::Backfill,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        db.add_prompt_revision("claude", "p1", &format!("v{i}"), source, i as i64, 2)
+            .expect("add revision");
+    }
+    db.add_prompt_revision("codex", "p1", "other", PromptRevisionSource::Ui, 0, 2)
+        .expect("add revision");
+
+    let revisions = db.get_prompt_revisions("claude", "p1").expect("list");
+    let contents: Vec<&str> = revisions.iter().map(|r| r.content.as_str()).collect();
+    assert_eq!(contents, vec!["v2", "v1"]);
+    assert_eq!(revisions[0].source, PromptRevisionSource::Backfill);
+
+    let latest = db
+        .get_latest_prompt_revision("claude", "p1")
+        .expect("latest")
+        .expect("exists");
+    assert_eq!(latest.id, revisions[0].id);
+    assert_eq!(
+        db.get_prompt_revision(latest.id)
+            .expect("get")
+            .map(|r| r.content),
+        Some("v2".to_string())
+    );
+
+    db.delete_prompt_revisions("claude", "p1").expect("delete");
+    assert!(db.get_prompt_revisions("claude", "p1").unwrap().is_empty());
+    assert_eq!(db.get_prompt_revisions("codex", "p1").unwrap().len(), 1);
+}
+
+#[test]
+fn merge_prompts_into_shared_moves_revisions() {
+    use crate::prompt::{Prompt, PromptRevisionSource, SharedPrompt};
+
+    let db = Database::memory().expect("create memory db");
+    let prompt = Prompt {
+        id: "rules".to_string(),
+        name: "Rules".to_string(),
+        content: "same".to_string(),
+        description: None,
+        enabled: true,
+        created_at: None,
+        updated_at: None,
+        sort_index: None,
+        projects: Vec::new(),
+    };
+    for app in ["claude", "codex"] {
+        db.save_prompt(app, &prompt).expect("save prompt");
+        db.add_prompt_revision(app, "rules", "same", PromptRevisionSource::Ui, 0, 10)
+            .expect("add revision");
+    }
+    let shared = SharedPrompt {
+        id: "shared-1".to_string(),
+        name: prompt.name.clone(),
+        content: prompt.content.clone(),
+        description: None,
+        apps: Default::default(),
+        overrides: Default::default(),
+        sort_index: None,
+        projects: Vec::new(),
+        created_at: None,
+        updated_at: None,
+    };
+
+    db.merge_prompts_into_shared(
+        &shared,
+        "shared",
+        &[("claude", "rules"), ("codex", "rules")],
+    )
+    .expect("merge prompts");
+
+    assert!(db.get_shared_prompts().unwrap().contains_key("shared-1"));
+    assert!(db.get_prompts("claude").unwrap().is_empty());
+    assert!(db.get_prompts("codex").unwrap().is_empty());
+    assert!(db
+        .get_prompt_revisions("claude", "rules")
+        .unwrap()
+        .is_empty());
+    assert!(db
+        .get_prompt_revisions("codex", "rules")
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        db.get_prompt_revisions("shared", "shared-1").unwrap().len(),
+        2
+    );
+}
+
+#[test]
+fn session_index_search_filters_and_replaces() {
+    use crate::session_manager::search::{IndexedSessionFile, SessionSearchQuery};
+    use crate::session_manager::SessionMessage;
+
+    let db = Database::memory().expect("create memory db");
+    let file = |path: &str, project: &str| IndexedSessionFile {
+        source_path: path.to_string(),
+        provider_id: "claude".to_string(),
+        session_id: path.trim_start_matches('/').to_string(),
+        title: None,
+        project_dir: Some(project.to_string()),
+        resume_command: None,
+        created_at: Some(1),
+        last_active_at: Some(10),
+        file_size: 1,
+        file_mtime: 1,
+        indexed_at: 1,
+    };
+    let message = |role: &str, content: &str, ts: i64| SessionMessage {
+        role: role.to_string(),
+        content: content.to_string(),
+        ts: Some(ts),
+        blocks: Vec::new(),
+        model: None,
+    };
+
+    db.replace_session_index(
+        &file("/a", "/work/app"),
+        &[
+            message("user", "the migration fails on startup", 100),
+            message("assistant", "Fixed the migration bug in schema.rs", 200),
+        ],
+    )
+    .expect("index a");
+    db.replace_session_index(
+        &file("/b", "/work/other"),
+        &[message("user", "迁移脚本出错", 300)],
+    )
+    .expect("index b");
+
+    let search = |query: SessionSearchQuery| {
+        let terms: Vec<String> = query.text.split_whitespace().map(str::to_string).collect();
+        db.search_session_index(&query, &terms, 50)
+            .expect("search")
+            .into_iter()
+            .map(|row| (row.session_id, row.seq))
+            .collect::<Vec<_>>()
+    };
+
+    let hits = search(SessionSearchQuery {
+        text: "migration bug".to_string(),
+        ..Default::default()
+    });
+    assert_eq!(hits, vec![("a".to_string(), 1)]);
+
+    let hits = search(SessionSearchQuery {
+        text: "migration".to_string(),
+        role: Some("user".to_string()),
+        project_dir: Some("/work/app".to_string()),
+        end_date: Some(150),
+        ..Default::default()
+    });
+    assert_eq!(hits, vec![("a".to_string(), 0)]);
+
+    // 中文子串与短关键词
+    assert_eq!(
+        search(SessionSearchQuery {
+            text: "脚本".to_string(),
+            ..Default::default()
+        }),
+        vec![("b".to_string(), 0)]
+    );
+
+    // 重新索引替换旧消息，删除后不再命中
+    db.replace_session_index(&file("/a", "/work/app"), &[message("user", "nothing", 1)])
+        .expect("reindex a");
+    assert!(search(SessionSearchQuery {
+        text: "migration".to_string(),
+        ..Default::default()
+    })
+    .is_empty());
+    db.remove_session_index("/b").expect("remove b");
+    assert_eq!(
+        db.get_session_index_files().expect("files").len(),
+        1,
+        "removed file should be dropped from the index"
+    );
+}
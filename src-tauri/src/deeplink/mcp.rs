@@ -122,6 +122,9 @@ pub fn import_mcp_from_deeplink(
                 homepage: existing.homepage.clone(),
                 docs: existing.docs.clone(),
                 tags: existing.tags.clone(),
+                tools: existing.tools.clone(),
+                disabled_tools: existing.disabled_tools.clone(),
+                projects: existing.projects.clone(),
             }
         } else {
             // New server - create with provided config
@@ -135,6 +138,9 @@ pub fn import_mcp_from_deeplink(
                 homepage: None,
                 docs: None,
                 tags: vec!["imported".to_string()],
+                tools: Vec::new(),
+                disabled_tools: Default::default(),
+                projects: Vec::new(),
             }
         };
 
@@ -5,7 +5,7 @@
 use super::utils::decode_base64_param;
 use super::DeepLinkImportRequest;
 use crate::error::AppError;
-use crate::prompt::Prompt;
+use crate::prompt::{Prompt, PromptRevisionSource};
 use crate::services::PromptService;
 use crate::store::AppState;
 use crate::AppType;
@@ -69,12 +69,20 @@ pub fn import_prompt_from_deeplink(
         enabled: false, // Always start as disabled, will be enabled later if needed
         created_at: Some(timestamp),
         updated_at: Some(timestamp),
+        sort_index: None,
+        projects: Vec::new(),
     };
 
     // Save using PromptService
-    PromptService::upsert_prompt(state, app_type.clone(), &id, prompt)?;
+    PromptService::upsert_prompt_from(
+        state,
+        app_type.clone(),
+        &id,
+        prompt,
+        PromptRevisionSource::Deeplink,
+    )?;
 
-    // If enabled flag is set, enable this prompt (which will disable others)
+    // If enabled flag is set, add this prompt to the active fragments
     if should_enable {
         PromptService::enable_prompt(state, app_type, &id)?;
         log::info!("Successfully imported and enabled prompt '{name}' for {app_str}");
@@ -228,6 +228,8 @@ fn build_provider_meta(request: &DeepLinkImportRequest) -> Result<Option<Provide
         user_id: request.usage_user_id.clone(),
         template_type: None, // Deeplink providers don't specify template type (will use backward compatibility logic)
         auto_query_interval: request.usage_auto_interval,
+        low_balance_threshold: None,
+        demote_on_low_balance: None,
     };
 
     Ok(Some(ProviderMeta {
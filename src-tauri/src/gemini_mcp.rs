@@ -36,12 +36,28 @@ fn write_json_value(path: &Path, value: &Value) -> Result<(), AppError> {
 /// - 仅有 url 字段 → 补齐 type: "sse"（Gemini 以字段名推断传输类型）
 /// - 仅有 command 字段 → 补齐 type: "stdio"
 pub fn read_mcp_servers_map() -> Result<std::collections::HashMap<String, Value>, AppError> {
-    let path = user_config_path();
+    read_mcp_servers_map_from(&user_config_path())
+}
+
+/// 读取项目级 `.gemini/settings.json` 中的 mcpServers 映射（格式转换同上）
+pub fn read_project_mcp_servers_map(
+    project_dir: &Path,
+) -> Result<std::collections::HashMap<String, Value>, AppError> {
+    read_mcp_servers_map_from(&project_settings_path(project_dir))
+}
+
+fn project_settings_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(".gemini").join("settings.json")
+}
+
+fn read_mcp_servers_map_from(
+    path: &Path,
+) -> Result<std::collections::HashMap<String, Value>, AppError> {
     if !path.exists() {
         return Ok(std::collections::HashMap::new());
     }
 
-    let root = read_json_value(&path)?;
+    let root = read_json_value(path)?;
     let mut servers: std::collections::HashMap<String, Value> = root
         .get("mcpServers")
         .and_then(|v| v.as_object())
@@ -76,9 +92,27 @@ pub fn read_mcp_servers_map() -> Result<std::collections::HashMap<String, Value>
 pub fn set_mcp_servers_map(
     servers: &std::collections::HashMap<String, Value>,
 ) -> Result<(), AppError> {
-    let path = user_config_path();
+    write_mcp_servers_map(&user_config_path(), servers)
+}
+
+/// 将 MCP 服务器映射写入项目级 `.gemini/settings.json`（格式转换同上）
+pub fn set_project_mcp_servers_map(
+    project_dir: &Path,
+    servers: &std::collections::HashMap<String, Value>,
+) -> Result<(), AppError> {
+    let path = project_settings_path(project_dir);
+    if servers.is_empty() && !path.exists() {
+        return Ok(());
+    }
+    write_mcp_servers_map(&path, servers)
+}
+
+fn write_mcp_servers_map(
+    path: &Path,
+    servers: &std::collections::HashMap<String, Value>,
+) -> Result<(), AppError> {
     let mut root = if path.exists() {
-        read_json_value(&path)?
+        read_json_value(path)?
     } else {
         serde_json::json!({})
     };
@@ -158,10 +192,10 @@ pub fn set_mcp_servers_map(
     {
         let obj = root
             .as_object_mut()
-            .ok_or_else(|| AppError::Config("~/.gemini/settings.json 根必须是对象".into()))?;
+            .ok_or_else(|| AppError::Config(format!("{} 根必须是对象", path.display())))?;
         obj.insert("mcpServers".into(), Value::Object(out));
     }
 
-    write_json_value(&path, &root)?;
+    write_json_value(path, &root)?;
     Ok(())
 }
@@ -21,13 +21,15 @@ mod prompt_files;
 mod provider;
 mod provider_defaults;
 mod proxy;
+mod secret_store;
 mod services;
+mod session_manager;
 mod settings;
 mod store;
 mod tray;
 mod usage_script;
 
-pub use app_config::{AppType, McpApps, McpServer, MultiAppConfig};
+pub use app_config::{AppType, InstalledSkill, McpApps, McpServer, MultiAppConfig, SkillApps};
 pub use codex_config::{get_codex_auth_path, get_codex_config_path, write_codex_live_atomic};
 pub use commands::open_provider_terminal;
 pub use commands::*;
@@ -41,6 +43,7 @@ pub use mcp::{
     sync_enabled_to_codex, sync_enabled_to_gemini, sync_single_server_to_claude,
     sync_single_server_to_codex, sync_single_server_to_gemini,
 };
+pub use prompt::Prompt;
 pub use provider::{Provider, ProviderMeta};
 pub use services::{
     ConfigService, EndpointLatency, McpService, PromptService, ProviderService, ProxyService,
@@ -536,6 +539,24 @@ pub fn run() {
                 Err(e) => log::warn!("✗ Failed to initialize default skill repos: {e}"),
             }
 
+            // 1.0. 将遗留的明文 Skill 仓库令牌迁移到密钥库（密钥库未就绪时下次启动重试）
+            match crate::services::skill::SkillService::migrate_repo_tokens(&app_state.db) {
+                Ok(count) if count > 0 => {
+                    log::info!("✓ Moved {count} skill repo token(s) into the secret store")
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("✗ Failed to move skill repo tokens into the secret store: {e}"),
+            }
+
+            // 1.0.1. 初始化默认 MCP 目录来源（仅补充缺失的默认来源）
+            match app_state.db.init_default_mcp_catalog_sources() {
+                Ok(count) if count > 0 => {
+                    log::info!("✓ Initialized {count} default MCP catalog sources");
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("✗ Failed to initialize default MCP catalog sources: {e}"),
+            }
+
             // 1.1. Skills 统一管理迁移：当数据库迁移到 v3 结构后，自动从各应用目录导入到 SSOT
             // 触发条件由 schema 迁移设置 settings.skills_ssot_migration_pending = true 控制。
             match app_state.db.get_setting("skills_ssot_migration_pending") {
@@ -876,6 +897,12 @@ pub fn run() {
                 restore_proxy_state_on_startup(&state).await;
             });
 
+            // 用量脚本定时查询
+            crate::services::usage_monitor::UsageMonitorService::start(app.handle().clone());
+
+            // 本地目录 Skill 来源变更监听
+            crate::services::skill::SkillService::start_local_watcher(app.handle().clone());
+
             // macOS: 确保 titleBarStyle 设置正确应用
             #[cfg(target_os = "macos")]
             if app.get_webview_window("main").is_some() {
@@ -954,6 +981,7 @@ pub fn run() {
             // usage query
             commands::queryProviderUsage,
             commands::testUsageScript,
+            commands::get_usage_templates,
             // New MCP via config.json (SSOT)
             commands::get_mcp_config,
             commands::upsert_mcp_server_in_config,
@@ -965,11 +993,45 @@ pub fn run() {
             commands::delete_mcp_server,
             commands::toggle_mcp_app,
             commands::import_mcp_from_apps,
+            commands::import_mcp_from_projects,
+            commands::get_mcp_known_projects,
+            commands::test_mcp_server,
+            commands::set_mcp_disabled_tools,
+            commands::get_mcp_catalog_sources,
+            commands::save_mcp_catalog_source,
+            commands::remove_mcp_catalog_source,
+            commands::search_mcp_catalog,
+            commands::install_mcp_from_catalog,
+            // Secret store
+            commands::get_secret_store_status,
+            commands::init_secret_store,
+            commands::unlock_secret_store,
+            commands::lock_secret_store,
+            commands::list_secrets,
+            commands::set_secret,
+            commands::delete_secret,
             // Prompt management
             commands::get_prompts,
             commands::upsert_prompt,
             commands::delete_prompt,
             commands::enable_prompt,
+            commands::disable_prompt,
+            commands::reorder_prompts,
+            commands::set_prompt_projects,
+            commands::import_prompts_from_projects,
+            commands::check_prompt_drift,
+            commands::preview_prompt,
+            commands::get_prompt_variables,
+            commands::set_prompt_variables,
+            commands::list_prompt_revisions,
+            commands::diff_prompt_revisions,
+            commands::restore_prompt_revision,
+            commands::get_shared_prompts,
+            commands::upsert_shared_prompt,
+            commands::delete_shared_prompt,
+            commands::toggle_shared_prompt_app,
+            commands::preview_prompt_dedup,
+            commands::dedup_prompts_into_shared,
             commands::import_prompt_from_file,
             commands::get_current_prompt_file_content,
             // ours: endpoint speed test + custom endpoint management
@@ -1009,8 +1071,13 @@ pub fn run() {
             // Skill management (v3.10.0+ unified)
             commands::get_installed_skills,
             commands::install_skill_unified,
+            commands::scan_skill,
             commands::uninstall_skill_unified,
+            commands::check_skill_updates,
+            commands::update_skill,
+            commands::rollback_skill,
             commands::toggle_skill_app,
+            commands::set_skill_projects,
             commands::scan_unmanaged_skills,
             commands::import_skills_from_apps,
             commands::discover_available_skills,
@@ -1025,6 +1092,7 @@ pub fn run() {
             commands::add_skill_repo,
             commands::remove_skill_repo,
             commands::install_skills_from_zip,
+            commands::scan_skills_zip,
             // Auto launch
             commands::set_auto_launch,
             commands::get_auto_launch_status,
@@ -1066,12 +1134,18 @@ pub fn run() {
             commands::get_usage_trends,
             commands::get_provider_stats,
             commands::get_model_stats,
+            commands::get_session_usage_stats,
+            commands::get_project_usage_stats,
             commands::get_request_logs,
             commands::get_request_detail,
             commands::get_model_pricing,
             commands::update_model_pricing,
             commands::delete_model_pricing,
             commands::check_provider_limits,
+            commands::get_budget_alert_config,
+            commands::save_budget_alert_config,
+            commands::get_budget_status,
+            commands::get_usage_query_history,
             // Stream health check
             commands::stream_check_provider,
             commands::stream_check_all_providers,
@@ -1096,6 +1170,21 @@ pub fn run() {
             commands::test_proxy_url,
             commands::get_upstream_proxy_status,
             commands::scan_local_proxies,
+            // Session manager
+            commands::list_sessions,
+            commands::get_session_messages,
+            commands::search_sessions,
+            commands::refresh_session_index,
+            commands::handoff_session,
+            commands::export_session,
+            commands::get_session_analytics,
+            commands::get_session_disk_usage,
+            commands::archive_sessions,
+            commands::delete_sessions,
+            commands::list_stored_sessions,
+            commands::restore_stored_session,
+            commands::empty_session_trash,
+            commands::launch_session_terminal,
         ]);
 
     let app = builder
@@ -0,0 +1,805 @@
+//! MCP 服务器目录（Catalog）解析模块
+//!
+//! 将不同格式的目录文档统一解析为 [`McpCatalogEntry`]：
+//! - 官方 MCP Registry 的 `/v0/servers` 响应（`packages` / `remotes`）
+//! - 目录清单（manifest）：`{ id, name, tags, server, inputs }`，可为单个对象或数组
+//! - `.mcp.json` 风格的 `{ "mcpServers": { ... } }` 集合
+//!
+//! 条目的 `server` 为连接模板，其中 `${input:NAME}` 由安装时填写的输入替换；
+//! 未填写的可选输入所在的 env/header 条目或参数会被移除。
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use crate::error::AppError;
+
+use super::validation::validate_server_spec;
+
+/// `${input:NAME}` 占位符
+static INPUT_REF_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\$\{input:([A-Za-z0-9_.\-]+)\}").expect("valid regex"));
+
+/// Registry 值模板中的 `{name}` 变量
+static REGISTRY_VAR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{([A-Za-z0-9_.\-]+)\}").expect("valid regex"));
+
+/// 目录来源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum McpCatalogSourceKind {
+    /// 官方 MCP Registry 格式的 HTTP 服务（location 为基础 URL）
+    Registry,
+    /// GitHub 仓库中的 JSON 清单（location 为 `owner/name`）
+    Github,
+    /// 本地目录中的 JSON 清单（location 为目录路径）
+    Local,
+}
+
+/// 目录来源配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpCatalogSource {
+    pub id: String,
+    pub name: String,
+    pub kind: McpCatalogSourceKind,
+    pub location: String,
+    /// GitHub 分支（为空时依次尝试 main/master）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub enabled: bool,
+}
+
+/// 输入类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum McpCatalogInputType {
+    #[default]
+    String,
+    Number,
+    Boolean,
+    Filepath,
+}
+
+/// 安装时需要填写的输入（环境变量、参数或请求头）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpCatalogInput {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub input_type: McpCatalogInputType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// 敏感值（界面中应隐藏，推荐填写 `${secret:name}` 引用）
+    #[serde(default)]
+    pub secret: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+}
+
+/// 目录条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpCatalogEntry {
+    /// 唯一标识：`<sourceId>:<id>`
+    pub key: String,
+    /// 建议的服务器 id
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+    /// 连接模板
+    pub server: Value,
+    #[serde(default)]
+    pub inputs: Vec<McpCatalogInput>,
+    pub source_id: String,
+}
+
+impl McpCatalogEntry {
+    /// 是否匹配搜索条件（关键字匹配 id/名称/描述/标签，标签需全部命中）
+    pub fn matches(&self, query: Option<&str>, tags: &[String]) -> bool {
+        let has_tags = tags
+            .iter()
+            .all(|tag| self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim())));
+        if !has_tags {
+            return false;
+        }
+        let Some(query) = query
+            .map(|q| q.trim().to_lowercase())
+            .filter(|q| !q.is_empty())
+        else {
+            return true;
+        };
+        self.id.to_lowercase().contains(&query)
+            || self.name.to_lowercase().contains(&query)
+            || self.description.to_lowercase().contains(&query)
+            || self.tags.iter().any(|t| t.to_lowercase().contains(&query))
+    }
+}
+
+// ============================================================================
+// 文档解析
+// ============================================================================
+
+/// 解析目录文档，无法识别的条目会被跳过
+pub fn parse_catalog_document(doc: &Value, source_id: &str) -> Vec<McpCatalogEntry> {
+    let mut out = Vec::new();
+    match doc {
+        Value::Array(items) => {
+            for item in items {
+                out.extend(parse_catalog_item(item, source_id));
+            }
+        }
+        Value::Object(obj) => {
+            if let Some(servers) = obj.get("servers").and_then(|v| v.as_array()) {
+                for item in servers {
+                    out.extend(parse_catalog_item(item, source_id));
+                }
+            } else if let Some(servers) = obj.get("mcpServers").and_then(|v| v.as_object()) {
+                for (id, spec) in servers {
+                    if validate_server_spec(spec).is_ok() {
+                        out.push(McpCatalogEntry {
+                            key: format!("{source_id}:{id}"),
+                            id: id.clone(),
+                            name: id.clone(),
+                            description: String::new(),
+                            version: None,
+                            tags: Vec::new(),
+                            homepage: None,
+                            docs: None,
+                            server: spec.clone(),
+                            inputs: Vec::new(),
+                            source_id: source_id.to_string(),
+                        });
+                    }
+                }
+            } else {
+                out.extend(parse_catalog_item(doc, source_id));
+            }
+        }
+        _ => {}
+    }
+    out
+}
+
+fn parse_catalog_item(item: &Value, source_id: &str) -> Option<McpCatalogEntry> {
+    // Registry 响应：{ server: {...}, _meta: {...} }；旧版为扁平结构
+    let registry_server = match item.get("server") {
+        Some(inner) if is_registry_server(inner) => Some(inner),
+        _ if is_registry_server(item) => Some(item),
+        _ => None,
+    };
+    if let Some(server) = registry_server {
+        if is_superseded_registry_version(item) {
+            return None;
+        }
+        return parse_registry_server(server, source_id);
+    }
+    parse_manifest(item, source_id)
+}
+
+fn is_registry_server(value: &Value) -> bool {
+    value.get("name").and_then(|v| v.as_str()).is_some()
+        && (value.get("packages").is_some_and(|v| v.is_array())
+            || value.get("remotes").is_some_and(|v| v.is_array()))
+}
+
+/// Registry 会返回同一服务器的多个版本，仅保留最新版
+fn is_superseded_registry_version(item: &Value) -> bool {
+    item.get("_meta")
+        .and_then(|m| m.get("io.modelcontextprotocol.registry/official"))
+        .and_then(|m| m.get("isLatest").or_else(|| m.get("is_latest")))
+        .and_then(|v| v.as_bool())
+        == Some(false)
+}
+
+/// 解析目录清单格式
+fn parse_manifest(item: &Value, source_id: &str) -> Option<McpCatalogEntry> {
+    #[derive(Deserialize)]
+    struct Manifest {
+        id: Option<String>,
+        name: Option<String>,
+        #[serde(default)]
+        description: String,
+        version: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        homepage: Option<String>,
+        docs: Option<String>,
+        server: Value,
+        #[serde(default)]
+        inputs: Vec<McpCatalogInput>,
+    }
+
+    let manifest: Manifest = serde_json::from_value(item.clone()).ok()?;
+    if !manifest.server.is_object() {
+        return None;
+    }
+    let id = manifest
+        .id
+        .or_else(|| manifest.name.as_deref().map(slugify))
+        .filter(|id| !id.is_empty())?;
+    Some(McpCatalogEntry {
+        key: format!("{source_id}:{id}"),
+        name: manifest.name.unwrap_or_else(|| id.clone()),
+        id,
+        description: manifest.description,
+        version: manifest.version,
+        tags: manifest.tags,
+        homepage: manifest.homepage,
+        docs: manifest.docs,
+        server: manifest.server,
+        inputs: manifest.inputs,
+        source_id: source_id.to_string(),
+    })
+}
+
+/// 读取 Registry 字段（兼容 camelCase 与旧版 snake_case）
+fn field<'a>(value: &'a Value, camel: &str, snake: &str) -> Option<&'a Value> {
+    value.get(camel).or_else(|| value.get(snake))
+}
+
+fn str_field<'a>(value: &'a Value, camel: &str, snake: &str) -> Option<&'a str> {
+    field(value, camel, snake)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty())
+}
+
+fn bool_field(value: &Value, camel: &str, snake: &str) -> bool {
+    field(value, camel, snake)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// 将 Registry 的输入定义转换为目录输入
+fn registry_input(name: &str, def: &Value) -> McpCatalogInput {
+    let input_type = match str_field(def, "format", "format") {
+        Some("number") => McpCatalogInputType::Number,
+        Some("boolean") => McpCatalogInputType::Boolean,
+        Some("filepath") => McpCatalogInputType::Filepath,
+        _ => McpCatalogInputType::String,
+    };
+    McpCatalogInput {
+        name: name.to_string(),
+        input_type,
+        description: str_field(def, "description", "description").map(str::to_string),
+        required: bool_field(def, "isRequired", "is_required"),
+        secret: bool_field(def, "isSecret", "is_secret"),
+        default: field(def, "default", "default").and_then(value_to_string),
+        choices: field(def, "choices", "choices")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(value_to_string).collect())
+            .unwrap_or_default(),
+    }
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// 转换 Registry 的值模板：`{var}` 变为 `${input:var}` 并收集变量输入
+///
+/// 没有固定值时整个值即为输入，以 `fallback_name` 命名。
+fn registry_value(def: &Value, fallback_name: &str, inputs: &mut Vec<McpCatalogInput>) -> String {
+    let Some(template) = str_field(def, "value", "value") else {
+        push_input(inputs, registry_input(fallback_name, def));
+        return format!("${{input:{fallback_name}}}");
+    };
+    let variables = field(def, "variables", "variables").and_then(|v| v.as_object());
+    REGISTRY_VAR_RE
+        .replace_all(template, |caps: &regex::Captures| {
+            let var = &caps[1];
+            match variables.and_then(|vars| vars.get(var)) {
+                Some(var_def) => {
+                    push_input(inputs, registry_input(var, var_def));
+                    format!("${{input:{var}}}")
+                }
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+fn push_input(inputs: &mut Vec<McpCatalogInput>, input: McpCatalogInput) {
+    if !inputs.iter().any(|i| i.name == input.name) {
+        inputs.push(input);
+    }
+}
+
+/// 将 Registry 参数定义追加到参数列表
+fn push_registry_arguments(
+    args: &mut Vec<Value>,
+    defs: Option<&Value>,
+    inputs: &mut Vec<McpCatalogInput>,
+) {
+    let Some(defs) = defs.and_then(|v| v.as_array()) else {
+        return;
+    };
+    for (index, def) in defs.iter().enumerate() {
+        let kind = str_field(def, "type", "type").unwrap_or("positional");
+        if kind == "named" {
+            let Some(flag) = str_field(def, "name", "name") else {
+                continue;
+            };
+            let input_name = flag.trim_start_matches('-').to_string();
+            args.push(json!(flag));
+            args.push(json!(registry_value(def, &input_name, inputs)));
+        } else {
+            let input_name = str_field(def, "valueHint", "value_hint")
+                .or_else(|| str_field(def, "name", "name"))
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("arg{}", index + 1));
+            args.push(json!(registry_value(def, &input_name, inputs)));
+        }
+    }
+}
+
+/// 将 Registry 的 package 转换为 stdio 连接模板
+fn registry_package_spec(package: &Value, inputs: &mut Vec<McpCatalogInput>) -> Option<Value> {
+    let registry_type = str_field(package, "registryType", "registry_type")
+        .or_else(|| str_field(package, "registryName", "registry_name"))?;
+    let identifier = str_field(package, "identifier", "identifier")
+        .or_else(|| str_field(package, "name", "name"))?;
+    let version = str_field(package, "version", "version");
+
+    let (default_command, package_ref) = match registry_type {
+        "npm" => (
+            "npx",
+            match version {
+                Some(v) => format!("{identifier}@{v}"),
+                None => identifier.to_string(),
+            },
+        ),
+        "pypi" => (
+            "uvx",
+            match version {
+                Some(v) => format!("{identifier}=={v}"),
+                None => identifier.to_string(),
+            },
+        ),
+        "oci" | "docker" => (
+            "docker",
+            match version {
+                Some(v) if !identifier.contains(':') => format!("{identifier}:{v}"),
+                _ => identifier.to_string(),
+            },
+        ),
+        _ => return None,
+    };
+    let command = str_field(package, "runtimeHint", "runtime_hint").unwrap_or(default_command);
+
+    let mut env = Map::new();
+    if let Some(vars) =
+        field(package, "environmentVariables", "environment_variables").and_then(|v| v.as_array())
+    {
+        for var in vars {
+            if let Some(name) = str_field(var, "name", "name") {
+                env.insert(name.to_string(), json!(registry_value(var, name, inputs)));
+            }
+        }
+    }
+
+    let mut args = Vec::new();
+    let runtime_args = field(package, "runtimeArguments", "runtime_arguments");
+    push_registry_arguments(&mut args, runtime_args, inputs);
+    match command {
+        "npx" if !args.iter().any(|a| a == "-y" || a == "--yes") => args.push(json!("-y")),
+        "docker" if runtime_args.is_none() => {
+            args.extend(["run", "-i", "--rm"].map(|a| json!(a)));
+            for name in env.keys() {
+                args.push(json!("-e"));
+                args.push(json!(name));
+            }
+        }
+        _ => {}
+    }
+    args.push(json!(package_ref));
+    push_registry_arguments(
+        &mut args,
+        field(package, "packageArguments", "package_arguments"),
+        inputs,
+    );
+
+    let mut spec = json!({ "type": "stdio", "command": command, "args": args });
+    if !env.is_empty() {
+        spec["env"] = Value::Object(env);
+    }
+    Some(spec)
+}
+
+/// 将 Registry 的 remote 转换为 http/sse 连接模板
+fn registry_remote_spec(remote: &Value, inputs: &mut Vec<McpCatalogInput>) -> Option<Value> {
+    let transport = match str_field(remote, "type", "transport_type")? {
+        "streamable-http" | "http" => "http",
+        "sse" => "sse",
+        _ => return None,
+    };
+    let url = registry_value(remote, "url", inputs);
+    let mut spec = json!({ "type": transport, "url": url });
+
+    if let Some(headers) = field(remote, "headers", "headers").and_then(|v| v.as_array()) {
+        let mut map = Map::new();
+        for header in headers {
+            if let Some(name) = str_field(header, "name", "name") {
+                map.insert(
+                    name.to_string(),
+                    json!(registry_value(header, name, inputs)),
+                );
+            }
+        }
+        if !map.is_empty() {
+            spec["headers"] = Value::Object(map);
+        }
+    }
+    Some(spec)
+}
+
+/// 解析 Registry 服务器：优先使用本地运行的 package，其次使用 remote
+fn parse_registry_server(server: &Value, source_id: &str) -> Option<McpCatalogEntry> {
+    let full_name = str_field(server, "name", "name")?;
+    let id = slugify(full_name.rsplit('/').next().unwrap_or(full_name));
+    if id.is_empty() {
+        return None;
+    }
+
+    let mut tags = Vec::new();
+    let mut inputs = Vec::new();
+    let mut spec = None;
+
+    if let Some(packages) = server.get("packages").and_then(|v| v.as_array()) {
+        for package in packages {
+            let mut package_inputs = Vec::new();
+            if let Some(s) = registry_package_spec(package, &mut package_inputs) {
+                if let Some(t) = str_field(package, "registryType", "registry_type") {
+                    tags.push(t.to_string());
+                }
+                tags.push("local".to_string());
+                inputs = package_inputs;
+                spec = Some(s);
+                break;
+            }
+        }
+    }
+    if spec.is_none() {
+        if let Some(remotes) = server.get("remotes").and_then(|v| v.as_array()) {
+            for remote in remotes {
+                let mut remote_inputs = Vec::new();
+                if let Some(s) = registry_remote_spec(remote, &mut remote_inputs) {
+                    tags.push("remote".to_string());
+                    inputs = remote_inputs;
+                    spec = Some(s);
+                    break;
+                }
+            }
+        }
+    }
+    let spec = spec?;
+
+    let repository = server
+        .get("repository")
+        .and_then(|r| str_field(r, "url", "url"))
+        .map(str::to_string);
+    Some(McpCatalogEntry {
+        key: format!("{source_id}:{full_name}"),
+        id,
+        name: str_field(server, "title", "title")
+            .unwrap_or(full_name)
+            .to_string(),
+        description: str_field(server, "description", "description")
+            .unwrap_or_default()
+            .to_string(),
+        version: str_field(server, "version", "version")
+            .or_else(|| {
+                server
+                    .get("version_detail")
+                    .and_then(|v| str_field(v, "version", "version"))
+            })
+            .map(str::to_string),
+        tags,
+        homepage: str_field(server, "websiteUrl", "website_url")
+            .map(str::to_string)
+            .or_else(|| repository.clone()),
+        docs: repository,
+        server: spec,
+        inputs,
+        source_id: source_id.to_string(),
+    })
+}
+
+/// 生成可用作服务器 id 的标识
+fn slugify(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_matches('-').to_string()
+}
+
+// ============================================================================
+// 安装：填充输入
+// ============================================================================
+
+/// 用输入值填充条目的连接模板，并校验结果
+pub fn instantiate(
+    entry: &McpCatalogEntry,
+    values: &HashMap<String, String>,
+) -> Result<Value, AppError> {
+    let provided = |name: &str| {
+        values
+            .get(name)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let mut resolved = HashMap::new();
+    for input in &entry.inputs {
+        match provided(&input.name).or_else(|| input.default.clone()) {
+            Some(value) => {
+                validate_input(input, &value)?;
+                resolved.insert(input.name.clone(), value);
+            }
+            None if input.required => return Err(missing_input_error(&input.name)),
+            None => {}
+        }
+    }
+
+    // 模板中引用了未声明的输入时视为必填
+    let mut referenced = Vec::new();
+    collect_input_refs(&entry.server, &mut referenced);
+    for name in referenced {
+        if entry.inputs.iter().any(|i| i.name == name) {
+            continue;
+        }
+        let value = provided(&name).ok_or_else(|| missing_input_error(&name))?;
+        resolved.insert(name, value);
+    }
+
+    let spec = fill_template(&entry.server, &resolved).unwrap_or(Value::Null);
+    validate_server_spec(&spec)?;
+    Ok(spec)
+}
+
+fn missing_input_error(name: &str) -> AppError {
+    AppError::localized(
+        "mcp.catalog.missingInput",
+        format!("缺少必填输入: {name}"),
+        format!("Missing required input: {name}"),
+    )
+}
+
+fn validate_input(input: &McpCatalogInput, value: &str) -> Result<(), AppError> {
+    // 密钥引用在写入 live 配置时才解析，此处不校验类型
+    if crate::secret_store::has_refs(value) {
+        return Ok(());
+    }
+    let valid = match input.input_type {
+        McpCatalogInputType::Number => value.parse::<f64>().is_ok(),
+        McpCatalogInputType::Boolean => matches!(value, "true" | "false"),
+        McpCatalogInputType::String | McpCatalogInputType::Filepath => true,
+    } && (input.choices.is_empty() || input.choices.iter().any(|c| c == value));
+    if valid {
+        return Ok(());
+    }
+    Err(AppError::localized(
+        "mcp.catalog.invalidInput",
+        format!("输入 {} 的值无效: {value}", input.name),
+        format!("Invalid value for input {}: {value}", input.name),
+    ))
+}
+
+fn collect_input_refs(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(s) => {
+            for caps in INPUT_REF_RE.captures_iter(s) {
+                if !out.iter().any(|n| n == &caps[1]) {
+                    out.push(caps[1].to_string());
+                }
+            }
+        }
+        Value::Array(arr) => arr.iter().for_each(|v| collect_input_refs(v, out)),
+        Value::Object(obj) => obj.values().for_each(|v| collect_input_refs(v, out)),
+        _ => {}
+    }
+}
+
+/// 替换占位符；引用了未填写输入的值返回 None，由上层移除
+fn fill_template(value: &Value, resolved: &HashMap<String, String>) -> Option<Value> {
+    match value {
+        Value::String(s) => {
+            let missing = INPUT_REF_RE
+                .captures_iter(s)
+                .any(|caps| !resolved.contains_key(&caps[1]));
+            if missing {
+                return None;
+            }
+            Some(Value::String(
+                INPUT_REF_RE
+                    .replace_all(s, |caps: &regex::Captures| resolved[&caps[1]].clone())
+                    .into_owned(),
+            ))
+        }
+        Value::Object(obj) => Some(Value::Object(
+            obj.iter()
+                .filter_map(|(k, v)| fill_template(v, resolved).map(|v| (k.clone(), v)))
+                .collect(),
+        )),
+        Value::Array(arr) => {
+            let mut out: Vec<Value> = Vec::new();
+            for item in arr {
+                match fill_template(item, resolved) {
+                    Some(v) => out.push(v),
+                    None => {
+                        // 可选的命名参数未填写时，一并移除其前面的 `--flag`
+                        let is_flag = out
+                            .last()
+                            .and_then(|v| v.as_str())
+                            .is_some_and(|s| s.starts_with('-'));
+                        if is_flag {
+                            out.pop();
+                        }
+                    }
+                }
+            }
+            Some(Value::Array(out))
+        }
+        other => Some(other.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_registry_response() {
+        let doc = json!({
+            "servers": [
+                {
+                    "server": {
+                        "name": "io.github.example/weather-mcp",
+                        "description": "Weather lookups",
+                        "version": "1.2.0",
+                        "repository": {"url": "https://github.com/example/weather-mcp", "source": "github"},
+                        "packages": [{
+                            "registryType": "npm",
+                            "identifier": "@example/weather-mcp",
+                            "version": "1.2.0",
+                            "transport": {"type": "stdio"},
+                            "packageArguments": [
+                                {"type": "named", "name": "--units", "isRequired": false, "choices": ["metric", "imperial"]}
+                            ],
+                            "environmentVariables": [
+                                {"name": "WEATHER_API_KEY", "isRequired": true, "isSecret": true}
+                            ]
+                        }]
+                    },
+                    "_meta": {"io.modelcontextprotocol.registry/official": {"isLatest": true}}
+                },
+                {
+                    "server": {
+                        "name": "io.github.example/weather-mcp",
+                        "version": "1.1.0",
+                        "packages": [{"registryType": "npm", "identifier": "@example/weather-mcp"}]
+                    },
+                    "_meta": {"io.modelcontextprotocol.registry/official": {"isLatest": false}}
+                },
+                {
+                    "server": {
+                        "name": "com.example/search",
+                        "remotes": [{
+                            "type": "streamable-http",
+                            "url": "https://mcp.example.com/mcp",
+                            "headers": [{"name": "Authorization", "value": "Bearer {token}", "variables": {"token": {"isRequired": true, "isSecret": true}}}]
+                        }]
+                    }
+                }
+            ],
+            "metadata": {"count": 3}
+        });
+
+        let entries = parse_catalog_document(&doc, "official");
+        assert_eq!(entries.len(), 2);
+
+        let weather = &entries[0];
+        assert_eq!(weather.id, "weather-mcp");
+        assert_eq!(weather.key, "official:io.github.example/weather-mcp");
+        assert_eq!(
+            weather.server,
+            json!({
+                "type": "stdio",
+                "command": "npx",
+                "args": ["-y", "@example/weather-mcp@1.2.0", "--units", "${input:units}"],
+                "env": {"WEATHER_API_KEY": "${input:WEATHER_API_KEY}"}
+            })
+        );
+        assert!(weather
+            .inputs
+            .iter()
+            .any(|i| i.name == "WEATHER_API_KEY" && i.secret));
+        assert_eq!(weather.tags, vec!["npm", "local"]);
+
+        let search = &entries[1];
+        assert_eq!(search.server["type"], "http");
+        assert_eq!(
+            search.server["headers"]["Authorization"],
+            "Bearer ${input:token}"
+        );
+    }
+
+    #[test]
+    fn test_instantiate_drops_missing_optional_inputs() {
+        let entry = parse_manifest(
+            &json!({
+                "id": "github",
+                "tags": ["git"],
+                "server": {
+                    "command": "npx",
+                    "args": ["-y", "server-github", "--host", "${input:HOST}"],
+                    "env": {
+                        "GITHUB_TOKEN": "${input:GITHUB_TOKEN}",
+                        "GITHUB_ORG": "${input:GITHUB_ORG}"
+                    }
+                },
+                "inputs": [
+                    {"name": "GITHUB_TOKEN", "required": true, "secret": true},
+                    {"name": "GITHUB_ORG"},
+                    {"name": "HOST", "default": "github.com"}
+                ]
+            }),
+            "local",
+        )
+        .unwrap();
+        assert!(entry.matches(Some("git"), &["GIT".to_string()]));
+
+        assert!(instantiate(&entry, &HashMap::new()).is_err());
+
+        let values = HashMap::from([("GITHUB_TOKEN".to_string(), "${secret:github}".to_string())]);
+        let spec = instantiate(&entry, &values).unwrap();
+        assert_eq!(
+            spec,
+            json!({
+                "command": "npx",
+                "args": ["-y", "server-github", "--host", "github.com"],
+                "env": {"GITHUB_TOKEN": "${secret:github}"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_typed_inputs() {
+        let input = McpCatalogInput {
+            name: "PORT".to_string(),
+            input_type: McpCatalogInputType::Number,
+            description: None,
+            required: true,
+            secret: false,
+            default: None,
+            choices: Vec::new(),
+        };
+        assert!(validate_input(&input, "8080").is_ok());
+        assert!(validate_input(&input, "abc").is_err());
+    }
+}
@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use crate::app_config::{McpApps, McpConfig, McpServer, MultiAppConfig};
 use crate::error::AppError;
 
+use super::tool_filter;
 use super::validation::{extract_server_spec, validate_server_spec};
 
 fn should_sync_claude_mcp() -> bool {
@@ -42,8 +43,23 @@ pub fn sync_enabled_to_claude(config: &MultiAppConfig) -> Result<(), AppError> {
     if !should_sync_claude_mcp() {
         return Ok(());
     }
-    let enabled = collect_enabled_servers(&config.mcp.claude);
-    crate::claude_mcp::set_mcp_servers_map(&enabled)
+    let mut enabled = collect_enabled_servers(&config.mcp.claude);
+    let filters = take_tool_filters(&mut enabled);
+    crate::claude_mcp::set_mcp_servers_map(&enabled)?;
+    tool_filter::sync_claude_tool_permissions(&filters)
+}
+
+/// 从服务器规范中取出工具过滤（~/.claude.json 不支持该字段，改写入 permissions.deny）
+fn take_tool_filters(servers: &mut HashMap<String, Value>) -> HashMap<String, Vec<String>> {
+    let mut filters = HashMap::new();
+    for (id, spec) in servers.iter_mut() {
+        if let Some(obj) = spec.as_object_mut() {
+            if let Some(tools) = tool_filter::take_disabled_tools(obj) {
+                filters.insert(id.clone(), tools);
+            }
+        }
+    }
+    filters
 }
 
 /// 从 ~/.claude.json 导入 mcpServers 到统一结构（v3.7.0+）
@@ -97,6 +113,9 @@ pub fn import_from_claude(config: &mut MultiAppConfig) -> Result<usize, AppError
                     homepage: None,
                     docs: None,
                     tags: Vec::new(),
+                    tools: Vec::new(),
+                    disabled_tools: Default::default(),
+                    projects: Vec::new(),
                 },
             );
             changed += 1;
@@ -126,9 +145,11 @@ pub fn sync_single_server_to_claude(
     // 创建新的 HashMap，包含现有的所有服务器 + 当前要同步的服务器
     let mut updated = current;
     updated.insert(id.to_string(), server_spec.clone());
+    let filters = take_tool_filters(&mut updated);
 
     // 写回
-    crate::claude_mcp::set_mcp_servers_map(&updated)
+    crate::claude_mcp::set_mcp_servers_map(&updated)?;
+    tool_filter::sync_claude_tool_permissions(&filters)
 }
 
 /// 从 Claude live 配置中移除单个 MCP 服务器
@@ -142,6 +163,7 @@ pub fn remove_server_from_claude(id: &str) -> Result<(), AppError> {
     // 移除指定服务器
     current.remove(id);
 
-    // 写回
-    crate::claude_mcp::set_mcp_servers_map(&current)
+    // 写回，并清理该服务器的工具拒绝规则
+    crate::claude_mcp::set_mcp_servers_map(&current)?;
+    tool_filter::sync_claude_tool_permissions(&HashMap::from([(id.to_string(), Vec::new())]))
 }
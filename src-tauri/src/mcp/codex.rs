@@ -8,9 +8,10 @@
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
-use crate::app_config::{McpApps, McpConfig, McpServer, MultiAppConfig};
+use crate::app_config::{AppType, McpApps, McpConfig, McpServer, McpToolFilters, MultiAppConfig};
 use crate::error::AppError;
 
+use super::tool_filter;
 use super::validation::{extract_server_spec, validate_server_spec};
 
 fn should_sync_codex_mcp() -> bool {
@@ -208,6 +209,12 @@ pub fn import_from_codex(config: &mut MultiAppConfig) -> Result<usize, AppError>
                 }
             }
 
+            // disabled_tools → 统一结构的按应用工具过滤
+            let disabled_tools = spec
+                .remove("disabled_tools")
+                .map(|v| tool_filter::string_list(&v))
+                .unwrap_or_default();
+
             let spec_v = serde_json::Value::Object(spec);
 
             // 校验：单项失败继续处理
@@ -225,6 +232,8 @@ pub fn import_from_codex(config: &mut MultiAppConfig) -> Result<usize, AppError>
                 }
             } else {
                 // 新建服务器：默认仅启用 Codex
+                let mut filters = McpToolFilters::default();
+                filters.set_for(&AppType::Codex, disabled_tools);
                 servers.insert(
                     id.clone(),
                     McpServer {
@@ -241,6 +250,9 @@ pub fn import_from_codex(config: &mut MultiAppConfig) -> Result<usize, AppError>
                         homepage: None,
                         docs: None,
                         tags: Vec::new(),
+                        tools: Vec::new(),
+                        disabled_tools: filters,
+                        projects: Vec::new(),
                     },
                 );
                 changed += 1;
@@ -661,6 +673,18 @@ fn json_server_to_toml_table(spec: &Value) -> Result<toml_edit::Table, AppError>
                 continue;
             }
 
+            // 工具过滤：disabledTools → disabled_tools（空列表时不写入）
+            if key == tool_filter::DISABLED_TOOLS_KEY {
+                continue;
+            }
+            if key == "disabled_tools"
+                && obj
+                    .get(tool_filter::DISABLED_TOOLS_KEY)
+                    .is_some_and(|v| !tool_filter::string_list(v).is_empty())
+            {
+                continue;
+            }
+
             // 尝试使用通用转换器
             if let Some(toml_item) = json_value_to_toml_item(value, key) {
                 t[&key[..]] = toml_item;
@@ -675,5 +699,16 @@ fn json_server_to_toml_table(spec: &Value) -> Result<toml_edit::Table, AppError>
         }
     }
 
+    if let Some(disabled) = spec.get(tool_filter::DISABLED_TOOLS_KEY) {
+        let tools = tool_filter::string_list(disabled);
+        if !tools.is_empty() {
+            let mut arr = Array::default();
+            for tool in &tools {
+                arr.push(tool.as_str());
+            }
+            t["disabled_tools"] = Item::Value(toml_edit::Value::Array(arr));
+        }
+    }
+
     Ok(t)
 }
@@ -3,9 +3,10 @@
 use serde_json::Value;
 use std::collections::HashMap;
 
-use crate::app_config::{McpApps, McpConfig, McpServer, MultiAppConfig};
+use crate::app_config::{AppType, McpApps, McpConfig, McpServer, McpToolFilters, MultiAppConfig};
 use crate::error::AppError;
 
+use super::tool_filter;
 use super::validation::{extract_server_spec, validate_server_spec};
 
 fn should_sync_gemini_mcp() -> bool {
@@ -42,10 +43,25 @@ pub fn sync_enabled_to_gemini(config: &MultiAppConfig) -> Result<(), AppError> {
     if !should_sync_gemini_mcp() {
         return Ok(());
     }
-    let enabled = collect_enabled_servers(&config.mcp.gemini);
+    let mut enabled = collect_enabled_servers(&config.mcp.gemini);
+    for spec in enabled.values_mut() {
+        apply_exclude_tools(spec);
+    }
     crate::gemini_mcp::set_mcp_servers_map(&enabled)
 }
 
+/// 工具过滤：disabledTools → excludeTools（Gemini CLI 原生字段）
+pub(super) fn apply_exclude_tools(spec: &mut Value) {
+    let Some(obj) = spec.as_object_mut() else {
+        return;
+    };
+    if let Some(tools) = tool_filter::take_disabled_tools(obj) {
+        if !tools.is_empty() {
+            obj.insert("excludeTools".into(), serde_json::json!(tools));
+        }
+    }
+}
+
 /// 从 Gemini MCP 配置导入到统一结构（v3.7.0+）
 /// 已存在的服务器将启用 Gemini 应用，不覆盖其他字段和应用状态
 pub fn import_from_gemini(config: &mut MultiAppConfig) -> Result<usize, AppError> {
@@ -76,13 +92,18 @@ pub fn import_from_gemini(config: &mut MultiAppConfig) -> Result<usize, AppError
                 log::info!("MCP 服务器 '{id}' 已启用 Gemini 应用");
             }
         } else {
-            // 新建服务器：默认仅启用 Gemini
+            // 新建服务器：默认仅启用 Gemini；excludeTools 转为统一结构的工具过滤
+            let mut spec = spec.clone();
+            let mut filters = McpToolFilters::default();
+            if let Some(excluded) = spec.as_object_mut().and_then(|o| o.remove("excludeTools")) {
+                filters.set_for(&AppType::Gemini, tool_filter::string_list(&excluded));
+            }
             servers.insert(
                 id.clone(),
                 McpServer {
                     id: id.clone(),
                     name: id.clone(),
-                    server: spec.clone(),
+                    server: spec,
                     apps: McpApps {
                         claude: false,
                         codex: false,
@@ -93,6 +114,9 @@ pub fn import_from_gemini(config: &mut MultiAppConfig) -> Result<usize, AppError
                     homepage: None,
                     docs: None,
                     tags: Vec::new(),
+                    tools: Vec::new(),
+                    disabled_tools: filters,
+                    projects: Vec::new(),
                 },
             );
             changed += 1;
@@ -120,7 +144,9 @@ pub fn sync_single_server_to_gemini(
     let mut current = crate::gemini_mcp::read_mcp_servers_map()?;
 
     // 添加/更新当前服务器
-    current.insert(id.to_string(), server_spec.clone());
+    let mut spec = server_spec.clone();
+    apply_exclude_tools(&mut spec);
+    current.insert(id.to_string(), spec);
 
     // 写回
     crate::gemini_mcp::set_mcp_servers_map(&current)
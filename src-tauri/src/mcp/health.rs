@@ -0,0 +1,876 @@
+//! MCP 服务器健康检查
+//!
+//! 实际启动 stdio 服务器或连接 http/sse 服务器，完成 MCP `initialize` +
+//! `tools/list` 握手，报告工具列表、耗时与 stderr 输出。
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::validation::validate_server_spec;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// 握手使用的协议版本
+const PROTOCOL_VERSION: &str = "2025-03-26";
+/// stderr 最多保留的字节数
+const MAX_STDERR_BYTES: usize = 8 * 1024;
+/// 默认超时（秒）
+pub const DEFAULT_TIMEOUT_SECS: u64 = 15;
+/// `tools/list` 最多翻页次数，避免服务器反复返回游标导致死循环
+const MAX_TOOL_PAGES: u64 = 20;
+
+/// MCP 工具信息
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// 健康检查结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpHealthReport {
+    pub ok: bool,
+    /// stdio / http / sse
+    pub transport: String,
+    /// 从开始连接到 tools/list 返回的耗时
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    pub tools: Vec<McpToolInfo>,
+    /// stdio 服务器的 stderr 输出（截断）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 握手成功后得到的信息
+#[derive(Debug, Default)]
+struct Handshake {
+    server_name: Option<String>,
+    server_version: Option<String>,
+    protocol_version: Option<String>,
+    tools: Vec<McpToolInfo>,
+}
+
+/// 检查单个 MCP 服务器
+///
+/// `spec` 为统一结构中的 `server` 字段（stdio/http/sse）。
+pub async fn check_server(spec: &Value, timeout: Duration) -> McpHealthReport {
+    let transport = spec
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or("stdio")
+        .to_string();
+    let started = Instant::now();
+
+    if let Err(e) = validate_server_spec(spec) {
+        return McpHealthReport::failed(transport, started, e.to_string(), None);
+    }
+
+    let deadline = started + timeout;
+    let (result, stderr) = match transport.as_str() {
+        "http" => (probe_http(spec, deadline).await, None),
+        "sse" => (probe_sse(spec, deadline).await, None),
+        _ => {
+            let spec = spec.clone();
+            match tokio::task::spawn_blocking(move || probe_stdio(&spec, deadline)).await {
+                Ok((result, stderr)) => (result, stderr),
+                Err(e) => (Err(format!("健康检查任务异常: {e}")), None),
+            }
+        }
+    };
+
+    match result {
+        Ok(handshake) => McpHealthReport {
+            ok: true,
+            transport,
+            latency_ms: started.elapsed().as_millis() as u64,
+            server_name: handshake.server_name,
+            server_version: handshake.server_version,
+            protocol_version: handshake.protocol_version,
+            tools: handshake.tools,
+            stderr,
+            error: None,
+        },
+        Err(e) => McpHealthReport::failed(transport, started, e, stderr),
+    }
+}
+
+impl McpHealthReport {
+    fn failed(transport: String, started: Instant, error: String, stderr: Option<String>) -> Self {
+        Self {
+            ok: false,
+            transport,
+            latency_ms: started.elapsed().as_millis() as u64,
+            server_name: None,
+            server_version: None,
+            protocol_version: None,
+            tools: Vec::new(),
+            stderr,
+            error: Some(error),
+        }
+    }
+}
+
+fn initialize_request(id: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {
+                "name": "cc-switch",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        },
+    })
+}
+
+fn initialized_notification() -> Value {
+    json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })
+}
+
+fn tools_list_request(id: u64, cursor: Option<&str>) -> Value {
+    let params = match cursor {
+        Some(cursor) => json!({ "cursor": cursor }),
+        None => json!({}),
+    };
+    json!({ "jsonrpc": "2.0", "id": id, "method": "tools/list", "params": params })
+}
+
+/// 按 `nextCursor` 逐页请求 `tools/list`
+struct ToolPager {
+    pages: u64,
+    cursor: Option<String>,
+    done: bool,
+}
+
+impl ToolPager {
+    fn new() -> Self {
+        Self {
+            pages: 0,
+            cursor: None,
+            done: false,
+        }
+    }
+
+    /// 下一页的请求 id 与请求体；全部取完或达到页数上限时返回 None
+    fn next_request(&mut self) -> Option<(u64, Value)> {
+        if self.done {
+            return None;
+        }
+        if self.pages >= MAX_TOOL_PAGES {
+            log::warn!("tools/list 翻页超过 {MAX_TOOL_PAGES} 次，忽略剩余工具");
+            return None;
+        }
+        // initialize 占用 id 1
+        let id = self.pages + 2;
+        self.pages += 1;
+        Some((id, tools_list_request(id, self.cursor.as_deref())))
+    }
+
+    fn apply(&mut self, handshake: &mut Handshake, result: &Value) {
+        handshake.tools.extend(parse_tools(result));
+        self.cursor = result
+            .get("nextCursor")
+            .and_then(Value::as_str)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string);
+        self.done = self.cursor.is_none();
+    }
+}
+
+/// 从 JSON-RPC 响应中取出 result，error 转为错误文本
+fn rpc_result(message: Value) -> Result<Value, String> {
+    if let Some(error) = message.get("error") {
+        let text = error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        return Err(format!("服务器返回错误: {text}"));
+    }
+    message
+        .get("result")
+        .cloned()
+        .ok_or_else(|| "响应缺少 result 字段".to_string())
+}
+
+fn apply_initialize(handshake: &mut Handshake, result: &Value) {
+    handshake.protocol_version = result
+        .get("protocolVersion")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    if let Some(info) = result.get("serverInfo") {
+        handshake.server_name = info.get("name").and_then(Value::as_str).map(str::to_string);
+        handshake.server_version = info
+            .get("version")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+    }
+}
+
+fn parse_tools(result: &Value) -> Vec<McpToolInfo> {
+    result
+        .get("tools")
+        .and_then(Value::as_array)
+        .map(|tools| {
+            tools
+                .iter()
+                .filter_map(|t| serde_json::from_value(t.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn remaining(deadline: Instant) -> Result<Duration, String> {
+    let left = deadline.saturating_duration_since(Instant::now());
+    if left.is_zero() {
+        Err("握手超时".to_string())
+    } else {
+        Ok(left)
+    }
+}
+
+// ============================================================================
+// stdio
+// ============================================================================
+
+fn build_stdio_command(spec: &Value) -> Command {
+    let program = spec
+        .get("command")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let args: Vec<String> = spec
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|a| {
+            a.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Windows 下 npx/uvx 等通常是 .cmd 脚本，需要经 cmd /C 启动
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        use std::os::windows::process::CommandExt;
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(program).args(&args);
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        cmd
+    };
+
+    // 独立进程组，结束时连同 npx/uvx 启动的子进程一起终止
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        use std::os::unix::process::CommandExt;
+        let mut cmd = Command::new(program);
+        cmd.args(&args).process_group(0);
+        cmd
+    };
+
+    if let Some(env) = spec.get("env").and_then(Value::as_object) {
+        for (k, v) in env {
+            if let Some(v) = v.as_str() {
+                cmd.env(k, v);
+            }
+        }
+    }
+    if let Some(cwd) = spec.get("cwd").and_then(Value::as_str) {
+        if !cwd.trim().is_empty() {
+            cmd.current_dir(cwd);
+        }
+    }
+
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    cmd
+}
+
+/// 启动 stdio 服务器并完成握手，返回握手结果与 stderr
+fn probe_stdio(spec: &Value, deadline: Instant) -> (Result<Handshake, String>, Option<String>) {
+    let mut child = match build_stdio_command(spec).spawn() {
+        Ok(child) => child,
+        Err(e) => return (Err(format!("启动进程失败: {e}")), None),
+    };
+    #[cfg(target_os = "windows")]
+    let job = ProcessJob::assign(&child);
+
+    let stderr_buf = Arc::new(Mutex::new(Vec::<u8>::new()));
+    if let Some(mut stderr) = child.stderr.take() {
+        let buf = stderr_buf.clone();
+        std::thread::spawn(move || {
+            let mut chunk = [0u8; 1024];
+            while let Ok(n) = stderr.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                if let Ok(mut guard) = buf.lock() {
+                    let room = MAX_STDERR_BYTES.saturating_sub(guard.len());
+                    guard.extend_from_slice(&chunk[..n.min(room)]);
+                }
+            }
+        });
+    }
+
+    let result = stdio_handshake(&mut child, deadline);
+
+    #[cfg(target_os = "windows")]
+    if let Some(job) = &job {
+        job.terminate();
+    }
+    kill_process_tree(&mut child);
+    let _ = child.wait();
+    // 给 stderr 线程一点时间读完剩余输出
+    std::thread::sleep(Duration::from_millis(50));
+
+    let stderr = stderr_buf
+        .lock()
+        .ok()
+        .map(|buf| String::from_utf8_lossy(&buf).trim().to_string())
+        .filter(|s| !s.is_empty());
+
+    (result, stderr)
+}
+
+/// 结束子进程及其派生的进程（Unix 下按进程组终止）
+fn kill_process_tree(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        // 子进程以自身 pid 作为进程组 id
+        libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+/// 包含子进程整棵进程树的作业对象（Windows 没有进程组的终止语义）
+#[cfg(target_os = "windows")]
+struct ProcessJob(windows::Win32::Foundation::HANDLE);
+
+#[cfg(target_os = "windows")]
+impl ProcessJob {
+    fn assign(child: &Child) -> Option<Self> {
+        use std::os::windows::io::AsRawHandle;
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::System::JobObjects::{AssignProcessToJobObject, CreateJobObjectW};
+
+        unsafe {
+            let job = CreateJobObjectW(None, windows::core::PCWSTR::null()).ok()?;
+            if AssignProcessToJobObject(job, HANDLE(child.as_raw_handle())).is_err() {
+                let _ = CloseHandle(job);
+                return None;
+            }
+            Some(Self(job))
+        }
+    }
+
+    fn terminate(&self) {
+        unsafe {
+            let _ = windows::Win32::System::JobObjects::TerminateJobObject(self.0, 1);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for ProcessJob {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = windows::Win32::Foundation::CloseHandle(self.0);
+        }
+    }
+}
+
+fn stdio_handshake(child: &mut Child, deadline: Instant) -> Result<Handshake, String> {
+    let mut stdin = child.stdin.take().ok_or("无法获取 stdin")?;
+    let stdout = child.stdout.take().ok_or("无法获取 stdout")?;
+
+    let (tx, rx) = mpsc::channel::<String>();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            match line {
+                Ok(line) => {
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    });
+
+    let mut send = |message: Value| -> Result<(), String> {
+        writeln!(stdin, "{message}")
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("写入 stdin 失败: {e}"))
+    };
+
+    let mut handshake = Handshake::default();
+
+    send(initialize_request(1))?;
+    let init = rpc_result(wait_stdio_response(&rx, 1, deadline)?)?;
+    apply_initialize(&mut handshake, &init);
+
+    send(initialized_notification())?;
+    let mut pager = ToolPager::new();
+    while let Some((id, request)) = pager.next_request() {
+        send(request)?;
+        let tools = rpc_result(wait_stdio_response(&rx, id, deadline)?)?;
+        pager.apply(&mut handshake, &tools);
+    }
+
+    Ok(handshake)
+}
+
+/// 等待指定 id 的响应，忽略日志行与通知
+fn wait_stdio_response(
+    rx: &mpsc::Receiver<String>,
+    id: u64,
+    deadline: Instant,
+) -> Result<Value, String> {
+    loop {
+        let line = match rx.recv_timeout(remaining(deadline)?) {
+            Ok(line) => line,
+            Err(mpsc::RecvTimeoutError::Timeout) => return Err("握手超时".to_string()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Err("服务器进程已退出".to_string()),
+        };
+        let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        if message.get("id").and_then(Value::as_u64) == Some(id) {
+            return Ok(message);
+        }
+    }
+}
+
+// ============================================================================
+// http / sse
+// ============================================================================
+
+fn spec_headers(spec: &Value) -> HashMap<String, String> {
+    spec.get("headers")
+        .and_then(Value::as_object)
+        .map(|h| {
+            h.iter()
+                .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn spec_url(spec: &Value) -> String {
+    spec.get("url")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// 增量解析 SSE 事件流
+struct SseReader<S> {
+    stream: S,
+    buf: String,
+}
+
+impl<S> SseReader<S>
+where
+    S: futures::Stream<Item = reqwest::Result<bytes::Bytes>> + Unpin,
+{
+    fn new(stream: S) -> Self {
+        Self {
+            stream,
+            buf: String::new(),
+        }
+    }
+
+    /// 读取下一个事件，返回 (event, data)
+    async fn next_event(&mut self, deadline: Instant) -> Result<(String, String), String> {
+        loop {
+            if let Some(pos) = self.buf.find("\n\n") {
+                let raw: String = self.buf.drain(..pos + 2).collect();
+                let mut event = "message".to_string();
+                let mut data = Vec::new();
+                for line in raw.lines() {
+                    if let Some(v) = line.strip_prefix("event:") {
+                        event = v.trim().to_string();
+                    } else if let Some(v) = line.strip_prefix("data:") {
+                        data.push(v.trim_start().to_string());
+                    }
+                }
+                if !data.is_empty() {
+                    return Ok((event, data.join("\n")));
+                }
+                continue;
+            }
+
+            let chunk = tokio::time::timeout(remaining(deadline)?, self.stream.next())
+                .await
+                .map_err(|_| "握手超时".to_string())?;
+            match chunk {
+                Some(Ok(bytes)) => {
+                    self.buf
+                        .push_str(&String::from_utf8_lossy(&bytes).replace("\r\n", "\n"));
+                }
+                Some(Err(e)) => return Err(format!("读取事件流失败: {e}")),
+                None => return Err("事件流已关闭".to_string()),
+            }
+        }
+    }
+
+    /// 读取直到出现指定 id 的 JSON-RPC 消息
+    async fn wait_response(&mut self, id: u64, deadline: Instant) -> Result<Value, String> {
+        loop {
+            let (_, data) = self.next_event(deadline).await?;
+            let Ok(message) = serde_json::from_str::<Value>(&data) else {
+                continue;
+            };
+            if message.get("id").and_then(Value::as_u64) == Some(id) {
+                return Ok(message);
+            }
+        }
+    }
+}
+
+/// Streamable HTTP：每个请求 POST 到同一端点，响应可能是 JSON 或 SSE
+async fn probe_http(spec: &Value, deadline: Instant) -> Result<Handshake, String> {
+    let client = crate::proxy::http_client::get();
+    let url = spec_url(spec);
+    let headers = spec_headers(spec);
+    let mut session_id: Option<String> = None;
+
+    let post = |session_id: Option<&str>, body: Value| {
+        let mut req = client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream")
+            .json(&body);
+        for (k, v) in &headers {
+            req = req.header(k, v);
+        }
+        if let Some(sid) = session_id {
+            req = req.header("Mcp-Session-Id", sid);
+        }
+        req
+    };
+
+    let mut handshake = Handshake::default();
+
+    let resp = send(post(None, initialize_request(1)), deadline).await?;
+    if let Some(sid) = resp
+        .headers()
+        .get("mcp-session-id")
+        .and_then(|v| v.to_str().ok())
+    {
+        session_id = Some(sid.to_string());
+    }
+    let init = rpc_result(read_http_response(resp, 1, deadline).await?)?;
+    apply_initialize(&mut handshake, &init);
+
+    send(
+        post(session_id.as_deref(), initialized_notification()),
+        deadline,
+    )
+    .await?;
+
+    let mut pager = ToolPager::new();
+    while let Some((id, request)) = pager.next_request() {
+        let resp = send(post(session_id.as_deref(), request), deadline).await?;
+        let tools = rpc_result(read_http_response(resp, id, deadline).await?)?;
+        pager.apply(&mut handshake, &tools);
+    }
+
+    Ok(handshake)
+}
+
+async fn send(
+    req: reqwest::RequestBuilder,
+    deadline: Instant,
+) -> Result<reqwest::Response, String> {
+    let resp = req
+        .timeout(remaining(deadline)?)
+        .send()
+        .await
+        .map_err(|e| format!("请求失败: {e}"))?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        let preview: String = text.chars().take(200).collect();
+        return Err(format!("HTTP {status}: {preview}"));
+    }
+    Ok(resp)
+}
+
+async fn read_http_response(
+    resp: reqwest::Response,
+    id: u64,
+    deadline: Instant,
+) -> Result<Value, String> {
+    let is_sse = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("text/event-stream"));
+
+    if is_sse {
+        return SseReader::new(resp.bytes_stream())
+            .wait_response(id, deadline)
+            .await;
+    }
+
+    let text = resp
+        .text()
+        .await
+        .map_err(|e| format!("读取响应失败: {e}"))?;
+    serde_json::from_str(&text).map_err(|e| format!("解析响应失败: {e}"))
+}
+
+/// 旧版 SSE：GET 建立事件流，`endpoint` 事件给出 POST 地址，响应经事件流返回
+async fn probe_sse(spec: &Value, deadline: Instant) -> Result<Handshake, String> {
+    let client = crate::proxy::http_client::get();
+    let url = spec_url(spec);
+    let headers = spec_headers(spec);
+
+    let mut get = client.get(&url).header("Accept", "text/event-stream");
+    for (k, v) in &headers {
+        get = get.header(k, v);
+    }
+    let resp = send(get, deadline).await?;
+    let mut reader = SseReader::new(resp.bytes_stream());
+
+    let endpoint = loop {
+        let (event, data) = reader.next_event(deadline).await?;
+        if event == "endpoint" {
+            break data;
+        }
+    };
+    let endpoint = url::Url::parse(&url)
+        .and_then(|base| base.join(endpoint.trim()))
+        .map_err(|e| format!("无效的 endpoint: {e}"))?;
+
+    let post = |body: Value| {
+        let mut req = client
+            .post(endpoint.clone())
+            .header("Content-Type", "application/json")
+            .json(&body);
+        for (k, v) in &headers {
+            req = req.header(k, v);
+        }
+        req
+    };
+
+    let mut handshake = Handshake::default();
+
+    send(post(initialize_request(1)), deadline).await?;
+    let init = rpc_result(reader.wait_response(1, deadline).await?)?;
+    apply_initialize(&mut handshake, &init);
+
+    send(post(initialized_notification()), deadline).await?;
+    let mut pager = ToolPager::new();
+    while let Some((id, request)) = pager.next_request() {
+        send(post(request), deadline).await?;
+        let tools = rpc_result(reader.wait_response(id, deadline).await?)?;
+        pager.apply(&mut handshake, &tools);
+    }
+
+    Ok(handshake)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    const INIT_RESPONSE: &str = r#"{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"fake","version":"0.1.0"}}}"#;
+    const TOOLS_RESPONSE: &str = r#"{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo","description":"Echo input","inputSchema":{"type":"object"}},{"name":"ping","inputSchema":{"type":"object"}}]}}"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_handshake_with_fake_server() {
+        // 极简 stdio MCP 服务器：读取 initialize / initialized / tools/list 三行并回复
+        let script = format!(
+            "read _; echo 'starting fake server' >&2; echo '{INIT_RESPONSE}'; \
+             read _; read _; echo 'not json log line'; echo '{TOOLS_RESPONSE}'; sleep 5"
+        );
+        let spec = json!({ "type": "stdio", "command": "sh", "args": ["-c", script] });
+
+        let report = check_server(&spec, Duration::from_secs(5)).await;
+        assert!(report.ok, "handshake should succeed: {:?}", report.error);
+        assert_eq!(report.server_name.as_deref(), Some("fake"));
+        assert_eq!(report.protocol_version.as_deref(), Some("2025-03-26"));
+        assert_eq!(report.tools.len(), 2);
+        assert_eq!(report.tools[0].name, "echo");
+        assert_eq!(report.tools[0].description.as_deref(), Some("Echo input"));
+        assert!(report
+            .stderr
+            .as_deref()
+            .is_some_and(|s| s.contains("starting fake server")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_tools_list_follows_next_cursor() {
+        let page1 = r#"{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo"}],"nextCursor":"page-2"}}"#;
+        let page2 = r#"{"jsonrpc":"2.0","id":3,"result":{"tools":[{"name":"ping"}]}}"#;
+        // 第二页请求需带上游标
+        let script = format!(
+            "read _; echo '{INIT_RESPONSE}'; read _; read _; echo '{page1}'; \
+             read req; case \"$req\" in *page-2*) echo '{page2}';; esac; sleep 5"
+        );
+        let spec = json!({ "command": "sh", "args": ["-c", script] });
+
+        let report = check_server(&spec, Duration::from_secs(5)).await;
+        assert!(report.ok, "handshake should succeed: {:?}", report.error);
+        let names: Vec<&str> = report.tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["echo", "ping"]);
+    }
+
+    #[test]
+    fn test_tool_pager_stops_at_page_limit() {
+        let mut pager = ToolPager::new();
+        let mut handshake = Handshake::default();
+        let result = json!({ "tools": [{ "name": "loop" }], "nextCursor": "again" });
+        let mut pages = 0;
+        while let Some((_, request)) = pager.next_request() {
+            if pages > 0 {
+                assert_eq!(request["params"]["cursor"], "again");
+            }
+            pager.apply(&mut handshake, &result);
+            pages += 1;
+        }
+        assert_eq!(pages, MAX_TOOL_PAGES);
+        assert_eq!(handshake.tools.len(), MAX_TOOL_PAGES as usize);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_timeout() {
+        let spec = json!({ "command": "sh", "args": ["-c", "sleep 5"] });
+        let started = Instant::now();
+        let report = check_server(&spec, Duration::from_millis(300)).await;
+        assert!(!report.ok);
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_probe_kills_grandchildren() {
+        // 模拟 npx：shell 再启动一个长时间运行的子进程，并把其 pid 写到 stderr
+        let spec = json!({
+            "command": "sh",
+            "args": ["-c", "sleep 30 & echo $! >&2; wait"]
+        });
+        let report = check_server(&spec, Duration::from_millis(500)).await;
+        assert!(!report.ok);
+        let pid: libc::pid_t = report
+            .stderr
+            .as_deref()
+            .and_then(|s| s.trim().parse().ok())
+            .expect("grandchild pid on stderr");
+
+        // 已退出但尚未被回收的进程（zombie）视为已结束
+        let alive = || {
+            let exists = unsafe { libc::kill(pid, 0) } == 0;
+            exists
+                && !std::fs::read_to_string(format!("/proc/{pid}/stat"))
+                    .is_ok_and(|stat| stat.contains(") Z "))
+        };
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while alive() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(!alive(), "grandchild {pid} should be killed with the probe");
+    }
+
+    #[tokio::test]
+    async fn test_missing_command_fails_validation() {
+        let report = check_server(&json!({ "type": "stdio" }), Duration::from_secs(1)).await;
+        assert!(!report.ok);
+        assert!(report.error.is_some());
+    }
+
+    /// 极简 streamable HTTP MCP 服务器：按请求方法返回固定响应
+    fn spawn_fake_http_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(3) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0usize;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = v.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+
+                let (status, payload) = match request["method"].as_str() {
+                    Some("initialize") => ("200 OK", INIT_RESPONSE.to_string()),
+                    Some("tools/list") => (
+                        "200 OK",
+                        format!("event: message\ndata: {TOOLS_RESPONSE}\n\n"),
+                    ),
+                    _ => ("202 Accepted", String::new()),
+                };
+                let content_type = if payload.starts_with("event:") {
+                    "text/event-stream"
+                } else {
+                    "application/json"
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nMcp-Session-Id: s1\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+                    payload.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{addr}/mcp")
+    }
+
+    #[tokio::test]
+    async fn test_http_handshake_with_fake_server() {
+        let url = spawn_fake_http_server();
+        let spec = json!({ "type": "http", "url": url });
+
+        let report = check_server(&spec, Duration::from_secs(5)).await;
+        assert!(report.ok, "handshake should succeed: {:?}", report.error);
+        assert_eq!(report.transport, "http");
+        assert_eq!(report.server_version.as_deref(), Some("0.1.0"));
+        assert_eq!(
+            report
+                .tools
+                .iter()
+                .map(|t| t.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["echo", "ping"]
+        );
+    }
+}
@@ -5,18 +5,30 @@
 //! ## 模块结构
 //!
 //! - `validation` - 服务器配置验证
+//! - `catalog` - 服务器目录解析（Registry / 清单格式）与安装模板填充
+//! - `health` - 服务器健康检查（实际握手）
 //! - `claude` - Claude MCP 同步和导入
 //! - `codex` - Codex MCP 同步和导入（含 TOML 转换）
 //! - `gemini` - Gemini MCP 同步和导入
 //! - `opencode` - OpenCode MCP 同步和导入（含 local/remote 格式转换）
+//! - `project` - 项目级 MCP 同步和导入（.mcp.json 等项目配置）
+//! - `tool_filter` - 按应用的工具过滤（转换为各应用的过滤语法）
 
+mod catalog;
 mod claude;
 mod codex;
 mod gemini;
+mod health;
 mod opencode;
+mod project;
+mod tool_filter;
 mod validation;
 
 // 重新导出公共 API
+pub use catalog::{
+    instantiate as instantiate_catalog_entry, parse_catalog_document, McpCatalogEntry,
+    McpCatalogSource, McpCatalogSourceKind,
+};
 pub use claude::{
     import_from_claude, remove_server_from_claude, sync_enabled_to_claude,
     sync_single_server_to_claude,
@@ -28,6 +40,9 @@ pub use gemini::{
     import_from_gemini, remove_server_from_gemini, sync_enabled_to_gemini,
     sync_single_server_to_gemini,
 };
+pub use health::{check_server, McpHealthReport, McpToolInfo, DEFAULT_TIMEOUT_SECS};
 pub use opencode::{
     import_from_opencode, remove_server_from_opencode, sync_single_server_to_opencode,
 };
+pub use project::{import_from_projects, remove_server_from_project, sync_server_to_project};
+pub(crate) use tool_filter::with_disabled_tools;
@@ -19,6 +19,7 @@ use crate::app_config::{McpApps, McpServer, MultiAppConfig};
 use crate::error::AppError;
 use crate::opencode_config;
 
+use super::tool_filter;
 use super::validation::validate_server_spec;
 
 // ============================================================================
@@ -194,7 +195,22 @@ pub fn sync_single_server_to_opencode(
     let opencode_spec = convert_to_opencode_format(server_spec)?;
 
     // Set in OpenCode config
-    opencode_config::set_mcp_server(id, opencode_spec)
+    opencode_config::set_mcp_server(id, opencode_spec)?;
+
+    // Per-tool filter lives in the top-level `tools` map
+    match server_spec.get(tool_filter::DISABLED_TOOLS_KEY) {
+        Some(disabled) => sync_tool_toggles(id, &tool_filter::string_list(disabled)),
+        None => Ok(()),
+    }
+}
+
+/// Write `"<id>_<tool>": false` entries for disabled tools, replacing stale ones
+fn sync_tool_toggles(id: &str, disabled: &[String]) -> Result<(), AppError> {
+    let mut config = opencode_config::read_opencode_config()?;
+    if tool_filter::apply_opencode_tool_toggles(&mut config, id, disabled) {
+        opencode_config::write_opencode_config(&config)?;
+    }
+    Ok(())
 }
 
 /// Remove a single MCP server from OpenCode live config
@@ -203,7 +219,8 @@ pub fn remove_server_from_opencode(id: &str) -> Result<(), AppError> {
         return Ok(());
     }
 
-    opencode_config::remove_mcp_server(id)
+    opencode_config::remove_mcp_server(id)?;
+    sync_tool_toggles(id, &[])
 }
 
 /// Import MCP servers from OpenCode config to unified structure
@@ -264,6 +281,9 @@ pub fn import_from_opencode(config: &mut MultiAppConfig) -> Result<usize, AppErr
                     homepage: None,
                     docs: None,
                     tags: Vec::new(),
+                    tools: Vec::new(),
+                    disabled_tools: Default::default(),
+                    projects: Vec::new(),
                 },
             );
             changed += 1;
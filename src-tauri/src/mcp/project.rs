@@ -0,0 +1,366 @@
+//! 项目级 MCP 同步和导入模块
+//!
+//! 设置了 `projects` 的 MCP 服务器不写入用户级配置，而是写入各项目目录下的配置文件：
+//!
+//! | 应用      | 项目配置文件                                   |
+//! |-----------|------------------------------------------------|
+//! | Claude    | `<dir>/.mcp.json`（工具过滤写入 `.claude/settings.local.json`） |
+//! | Gemini    | `<dir>/.gemini/settings.json`                  |
+//! | OpenCode  | `<dir>/opencode.json`                          |
+//! | Codex     | 不支持项目级 MCP，跳过                          |
+//!
+//! 与用户级配置一致，`${secret:name}` 在写入前解析为明文：应用可能由用户直接启动，
+//! 无法依赖 CC Switch 注入环境变量。含密钥的项目配置文件应自行加入 `.gitignore`。
+//!
+//! 导入时还会读取 `~/.claude.json` 中 `projects[path].mcpServers` 记录的本地项目服务器。
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::app_config::{AppType, McpApps, McpServer, McpToolFilters, MultiAppConfig};
+use crate::error::AppError;
+use crate::secret_store;
+
+use super::gemini::apply_exclude_tools;
+use super::opencode::{convert_from_opencode_format, convert_to_opencode_format};
+use super::tool_filter;
+use super::validation::validate_server_spec;
+
+/// 支持项目级 MCP 的应用
+pub const PROJECT_SCOPED_APPS: [AppType; 3] = [AppType::Claude, AppType::Gemini, AppType::OpenCode];
+
+fn opencode_project_config_path(project_dir: &Path) -> std::path::PathBuf {
+    project_dir.join("opencode.json")
+}
+
+fn claude_project_local_settings_path(project_dir: &Path) -> std::path::PathBuf {
+    project_dir.join(".claude").join("settings.local.json")
+}
+
+fn read_json_or_empty(path: &Path) -> Result<Value, AppError> {
+    if !path.exists() {
+        return Ok(json!({}));
+    }
+    crate::config::read_json_file(path)
+}
+
+/// 将单个 MCP 服务器同步到项目配置
+///
+/// `server_spec` 可以包含 `${secret:name}`，写入前在此解析。
+pub fn sync_server_to_project(
+    project_dir: &Path,
+    app: &AppType,
+    id: &str,
+    server_spec: &Value,
+) -> Result<(), AppError> {
+    let server_spec = &secret_store::resolve_value(server_spec)?;
+    match app {
+        AppType::Claude => {
+            let mut spec = server_spec.clone();
+            let disabled = spec
+                .as_object_mut()
+                .and_then(tool_filter::take_disabled_tools);
+
+            let mut current = crate::claude_mcp::read_project_mcp_servers_map(project_dir)?;
+            current.insert(id.to_string(), spec);
+            crate::claude_mcp::set_project_mcp_servers_map(project_dir, &current)?;
+
+            if let Some(tools) = disabled {
+                tool_filter::sync_claude_tool_permissions_at(
+                    &claude_project_local_settings_path(project_dir),
+                    &HashMap::from([(id.to_string(), tools)]),
+                )?;
+            }
+        }
+        AppType::Gemini => {
+            let mut spec = server_spec.clone();
+            apply_exclude_tools(&mut spec);
+
+            let mut current = crate::gemini_mcp::read_project_mcp_servers_map(project_dir)?;
+            current.insert(id.to_string(), spec);
+            crate::gemini_mcp::set_project_mcp_servers_map(project_dir, &current)?;
+        }
+        AppType::OpenCode => {
+            let path = opencode_project_config_path(project_dir);
+            let mut config = if path.exists() {
+                crate::config::read_json_file(&path)?
+            } else {
+                json!({ "$schema": "https://opencode.ai/config.json" })
+            };
+            let root = config
+                .as_object_mut()
+                .ok_or_else(|| AppError::Config(format!("{} 根必须是对象", path.display())))?;
+            let mcp = root.entry("mcp").or_insert_with(|| json!({}));
+            if !mcp.is_object() {
+                *mcp = json!({});
+            }
+            if let Some(mcp) = mcp.as_object_mut() {
+                mcp.insert(id.to_string(), convert_to_opencode_format(server_spec)?);
+            }
+
+            if let Some(disabled) = server_spec.get(tool_filter::DISABLED_TOOLS_KEY) {
+                tool_filter::apply_opencode_tool_toggles(
+                    &mut config,
+                    id,
+                    &tool_filter::string_list(disabled),
+                );
+            }
+            crate::config::write_json_file(&path, &config)?;
+        }
+        AppType::Codex => {
+            log::warn!(
+                "Codex 不支持项目级 MCP，跳过同步 '{id}' 到 {}",
+                project_dir.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 从项目配置中移除单个 MCP 服务器
+pub fn remove_server_from_project(
+    project_dir: &Path,
+    app: &AppType,
+    id: &str,
+) -> Result<(), AppError> {
+    match app {
+        AppType::Claude => {
+            let mut current = crate::claude_mcp::read_project_mcp_servers_map(project_dir)?;
+            if current.remove(id).is_some() {
+                crate::claude_mcp::set_project_mcp_servers_map(project_dir, &current)?;
+            }
+            let settings_path = claude_project_local_settings_path(project_dir);
+            if settings_path.exists() {
+                tool_filter::sync_claude_tool_permissions_at(
+                    &settings_path,
+                    &HashMap::from([(id.to_string(), Vec::new())]),
+                )?;
+            }
+        }
+        AppType::Gemini => {
+            let mut current = crate::gemini_mcp::read_project_mcp_servers_map(project_dir)?;
+            if current.remove(id).is_some() {
+                crate::gemini_mcp::set_project_mcp_servers_map(project_dir, &current)?;
+            }
+        }
+        AppType::OpenCode => {
+            let path = opencode_project_config_path(project_dir);
+            if !path.exists() {
+                return Ok(());
+            }
+            let mut config: Value = crate::config::read_json_file(&path)?;
+            let removed = config
+                .get_mut("mcp")
+                .and_then(|m| m.as_object_mut())
+                .is_some_and(|m| m.remove(id).is_some());
+            let toggled = tool_filter::apply_opencode_tool_toggles(&mut config, id, &[]);
+            if removed || toggled {
+                crate::config::write_json_file(&path, &config)?;
+            }
+        }
+        AppType::Codex => {}
+    }
+    Ok(())
+}
+
+/// 读取项目配置中的 MCP 服务器（统一格式）
+///
+/// Gemini 的 `excludeTools` 保留在返回的规范中，由调用方转换为工具过滤。
+pub fn read_project_servers(
+    project_dir: &Path,
+    app: &AppType,
+) -> Result<HashMap<String, Value>, AppError> {
+    match app {
+        AppType::Claude => crate::claude_mcp::read_project_mcp_servers_map(project_dir),
+        AppType::Gemini => crate::gemini_mcp::read_project_mcp_servers_map(project_dir),
+        AppType::OpenCode => {
+            let config = read_json_or_empty(&opencode_project_config_path(project_dir))?;
+            let mut out = HashMap::new();
+            if let Some(mcp) = config.get("mcp").and_then(|m| m.as_object()) {
+                for (id, spec) in mcp {
+                    match convert_from_opencode_format(spec) {
+                        Ok(spec) => {
+                            out.insert(id.clone(), spec);
+                        }
+                        Err(e) => log::warn!("跳过无效的 OpenCode 项目 MCP '{id}': {e}"),
+                    }
+                }
+            }
+            Ok(out)
+        }
+        AppType::Codex => Ok(HashMap::new()),
+    }
+}
+
+/// 从项目配置导入 MCP 到统一结构
+///
+/// `project_dirs` 为空时使用 `~/.claude.json` 中记录过的全部项目目录。
+/// 同一 id 出现在多个项目中时合并为一个服务器，作用域为这些项目的并集。
+pub fn import_from_projects(
+    config: &mut MultiAppConfig,
+    project_dirs: &[String],
+) -> Result<usize, AppError> {
+    let dirs = if project_dirs.is_empty() {
+        crate::claude_mcp::list_known_project_dirs()?
+    } else {
+        project_dirs.to_vec()
+    };
+
+    let mut found: Vec<(String, AppType, String, Value)> = Vec::new();
+    for dir in &dirs {
+        let path = Path::new(dir);
+        if !path.is_dir() {
+            log::warn!("跳过不存在的项目目录: {dir}");
+            continue;
+        }
+        for app in PROJECT_SCOPED_APPS {
+            let mut servers: Vec<_> = read_project_servers(path, &app)?.into_iter().collect();
+            servers.sort_by(|a, b| a.0.cmp(&b.0));
+            for (id, spec) in servers {
+                found.push((dir.clone(), app.clone(), id, spec));
+            }
+        }
+    }
+
+    // Claude 本地作用域：~/.claude.json 的 projects[path].mcpServers
+    for (dir, servers) in crate::claude_mcp::read_local_project_mcp_servers()? {
+        if !dirs.contains(&dir) {
+            continue;
+        }
+        let mut servers: Vec<_> = servers.into_iter().collect();
+        servers.sort_by(|a, b| a.0.cmp(&b.0));
+        for (id, spec) in servers {
+            found.push((dir.clone(), AppType::Claude, id, spec));
+        }
+    }
+
+    let servers = config.mcp.servers.get_or_insert_with(HashMap::new);
+    let mut changed = 0;
+
+    for (dir, app, id, mut spec) in found {
+        if let Err(e) = validate_server_spec(&spec) {
+            log::warn!("跳过无效的项目 MCP 服务器 '{id}' ({dir}): {e}");
+            continue;
+        }
+
+        if let Some(existing) = servers.get_mut(&id) {
+            let mut updated = false;
+            if !existing.projects.contains(&dir) {
+                existing.projects.push(dir.clone());
+                updated = true;
+            }
+            if !existing.apps.is_enabled_for(&app) {
+                existing.apps.set_enabled_for(&app, true);
+                updated = true;
+            }
+            if updated {
+                changed += 1;
+            }
+            continue;
+        }
+
+        let mut filters = McpToolFilters::default();
+        if app == AppType::Gemini {
+            if let Some(excluded) = spec.as_object_mut().and_then(|o| o.remove("excludeTools")) {
+                filters.set_for(&AppType::Gemini, tool_filter::string_list(&excluded));
+            }
+        }
+        let mut apps = McpApps::default();
+        apps.set_enabled_for(&app, true);
+
+        servers.insert(
+            id.clone(),
+            McpServer {
+                id: id.clone(),
+                name: id.clone(),
+                server: spec,
+                apps,
+                description: None,
+                homepage: None,
+                docs: None,
+                tags: Vec::new(),
+                tools: Vec::new(),
+                disabled_tools: filters,
+                projects: vec![dir.clone()],
+            },
+        );
+        changed += 1;
+        log::info!("导入项目级 MCP 服务器 '{id}' ({dir})");
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_roundtrip_for_all_apps() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let spec = tool_filter::with_disabled_tools(
+            &json!({"type": "stdio", "command": "npx", "args": ["-y", "server-github"]}),
+            &["delete_repo".to_string()],
+        );
+
+        for app in PROJECT_SCOPED_APPS {
+            sync_server_to_project(dir.path(), &app, "github", &spec).expect("sync");
+            let servers = read_project_servers(dir.path(), &app).expect("read");
+            let synced = servers.get("github").expect("server synced");
+            assert_eq!(synced["command"], json!("npx"));
+            assert!(synced.get(tool_filter::DISABLED_TOOLS_KEY).is_none());
+        }
+
+        let mcp_json: Value =
+            crate::config::read_json_file(&dir.path().join(".mcp.json")).expect("mcp.json");
+        assert_eq!(
+            mcp_json["mcpServers"]["github"]["args"],
+            json!(["-y", "server-github"])
+        );
+        let local: Value =
+            crate::config::read_json_file(&claude_project_local_settings_path(dir.path()))
+                .expect("settings.local.json");
+        assert_eq!(
+            local["permissions"]["deny"],
+            json!(["mcp__github__delete_repo"])
+        );
+        let gemini = read_project_servers(dir.path(), &AppType::Gemini).expect("gemini");
+        assert_eq!(gemini["github"]["excludeTools"], json!(["delete_repo"]));
+        let opencode: Value =
+            crate::config::read_json_file(&opencode_project_config_path(dir.path()))
+                .expect("opencode.json");
+        assert_eq!(opencode["tools"]["github_delete_repo"], json!(false));
+
+        for app in PROJECT_SCOPED_APPS {
+            remove_server_from_project(dir.path(), &app, "github").expect("remove");
+            assert!(read_project_servers(dir.path(), &app)
+                .expect("read")
+                .is_empty());
+        }
+        // 仅包含 mcpServers 的 .mcp.json 在清空后被删除
+        assert!(!dir.path().join(".mcp.json").exists());
+    }
+
+    #[test]
+    fn test_import_from_projects_merges_scopes() {
+        let a = tempfile::tempdir().expect("tempdir");
+        let b = tempfile::tempdir().expect("tempdir");
+        let spec = json!({"type": "stdio", "command": "uvx", "args": ["mcp-server-git"]});
+        sync_server_to_project(a.path(), &AppType::Claude, "git", &spec).expect("sync a");
+        sync_server_to_project(b.path(), &AppType::OpenCode, "git", &spec).expect("sync b");
+
+        let dirs = vec![
+            a.path().to_string_lossy().to_string(),
+            b.path().to_string_lossy().to_string(),
+        ];
+        let mut config = MultiAppConfig::default();
+        let changed = import_from_projects(&mut config, &dirs).expect("import");
+        assert_eq!(changed, 2);
+
+        let server = &config.mcp.servers.as_ref().expect("servers")["git"];
+        assert_eq!(server.projects, dirs);
+        assert!(server.apps.claude && server.apps.opencode);
+        assert!(!server.apps.codex && !server.apps.gemini);
+    }
+}
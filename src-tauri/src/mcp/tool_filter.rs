@@ -0,0 +1,300 @@
+//! MCP 工具过滤模块
+//!
+//! 统一结构中按应用记录被禁用的工具（`McpServer.disabled_tools`）。同步时由服务层
+//! 将目标应用的禁用列表以内部字段 `disabledTools` 附加到服务器规范中，各应用模块
+//! 再将其转换为各自的过滤语法：
+//!
+//! | 应用      | 过滤语法                                                  |
+//! |-----------|-----------------------------------------------------------|
+//! | Claude    | `~/.claude/settings.json` 的 `permissions.deny`（`mcp__<id>__<tool>`） |
+//! | Codex     | `[mcp_servers.<id>] disabled_tools = [...]`               |
+//! | Gemini    | `mcpServers.<id>.excludeTools`                            |
+//! | OpenCode  | 顶层 `tools` 映射（`"<id>_<tool>": false`）               |
+
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::AppError;
+
+/// 服务器规范中携带禁用工具列表的内部字段名（不会原样写入任何应用配置）
+pub(crate) const DISABLED_TOOLS_KEY: &str = "disabledTools";
+
+/// 为服务器规范附加指定应用的禁用工具列表
+///
+/// 即使列表为空也会附加，以便写入端清理该服务器此前的过滤规则。
+pub(crate) fn with_disabled_tools(spec: &Value, tools: &[String]) -> Value {
+    let mut spec = spec.clone();
+    if let Some(obj) = spec.as_object_mut() {
+        obj.insert(DISABLED_TOOLS_KEY.into(), json!(tools));
+    }
+    spec
+}
+
+/// 从服务器规范中取出禁用工具列表
+///
+/// 返回 None 表示规范未携带过滤信息（如直接读取自 live 配置的其他服务器），
+/// 此时写入端不应改动该服务器已有的过滤规则。
+pub(crate) fn take_disabled_tools(obj: &mut Map<String, Value>) -> Option<Vec<String>> {
+    let value = obj.remove(DISABLED_TOOLS_KEY)?;
+    Some(string_list(&value))
+}
+
+/// 将 JSON 数组转换为去空的字符串列表
+pub(crate) fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Claude：permissions.deny
+// ============================================================================
+
+fn claude_rule_prefix(id: &str) -> String {
+    format!("mcp__{id}__")
+}
+
+/// 在 Claude settings 中替换指定服务器的工具拒绝规则
+///
+/// 仅替换以 `mcp__<id>__` 开头的条目，用户手写的其他规则保持不变。
+/// 返回配置是否发生变化。
+pub(crate) fn apply_claude_deny_rules(
+    settings: &mut Value,
+    filters: &HashMap<String, Vec<String>>,
+) -> bool {
+    let Some(root) = settings.as_object_mut() else {
+        return false;
+    };
+
+    let existing: Vec<Value> = root
+        .get("permissions")
+        .and_then(|p| p.get("deny"))
+        .and_then(|d| d.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut deny: Vec<Value> = existing
+        .iter()
+        .filter(|rule| {
+            let Some(rule) = rule.as_str() else {
+                return true;
+            };
+            !filters
+                .keys()
+                .any(|id| rule.starts_with(&claude_rule_prefix(id)))
+        })
+        .cloned()
+        .collect();
+
+    let mut ids: Vec<_> = filters.keys().collect();
+    ids.sort();
+    for id in ids {
+        for tool in &filters[id] {
+            deny.push(json!(format!("{}{tool}", claude_rule_prefix(id))));
+        }
+    }
+
+    if deny == existing {
+        return false;
+    }
+
+    let permissions = root.entry("permissions").or_insert_with(|| json!({}));
+    if !permissions.is_object() {
+        *permissions = json!({});
+    }
+    let permissions = permissions.as_object_mut().expect("permissions is object");
+    if deny.is_empty() {
+        permissions.remove("deny");
+        if permissions.is_empty() {
+            root.remove("permissions");
+        }
+    } else {
+        permissions.insert("deny".into(), Value::Array(deny));
+    }
+    true
+}
+
+/// 将工具过滤写入 ~/.claude/settings.json 的 permissions.deny
+pub(crate) fn sync_claude_tool_permissions(
+    filters: &HashMap<String, Vec<String>>,
+) -> Result<(), AppError> {
+    sync_claude_tool_permissions_at(&crate::config::get_claude_settings_path(), filters)
+}
+
+/// 将工具过滤写入指定 Claude settings 文件（项目级为 `.claude/settings.local.json`）
+pub(crate) fn sync_claude_tool_permissions_at(
+    path: &Path,
+    filters: &HashMap<String, Vec<String>>,
+) -> Result<(), AppError> {
+    if filters.is_empty() {
+        return Ok(());
+    }
+    let mut settings: Value = if path.exists() {
+        crate::config::read_json_file(path)?
+    } else {
+        // 无需写入任何规则时不创建 settings.json
+        if filters.values().all(|tools| tools.is_empty()) {
+            return Ok(());
+        }
+        json!({})
+    };
+
+    if apply_claude_deny_rules(&mut settings, filters) {
+        crate::config::write_json_file(path, &settings)?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// OpenCode：顶层 tools 映射
+// ============================================================================
+
+/// 在 OpenCode 配置中替换指定服务器的工具开关
+///
+/// OpenCode 中 MCP 工具名为 `<server>_<tool>`，通过顶层 `tools` 映射设为 false 即可禁用。
+/// 清理旧规则时会跳过属于其他服务器（id 以 `<id>_` 开头）的条目。
+pub(crate) fn apply_opencode_tool_toggles(config: &mut Value, id: &str, tools: &[String]) -> bool {
+    let Some(root) = config.as_object_mut() else {
+        return false;
+    };
+
+    let prefix = format!("{id}_");
+    let other_prefixes: Vec<String> = root
+        .get("mcp")
+        .and_then(|m| m.as_object())
+        .map(|m| {
+            m.keys()
+                .filter(|k| k.as_str() != id && k.starts_with(&prefix))
+                .map(|k| format!("{k}_"))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let existing = root
+        .get("tools")
+        .and_then(|t| t.as_object())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut updated: Map<String, Value> = existing
+        .iter()
+        .filter(|(key, value)| {
+            !(value.as_bool() == Some(false)
+                && key.starts_with(&prefix)
+                && !other_prefixes.iter().any(|p| key.starts_with(p)))
+        })
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    for tool in tools {
+        updated.insert(format!("{prefix}{tool}"), json!(false));
+    }
+
+    if updated == existing {
+        return false;
+    }
+    if updated.is_empty() {
+        root.remove("tools");
+    } else {
+        root.insert("tools".into(), Value::Object(updated));
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_disabled_tools() {
+        let spec = with_disabled_tools(
+            &json!({"type": "stdio", "command": "npx"}),
+            &["create_issue".to_string()],
+        );
+        let mut obj = spec.as_object().cloned().unwrap();
+        assert_eq!(
+            take_disabled_tools(&mut obj),
+            Some(vec!["create_issue".to_string()])
+        );
+        assert!(!obj.contains_key(DISABLED_TOOLS_KEY));
+        assert_eq!(take_disabled_tools(&mut obj), None);
+    }
+
+    #[test]
+    fn test_apply_claude_deny_rules_replaces_only_server_rules() {
+        let mut settings = json!({
+            "env": {"ANTHROPIC_BASE_URL": "https://example.com"},
+            "permissions": {
+                "deny": ["Bash(rm:*)", "mcp__github__old_tool", "mcp__github2__keep"]
+            }
+        });
+        let mut filters = HashMap::new();
+        filters.insert(
+            "github".to_string(),
+            vec!["create_issue".to_string(), "delete_repo".to_string()],
+        );
+
+        assert!(apply_claude_deny_rules(&mut settings, &filters));
+        assert_eq!(
+            settings["permissions"]["deny"],
+            json!([
+                "Bash(rm:*)",
+                "mcp__github2__keep",
+                "mcp__github__create_issue",
+                "mcp__github__delete_repo"
+            ])
+        );
+        // 幂等
+        assert!(!apply_claude_deny_rules(&mut settings, &filters));
+
+        // 清空过滤后移除规则，空的 permissions 一并移除
+        let mut settings = json!({"permissions": {"deny": ["mcp__github__create_issue"]}});
+        filters.insert("github".to_string(), Vec::new());
+        assert!(apply_claude_deny_rules(&mut settings, &filters));
+        assert!(settings.get("permissions").is_none());
+    }
+
+    #[test]
+    fn test_apply_opencode_tool_toggles() {
+        let mut config = json!({
+            "mcp": {"github": {}, "github_enterprise": {}},
+            "tools": {
+                "github_old": false,
+                "github_enterprise_search": false,
+                "bash": true
+            }
+        });
+
+        assert!(apply_opencode_tool_toggles(
+            &mut config,
+            "github",
+            &["create_issue".to_string()]
+        ));
+        assert_eq!(
+            config["tools"],
+            json!({
+                "github_enterprise_search": false,
+                "bash": true,
+                "github_create_issue": false
+            })
+        );
+
+        assert!(apply_opencode_tool_toggles(
+            &mut config,
+            "github_enterprise",
+            &[]
+        ));
+        assert_eq!(
+            config["tools"],
+            json!({"bash": true, "github_create_issue": false})
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::app_config::{AppType, PromptApps};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Prompt {
     pub id: String,
@@ -13,4 +17,207 @@ pub struct Prompt {
     pub created_at: Option<i64>,
     #[serde(rename = "updatedAt", skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<i64>,
+    /// 片段在托管区块中的渲染顺序（越小越靠前）
+    #[serde(rename = "sortIndex", default, skip_serializing_if = "Option::is_none")]
+    pub sort_index: Option<usize>,
+    /// 项目作用域：非空时写入这些项目目录下的提示词文件，而不是用户级文件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+}
+
+/// 提示词文件与应用内记录的一致性状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PromptDriftStatus {
+    /// 托管片段与记录一致
+    InSync,
+    /// 托管片段在应用外被修改、增删或调整了顺序
+    Modified,
+    /// 文件或托管区块不存在
+    Missing,
+}
+
+/// 单个提示词文件的漂移检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFileDrift {
+    /// 项目目录（用户级文件为 `None`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    pub path: String,
+    pub status: PromptDriftStatus,
+    /// 不一致的提示词 id
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prompt_ids: Vec<String>,
+}
+
+/// 提示词版本的变更来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PromptRevisionSource {
+    /// 界面编辑
+    Ui,
+    /// 从 live 文件回填的手动修改
+    Backfill,
+    /// 从已有文件导入
+    Import,
+    /// 通过 deeplink 导入
+    Deeplink,
+    /// 恢复历史版本
+    Restore,
+    /// 启用版本历史前的原有内容
+    Initial,
+}
+
+impl PromptRevisionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ui => "ui",
+            Self::Backfill => "backfill",
+            Self::Import => "import",
+            Self::Deeplink => "deeplink",
+            Self::Restore => "restore",
+            Self::Initial => "initial",
+        }
+    }
+}
+
+/// 提示词内容的一个历史版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptRevision {
+    pub id: i64,
+    /// 应用标识（共享提示词为 `shared`）
+    pub app_type: String,
+    pub prompt_id: String,
+    pub content: String,
+    pub source: PromptRevisionSource,
+    pub created_at: i64,
+}
+
+/// 提示词模板预览结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptPreview {
+    pub content: String,
+    /// 未定义的变量（原样保留在内容中）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unknown_variables: Vec<String>,
+    /// 引用了不存在的提示词 id
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_includes: Vec<String>,
+}
+
+/// 共享提示词在单个应用中的格式调整
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptAppOverride {
+    /// 替换共享内容（为空时沿用共享内容）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// 追加在内容之后的应用专属段落
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+/// 合并预览中的一组重复提示词：内容与项目作用域相同、分布在多个应用中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptDedupGroup {
+    pub name: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    /// 将被合并的各应用提示词
+    pub members: Vec<PromptDedupMember>,
+}
+
+/// 合并预览中某个应用的提示词
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptDedupMember {
+    pub app: String,
+    pub id: String,
+    pub enabled: bool,
+}
+
+/// 跨应用共享的提示词：同一份内容写入多个应用的提示词文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedPrompt {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 启用的应用
+    #[serde(default)]
+    pub apps: PromptApps,
+    /// 按应用（`AppType::as_str`）的格式调整
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: BTreeMap<String, PromptAppOverride>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
+impl SharedPrompt {
+    /// 渲染到指定应用时的内容：应用覆盖内容（或共享内容）+ 应用专属段落
+    pub fn content_for(&self, app: &AppType) -> String {
+        let adjust = self.overrides.get(app.as_str());
+        let body = adjust
+            .and_then(|o| o.content.as_deref())
+            .unwrap_or(&self.content)
+            .trim_end();
+        match adjust.and_then(|o| o.section.as_deref()) {
+            Some(section) if !section.trim().is_empty() => {
+                format!("{body}\n\n{}", section.trim_end())
+            }
+            _ => body.to_string(),
+        }
+    }
+
+    /// 将在应用文件中编辑后的内容回填：保留应用专属段落，其余部分写回
+    /// 应用覆盖内容（若有）或共享内容
+    pub fn apply_edit(&mut self, app: &AppType, edited: &str) {
+        let key = app.as_str().to_string();
+        let section = self
+            .overrides
+            .get(&key)
+            .and_then(|o| o.section.clone())
+            .filter(|s| !s.trim().is_empty());
+        let edited = edited.trim_end();
+
+        let (body, keep_section) = match &section {
+            Some(section) => match edited.strip_suffix(section.trim_end()) {
+                Some(body) => (body.trim_end().to_string(), true),
+                None => (edited.to_string(), false),
+            },
+            None => (edited.to_string(), true),
+        };
+
+        let has_override = self
+            .overrides
+            .get(&key)
+            .is_some_and(|o| o.content.is_some());
+        if has_override || !keep_section {
+            // 专属段落被改动时无法拆分，整体作为该应用的覆盖内容
+            let entry = self.overrides.entry(key).or_default();
+            entry.content = Some(if keep_section {
+                body
+            } else {
+                edited.to_string()
+            });
+            if !keep_section {
+                entry.section = None;
+            }
+        } else {
+            self.content = body;
+        }
+    }
 }
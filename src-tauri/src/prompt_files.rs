@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::app_config::AppType;
 use crate::codex_config::get_codex_auth_path;
@@ -16,14 +16,21 @@ pub fn prompt_file_path(app: &AppType) -> Result<PathBuf, AppError> {
         AppType::OpenCode => get_opencode_dir(),
     };
 
-    let filename = match app {
+    Ok(base_dir.join(prompt_file_name(app)))
+}
+
+/// 返回项目目录下指定应用读取的提示词文件路径（如 `<repo>/CLAUDE.md`）。
+pub fn project_prompt_file_path(project: &Path, app: &AppType) -> PathBuf {
+    project.join(prompt_file_name(app))
+}
+
+fn prompt_file_name(app: &AppType) -> &'static str {
+    match app {
         AppType::Claude => "CLAUDE.md",
         AppType::Codex => "AGENTS.md",
         AppType::Gemini => "GEMINI.md",
         AppType::OpenCode => "AGENTS.md",
-    };
-
-    Ok(base_dir.join(filename))
+    }
 }
 
 fn get_base_dir_with_fallback(
@@ -106,6 +106,14 @@ pub struct UsageScript {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "autoQueryInterval")]
     pub auto_query_interval: Option<u64>,
+    /// 低余额告警阈值（remaining 低于该值时告警，单位与查询结果一致）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "lowBalanceThreshold")]
+    pub low_balance_threshold: Option<f64>,
+    /// 低余额时是否将供应商移至故障转移队列末尾
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "demoteOnLowBalance")]
+    pub demote_on_low_balance: Option<bool>,
 }
 
 /// 用量数据
@@ -650,7 +650,7 @@ impl RequestForwarder {
         request = request.header("accept-encoding", "identity");
 
         // 使用适配器添加认证头
-        if let Some(auth) = adapter.extract_auth(provider) {
+        if let Some(auth) = adapter.extract_resolved_auth(provider)? {
             request = adapter.add_auth_headers(request, &auth);
         }
 
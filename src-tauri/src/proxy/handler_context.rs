@@ -9,7 +9,7 @@ use crate::proxy::{
     forwarder::RequestForwarder,
     server::ProxyState,
     types::{AppProxyConfig, RectifierConfig},
-    ProxyError,
+    ProxyError, SessionIdSource,
 };
 use axum::http::HeaderMap;
 use std::time::Instant;
@@ -57,6 +57,8 @@ pub struct RequestContext {
     pub app_type: AppType,
     /// Session ID（从客户端请求提取或新生成）
     pub session_id: String,
+    /// Session ID 来源（新生成的 ID 只对应单个请求）
+    pub session_source: SessionIdSource,
     /// 整流器配置
     pub rectifier_config: RectifierConfig,
 }
@@ -155,10 +157,24 @@ impl RequestContext {
             app_type_str,
             app_type,
             session_id,
+            session_source: session_result.source,
             rectifier_config,
         })
     }
 
+    /// 写入请求日志的 Session ID：仅记录跨请求稳定的客户端会话 ID，避免每个请求被统计为一个会话
+    ///
+    /// 新生成的 ID 与 Codex `previous_response_id` 派生的 ID 每轮都会变化，均不记录。
+    pub fn logged_session_id(&self) -> Option<String> {
+        matches!(
+            self.session_source,
+            SessionIdSource::MetadataUserId
+                | SessionIdSource::MetadataSessionId
+                | SessionIdSource::Header
+        )
+        .then(|| self.session_id.clone())
+    }
+
     /// 从 URI 提取模型名称（Gemini 专用）
     ///
     /// Gemini API 的模型名称在 URI 中，格式如：
@@ -428,7 +428,7 @@ fn log_forward_error(
         error_message,
         ctx.latency_ms(),
         is_streaming,
-        Some(ctx.session_id.clone()),
+        ctx.logged_session_id(),
         None,
     ) {
         log::warn!("记录失败请求日志失败: {e}");
@@ -480,5 +480,13 @@ async fn log_usage(
         is_streaming,
     ) {
         log::warn!("[USG-001] 记录使用量失败: {e}");
+        return;
     }
+
+    crate::services::budget::BudgetAlertService::evaluate_and_notify(
+        &state.db,
+        state.app_handle.as_ref(),
+        provider_id,
+        app_type,
+    );
 }
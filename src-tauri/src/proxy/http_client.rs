@@ -7,7 +7,7 @@ use crate::provider::ProviderProxyConfig;
 use once_cell::sync::OnceCell;
 use reqwest::Client;
 use std::env;
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::RwLock;
 use std::time::Duration;
 
@@ -213,8 +213,30 @@ pub fn is_proxy_enabled() -> bool {
     get_current_proxy_url().is_some()
 }
 
+/// 构建不跟随重定向的 HTTP 客户端（沿用当前代理设置）
+///
+/// 用于需要逐跳校验目标地址的请求（如用量脚本）：重定向交由调用方重新校验后再发起。
+/// `pinned` 将域名固定解析到已校验的地址，避免校验后被 DNS 重绑定。
+pub fn build_no_redirect_client(pinned: Option<(&str, &[SocketAddr])>) -> Result<Client, String> {
+    let mut builder = client_builder(get_current_proxy_url().as_deref())?
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some((host, addrs)) = pinned {
+        builder = builder.resolve_to_addrs(host, addrs);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))
+}
+
 /// 构建 HTTP 客户端
 fn build_client(proxy_url: Option<&str>) -> Result<Client, String> {
+    client_builder(proxy_url)?
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))
+}
+
+/// 按代理设置创建客户端构建器
+fn client_builder(proxy_url: Option<&str>) -> Result<reqwest::ClientBuilder, String> {
     let mut builder = Client::builder()
         .timeout(Duration::from_secs(600))
         .connect_timeout(Duration::from_secs(30))
@@ -253,9 +275,7 @@ fn build_client(proxy_url: Option<&str>) -> Result<Client, String> {
         }
     }
 
-    builder
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {e}"))
+    Ok(builder)
 }
 
 fn system_proxy_points_to_loopback() -> bool {
@@ -56,4 +56,9 @@ pub mod rsp {
 pub mod usg {
     pub const LOG_FAILED: &str = "USG-001";
     pub const PRICING_NOT_FOUND: &str = "USG-002";
+    pub const PRICING_CONFIG_INVALID: &str = "USG-003";
+    pub const BUDGET_EVAL_FAILED: &str = "USG-004";
+    pub const BUDGET_ALERT_FIRED: &str = "USG-005";
+    pub const USAGE_QUERY_FAILED: &str = "USG-006";
+    pub const LOW_BALANCE: &str = "USG-007";
 }
@@ -64,6 +64,19 @@ pub trait ProviderAdapter: Send + Sync {
     /// * `None` - 未找到认证信息
     fn extract_auth(&self, provider: &Provider) -> Option<AuthInfo>;
 
+    /// 提取认证信息，并解析其中的 `${secret:name}` 密钥引用
+    ///
+    /// 转发请求时应使用此方法；密钥库锁定或引用不存在时返回 `ConfigError`。
+    fn extract_resolved_auth(&self, provider: &Provider) -> Result<Option<AuthInfo>, ProxyError> {
+        if !crate::secret_store::contains_refs(&provider.settings_config) {
+            return Ok(self.extract_auth(provider));
+        }
+        let mut resolved = provider.clone();
+        resolved.settings_config = crate::secret_store::resolve_value(&provider.settings_config)
+            .map_err(|e| ProxyError::ConfigError(e.to_string()))?;
+        Ok(self.extract_auth(&resolved))
+    }
+
     /// 构建请求 URL
     ///
     /// # Arguments
@@ -119,7 +119,7 @@ impl ProviderType {
             AppType::Gemini => {
                 // 检测是否为 CLI 模式（OAuth）
                 let adapter = GeminiAdapter::new();
-                if let Some(auth) = adapter.extract_auth(provider) {
+                if let Some(auth) = adapter.extract_resolved_auth(provider).ok().flatten() {
                     let key = &auth.api_key;
                     // OAuth access_token 以 ya29. 开头
                     if key.starts_with("ya29.") {
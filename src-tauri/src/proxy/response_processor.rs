@@ -291,7 +291,7 @@ fn create_usage_collector(
     let start_time = ctx.start_time;
     let stream_parser = parser_config.stream_parser;
     let model_extractor = parser_config.model_extractor;
-    let session_id = ctx.session_id.clone();
+    let session_id = ctx.logged_session_id();
 
     SseUsageCollector::new(start_time, move |events, first_token_ms| {
         if let Some(usage) = stream_parser(&events) {
@@ -315,7 +315,7 @@ fn create_usage_collector(
                     first_token_ms,
                     true, // is_streaming
                     status_code,
-                    Some(session_id),
+                    session_id,
                 )
                 .await;
             });
@@ -339,7 +339,7 @@ fn create_usage_collector(
                     first_token_ms,
                     true, // is_streaming
                     status_code,
-                    Some(session_id),
+                    session_id,
                 )
                 .await;
             });
@@ -364,7 +364,7 @@ fn spawn_log_usage(
     let model = model.to_string();
     let request_model = request_model.to_string();
     let latency_ms = ctx.latency_ms();
-    let session_id = ctx.session_id.clone();
+    let session_id = ctx.logged_session_id();
 
     tokio::spawn(async move {
         log_usage_internal(
@@ -378,7 +378,7 @@ fn spawn_log_usage(
             None,
             is_streaming,
             status_code,
-            Some(session_id),
+            session_id,
         )
         .await;
     });
@@ -438,7 +438,15 @@ async fn log_usage_internal(
         is_streaming,
     ) {
         log::warn!("[USG-001] 记录使用量失败: {e}");
+        return;
     }
+
+    crate::services::budget::BudgetAlertService::evaluate_and_notify(
+        &state.db,
+        state.app_handle.as_ref(),
+        provider_id,
+        app_type,
+    );
 }
 
 /// 创建带日志记录和超时控制的透传流
@@ -0,0 +1,187 @@
+//! 系统密钥环访问
+//!
+//! 仅用于保存密钥库的主密钥（base64），不保存任何具体密钥：
+//! - macOS：Keychain（`security` 命令）
+//! - Linux：Secret Service（`secret-tool` 命令，需要桌面会话的 D-Bus）
+//! - Windows：DPAPI 加密后写入密钥库旁的 `secrets.key`（绑定当前用户）
+//!
+//! 均通过系统自带命令实现，无需额外依赖；不可用时由调用方回退到口令模式。
+
+use std::path::Path;
+use std::process::Command;
+
+#[cfg(not(target_os = "windows"))]
+const SERVICE: &str = "cc-switch";
+
+/// 密钥环中的账户名：按密钥库目录区分，避免多个密钥库互相覆盖主密钥
+#[cfg(not(target_os = "windows"))]
+fn account(store_dir: &Path) -> String {
+    format!("secret-store-key:{}", store_dir.display())
+}
+
+/// 当前环境是否可用系统密钥环
+pub(super) fn is_available() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        true
+    }
+    #[cfg(target_os = "windows")]
+    {
+        true
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        // 无头环境（SSH、容器）通常没有 D-Bus 会话
+        let has_bus = std::env::var("DBUS_SESSION_BUS_ADDRESS")
+            .map(|v| !v.trim().is_empty())
+            .unwrap_or(false);
+        has_bus
+            && Command::new("secret-tool")
+                .arg("--version")
+                .output()
+                .is_ok()
+    }
+}
+
+/// 读取主密钥
+pub(super) fn get(store_dir: &Path) -> Option<String> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("security")
+            .args([
+                "find-generic-password",
+                "-s",
+                SERVICE,
+                "-a",
+                &account(store_dir),
+                "-w",
+            ])
+            .output()
+            .ok()?;
+        non_empty_stdout(output)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let protected = std::fs::read_to_string(store_dir.join("secrets.key")).ok()?;
+        run_dpapi("Unprotect", protected.trim()).ok()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let output = Command::new("secret-tool")
+            .args(["lookup", "service", SERVICE, "account", &account(store_dir)])
+            .output()
+            .ok()?;
+        non_empty_stdout(output)
+    }
+}
+
+/// 写入主密钥
+pub(super) fn set(store_dir: &Path, value: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        // `-w` 放在最后且不带值时从标准输入读取（需输入两次确认），避免主密钥出现在进程参数中
+        let input = format!("{value}\n{value}\n");
+        write_via_stdin(
+            Command::new("security").args([
+                "add-generic-password",
+                "-U",
+                "-s",
+                SERVICE,
+                "-a",
+                &account(store_dir),
+                "-l",
+                "CC Switch secret store",
+                "-w",
+            ]),
+            &input,
+        )
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let protected = run_dpapi("Protect", value)?;
+        std::fs::create_dir_all(store_dir).map_err(|e| e.to_string())?;
+        crate::config::atomic_write(&store_dir.join("secrets.key"), protected.as_bytes())
+            .map_err(|e| e.to_string())
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        write_via_stdin(
+            Command::new("secret-tool").args([
+                "store",
+                "--label=CC Switch secret store",
+                "service",
+                SERVICE,
+                "account",
+                &account(store_dir),
+            ]),
+            value,
+        )
+    }
+}
+
+/// 通过标准输入传递密钥，避免出现在进程参数中
+#[cfg(not(target_os = "windows"))]
+fn write_via_stdin(command: &mut Command, input: &str) -> Result<(), String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    check_status(output)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn non_empty_stdout(output: std::process::Output) -> Option<String> {
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn check_status(output: std::process::Output) -> Result<(), String> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// 调用 PowerShell 的 DPAPI（CurrentUser 作用域），输入输出均为 base64
+#[cfg(target_os = "windows")]
+fn run_dpapi(operation: &str, input_b64: &str) -> Result<String, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let script = format!(
+        "Add-Type -AssemblyName System.Security; \
+         $b = [Convert]::FromBase64String($env:CC_SWITCH_DPAPI_INPUT); \
+         [Convert]::ToBase64String([Security.Cryptography.ProtectedData]::{operation}($b, $null, 'CurrentUser'))"
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .env("CC_SWITCH_DPAPI_INPUT", input_b64)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if value.is_empty() {
+        return Err("DPAPI 返回空结果".to_string());
+    }
+    Ok(value)
+}
@@ -0,0 +1,728 @@
+//! 本地加密密钥库
+//!
+//! MCP 服务器的 env/headers 与供应商配置中可以用 `${secret:name}` 引用密钥，
+//! 数据库与导出文件中只保存引用本身，明文仅在写入 live 配置或代理转发时解析。
+//!
+//! 密钥库文件为 `~/.cc-switch/secrets.json`，每个条目使用 AES-256-GCM 单独加密。
+//! 主密钥来源：
+//! - `keyring`：随机生成并保存在系统密钥环（见 [`keyring`] 模块）
+//! - `passphrase`：由口令经 PBKDF2-HMAC-SHA256 派生，适用于无密钥环的无头环境；
+//!   可通过环境变量 `CC_SWITCH_SECRETS_PASSPHRASE` 提供，或在界面中解锁
+//!
+//! 主密钥解锁后仅缓存在进程内存中。
+
+mod keyring;
+
+use base64::prelude::*;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::AppError;
+
+/// 无头环境下提供口令的环境变量
+pub const PASSPHRASE_ENV: &str = "CC_SWITCH_SECRETS_PASSPHRASE";
+
+const STORE_VERSION: u32 = 1;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const PBKDF2_ITERATIONS: u32 = 210_000;
+const VERIFIER_PLAINTEXT: &[u8] = b"cc-switch-secret-store";
+const VERIFIER_AAD: &[u8] = b"cc-switch:verifier";
+
+/// `${secret:name}` 引用，名称仅允许字母、数字、`_`、`-`、`.`
+static SECRET_REF_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\$\{secret:([A-Za-z0-9_.\-]{1,64})\}").expect("valid regex"));
+
+/// 已解锁的主密钥（按密钥库路径缓存）
+static UNLOCKED_KEYS: Lazy<Mutex<HashMap<PathBuf, [u8; KEY_LEN]>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 串行化密钥库文件的读改写
+static STORE_WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySource {
+    Keyring,
+    Passphrase,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptedEntry {
+    /// base64(nonce || ciphertext || tag)
+    value: String,
+    updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoreFile {
+    version: u32,
+    key_source: KeySource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    /// 用于校验主密钥是否正确
+    verifier: String,
+    #[serde(default)]
+    secrets: BTreeMap<String, EncryptedEntry>,
+}
+
+/// 密钥条目信息（不含明文）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretInfo {
+    pub name: String,
+    pub updated_at: i64,
+}
+
+/// 密钥库状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretStoreStatus {
+    pub initialized: bool,
+    pub key_source: Option<KeySource>,
+    pub unlocked: bool,
+    pub keyring_available: bool,
+    pub secrets: Vec<SecretInfo>,
+}
+
+/// 密钥库句柄
+pub struct SecretStore {
+    path: PathBuf,
+}
+
+impl SecretStore {
+    /// 默认位置：`~/.cc-switch/secrets.json`
+    pub fn open_default() -> Self {
+        Self::at(crate::config::get_app_config_dir().join("secrets.json"))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn store_dir(&self) -> &Path {
+        self.path.parent().unwrap_or_else(|| Path::new("."))
+    }
+
+    pub fn status(&self) -> Result<SecretStoreStatus, AppError> {
+        let keyring_available = keyring::is_available();
+        let Some(file) = self.read_file()? else {
+            return Ok(SecretStoreStatus {
+                initialized: false,
+                key_source: None,
+                unlocked: false,
+                keyring_available,
+                secrets: Vec::new(),
+            });
+        };
+        let unlocked = self.unlocked_key(&file).is_ok();
+        Ok(SecretStoreStatus {
+            initialized: true,
+            key_source: Some(file.key_source),
+            unlocked,
+            keyring_available,
+            secrets: list_entries(&file),
+        })
+    }
+
+    /// 初始化密钥库
+    ///
+    /// 提供口令时使用口令模式，否则使用系统密钥环；已初始化时返回错误。
+    pub fn init(&self, passphrase: Option<&str>) -> Result<(), AppError> {
+        let _guard = lock_writes()?;
+        if self.path.exists() {
+            return Err(AppError::localized(
+                "secret.alreadyInitialized",
+                "密钥库已初始化",
+                "Secret store is already initialized",
+            ));
+        }
+
+        let rng = SystemRandom::new();
+        let (key, key_source, salt) = match passphrase {
+            Some(passphrase) => {
+                let passphrase = validate_passphrase(passphrase)?;
+                let mut salt = [0u8; SALT_LEN];
+                fill_random(&rng, &mut salt)?;
+                (
+                    derive_key(passphrase, &salt),
+                    KeySource::Passphrase,
+                    Some(BASE64_STANDARD.encode(salt)),
+                )
+            }
+            None => {
+                if !keyring::is_available() {
+                    return Err(AppError::localized(
+                        "secret.keyringUnavailable",
+                        "系统密钥环不可用，请改用口令初始化密钥库",
+                        "OS keyring is unavailable; initialize the secret store with a passphrase instead",
+                    ));
+                }
+                let mut key = [0u8; KEY_LEN];
+                fill_random(&rng, &mut key)?;
+                keyring::set(self.store_dir(), &BASE64_STANDARD.encode(key)).map_err(|e| {
+                    AppError::localized(
+                        "secret.keyringWriteFailed",
+                        format!("写入系统密钥环失败: {e}"),
+                        format!("Failed to write OS keyring: {e}"),
+                    )
+                })?;
+                (key, KeySource::Keyring, None)
+            }
+        };
+
+        let file = StoreFile {
+            version: STORE_VERSION,
+            key_source,
+            salt,
+            verifier: encrypt(&key, VERIFIER_AAD, VERIFIER_PLAINTEXT)?,
+            secrets: BTreeMap::new(),
+        };
+        self.write_file(&file)?;
+        cache_key(&self.path, key)?;
+        log::info!("密钥库已初始化（{key_source:?}）");
+        Ok(())
+    }
+
+    /// 使用口令解锁（仅口令模式）
+    pub fn unlock(&self, passphrase: &str) -> Result<(), AppError> {
+        let file = self.require_file()?;
+        if file.key_source != KeySource::Passphrase {
+            return self.unlocked_key(&file).map(|_| ());
+        }
+        let key = key_from_passphrase(&file, passphrase)?;
+        verify_key(&file, &key)?;
+        cache_key(&self.path, key)
+    }
+
+    /// 清除内存中的主密钥
+    pub fn lock(&self) -> Result<(), AppError> {
+        UNLOCKED_KEYS
+            .lock()
+            .map_err(AppError::from)?
+            .remove(&self.path);
+        Ok(())
+    }
+
+    pub fn list(&self) -> Result<Vec<SecretInfo>, AppError> {
+        Ok(self
+            .read_file()?
+            .map(|file| list_entries(&file))
+            .unwrap_or_default())
+    }
+
+    /// 新增或更新密钥
+    pub fn set(&self, name: &str, value: &str) -> Result<(), AppError> {
+        validate_name(name)?;
+        let _guard = lock_writes()?;
+        let mut file = self.require_file()?;
+        let key = self.unlocked_key(&file)?;
+        file.secrets.insert(
+            name.to_string(),
+            EncryptedEntry {
+                value: encrypt(&key, name.as_bytes(), value.as_bytes())?,
+                updated_at: chrono::Utc::now().timestamp_millis(),
+            },
+        );
+        self.write_file(&file)
+    }
+
+    /// 删除密钥，返回是否存在
+    pub fn delete(&self, name: &str) -> Result<bool, AppError> {
+        let _guard = lock_writes()?;
+        let Some(mut file) = self.read_file()? else {
+            return Ok(false);
+        };
+        let removed = file.secrets.remove(name).is_some();
+        if removed {
+            self.write_file(&file)?;
+        }
+        Ok(removed)
+    }
+
+    /// 读取密钥明文
+    pub fn get(&self, name: &str) -> Result<Option<String>, AppError> {
+        let Some(file) = self.read_file()? else {
+            return Ok(None);
+        };
+        let Some(entry) = file.secrets.get(name) else {
+            return Ok(None);
+        };
+        let key = self.unlocked_key(&file)?;
+        let plain = decrypt(&key, name.as_bytes(), &entry.value)?;
+        String::from_utf8(plain)
+            .map(Some)
+            .map_err(|e| AppError::Message(format!("密钥 '{name}' 不是有效的 UTF-8: {e}")))
+    }
+
+    /// 解析 JSON 中所有字符串里的密钥引用
+    pub fn resolve_value(&self, value: &Value) -> Result<Value, AppError> {
+        let mut names = HashSet::new();
+        collect_ref_names(value, &mut names);
+        if names.is_empty() {
+            return Ok(value.clone());
+        }
+        let secrets = self.load_many(&names)?;
+        Ok(resolve_value_with(value, &secrets))
+    }
+
+    /// 解析字符串中的密钥引用
+    pub fn resolve_str(&self, input: &str) -> Result<String, AppError> {
+        match self.resolve_value(&Value::String(input.to_string()))? {
+            Value::String(resolved) => Ok(resolved),
+            other => Ok(other.to_string()),
+        }
+    }
+
+    fn load_many(&self, names: &HashSet<String>) -> Result<HashMap<String, String>, AppError> {
+        let mut out = HashMap::new();
+        let mut sorted: Vec<_> = names.iter().collect();
+        sorted.sort();
+        for name in sorted {
+            let value = self.get(name)?.ok_or_else(|| {
+                AppError::localized(
+                    "secret.notFound",
+                    format!("引用的密钥不存在: {name}"),
+                    format!("Referenced secret not found: {name}"),
+                )
+            })?;
+            out.insert(name.clone(), value);
+        }
+        Ok(out)
+    }
+
+    /// 获取主密钥：优先内存缓存，其次系统密钥环或口令环境变量
+    fn unlocked_key(&self, file: &StoreFile) -> Result<[u8; KEY_LEN], AppError> {
+        if let Some(key) = UNLOCKED_KEYS
+            .lock()
+            .map_err(AppError::from)?
+            .get(&self.path)
+            .copied()
+        {
+            return Ok(key);
+        }
+
+        let key = match file.key_source {
+            KeySource::Keyring => keyring::get(self.store_dir())
+                .and_then(|encoded| BASE64_STANDARD.decode(encoded.trim()).ok())
+                .and_then(|bytes| <[u8; KEY_LEN]>::try_from(bytes.as_slice()).ok()),
+            KeySource::Passphrase => std::env::var(PASSPHRASE_ENV)
+                .ok()
+                .filter(|p| !p.is_empty())
+                .map(|p| key_from_passphrase(file, &p))
+                .transpose()?,
+        };
+        let Some(key) = key else {
+            return Err(AppError::localized(
+                "secret.locked",
+                "密钥库已锁定，请先解锁",
+                "Secret store is locked; unlock it first",
+            ));
+        };
+        verify_key(file, &key)?;
+        cache_key(&self.path, key)?;
+        Ok(key)
+    }
+
+    fn require_file(&self) -> Result<StoreFile, AppError> {
+        self.read_file()?.ok_or_else(|| {
+            AppError::localized(
+                "secret.notInitialized",
+                "密钥库尚未初始化",
+                "Secret store is not initialized",
+            )
+        })
+    }
+
+    fn read_file(&self) -> Result<Option<StoreFile>, AppError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let file: StoreFile = crate::config::read_json_file(&self.path)?;
+        if file.version > STORE_VERSION {
+            return Err(AppError::Config(format!(
+                "不支持的密钥库版本: {}",
+                file.version
+            )));
+        }
+        Ok(Some(file))
+    }
+
+    fn write_file(&self, file: &StoreFile) -> Result<(), AppError> {
+        crate::config::write_json_file(&self.path, file)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&self.path, std::fs::Permissions::from_mode(0o600));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// 引用解析（无引用时不会访问密钥库）
+// ============================================================================
+
+/// 字符串中是否包含密钥引用
+pub fn has_refs(input: &str) -> bool {
+    SECRET_REF_RE.is_match(input)
+}
+
+/// JSON 中是否包含密钥引用
+pub fn contains_refs(value: &Value) -> bool {
+    match value {
+        Value::String(s) => has_refs(s),
+        Value::Array(arr) => arr.iter().any(contains_refs),
+        Value::Object(obj) => obj.values().any(contains_refs),
+        _ => false,
+    }
+}
+
+/// 使用默认密钥库解析 JSON 中的密钥引用
+pub fn resolve_value(value: &Value) -> Result<Value, AppError> {
+    if !contains_refs(value) {
+        return Ok(value.clone());
+    }
+    SecretStore::open_default().resolve_value(value)
+}
+
+/// 使用默认密钥库解析字符串中的密钥引用
+pub fn resolve_str(input: &str) -> Result<String, AppError> {
+    if !has_refs(input) {
+        return Ok(input.to_string());
+    }
+    SecretStore::open_default().resolve_str(input)
+}
+
+fn ref_names(input: &str) -> impl Iterator<Item = String> + '_ {
+    SECRET_REF_RE
+        .captures_iter(input)
+        .map(|caps| caps[1].to_string())
+}
+
+fn collect_ref_names(value: &Value, out: &mut HashSet<String>) {
+    match value {
+        Value::String(s) => out.extend(ref_names(s)),
+        Value::Array(arr) => arr.iter().for_each(|v| collect_ref_names(v, out)),
+        Value::Object(obj) => obj.values().for_each(|v| collect_ref_names(v, out)),
+        _ => {}
+    }
+}
+
+fn resolve_str_with(input: &str, secrets: &HashMap<String, String>) -> String {
+    SECRET_REF_RE
+        .replace_all(input, |caps: &regex::Captures| {
+            secrets
+                .get(&caps[1])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+fn resolve_value_with(value: &Value, secrets: &HashMap<String, String>) -> Value {
+    match value {
+        Value::String(s) => Value::String(resolve_str_with(s, secrets)),
+        Value::Array(arr) => {
+            Value::Array(arr.iter().map(|v| resolve_value_with(v, secrets)).collect())
+        }
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), resolve_value_with(v, secrets)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// 回填 live 配置时保留原有的密钥引用
+///
+/// live 配置中是解析后的明文；若某个字符串与原配置中含引用的模板相匹配
+/// （仅引用位置不同），则写回原模板，避免明文进入数据库。
+/// 多行字符串（如 Codex 的 config.toml）在整体不匹配时按行处理。
+pub fn preserve_refs(original: &Value, live: Value) -> Value {
+    if !contains_refs(original) {
+        return live;
+    }
+    match (original, live) {
+        (Value::String(orig), Value::String(live)) => Value::String(preserve_str_refs(orig, live)),
+        (Value::Object(orig), Value::Object(live)) => Value::Object(
+            live.into_iter()
+                .map(|(k, v)| {
+                    let v = match orig.get(&k) {
+                        Some(o) => preserve_refs(o, v),
+                        None => v,
+                    };
+                    (k, v)
+                })
+                .collect(),
+        ),
+        (Value::Array(orig), Value::Array(live)) if orig.len() == live.len() => Value::Array(
+            orig.iter()
+                .zip(live)
+                .map(|(o, v)| preserve_refs(o, v))
+                .collect(),
+        ),
+        (_, live) => live,
+    }
+}
+
+fn preserve_str_refs(original: &str, live: String) -> String {
+    if !has_refs(original) {
+        return live;
+    }
+    if matches_template(original, &live) {
+        return original.to_string();
+    }
+    if !original.contains('\n') || !live.contains('\n') {
+        return live;
+    }
+    let templates: Vec<&str> = original.lines().filter(|line| has_refs(line)).collect();
+    let mut out: Vec<&str> = live
+        .lines()
+        .map(|line| {
+            templates
+                .iter()
+                .find(|template| matches_template(template, line))
+                .copied()
+                .unwrap_or(line)
+        })
+        .collect();
+    if live.ends_with('\n') {
+        out.push("");
+    }
+    out.join("\n")
+}
+
+/// 判断 `candidate` 是否为 `template` 解析引用后的结果
+fn matches_template(template: &str, candidate: &str) -> bool {
+    let mut pattern = String::from("^");
+    let mut last = 0;
+    for m in SECRET_REF_RE.find_iter(template) {
+        pattern.push_str(&regex::escape(&template[last..m.start()]));
+        pattern.push_str("(?s:.+)");
+        last = m.end();
+    }
+    pattern.push_str(&regex::escape(&template[last..]));
+    pattern.push('$');
+    Regex::new(&pattern)
+        .map(|re| re.is_match(candidate))
+        .unwrap_or(false)
+}
+
+// ============================================================================
+// 加解密
+// ============================================================================
+
+fn lock_writes() -> Result<std::sync::MutexGuard<'static, ()>, AppError> {
+    STORE_WRITE_LOCK.lock().map_err(AppError::from)
+}
+
+fn cache_key(path: &Path, key: [u8; KEY_LEN]) -> Result<(), AppError> {
+    UNLOCKED_KEYS
+        .lock()
+        .map_err(AppError::from)?
+        .insert(path.to_path_buf(), key);
+    Ok(())
+}
+
+fn list_entries(file: &StoreFile) -> Vec<SecretInfo> {
+    file.secrets
+        .iter()
+        .map(|(name, entry)| SecretInfo {
+            name: name.clone(),
+            updated_at: entry.updated_at,
+        })
+        .collect()
+}
+
+fn validate_name(name: &str) -> Result<(), AppError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::localized(
+            "secret.invalidName",
+            format!("无效的密钥名称: {name}（仅允许字母、数字、_、-、.）"),
+            format!("Invalid secret name: {name} (only letters, digits, _, - and . are allowed)"),
+        ))
+    }
+}
+
+fn validate_passphrase(passphrase: &str) -> Result<&str, AppError> {
+    if passphrase.chars().count() < 8 {
+        return Err(AppError::localized(
+            "secret.passphraseTooShort",
+            "口令至少需要 8 个字符",
+            "Passphrase must be at least 8 characters",
+        ));
+    }
+    Ok(passphrase)
+}
+
+fn fill_random(rng: &SystemRandom, buf: &mut [u8]) -> Result<(), AppError> {
+    rng.fill(buf)
+        .map_err(|_| AppError::Message("生成随机数失败".to_string()))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    ring::pbkdf2::derive(
+        ring::pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).expect("non-zero iterations"),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+fn key_from_passphrase(file: &StoreFile, passphrase: &str) -> Result<[u8; KEY_LEN], AppError> {
+    let salt = file
+        .salt
+        .as_deref()
+        .and_then(|s| BASE64_STANDARD.decode(s).ok())
+        .ok_or_else(|| AppError::Config("密钥库缺少口令盐值".to_string()))?;
+    Ok(derive_key(passphrase, &salt))
+}
+
+fn verify_key(file: &StoreFile, key: &[u8; KEY_LEN]) -> Result<(), AppError> {
+    match decrypt(key, VERIFIER_AAD, &file.verifier) {
+        Ok(plain) if plain == VERIFIER_PLAINTEXT => Ok(()),
+        _ => Err(AppError::localized(
+            "secret.wrongKey",
+            "口令错误或主密钥不匹配",
+            "Wrong passphrase or mismatched master key",
+        )),
+    }
+}
+
+fn aead_key(key: &[u8; KEY_LEN]) -> Result<LessSafeKey, AppError> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| AppError::Message("无效的主密钥".to_string()))
+}
+
+/// 加密；关联数据绑定条目名称，防止密文在条目间被替换
+fn encrypt(key: &[u8; KEY_LEN], aad: &[u8], plaintext: &[u8]) -> Result<String, AppError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    fill_random(&SystemRandom::new(), &mut nonce)?;
+    let mut in_out = plaintext.to_vec();
+    aead_key(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad),
+            &mut in_out,
+        )
+        .map_err(|_| AppError::Message("加密失败".to_string()))?;
+    let mut out = nonce.to_vec();
+    out.extend_from_slice(&in_out);
+    Ok(BASE64_STANDARD.encode(out))
+}
+
+fn decrypt(key: &[u8; KEY_LEN], aad: &[u8], encoded: &str) -> Result<Vec<u8>, AppError> {
+    let data = BASE64_STANDARD
+        .decode(encoded)
+        .map_err(|e| AppError::Message(format!("密文格式无效: {e}")))?;
+    if data.len() < NONCE_LEN {
+        return Err(AppError::Message("密文格式无效".to_string()));
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| AppError::Message("密文格式无效".to_string()))?;
+    let mut in_out = ciphertext.to_vec();
+    let plain = aead_key(key)?
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|_| AppError::Message("解密失败".to_string()))?;
+    Ok(plain.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_passphrase_store_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("secrets.json");
+        let store = SecretStore::at(&path);
+
+        store.init(Some("correct horse battery")).unwrap();
+        store.set("github_token", "ghp_123").unwrap();
+        assert_eq!(
+            store.get("github_token").unwrap().as_deref(),
+            Some("ghp_123")
+        );
+
+        // 文件中不含明文
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("ghp_123"));
+
+        // 锁定后需要口令解锁
+        store.lock().unwrap();
+        assert!(store.get("github_token").is_err());
+        assert!(store.unlock("wrong passphrase").is_err());
+        store.unlock("correct horse battery").unwrap();
+
+        let resolved = store
+            .resolve_value(&json!({"env": {"TOKEN": "Bearer ${secret:github_token}"}}))
+            .unwrap();
+        assert_eq!(resolved["env"]["TOKEN"], "Bearer ghp_123");
+
+        assert!(store.resolve_value(&json!("${secret:missing}")).is_err());
+        assert!(store.delete("github_token").unwrap());
+        assert!(store.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_without_refs_does_not_need_store() {
+        let value = json!({"env": {"KEY": "plain", "PRICE": "${HOME}"}});
+        assert!(!contains_refs(&value));
+        assert_eq!(resolve_value(&value).unwrap(), value);
+    }
+
+    #[test]
+    fn test_preserve_refs() {
+        let original = json!({
+            "env": {
+                "ANTHROPIC_AUTH_TOKEN": "${secret:relay}",
+                "ANTHROPIC_BASE_URL": "https://old.example.com"
+            },
+            "config": "model = \"x\"\nbearer = \"Bearer ${secret:relay}\"\n"
+        });
+        let live = json!({
+            "env": {
+                "ANTHROPIC_AUTH_TOKEN": "sk-live",
+                "ANTHROPIC_BASE_URL": "https://new.example.com"
+            },
+            "config": "model = \"y\"\nbearer = \"Bearer sk-live\"\n"
+        });
+
+        let merged = preserve_refs(&original, live);
+        assert_eq!(merged["env"]["ANTHROPIC_AUTH_TOKEN"], "${secret:relay}");
+        assert_eq!(
+            merged["env"]["ANTHROPIC_BASE_URL"],
+            "https://new.example.com"
+        );
+        assert_eq!(
+            merged["config"],
+            "model = \"y\"\nbearer = \"Bearer ${secret:relay}\"\n"
+        );
+    }
+}
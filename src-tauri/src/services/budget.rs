@@ -0,0 +1,590 @@
+//! 预算告警服务
+//!
+//! 在每次记录请求用量后评估软阈值（日/月限额或全局月度预算的百分比），
+//! 结合本月消费速率预测预算耗尽日期，并通过前端事件与托盘提示通知用户。
+//! 同一周期内每个阈值只触发一次。
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::database::Database;
+use crate::error::AppError;
+use crate::proxy::log_codes::usg as log_usg;
+
+/// 预算告警配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAlertConfig {
+    /// 是否启用预算告警
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 告警阈值（百分比），如 [50, 80, 100]
+    #[serde(default = "default_thresholds")]
+    pub thresholds: Vec<u32>,
+    /// 全局月度预算（USD），跨所有供应商
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global_monthly_budget_usd: Option<String>,
+    /// 是否在托盘提示中显示告警
+    #[serde(default = "default_enabled")]
+    pub tray_badge: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_thresholds() -> Vec<u32> {
+    vec![50, 80, 100]
+}
+
+impl Default for BudgetAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            thresholds: default_thresholds(),
+            global_monthly_budget_usd: None,
+            tray_badge: true,
+        }
+    }
+}
+
+impl BudgetAlertConfig {
+    /// 规范化后的阈值列表（升序、去重、剔除 0）
+    fn normalized_thresholds(&self) -> Vec<u32> {
+        let mut thresholds: Vec<u32> = self.thresholds.iter().copied().filter(|t| *t > 0).collect();
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        thresholds
+    }
+
+    fn global_monthly_budget(&self) -> Option<f64> {
+        self.global_monthly_budget_usd
+            .as_deref()
+            .and_then(|s| s.trim().parse::<f64>().ok())
+            .filter(|v| *v > 0.0)
+    }
+}
+
+/// 告警作用范围
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetScope {
+    /// 供应商日限额（limitDailyUsd）
+    ProviderDaily,
+    /// 供应商月限额（limitMonthlyUsd）
+    ProviderMonthly,
+    /// 全局月度预算
+    GlobalMonthly,
+}
+
+/// 月度消费预测
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetProjection {
+    /// 本月平均每日消费
+    pub daily_burn_rate: String,
+    /// 按当前速率预计的本月总消费
+    pub projected_month_total: String,
+    /// 按当前速率预计达到预算的日期（YYYY-MM-DD），本月内不会达到则为 None
+    pub exhaust_date: Option<String>,
+}
+
+/// 单次预算告警
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAlert {
+    pub scope: BudgetScope,
+    pub provider_id: Option<String>,
+    pub app_type: Option<String>,
+    /// 周期标识（日：YYYY-MM-DD，月：YYYY-MM）
+    pub period: String,
+    pub threshold: u32,
+    pub usage: String,
+    pub limit: String,
+    pub percent: f64,
+    pub projection: Option<BudgetProjection>,
+}
+
+/// 全局预算状态（供前端展示）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub monthly_usage: String,
+    pub monthly_budget: Option<String>,
+    pub percent: Option<f64>,
+    pub projection: BudgetProjection,
+}
+
+/// 已显示在托盘提示中的预算告警（提示来源 → 周期），周期结束后清除
+static TRAY_NOTICE_PERIODS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// 预算告警服务
+pub struct BudgetAlertService;
+
+impl BudgetAlertService {
+    /// 评估并通知（记录用量后调用）
+    ///
+    /// 评估失败只记录日志，不影响请求处理。
+    pub fn evaluate_and_notify(
+        db: &Database,
+        app_handle: Option<&tauri::AppHandle>,
+        provider_id: &str,
+        app_type: &str,
+    ) {
+        let now = Local::now();
+        let alerts = match Self::evaluate(db, provider_id, app_type, now) {
+            Ok(alerts) => alerts,
+            Err(e) => {
+                log::warn!("[{}] 预算告警评估失败: {e}", log_usg::BUDGET_EVAL_FAILED);
+                return;
+            }
+        };
+
+        let tray_badge = db
+            .get_budget_alert_config()
+            .map(|c| c.tray_badge)
+            .unwrap_or(true);
+        if let Some(app) = app_handle {
+            Self::clear_expired_tray_notices(app, now, tray_badge);
+        }
+
+        if alerts.is_empty() {
+            return;
+        }
+
+        for alert in &alerts {
+            log::info!(
+                "[{}] 预算告警: scope={:?}, provider={:?}, threshold={}%, usage={}, limit={}",
+                log_usg::BUDGET_ALERT_FIRED,
+                alert.scope,
+                alert.provider_id,
+                alert.threshold,
+                alert.usage,
+                alert.limit
+            );
+        }
+
+        if let Some(app) = app_handle {
+            for alert in &alerts {
+                if let Err(e) = app.emit("budget-alert", alert) {
+                    log::error!(
+                        "[{}] 发射预算告警事件失败: {e}",
+                        log_usg::BUDGET_EVAL_FAILED
+                    );
+                }
+            }
+
+            if tray_badge {
+                if let Ok(mut shown) = TRAY_NOTICE_PERIODS.lock() {
+                    for alert in &alerts {
+                        let key = Self::tray_notice_key(alert);
+                        crate::tray::set_tray_notice(app, &key, Some(Self::describe(alert)));
+                        shown.insert(key, alert.period.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// 清除周期已结束（或已关闭托盘提示）的预算告警提示
+    fn clear_expired_tray_notices(app: &tauri::AppHandle, now: DateTime<Local>, tray_badge: bool) {
+        let day_period = now.format("%Y-%m-%d").to_string();
+        let month_period = now.format("%Y-%m").to_string();
+        let Ok(mut shown) = TRAY_NOTICE_PERIODS.lock() else {
+            return;
+        };
+        shown.retain(|key, period| {
+            let current = tray_badge && (*period == day_period || *period == month_period);
+            if !current {
+                crate::tray::set_tray_notice(app, key, None);
+            }
+            current
+        });
+    }
+
+    /// 托盘提示行的来源标识（每个预算范围一行）
+    fn tray_notice_key(alert: &BudgetAlert) -> String {
+        format!(
+            "budget:{:?}:{}:{}",
+            alert.scope,
+            alert.app_type.as_deref().unwrap_or_default(),
+            alert.provider_id.as_deref().unwrap_or_default()
+        )
+    }
+
+    /// 评估指定供应商及全局预算，返回本次新触发的告警
+    ///
+    /// 一次跨越多个阈值时（例如从 40% 直接到 85%），低阈值同样会被标记为已触发，
+    /// 但只返回最高的那一个，避免重复提醒。
+    pub fn evaluate(
+        db: &Database,
+        provider_id: &str,
+        app_type: &str,
+        now: DateTime<Local>,
+    ) -> Result<Vec<BudgetAlert>, AppError> {
+        let config = db.get_budget_alert_config()?;
+        if !config.enabled {
+            return Ok(Vec::new());
+        }
+        let thresholds = config.normalized_thresholds();
+        if thresholds.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let day_period = now.format("%Y-%m-%d").to_string();
+        let month_period = now.format("%Y-%m").to_string();
+        let mut alerts = Vec::new();
+
+        let limits = db.check_provider_limits(provider_id, app_type)?;
+        let daily_usage = parse_amount(&limits.daily_usage);
+        let monthly_usage = parse_amount(&limits.monthly_usage);
+
+        if let Some(limit) = limits.daily_limit.as_deref().map(parse_amount) {
+            let scope_key = format!("provider:{app_type}:{provider_id}:daily");
+            if let Some(threshold) = Self::fire_thresholds(
+                db,
+                &scope_key,
+                &day_period,
+                &thresholds,
+                daily_usage,
+                limit,
+                now.timestamp(),
+            )? {
+                alerts.push(BudgetAlert {
+                    scope: BudgetScope::ProviderDaily,
+                    provider_id: Some(provider_id.to_string()),
+                    app_type: Some(app_type.to_string()),
+                    period: day_period.clone(),
+                    threshold,
+                    usage: format!("{daily_usage:.6}"),
+                    limit: format!("{limit:.2}"),
+                    percent: percent_of(daily_usage, limit),
+                    projection: None,
+                });
+            }
+        }
+
+        if let Some(limit) = limits.monthly_limit.as_deref().map(parse_amount) {
+            let scope_key = format!("provider:{app_type}:{provider_id}:monthly");
+            if let Some(threshold) = Self::fire_thresholds(
+                db,
+                &scope_key,
+                &month_period,
+                &thresholds,
+                monthly_usage,
+                limit,
+                now.timestamp(),
+            )? {
+                alerts.push(BudgetAlert {
+                    scope: BudgetScope::ProviderMonthly,
+                    provider_id: Some(provider_id.to_string()),
+                    app_type: Some(app_type.to_string()),
+                    period: month_period.clone(),
+                    threshold,
+                    usage: format!("{monthly_usage:.6}"),
+                    limit: format!("{limit:.2}"),
+                    percent: percent_of(monthly_usage, limit),
+                    projection: Some(project_monthly_spend(monthly_usage, Some(limit), now)),
+                });
+            }
+        }
+
+        if let Some(budget) = config.global_monthly_budget() {
+            let global_usage = db.get_global_monthly_usage(now)?;
+            if let Some(threshold) = Self::fire_thresholds(
+                db,
+                "global:monthly",
+                &month_period,
+                &thresholds,
+                global_usage,
+                budget,
+                now.timestamp(),
+            )? {
+                alerts.push(BudgetAlert {
+                    scope: BudgetScope::GlobalMonthly,
+                    provider_id: None,
+                    app_type: None,
+                    period: month_period.clone(),
+                    threshold,
+                    usage: format!("{global_usage:.6}"),
+                    limit: format!("{budget:.2}"),
+                    percent: percent_of(global_usage, budget),
+                    projection: Some(project_monthly_spend(global_usage, Some(budget), now)),
+                });
+            }
+        }
+
+        Ok(alerts)
+    }
+
+    /// 获取全局月度预算状态与消费预测
+    pub fn get_status(db: &Database) -> Result<BudgetStatus, AppError> {
+        let config = db.get_budget_alert_config()?;
+        let budget = config.global_monthly_budget();
+        let now = Local::now();
+        let usage = db.get_global_monthly_usage(now)?;
+
+        Ok(BudgetStatus {
+            monthly_usage: format!("{usage:.6}"),
+            monthly_budget: budget.map(|b| format!("{b:.2}")),
+            percent: budget.map(|b| percent_of(usage, b)),
+            projection: project_monthly_spend(usage, budget, now),
+        })
+    }
+
+    /// 标记所有已达到的阈值，返回本次新达到的最高阈值
+    fn fire_thresholds(
+        db: &Database,
+        scope_key: &str,
+        period: &str,
+        thresholds: &[u32],
+        usage: f64,
+        limit: f64,
+        fired_at: i64,
+    ) -> Result<Option<u32>, AppError> {
+        if limit <= 0.0 {
+            return Ok(None);
+        }
+        let percent = percent_of(usage, limit);
+
+        let mut highest = None;
+        for &threshold in thresholds {
+            if percent < threshold as f64 {
+                break;
+            }
+            if db.try_record_budget_alert(scope_key, period, threshold, fired_at)? {
+                highest = Some(threshold);
+            }
+        }
+        Ok(highest)
+    }
+
+    fn describe(alert: &BudgetAlert) -> String {
+        let target = match alert.scope {
+            BudgetScope::ProviderDaily => format!(
+                "{} 日限额",
+                alert.provider_id.as_deref().unwrap_or_default()
+            ),
+            BudgetScope::ProviderMonthly => format!(
+                "{} 月限额",
+                alert.provider_id.as_deref().unwrap_or_default()
+            ),
+            BudgetScope::GlobalMonthly => "全局月度预算".to_string(),
+        };
+        let mut text = format!(
+            "{target}已达 {}%（${} / ${}）",
+            alert.threshold, alert.usage, alert.limit
+        );
+        if let Some(date) = alert
+            .projection
+            .as_ref()
+            .and_then(|p| p.exhaust_date.as_deref())
+        {
+            text.push_str(&format!("，预计 {date} 用尽"));
+        }
+        text
+    }
+}
+
+/// 按本月已消费金额与已过天数预测月度消费
+///
+/// 月初数据较少时，按至少 1 天计算速率，避免预测值被放大。
+pub(crate) fn project_monthly_spend(
+    spent: f64,
+    budget: Option<f64>,
+    now: DateTime<Local>,
+) -> BudgetProjection {
+    let today = now.date_naive();
+    let elapsed_days = (today.day() - 1) as f64 + now.num_seconds_from_midnight() as f64 / 86_400.0;
+    let daily_rate = spent / elapsed_days.max(1.0);
+    let days_in_month = days_in_month(today) as f64;
+
+    let exhaust_date = budget.and_then(|budget| {
+        if spent >= budget {
+            return Some(today);
+        }
+        if daily_rate <= 0.0 {
+            return None;
+        }
+        let remaining_days = (budget - spent) / daily_rate;
+        let date = (now + Duration::seconds((remaining_days * 86_400.0) as i64)).date_naive();
+        (date.month() == today.month() && date.year() == today.year()).then_some(date)
+    });
+
+    BudgetProjection {
+        daily_burn_rate: format!("{daily_rate:.6}"),
+        projected_month_total: format!("{:.6}", daily_rate * days_in_month),
+        exhaust_date: exhaust_date.map(|d| d.format("%Y-%m-%d").to_string()),
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first_of_next| first_of_next.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(30)
+}
+
+fn parse_amount(value: &str) -> f64 {
+    value.trim().parse::<f64>().unwrap_or(0.0)
+}
+
+fn percent_of(usage: f64, limit: f64) -> f64 {
+    if limit <= 0.0 {
+        0.0
+    } else {
+        usage / limit * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::lock_conn;
+    use chrono::TimeZone;
+    use rusqlite::params;
+
+    fn local(y: i32, m: u32, d: u32, h: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(y, m, d, h, 0, 0)
+            .single()
+            .expect("valid local time")
+    }
+
+    #[test]
+    fn test_projection_estimates_exhaust_date() {
+        // 10 日 0 点已花费 90，速率 10/天，预算 180 → 19 日用尽
+        let projection = project_monthly_spend(90.0, Some(180.0), local(2026, 10, 10, 0));
+        assert_eq!(projection.daily_burn_rate, "10.000000");
+        assert_eq!(projection.projected_month_total, "310.000000");
+        assert_eq!(projection.exhaust_date.as_deref(), Some("2026-10-19"));
+
+        // 本月内不会用尽
+        let projection = project_monthly_spend(10.0, Some(1000.0), local(2026, 10, 10, 0));
+        assert!(projection.exhaust_date.is_none());
+
+        // 已超出预算
+        let projection = project_monthly_spend(200.0, Some(100.0), local(2026, 2, 5, 12));
+        assert_eq!(projection.exhaust_date.as_deref(), Some("2026-02-05"));
+    }
+
+    #[test]
+    fn test_days_in_month() {
+        assert_eq!(
+            days_in_month(NaiveDate::from_ymd_opt(2024, 2, 10).unwrap()),
+            29
+        );
+        assert_eq!(
+            days_in_month(NaiveDate::from_ymd_opt(2026, 12, 31).unwrap()),
+            31
+        );
+        assert_eq!(
+            days_in_month(NaiveDate::from_ymd_opt(2026, 4, 1).unwrap()),
+            30
+        );
+    }
+
+    #[test]
+    fn test_thresholds_fire_once_per_period() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.save_budget_alert_config(&BudgetAlertConfig {
+            global_monthly_budget_usd: Some("10".to_string()),
+            ..Default::default()
+        })?;
+
+        let insert_log = |id: &str, cost: &str| -> Result<(), AppError> {
+            let conn = lock_conn!(db.conn);
+            conn.execute(
+                "INSERT INTO proxy_request_logs (
+                    request_id, provider_id, app_type, model, total_cost_usd,
+                    latency_ms, status_code, created_at
+                ) VALUES (?, 'p1', 'claude', 'm', ?, 100, 200, ?)",
+                params![id, cost, Local::now().timestamp()],
+            )?;
+            Ok(())
+        };
+
+        // 85%：50 与 80 同时达到，只返回最高阈值
+        insert_log("req1", "8.5")?;
+        let alerts = BudgetAlertService::evaluate(&db, "p1", "claude", Local::now())?;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].scope, BudgetScope::GlobalMonthly);
+        assert_eq!(alerts[0].threshold, 80);
+        assert!(alerts[0].projection.is_some());
+
+        // 同一周期内不再重复触发
+        let alerts = BudgetAlertService::evaluate(&db, "p1", "claude", Local::now())?;
+        assert!(alerts.is_empty());
+
+        // 越过 100% 后触发新阈值
+        insert_log("req2", "2")?;
+        let alerts = BudgetAlertService::evaluate(&db, "p1", "claude", Local::now())?;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].threshold, 100);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_global_usage_counts_the_month_of_now() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, cost, at) in [
+                ("sep", "1", local(2026, 9, 30, 12)),
+                ("oct-start", "0.5", local(2026, 10, 1, 0)),
+                ("oct1", "2", local(2026, 10, 1, 12)),
+                ("oct2", "3", local(2026, 10, 20, 12)),
+                ("nov-start", "7", local(2026, 11, 1, 0)),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model, total_cost_usd,
+                        latency_ms, status_code, created_at
+                    ) VALUES (?, 'p1', 'claude', 'm', ?, 100, 200, ?)",
+                    params![id, cost, at.timestamp()],
+                )?;
+            }
+        }
+
+        // 月初零点计入当月，下月零点不计入
+        assert_eq!(db.get_global_monthly_usage(local(2026, 10, 15, 0))?, 5.5);
+        assert_eq!(db.get_global_monthly_usage(local(2026, 9, 1, 0))?, 1.0);
+        assert_eq!(db.get_global_monthly_usage(local(2026, 11, 1, 0))?, 7.0);
+        assert_eq!(db.get_global_monthly_usage(local(2026, 12, 1, 0))?, 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disabled_config_skips_evaluation() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.save_budget_alert_config(&BudgetAlertConfig {
+            enabled: false,
+            global_monthly_budget_usd: Some("1".to_string()),
+            ..Default::default()
+        })?;
+        {
+            let conn = lock_conn!(db.conn);
+            conn.execute(
+                "INSERT INTO proxy_request_logs (
+                    request_id, provider_id, app_type, model, total_cost_usd,
+                    latency_ms, status_code, created_at
+                ) VALUES ('req1', 'p1', 'claude', 'm', '5', 100, 200, ?)",
+                params![Local::now().timestamp()],
+            )?;
+        }
+
+        let alerts = BudgetAlertService::evaluate(&db, "p1", "claude", Local::now())?;
+        assert!(alerts.is_empty());
+        Ok(())
+    }
+}
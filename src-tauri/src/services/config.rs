@@ -118,6 +118,7 @@ impl ConfigService {
             (current_id, provider)
         };
 
+        // live 配置写入解析后的明文，回填时恢复 ${secret:name} 引用，避免明文进入 config.json
         match app_type {
             AppType::Codex => Self::sync_codex_live(config, &current_id, &provider)?,
             AppType::Claude => Self::sync_claude_live(config, &current_id, &provider)?,
@@ -136,7 +137,8 @@ impl ConfigService {
         provider_id: &str,
         provider: &Provider,
     ) -> Result<(), AppError> {
-        let settings = provider.settings_config.as_object().ok_or_else(|| {
+        let resolved = crate::secret_store::resolve_value(&provider.settings_config)?;
+        let settings = resolved.as_object().ok_or_else(|| {
             AppError::Config(format!("供应商 {provider_id} 的 Codex 配置必须是对象"))
         })?;
         let auth = settings.get("auth").ok_or_else(|| {
@@ -155,13 +157,17 @@ impl ConfigService {
         // MCP 的启用/禁用应通过 McpService::toggle_app 进行
 
         let cfg_text_after = crate::codex_config::read_and_validate_codex_config_text()?;
+        let cfg_after = match provider.settings_config.get("config") {
+            Some(original) => crate::secret_store::preserve_refs(
+                original,
+                serde_json::Value::String(cfg_text_after),
+            ),
+            None => serde_json::Value::String(cfg_text_after),
+        };
         if let Some(manager) = config.get_manager_mut(&AppType::Codex) {
             if let Some(target) = manager.providers.get_mut(provider_id) {
                 if let Some(obj) = target.settings_config.as_object_mut() {
-                    obj.insert(
-                        "config".to_string(),
-                        serde_json::Value::String(cfg_text_after),
-                    );
+                    obj.insert("config".to_string(), cfg_after);
                 }
             }
         }
@@ -181,10 +187,12 @@ impl ConfigService {
             fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
         }
 
-        let settings = sanitize_claude_settings_for_live(&provider.settings_config);
+        let resolved = crate::secret_store::resolve_value(&provider.settings_config)?;
+        let settings = sanitize_claude_settings_for_live(&resolved);
         write_json_file(&settings_path, &settings)?;
 
         let live_after = read_json_file::<serde_json::Value>(&settings_path)?;
+        let live_after = crate::secret_store::preserve_refs(&provider.settings_config, live_after);
         if let Some(manager) = config.get_manager_mut(&AppType::Claude) {
             if let Some(target) = manager.providers.get_mut(provider_id) {
                 target.settings_config = live_after;
@@ -201,7 +209,9 @@ impl ConfigService {
     ) -> Result<(), AppError> {
         use crate::gemini_config::{env_to_json, read_gemini_env};
 
-        ProviderService::write_gemini_live(provider)?;
+        let mut resolved = provider.clone();
+        resolved.settings_config = crate::secret_store::resolve_value(&provider.settings_config)?;
+        ProviderService::write_gemini_live(&resolved)?;
 
         // 读回实际写入的内容并更新到配置中（包含 settings.json）
         let live_after_env = read_gemini_env()?;
@@ -215,6 +225,7 @@ impl ConfigService {
         if let Some(obj) = live_after.as_object_mut() {
             obj.insert("config".to_string(), live_after_config);
         }
+        let live_after = crate::secret_store::preserve_refs(&provider.settings_config, live_after);
 
         if let Some(manager) = config.get_manager_mut(&AppType::Gemini) {
             if let Some(target) = manager.providers.get_mut(provider_id) {
@@ -1,9 +1,11 @@
 use indexmap::IndexMap;
 use std::collections::HashMap;
+use std::path::Path;
 
 use crate::app_config::{AppType, McpServer};
 use crate::error::AppError;
 use crate::mcp;
+use crate::secret_store;
 use crate::store::AppState;
 
 /// MCP 相关业务逻辑（v3.7.0 统一结构）
@@ -16,29 +18,22 @@ impl McpService {
     }
 
     /// 添加或更新 MCP 服务器
-    pub fn upsert_server(state: &AppState, server: McpServer) -> Result<(), AppError> {
-        // 读取旧状态：用于处理“编辑时取消勾选某个应用”的场景（需要从对应 live 配置中移除）
-        let prev_apps = state
-            .db
-            .get_all_mcp_servers()?
-            .get(&server.id)
-            .map(|s| s.apps.clone())
-            .unwrap_or_default();
+    pub fn upsert_server(state: &AppState, mut server: McpServer) -> Result<(), AppError> {
+        server.projects = normalize_project_dirs(&server.projects)?;
+
+        // 读取旧状态：用于处理“编辑时取消勾选某个应用”或“调整项目作用域”的场景
+        // （需要从旧的 live 配置中移除）
+        let prev = state.db.get_all_mcp_servers()?.shift_remove(&server.id);
 
         state.db.save_mcp_server(&server)?;
 
-        // 处理禁用：若旧版本启用但新版本取消，则需要从该应用的 live 配置移除
-        if prev_apps.claude && !server.apps.claude {
-            Self::remove_server_from_app(state, &server.id, &AppType::Claude)?;
-        }
-        if prev_apps.codex && !server.apps.codex {
-            Self::remove_server_from_app(state, &server.id, &AppType::Codex)?;
-        }
-        if prev_apps.gemini && !server.apps.gemini {
-            Self::remove_server_from_app(state, &server.id, &AppType::Gemini)?;
-        }
-        if prev_apps.opencode && !server.apps.opencode {
-            Self::remove_server_from_app(state, &server.id, &AppType::OpenCode)?;
+        if let Some(prev) = prev {
+            let scope_changed = prev.projects != server.projects;
+            for app in prev.apps.enabled_apps() {
+                if scope_changed || !server.apps.is_enabled_for(&app) {
+                    Self::remove_server_from_app(state, &prev, &app)?;
+                }
+            }
         }
 
         // 同步到各个启用的应用
@@ -55,7 +50,7 @@ impl McpService {
             state.db.delete_mcp_server(id)?;
 
             // 从所有应用的 live 配置中移除
-            Self::remove_server_from_all_apps(state, id, &server)?;
+            Self::remove_server_from_all_apps(state, &server)?;
             Ok(true)
         } else {
             Ok(false)
@@ -79,13 +74,52 @@ impl McpService {
             if enabled {
                 Self::sync_server_to_app(state, server, &app)?;
             } else {
-                Self::remove_server_from_app(state, server_id, &app)?;
+                Self::remove_server_from_app(state, server, &app)?;
             }
         }
 
         Ok(())
     }
 
+    /// 设置指定应用禁用的工具，并重新同步到该应用
+    pub fn set_disabled_tools(
+        state: &AppState,
+        server_id: &str,
+        app: AppType,
+        tools: Vec<String>,
+    ) -> Result<(), AppError> {
+        let mut servers = state.db.get_all_mcp_servers()?;
+        let server = servers.get_mut(server_id).ok_or_else(|| {
+            AppError::localized(
+                "mcp.server.notFound",
+                format!("未找到 MCP 服务器: {server_id}"),
+                format!("MCP server not found: {server_id}"),
+            )
+        })?;
+
+        server.disabled_tools.set_for(&app, tools);
+        state.db.save_mcp_server(server)?;
+
+        if server.apps.is_enabled_for(&app) {
+            Self::sync_server_to_app(state, server, &app)?;
+        }
+        Ok(())
+    }
+
+    /// 缓存健康检查发现的工具列表
+    pub fn save_discovered_tools(
+        state: &AppState,
+        server_id: &str,
+        tools: Vec<mcp::McpToolInfo>,
+    ) -> Result<(), AppError> {
+        let mut servers = state.db.get_all_mcp_servers()?;
+        if let Some(server) = servers.get_mut(server_id) {
+            server.tools = tools;
+            state.db.save_mcp_server(server)?;
+        }
+        Ok(())
+    }
+
     /// 将 MCP 服务器同步到所有启用的应用
     fn sync_server_to_apps(_state: &AppState, server: &McpServer) -> Result<(), AppError> {
         for app in server.apps.enabled_apps() {
@@ -105,42 +139,61 @@ impl McpService {
     }
 
     fn sync_server_to_app_no_config(server: &McpServer, app: &AppType) -> Result<(), AppError> {
+        // 附加该应用的工具过滤，由各应用模块转换为对应的过滤语法
+        let spec = mcp::with_disabled_tools(&server.server, server.disabled_tools.for_app(app));
+
+        // 项目作用域：写入各项目目录的配置，而非用户级全局配置。
+        // 项目配置可能随仓库提交，不解析密钥，引用由项目模块改写为环境变量引用
+        if !server.projects.is_empty() {
+            for dir in &server.projects {
+                mcp::sync_server_to_project(Path::new(dir), app, &server.id, &spec)?;
+            }
+            return Ok(());
+        }
+
+        // 密钥引用仅在写入用户级 live 配置时解析，数据库中保留 ${secret:name}
+        let spec = secret_store::resolve_value(&spec)?;
+
         match app {
             AppType::Claude => {
-                mcp::sync_single_server_to_claude(&Default::default(), &server.id, &server.server)?;
+                mcp::sync_single_server_to_claude(&Default::default(), &server.id, &spec)?;
             }
             AppType::Codex => {
                 // Codex uses TOML format, must use the correct function
-                mcp::sync_single_server_to_codex(&Default::default(), &server.id, &server.server)?;
+                mcp::sync_single_server_to_codex(&Default::default(), &server.id, &spec)?;
             }
             AppType::Gemini => {
-                mcp::sync_single_server_to_gemini(&Default::default(), &server.id, &server.server)?;
+                mcp::sync_single_server_to_gemini(&Default::default(), &server.id, &spec)?;
             }
             AppType::OpenCode => {
-                mcp::sync_single_server_to_opencode(
-                    &Default::default(),
-                    &server.id,
-                    &server.server,
-                )?;
+                mcp::sync_single_server_to_opencode(&Default::default(), &server.id, &spec)?;
             }
         }
         Ok(())
     }
 
     /// 从所有曾启用过该服务器的应用中移除
-    fn remove_server_from_all_apps(
-        state: &AppState,
-        id: &str,
-        server: &McpServer,
-    ) -> Result<(), AppError> {
+    fn remove_server_from_all_apps(state: &AppState, server: &McpServer) -> Result<(), AppError> {
         // 从所有曾启用的应用中移除
         for app in server.apps.enabled_apps() {
-            Self::remove_server_from_app(state, id, &app)?;
+            Self::remove_server_from_app(state, server, &app)?;
         }
         Ok(())
     }
 
-    fn remove_server_from_app(_state: &AppState, id: &str, app: &AppType) -> Result<(), AppError> {
+    fn remove_server_from_app(
+        _state: &AppState,
+        server: &McpServer,
+        app: &AppType,
+    ) -> Result<(), AppError> {
+        let id = server.id.as_str();
+        if !server.projects.is_empty() {
+            for dir in &server.projects {
+                mcp::remove_server_from_project(Path::new(dir), app, id)?;
+            }
+            return Ok(());
+        }
+
         match app {
             AppType::Claude => mcp::remove_server_from_claude(id)?,
             AppType::Codex => mcp::remove_server_from_codex(id)?,
@@ -362,4 +415,88 @@ impl McpService {
 
         Ok(new_count)
     }
+
+    /// 从项目配置（.mcp.json、.gemini/settings.json、opencode.json）导入 MCP
+    ///
+    /// `project_dirs` 为空时扫描 ~/.claude.json 中记录过的全部项目。
+    /// 已存在的项目级服务器合并项目作用域；已存在的全局服务器保持不变。
+    pub fn import_from_projects(
+        state: &AppState,
+        project_dirs: &[String],
+    ) -> Result<usize, AppError> {
+        let mut temp_config = crate::app_config::MultiAppConfig::default();
+        let count = crate::mcp::import_from_projects(&mut temp_config, project_dirs)?;
+
+        let mut new_count = 0;
+
+        if count > 0 {
+            if let Some(servers) = &temp_config.mcp.servers {
+                let mut existing = state.db.get_all_mcp_servers()?;
+                let mut ids: Vec<_> = servers.keys().cloned().collect();
+                ids.sort();
+                for id in ids {
+                    let server = &servers[&id];
+                    let to_save = match existing.get(&id) {
+                        Some(existing_server) if existing_server.projects.is_empty() => {
+                            log::info!("MCP 服务器 '{id}' 已作为全局服务器存在，跳过项目导入");
+                            continue;
+                        }
+                        Some(existing_server) => {
+                            let mut merged = existing_server.clone();
+                            for dir in &server.projects {
+                                if !merged.projects.contains(dir) {
+                                    merged.projects.push(dir.clone());
+                                }
+                            }
+                            for app in server.apps.enabled_apps() {
+                                merged.apps.set_enabled_for(&app, true);
+                            }
+                            merged
+                        }
+                        None => {
+                            new_count += 1;
+                            server.clone()
+                        }
+                    };
+
+                    state.db.save_mcp_server(&to_save)?;
+                    existing.insert(to_save.id.clone(), to_save.clone());
+
+                    // 同步到对应项目配置
+                    Self::sync_server_to_apps(state, &to_save)?;
+                }
+            }
+        }
+
+        Ok(new_count)
+    }
+
+    /// 列出可用作项目作用域的已知项目目录
+    pub fn list_known_projects() -> Result<Vec<String>, AppError> {
+        crate::claude_mcp::list_known_project_dirs()
+    }
+}
+
+/// 规范化项目目录：去除空白与重复项，并校验为已存在的绝对路径目录
+fn normalize_project_dirs(dirs: &[String]) -> Result<Vec<String>, AppError> {
+    let mut out: Vec<String> = Vec::new();
+    for dir in dirs {
+        let dir = dir.trim();
+        if dir.is_empty() {
+            continue;
+        }
+        let path = Path::new(dir);
+        if !path.is_absolute() || !path.is_dir() {
+            return Err(AppError::localized(
+                "mcp.project.invalidDir",
+                format!("项目目录无效（需为已存在的绝对路径）: {dir}"),
+                format!("Invalid project directory (must be an existing absolute path): {dir}"),
+            ));
+        }
+        let dir = dir.to_string();
+        if !out.contains(&dir) {
+            out.push(dir);
+        }
+    }
+    Ok(out)
 }
@@ -0,0 +1,376 @@
+//! MCP 服务器目录服务
+//!
+//! 从配置的目录来源加载服务器定义，支持按关键字和标签搜索，
+//! 并将填写输入后的条目安装到 `mcp_servers`：
+//! - `registry`：官方 MCP Registry 格式的 HTTP 服务（`/v0/servers`，分页拉取）
+//! - `github`：GitHub 仓库中的 JSON 清单（下载仓库压缩包后扫描）
+//! - `local`：本地目录中的 JSON 清单
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
+use tokio::time::timeout;
+
+use crate::app_config::{McpApps, McpServer};
+use crate::error::AppError;
+use crate::mcp::{self, McpCatalogEntry, McpCatalogSource, McpCatalogSourceKind};
+use crate::services::{McpService, SkillService};
+use crate::store::AppState;
+
+/// 官方 MCP Registry
+const OFFICIAL_REGISTRY_URL: &str = "https://registry.modelcontextprotocol.io";
+
+/// 单个来源的加载超时
+const SOURCE_TIMEOUT_SECS: u64 = 60;
+
+/// Registry 每页条目数与最大页数（避免一次拉取整个 Registry）
+const REGISTRY_PAGE_SIZE: u32 = 100;
+const REGISTRY_MAX_PAGES: usize = 5;
+
+/// 扫描清单时跳过的常见非清单文件
+const IGNORED_JSON_FILES: &[&str] = &[
+    "package.json",
+    "package-lock.json",
+    "tsconfig.json",
+    "renovate.json",
+];
+
+/// 默认目录来源
+pub fn default_catalog_sources() -> Vec<McpCatalogSource> {
+    vec![McpCatalogSource {
+        id: "official".to_string(),
+        name: "MCP Registry".to_string(),
+        kind: McpCatalogSourceKind::Registry,
+        location: OFFICIAL_REGISTRY_URL.to_string(),
+        branch: None,
+        enabled: true,
+    }]
+}
+
+pub struct McpCatalogService;
+
+impl McpCatalogService {
+    /// 校验并规范化目录来源
+    pub fn normalize_source(mut source: McpCatalogSource) -> Result<McpCatalogSource, AppError> {
+        source.id = source.id.trim().to_string();
+        source.location = source.location.trim().to_string();
+        source.branch = source
+            .branch
+            .map(|b| b.trim().to_string())
+            .filter(|b| !b.is_empty());
+        if source.name.trim().is_empty() {
+            source.name = source.id.clone();
+        }
+
+        let valid_location = match source.kind {
+            McpCatalogSourceKind::Registry => {
+                source.location.starts_with("https://") || source.location.starts_with("http://")
+            }
+            McpCatalogSourceKind::Github => parse_github_location(&source.location).is_some(),
+            McpCatalogSourceKind::Local => Path::new(&source.location).is_absolute(),
+        };
+        if source.id.is_empty() || !valid_location {
+            return Err(AppError::localized(
+                "mcp.catalog.invalidSource",
+                format!("无效的 MCP 目录来源: {}", source.location),
+                format!("Invalid MCP catalog source: {}", source.location),
+            ));
+        }
+        Ok(source)
+    }
+
+    /// 从启用的来源加载并搜索目录条目
+    ///
+    /// 单个来源失败时仅记录日志，不影响其他来源。
+    pub async fn search(
+        sources: Vec<McpCatalogSource>,
+        query: Option<String>,
+        tags: Vec<String>,
+    ) -> Result<Vec<McpCatalogEntry>, AppError> {
+        let query = query
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty());
+        let enabled: Vec<McpCatalogSource> = sources.into_iter().filter(|s| s.enabled).collect();
+
+        let tasks = enabled.iter().map(|source| {
+            timeout(
+                Duration::from_secs(SOURCE_TIMEOUT_SECS),
+                Self::fetch_source(source, query.as_deref()),
+            )
+        });
+        let results = futures::future::join_all(tasks).await;
+
+        let mut entries = Vec::new();
+        let mut seen = HashSet::new();
+        for (source, result) in enabled.iter().zip(results) {
+            match result {
+                Ok(Ok(items)) => {
+                    for entry in items {
+                        if entry.matches(query.as_deref(), &tags) && seen.insert(entry.key.clone())
+                        {
+                            entries.push(entry);
+                        }
+                    }
+                }
+                Ok(Err(e)) => log::warn!("加载 MCP 目录来源 '{}' 失败: {e}", source.id),
+                Err(_) => log::warn!(
+                    "加载 MCP 目录来源 '{}' 超时（{SOURCE_TIMEOUT_SECS}s）",
+                    source.id
+                ),
+            }
+        }
+
+        entries.sort_by_key(|e| e.name.to_lowercase());
+        Ok(entries)
+    }
+
+    async fn fetch_source(
+        source: &McpCatalogSource,
+        query: Option<&str>,
+    ) -> Result<Vec<McpCatalogEntry>, AppError> {
+        match source.kind {
+            McpCatalogSourceKind::Registry => Self::fetch_registry(source, query).await,
+            McpCatalogSourceKind::Github => Self::fetch_github(source).await,
+            McpCatalogSourceKind::Local => {
+                load_manifest_dir(Path::new(&source.location), &source.id)
+            }
+        }
+    }
+
+    /// 分页拉取 Registry（关键字交由服务端过滤）
+    async fn fetch_registry(
+        source: &McpCatalogSource,
+        query: Option<&str>,
+    ) -> Result<Vec<McpCatalogEntry>, AppError> {
+        let base = source.location.trim_end_matches('/');
+        let url = if base.ends_with("/servers") {
+            base.to_string()
+        } else {
+            format!("{base}/v0/servers")
+        };
+        let client = crate::proxy::http_client::get();
+
+        let mut entries = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..REGISTRY_MAX_PAGES {
+            let mut params = vec![
+                ("limit", REGISTRY_PAGE_SIZE.to_string()),
+                ("version", "latest".to_string()),
+            ];
+            if let Some(q) = query {
+                params.push(("search", q.to_string()));
+            }
+            if let Some(c) = &cursor {
+                params.push(("cursor", c.clone()));
+            }
+
+            let response = client
+                .get(&url)
+                .query(&params)
+                .send()
+                .await
+                .map_err(|e| AppError::Message(format!("请求 MCP Registry 失败: {e}")))?;
+            if !response.status().is_success() {
+                return Err(AppError::Message(format!(
+                    "MCP Registry 返回错误状态: {}",
+                    response.status()
+                )));
+            }
+            let doc: serde_json::Value = response
+                .json()
+                .await
+                .map_err(|e| AppError::Message(format!("解析 MCP Registry 响应失败: {e}")))?;
+
+            entries.extend(mcp::parse_catalog_document(&doc, &source.id));
+
+            cursor = doc
+                .get("metadata")
+                .and_then(|m| m.get("nextCursor").or_else(|| m.get("next_cursor")))
+                .and_then(|c| c.as_str())
+                .filter(|c| !c.is_empty())
+                .map(str::to_string);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(entries)
+    }
+
+    /// 下载 GitHub 仓库压缩包并扫描其中的 JSON 清单
+    async fn fetch_github(source: &McpCatalogSource) -> Result<Vec<McpCatalogEntry>, AppError> {
+        let (owner, name) = parse_github_location(&source.location).ok_or_else(|| {
+            AppError::InvalidInput(format!("无效的 GitHub 仓库: {}", source.location))
+        })?;
+        let temp_dir = tempfile::tempdir().map_err(|e| AppError::IoContext {
+            context: "创建临时目录失败".to_string(),
+            source: e,
+        })?;
+
+        let branches = match source.branch.as_deref() {
+            Some(branch) => vec![branch, "main", "master"],
+            None => vec!["main", "master"],
+        };
+        let downloader = SkillService::new();
+        let mut last_error = None;
+        for branch in branches {
+            let url = format!("https://github.com/{owner}/{name}/archive/refs/heads/{branch}.zip");
+            match downloader.download_and_extract(&url, temp_dir.path()).await {
+                Ok(()) => return load_manifest_dir(temp_dir.path(), &source.id),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(AppError::Message(format!(
+            "下载 MCP 目录仓库 {owner}/{name} 失败: {}",
+            last_error.map(|e| e.to_string()).unwrap_or_default()
+        )))
+    }
+
+    /// 填写输入并安装目录条目
+    ///
+    /// `id` 为空时使用条目建议的 id；已存在同 id 的服务器时返回错误，避免覆盖用户配置。
+    pub fn install(
+        state: &AppState,
+        entry: &McpCatalogEntry,
+        inputs: &HashMap<String, String>,
+        apps: McpApps,
+        id: Option<String>,
+    ) -> Result<McpServer, AppError> {
+        let id = id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| entry.id.clone());
+        if state.db.get_all_mcp_servers()?.contains_key(&id) {
+            return Err(AppError::localized(
+                "mcp.catalog.idExists",
+                format!("已存在 id 为 {id} 的 MCP 服务器"),
+                format!("An MCP server with id {id} already exists"),
+            ));
+        }
+
+        let spec = mcp::instantiate_catalog_entry(entry, inputs)?;
+        let server = McpServer {
+            id,
+            name: entry.name.clone(),
+            server: spec,
+            apps,
+            description: Some(entry.description.clone()).filter(|d| !d.is_empty()),
+            homepage: entry.homepage.clone(),
+            docs: entry.docs.clone(),
+            tags: entry.tags.clone(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
+        };
+        McpService::upsert_server(state, server.clone())?;
+        log::info!(
+            "已从目录 '{}' 安装 MCP 服务器 '{}'",
+            entry.source_id,
+            server.id
+        );
+        Ok(server)
+    }
+}
+
+/// 解析 `owner/name`（兼容完整 GitHub URL）
+fn parse_github_location(location: &str) -> Option<(String, String)> {
+    let trimmed = location
+        .trim()
+        .trim_start_matches("https://github.com/")
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    let mut parts = trimmed.split('/');
+    let owner = parts.next().filter(|s| !s.is_empty())?;
+    let name = parts.next().filter(|s| !s.is_empty())?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((owner.to_string(), name.to_string()))
+}
+
+/// 递归扫描目录中的 JSON 清单（跳过隐藏目录与 node_modules）
+fn load_manifest_dir(dir: &Path, source_id: &str) -> Result<Vec<McpCatalogEntry>, AppError> {
+    if !dir.is_dir() {
+        return Err(AppError::localized(
+            "mcp.catalog.dirNotFound",
+            format!("目录不存在: {}", dir.display()),
+            format!("Directory not found: {}", dir.display()),
+        ));
+    }
+    let mut entries = Vec::new();
+    scan_manifest_dir(dir, source_id, &mut entries)?;
+    Ok(entries)
+}
+
+fn scan_manifest_dir(
+    dir: &Path,
+    source_id: &str,
+    entries: &mut Vec<McpCatalogEntry>,
+) -> Result<(), AppError> {
+    for entry in std::fs::read_dir(dir).map_err(|e| AppError::io(dir, e))? {
+        let path = entry.map_err(|e| AppError::io(dir, e))?.path();
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            if file_name != "node_modules" {
+                scan_manifest_dir(&path, source_id, entries)?;
+            }
+            continue;
+        }
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if !is_json || IGNORED_JSON_FILES.contains(&file_name.as_str()) {
+            continue;
+        }
+        match crate::config::read_json_file::<serde_json::Value>(&path) {
+            Ok(doc) => entries.extend(mcp::parse_catalog_document(&doc, source_id)),
+            Err(e) => log::debug!("跳过无法解析的 MCP 目录清单 {}: {e}", path.display()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_manifest_dir() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("servers")).unwrap();
+        std::fs::write(
+            dir.path().join("servers/fetch.json"),
+            json!({
+                "id": "fetch",
+                "name": "Fetch",
+                "tags": ["web"],
+                "server": {"command": "uvx", "args": ["mcp-server-fetch"]}
+            })
+            .to_string(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+
+        let entries = load_manifest_dir(dir.path(), "local").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "local:fetch");
+        assert!(entries[0].matches(None, &["web".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_github_location() {
+        assert_eq!(
+            parse_github_location("https://github.com/example/mcp-catalog.git"),
+            Some(("example".to_string(), "mcp-catalog".to_string()))
+        );
+        assert_eq!(parse_github_location("example"), None);
+    }
+}
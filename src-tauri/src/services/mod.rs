@@ -1,17 +1,23 @@
+pub mod budget;
 pub mod config;
 pub mod env_checker;
 pub mod env_manager;
 pub mod mcp;
+pub mod mcp_catalog;
 pub mod prompt;
+pub mod prompt_template;
 pub mod provider;
 pub mod proxy;
 pub mod skill;
+pub mod skill_scan;
 pub mod speedtest;
 pub mod stream_check;
+pub mod usage_monitor;
 pub mod usage_stats;
 
 pub use config::ConfigService;
 pub use mcp::McpService;
+pub use mcp_catalog::McpCatalogService;
 pub use prompt::PromptService;
 pub use provider::{ProviderService, ProviderSortUpdate};
 pub use proxy::ProxyService;
@@ -20,6 +26,6 @@ pub use skill::{DiscoverableSkill, Skill, SkillRepo, SkillService};
 pub use speedtest::{EndpointLatency, SpeedtestService};
 #[allow(unused_imports)]
 pub use usage_stats::{
-    DailyStats, LogFilters, ModelStats, PaginatedLogs, ProviderLimitStatus, ProviderStats,
-    RequestLogDetail, UsageSummary,
+    DailyStats, LogFilters, ModelStats, PaginatedLogs, ProjectUsageStats, ProviderLimitStatus,
+    ProviderStats, RequestLogDetail, SessionUsageStats, UsageSummary,
 };
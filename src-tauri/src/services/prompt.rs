@@ -1,12 +1,42 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
 use indexmap::IndexMap;
 
 use crate::app_config::AppType;
 use crate::config::write_text_file;
 use crate::error::AppError;
-use crate::prompt::Prompt;
-use crate::prompt_files::prompt_file_path;
+use crate::prompt::{
+    Prompt, PromptDedupGroup, PromptDedupMember, PromptDriftStatus, PromptFileDrift, PromptPreview,
+    PromptRevision, PromptRevisionSource, SharedPrompt,
+};
+use crate::prompt_files::{project_prompt_file_path, prompt_file_path};
+use crate::services::prompt_template::{
+    builtin_variables, render_template, RenderedTemplate, TemplateContext, VOLATILE_VARIABLES,
+};
 use crate::store::AppState;
 
+/// 托管区块起止标记：区块外的内容由用户维护，cc-switch 不会改动
+const BLOCK_BEGIN: &str = "<!-- cc-switch:prompts:begin -->";
+const BLOCK_END: &str = "<!-- cc-switch:prompts:end -->";
+/// 片段标记前缀/后缀，形如 `<!-- cc-switch:prompt id="xxx" -->`
+const FRAGMENT_PREFIX: &str = "<!-- cc-switch:prompt id=\"";
+const FRAGMENT_SUFFIX: &str = "\" -->";
+/// 共享提示词在片段标记中的 id 前缀，与按应用存储的提示词区分
+const SHARED_PREFIX: &str = "shared:";
+
+/// 共享提示词在版本历史中使用的应用标识
+const SHARED_REVISION_APP: &str = "shared";
+/// 每个提示词保留的最大版本数
+const MAX_REVISIONS_PER_PROMPT: usize = 100;
+
+const ALL_APPS: [AppType; 4] = [
+    AppType::Claude,
+    AppType::Codex,
+    AppType::Gemini,
+    AppType::OpenCode,
+];
+
 /// 安全地获取当前 Unix 时间戳
 fn get_unix_timestamp() -> Result<i64, AppError> {
     std::time::SystemTime::now()
@@ -17,6 +47,297 @@ fn get_unix_timestamp() -> Result<i64, AppError> {
 
 pub struct PromptService;
 
+/// 待合并的提示词分组：(内容, 项目作用域) -> 各应用中的提示词
+type DedupGroups = IndexMap<(String, Vec<String>), Vec<(AppType, Prompt)>>;
+
+/// 托管区块的起止标记
+#[derive(Debug, Clone)]
+struct BlockMarkers {
+    begin: String,
+    end: String,
+}
+
+impl BlockMarkers {
+    /// 用户级文件只属于一个应用，使用通用标记；项目级 `AGENTS.md` 可能被
+    /// Codex 与 OpenCode 共用，因此项目级区块按应用区分
+    fn for_target(app: &AppType, project: Option<&str>) -> Self {
+        match project {
+            None => Self {
+                begin: BLOCK_BEGIN.to_string(),
+                end: BLOCK_END.to_string(),
+            },
+            Some(_) => Self {
+                begin: format!("<!-- cc-switch:prompts:{}:begin -->", app.as_str()),
+                end: format!("<!-- cc-switch:prompts:{}:end -->", app.as_str()),
+            },
+        }
+    }
+}
+
+/// live 提示词文件中托管区块以外的内容
+#[derive(Debug, Default)]
+struct LiveLayout {
+    before: String,
+    after: String,
+    /// 文件内容是否已由 cc-switch 托管（含托管区块，或旧版单提示词文件）
+    managed: bool,
+    /// 在文件中被手动修改、但无法回填到模板的片段 id；写入时跳过该文件以免覆盖修改
+    edited: Vec<String>,
+}
+
+/// 一个需要渲染的 live 文件：用户级（`project` 为 `None`）或项目级
+struct LiveTarget {
+    project: Option<String>,
+    path: PathBuf,
+    layout: LiveLayout,
+}
+
+/// live 文件解析结果
+#[derive(Debug, PartialEq)]
+struct ParsedLive {
+    before: String,
+    after: String,
+    /// 托管区块中的片段 `(id, content)`；文件中没有托管区块时为 `None`
+    fragments: Option<Vec<(String, String)>>,
+}
+
+/// 解析 live 文件，拆分出托管区块前后的用户内容与区块内的片段
+fn parse_live_file(content: &str, markers: &BlockMarkers) -> ParsedLive {
+    let Some(begin) = content.find(&markers.begin) else {
+        return ParsedLive {
+            before: content.to_string(),
+            after: String::new(),
+            fragments: None,
+        };
+    };
+    let inner_start = begin + markers.begin.len();
+    let (inner, after) = match content[inner_start..].find(&markers.end) {
+        Some(offset) => (
+            &content[inner_start..inner_start + offset],
+            &content[inner_start + offset + markers.end.len()..],
+        ),
+        // 结束标记丢失时，视为区块延伸到文件末尾
+        None => (&content[inner_start..], ""),
+    };
+
+    let mut fragments: Vec<(String, Vec<&str>)> = Vec::new();
+    for line in inner.lines() {
+        let marker = line
+            .trim()
+            .strip_prefix(FRAGMENT_PREFIX)
+            .and_then(|rest| rest.strip_suffix(FRAGMENT_SUFFIX));
+        match (marker, fragments.last_mut()) {
+            (Some(id), _) => fragments.push((id.to_string(), Vec::new())),
+            (None, Some((_, lines))) => lines.push(line),
+            // 片段标记之前的内容不属于任何片段，忽略
+            (None, None) => {}
+        }
+    }
+
+    ParsedLive {
+        before: content[..begin].to_string(),
+        after: after.to_string(),
+        fragments: Some(
+            fragments
+                .into_iter()
+                .map(|(id, lines)| (id, lines.join("\n").trim_matches('\n').to_string()))
+                .collect(),
+        ),
+    }
+}
+
+/// 将已启用的片段渲染进 live 文件，保留区块外的用户内容
+fn render_live_file(
+    layout: &LiveLayout,
+    markers: &BlockMarkers,
+    fragments: &[(&str, &str)],
+) -> String {
+    if fragments.is_empty() {
+        let before = layout.before.trim_end();
+        let after = layout.after.trim_start();
+        let separator = if before.is_empty() || after.is_empty() {
+            ""
+        } else {
+            "\n\n"
+        };
+        let text = format!("{before}{separator}{after}");
+        return if text.trim().is_empty() {
+            String::new()
+        } else {
+            format!("{}\n", text.trim_end())
+        };
+    }
+
+    let body = fragments
+        .iter()
+        .map(|(id, content)| {
+            format!(
+                "{FRAGMENT_PREFIX}{id}{FRAGMENT_SUFFIX}\n{}",
+                content.trim_end()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let after = if layout.after.is_empty() {
+        "\n"
+    } else {
+        layout.after.as_str()
+    };
+    format!(
+        "{}{}\n{body}\n{}{after}",
+        layout.before, markers.begin, markers.end
+    )
+}
+
+/// 所有可能出现在提示词文件中的托管区块标记
+fn all_block_markers() -> Vec<BlockMarkers> {
+    let mut markers = vec![BlockMarkers::for_target(&AppType::Claude, None)];
+    for app in ALL_APPS {
+        markers.push(BlockMarkers::for_target(&app, Some("")));
+    }
+    markers
+}
+
+/// 去掉文件中所有 cc-switch 托管区块，只保留用户编写的内容
+fn user_authored_text(content: &str) -> String {
+    let mut text = content.to_string();
+    for markers in all_block_markers() {
+        let parsed = parse_live_file(&text, &markers);
+        if parsed.fragments.is_some() {
+            let layout = LiveLayout {
+                before: parsed.before,
+                after: parsed.after,
+                managed: true,
+                ..Default::default()
+            };
+            text = render_live_file(&layout, &markers, &[]);
+        }
+    }
+    text
+}
+
+/// 提取文件中除 `own` 以外的托管区块原文（如共用 `AGENTS.md` 中其它应用的区块）
+fn other_managed_blocks(content: &str, own: &BlockMarkers) -> Vec<String> {
+    all_block_markers()
+        .into_iter()
+        .filter(|markers| markers.begin != own.begin)
+        .filter_map(|markers| {
+            let begin = content.find(&markers.begin)?;
+            let end = content[begin..]
+                .find(&markers.end)
+                .map(|offset| begin + offset + markers.end.len())
+                .unwrap_or(content.len());
+            Some(content[begin..end].trim_end().to_string())
+        })
+        .collect()
+}
+
+/// 提示词是否渲染到该文件：无项目作用域的提示词写入用户级文件，否则写入所列项目
+fn belongs_to(prompt: &Prompt, project: Option<&str>) -> bool {
+    match project {
+        None => prompt.projects.is_empty(),
+        Some(dir) => prompt.projects.iter().any(|p| p == dir),
+    }
+}
+
+/// 按模板渲染提示词内容（内置变量优先于用户自定义变量）
+fn render_prompt(
+    app: &AppType,
+    project: Option<&str>,
+    variables: &BTreeMap<String, String>,
+    prompts: &IndexMap<String, Prompt>,
+    id: Option<&str>,
+    content: &str,
+) -> Result<RenderedTemplate, AppError> {
+    let mut variables = variables.clone();
+    variables.extend(builtin_variables(app, project.map(Path::new)));
+    let resolve = |include: &str| prompts.get(include).map(|p| p.content.clone());
+    let ctx = TemplateContext {
+        app: app.clone(),
+        variables,
+        resolve_include: &resolve,
+    };
+    render_template(content, &ctx, id)
+}
+
+/// 写入 live 文件的片段内容；模板有误时记录警告并退回原始内容
+fn rendered_content(
+    app: &AppType,
+    project: Option<&str>,
+    variables: &BTreeMap<String, String>,
+    prompts: &IndexMap<String, Prompt>,
+    prompt: &Prompt,
+) -> String {
+    match render_prompt(
+        app,
+        project,
+        variables,
+        prompts,
+        Some(&prompt.id),
+        &prompt.content,
+    ) {
+        Ok(rendered) => rendered.content,
+        Err(e) => {
+            log::warn!("提示词模板渲染失败，写入原始内容: {} ({e})", prompt.id);
+            prompt.content.clone()
+        }
+    }
+}
+
+/// 用户显式保存或切换提示词时，以数据库内容为准覆盖文件中对该片段的手动修改
+fn discard_edits(targets: &mut [LiveTarget], id: &str) {
+    for target in targets {
+        target.layout.edited.retain(|edited| edited != id);
+    }
+}
+
+/// 取值随时间变化的变量在比较用渲染结果中的占位符
+fn volatile_placeholder(name: &str) -> String {
+    format!("\u{E000}{name}\u{E000}")
+}
+
+/// live 文件中的片段是否与提示词的渲染结果一致
+///
+/// `{{date}}` 等取值随时间变化的内置变量按格式匹配，不因日期变化而判定为已修改。
+fn fragment_matches(
+    app: &AppType,
+    project: Option<&str>,
+    variables: &BTreeMap<String, String>,
+    prompts: &IndexMap<String, Prompt>,
+    prompt: &Prompt,
+    live: &str,
+) -> bool {
+    let mut variables = variables.clone();
+    variables.extend(builtin_variables(app, project.map(Path::new)));
+    for (name, _) in VOLATILE_VARIABLES {
+        variables.insert(name.to_string(), volatile_placeholder(name));
+    }
+    let resolve = |include: &str| prompts.get(include).map(|p| p.content.clone());
+    let ctx = TemplateContext {
+        app: app.clone(),
+        variables,
+        resolve_include: &resolve,
+    };
+    let expected = render_template(&prompt.content, &ctx, Some(&prompt.id))
+        .map(|rendered| rendered.content)
+        .unwrap_or_else(|_| prompt.content.clone());
+    let (expected, live) = (expected.trim(), live.trim());
+    if !VOLATILE_VARIABLES
+        .iter()
+        .any(|(name, _)| expected.contains(&volatile_placeholder(name)))
+    {
+        return expected == live;
+    }
+
+    let mut pattern = regex::escape(expected);
+    for (name, format) in VOLATILE_VARIABLES {
+        pattern = pattern.replace(&regex::escape(&volatile_placeholder(name)), format);
+    }
+    regex::Regex::new(&format!("^{pattern}$"))
+        .map(|re| re.is_match(live))
+        .unwrap_or(false)
+}
+
 impl PromptService {
     pub fn get_prompts(
         state: &AppState,
@@ -28,33 +349,50 @@ impl PromptService {
     pub fn upsert_prompt(
         state: &AppState,
         app: AppType,
-        _id: &str,
+        id: &str,
         prompt: Prompt,
-    ) -> Result<(), AppError> {
-        // 检查是否为已启用的提示词
-        let is_enabled = prompt.enabled;
-
-        state.db.save_prompt(app.as_str(), &prompt)?;
+    ) -> Result<Vec<String>, AppError> {
+        Self::upsert_prompt_from(state, app, id, prompt, PromptRevisionSource::Ui)
+    }
 
-        if is_enabled {
-            // 启用提示词：写入内容到文件
-            let target_path = prompt_file_path(&app)?;
-            write_text_file(&target_path, &prompt.content)?;
-        } else {
-            // 禁用提示词：检查是否还有其他已启用的提示词
-            let prompts = state.db.get_prompts(app.as_str())?;
-            let any_enabled = prompts.values().any(|p| p.enabled);
-
-            if !any_enabled {
-                // 所有提示词都已禁用，清空文件
-                let target_path = prompt_file_path(&app)?;
-                if target_path.exists() {
-                    write_text_file(&target_path, "")?;
-                }
+    /// 保存提示词并按来源记录版本历史，返回因含手动修改而未重写的文件
+    pub fn upsert_prompt_from(
+        state: &AppState,
+        app: AppType,
+        _id: &str,
+        mut prompt: Prompt,
+        source: PromptRevisionSource,
+    ) -> Result<Vec<String>, AppError> {
+        // 编辑表单不一定携带项目作用域与排序；未提供时沿用已保存的值
+        // （作用域由 set_projects 修改，排序由 reorder_prompts 修改）
+        let stored = state.db.get_prompts(app.as_str())?.shift_remove(&prompt.id);
+        if let Some(stored) = &stored {
+            if prompt.projects.is_empty() {
+                prompt.projects = stored.projects.clone();
+            }
+            if prompt.sort_index.is_none() {
+                prompt.sort_index = stored.sort_index;
             }
         }
 
-        Ok(())
+        // 模板有误（如引用成环）时拒绝保存
+        let mut prompts = Self::live_prompts(state, &app)?;
+        prompts.insert(prompt.id.clone(), prompt.clone());
+        Self::validate_template(state, &app, &prompts, &prompt.id, &prompt.content)?;
+
+        // 先回填 live 文件中的手动修改，再以本次保存的内容为准重新渲染
+        let mut targets = Self::backfill_from_live(state, &app)?;
+        discard_edits(&mut targets, &prompt.id);
+        state.db.save_prompt(app.as_str(), &prompt)?;
+        Self::record_revision(
+            state,
+            app.as_str(),
+            &prompt.id,
+            stored.as_ref().map(|p| p.content.as_str()),
+            &prompt.content,
+            source,
+        )?;
+        Self::write_live_files(state, &app, targets)
     }
 
     pub fn delete_prompt(state: &AppState, app: AppType, id: &str) -> Result<(), AppError> {
@@ -67,80 +405,598 @@ impl PromptService {
         }
 
         state.db.delete_prompt(app.as_str(), id)?;
+        state.db.delete_prompt_revisions(app.as_str(), id)?;
         Ok(())
     }
 
-    pub fn enable_prompt(state: &AppState, app: AppType, id: &str) -> Result<(), AppError> {
-        // 回填当前 live 文件内容到已启用的提示词，或创建备份
-        let target_path = prompt_file_path(&app)?;
-        if target_path.exists() {
-            if let Ok(live_content) = std::fs::read_to_string(&target_path) {
-                if !live_content.trim().is_empty() {
-                    let mut prompts = state.db.get_prompts(app.as_str())?;
-
-                    // 尝试回填到当前已启用的提示词
-                    if let Some((enabled_id, enabled_prompt)) = prompts
-                        .iter_mut()
-                        .find(|(_, p)| p.enabled)
-                        .map(|(id, p)| (id.clone(), p))
-                    {
-                        let timestamp = get_unix_timestamp()?;
-                        enabled_prompt.content = live_content.clone();
-                        enabled_prompt.updated_at = Some(timestamp);
-                        log::info!("回填 live 提示词内容到已启用项: {enabled_id}");
+    /// 将提示词加入已启用片段集合（可同时启用多个）
+    pub fn enable_prompt(
+        state: &AppState,
+        app: AppType,
+        id: &str,
+    ) -> Result<Vec<String>, AppError> {
+        Self::set_prompt_enabled(state, app, id, true)
+    }
+
+    /// 将提示词从已启用片段集合中移除
+    pub fn disable_prompt(
+        state: &AppState,
+        app: AppType,
+        id: &str,
+    ) -> Result<Vec<String>, AppError> {
+        Self::set_prompt_enabled(state, app, id, false)
+    }
+
+    fn set_prompt_enabled(
+        state: &AppState,
+        app: AppType,
+        id: &str,
+        enabled: bool,
+    ) -> Result<Vec<String>, AppError> {
+        let mut targets = Self::backfill_from_live(state, &app)?;
+        discard_edits(&mut targets, id);
+
+        let mut prompts = state.db.get_prompts(app.as_str())?;
+        let Some(prompt) = prompts.get_mut(id) else {
+            return Err(AppError::InvalidInput(format!("提示词 {id} 不存在")));
+        };
+        if prompt.enabled != enabled {
+            prompt.enabled = enabled;
+            state.db.save_prompt(app.as_str(), prompt)?;
+        }
+
+        Self::write_live_files(state, &app, targets)
+    }
+
+    /// 按给定 id 顺序重排片段，并重新渲染 live 文件
+    ///
+    /// 共享提示词以 `shared:<id>` 出现在列表中，其排序对所有应用生效。
+    pub fn reorder_prompts(
+        state: &AppState,
+        app: AppType,
+        ids: &[String],
+    ) -> Result<Vec<String>, AppError> {
+        let reorder = || -> Result<(), AppError> {
+            let mut prompts = state.db.get_prompts(app.as_str())?;
+            let mut shared = state.db.get_shared_prompts()?;
+            for (index, id) in ids.iter().enumerate() {
+                if let Some(prompt) = id
+                    .strip_prefix(SHARED_PREFIX)
+                    .and_then(|shared_id| shared.get_mut(shared_id))
+                {
+                    prompt.sort_index = Some(index);
+                    state.db.save_shared_prompt(prompt)?;
+                } else if let Some(prompt) = prompts.get_mut(id) {
+                    prompt.sort_index = Some(index);
+                    state.db.save_prompt(app.as_str(), prompt)?;
+                }
+            }
+            Ok(())
+        };
+
+        if ids.iter().any(|id| id.starts_with(SHARED_PREFIX)) {
+            return Self::with_all_apps(state, None, reorder);
+        }
+        let targets = Self::backfill_from_live(state, &app)?;
+        reorder()?;
+        Self::write_live_files(state, &app, targets)
+    }
+
+    /// 设置提示词的项目作用域（空列表表示用户级），并重新渲染受影响的文件
+    pub fn set_projects(
+        state: &AppState,
+        app: AppType,
+        id: &str,
+        projects: Vec<String>,
+    ) -> Result<Prompt, AppError> {
+        let mut normalized: Vec<String> = Vec::new();
+        for dir in projects {
+            let dir = dir.trim().to_string();
+            if dir.is_empty() || normalized.contains(&dir) {
+                continue;
+            }
+            let path = Path::new(&dir);
+            if !path.is_absolute() || !path.is_dir() {
+                return Err(AppError::InvalidInput(format!(
+                    "项目目录无效（需为已存在的绝对路径）: {dir}"
+                )));
+            }
+            normalized.push(dir);
+        }
+
+        let targets = Self::backfill_from_live(state, &app)?;
+
+        let mut prompts = state.db.get_prompts(app.as_str())?;
+        let Some(prompt) = prompts.get_mut(id) else {
+            return Err(AppError::InvalidInput(format!("提示词 {id} 不存在")));
+        };
+        if prompt.projects != normalized {
+            prompt.projects = normalized;
+            prompt.updated_at = Some(get_unix_timestamp()?);
+            state.db.save_prompt(app.as_str(), prompt)?;
+        }
+        let prompt = prompt.clone();
+
+        Self::write_live_files(state, &app, targets)?;
+        Ok(prompt)
+    }
+
+    /// 渲染到指定应用的所有提示词：该应用的提示词 + 按应用调整后的共享提示词，按排序合并
+    fn live_prompts(state: &AppState, app: &AppType) -> Result<IndexMap<String, Prompt>, AppError> {
+        let mut prompts: Vec<Prompt> = state.db.get_prompts(app.as_str())?.into_values().collect();
+        for shared in state.db.get_shared_prompts()?.into_values() {
+            prompts.push(Prompt {
+                id: format!("{SHARED_PREFIX}{}", shared.id),
+                name: shared.name.clone(),
+                content: shared.content_for(app),
+                description: shared.description.clone(),
+                enabled: shared.apps.is_enabled_for(app),
+                created_at: shared.created_at,
+                updated_at: shared.updated_at,
+                sort_index: shared.sort_index,
+                projects: shared.projects,
+            });
+        }
+        // 稳定排序：未设置排序的提示词保持原有相对顺序
+        prompts.sort_by_key(|p| p.sort_index.unwrap_or(999999));
+        Ok(prompts.into_iter().map(|p| (p.id.clone(), p)).collect())
+    }
+
+    /// 需要渲染的所有 live 文件：用户级文件 + 提示词引用的项目文件
+    fn live_paths(
+        app: &AppType,
+        prompts: &IndexMap<String, Prompt>,
+    ) -> Result<Vec<(Option<String>, PathBuf)>, AppError> {
+        let mut paths = vec![(None, prompt_file_path(app)?)];
+        for prompt in prompts.values() {
+            for dir in &prompt.projects {
+                if !paths
+                    .iter()
+                    .any(|(p, _)| p.as_deref() == Some(dir.as_str()))
+                {
+                    paths.push((
+                        Some(dir.clone()),
+                        project_prompt_file_path(Path::new(dir), app),
+                    ));
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    /// 回填各 live 文件中的手动修改到对应提示词，返回各文件托管区块外的用户内容
+    ///
+    /// - 文件含托管区块：逐个片段回填到同 id 的提示词；找不到对应提示词的片段另存为备份
+    /// - 用户级文件不含托管区块但存在已启用提示词：旧版单提示词模式，整个文件属于该提示词
+    /// - 其余情况：整个文件视为用户内容，原样保留在区块之外
+    fn backfill_from_live(state: &AppState, app: &AppType) -> Result<Vec<LiveTarget>, AppError> {
+        let mut prompts = state.db.get_prompts(app.as_str())?;
+        let mut shared = state.db.get_shared_prompts()?;
+        // 同一提示词可能渲染到多个文件，只回填相对修改前渲染结果发生变化的片段
+        let original = Self::live_prompts(state, app)?;
+        let variables = state.db.get_prompt_variables()?;
+
+        let mut targets = Vec::new();
+        for (project, path) in Self::live_paths(app, &original)? {
+            let layout = Self::backfill_file(
+                state,
+                app,
+                &mut prompts,
+                &mut shared,
+                &original,
+                &variables,
+                project.as_deref(),
+                &path,
+            )?;
+            targets.push(LiveTarget {
+                project,
+                path,
+                layout,
+            });
+        }
+        Ok(targets)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn backfill_file(
+        state: &AppState,
+        app: &AppType,
+        prompts: &mut IndexMap<String, Prompt>,
+        shared: &mut IndexMap<String, SharedPrompt>,
+        original: &IndexMap<String, Prompt>,
+        variables: &BTreeMap<String, String>,
+        project: Option<&str>,
+        path: &Path,
+    ) -> Result<LiveLayout, AppError> {
+        if !path.exists() {
+            return Ok(LiveLayout::default());
+        }
+        let live_content = std::fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
+        if live_content.trim().is_empty() {
+            return Ok(LiveLayout::default());
+        }
+
+        let markers = BlockMarkers::for_target(app, project);
+        let parsed = parse_live_file(&live_content, &markers);
+
+        let Some(fragments) = parsed.fragments else {
+            if project.is_none() {
+                if let Some(enabled_prompt) = prompts
+                    .values_mut()
+                    .find(|p| p.enabled && p.projects.is_empty())
+                {
+                    if enabled_prompt.content.trim() != live_content.trim() {
+                        let previous = std::mem::replace(&mut enabled_prompt.content, live_content);
+                        enabled_prompt.updated_at = Some(get_unix_timestamp()?);
+                        log::info!("回填 live 提示词内容到已启用项: {}", enabled_prompt.id);
                         state.db.save_prompt(app.as_str(), enabled_prompt)?;
-                    } else {
-                        // 没有已启用的提示词，则创建一次备份（避免重复备份）
-                        let content_exists = prompts
-                            .values()
-                            .any(|p| p.content.trim() == live_content.trim());
-                        if !content_exists {
-                            let timestamp = std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_secs() as i64;
-                            let backup_id = format!("backup-{timestamp}");
-                            let backup_prompt = Prompt {
-                                id: backup_id.clone(),
-                                name: format!(
-                                    "原始提示词 {}",
-                                    chrono::Local::now().format("%Y-%m-%d %H:%M")
-                                ),
-                                content: live_content,
-                                description: Some("自动备份的原始提示词".to_string()),
-                                enabled: false,
-                                created_at: Some(timestamp),
-                                updated_at: Some(timestamp),
-                            };
-                            log::info!("回填 live 提示词内容，创建备份: {backup_id}");
-                            state.db.save_prompt(app.as_str(), &backup_prompt)?;
-                        }
+                        Self::record_revision(
+                            state,
+                            app.as_str(),
+                            &enabled_prompt.id,
+                            Some(&previous),
+                            &enabled_prompt.content,
+                            PromptRevisionSource::Backfill,
+                        )?;
                     }
+                    return Ok(LiveLayout {
+                        managed: true,
+                        ..Default::default()
+                    });
                 }
             }
+            return Ok(LiveLayout {
+                before: format!("{}\n\n", live_content.trim_end()),
+                ..Default::default()
+            });
+        };
+
+        let mut edited = Vec::new();
+        for (id, content) in fragments {
+            let (changed, templated) = match original.get(&id) {
+                Some(before) => {
+                    let rendered = rendered_content(app, project, variables, original, before);
+                    (
+                        !fragment_matches(app, project, variables, original, before, &content),
+                        rendered.trim() != before.content.trim(),
+                    )
+                }
+                None => (false, false),
+            };
+            if changed && templated {
+                // 渲染结果无法还原为模板：保留文件中的修改，本次不重写该文件（漂移检查会报告该片段）
+                log::warn!(
+                    "模板提示词片段在文件中被修改，跳过重写以保留修改: {id} ({})",
+                    path.display()
+                );
+                edited.push(id);
+                continue;
+            }
+            if let Some(prompt) = id
+                .strip_prefix(SHARED_PREFIX)
+                .and_then(|shared_id| shared.get_mut(shared_id))
+            {
+                if changed {
+                    let previous = prompt.content.clone();
+                    prompt.apply_edit(app, &content);
+                    prompt.updated_at = Some(get_unix_timestamp()?);
+                    log::info!("回填 live 共享提示词片段: {id} ({})", path.display());
+                    state.db.save_shared_prompt(prompt)?;
+                    Self::record_revision(
+                        state,
+                        SHARED_REVISION_APP,
+                        &prompt.id,
+                        Some(&previous),
+                        &prompt.content,
+                        PromptRevisionSource::Backfill,
+                    )?;
+                }
+                continue;
+            }
+            if let Some(prompt) = prompts.get_mut(&id) {
+                if changed {
+                    let previous = std::mem::replace(&mut prompt.content, content);
+                    prompt.updated_at = Some(get_unix_timestamp()?);
+                    log::info!("回填 live 提示词片段: {id} ({})", path.display());
+                    state.db.save_prompt(app.as_str(), prompt)?;
+                    Self::record_revision(
+                        state,
+                        app.as_str(),
+                        &prompt.id,
+                        Some(&previous),
+                        &prompt.content,
+                        PromptRevisionSource::Backfill,
+                    )?;
+                }
+                continue;
+            }
+
+            // 片段对应的提示词已不存在，创建一次备份（避免重复备份）
+            let content_exists = prompts.values().any(|p| p.content.trim() == content.trim())
+                || original
+                    .values()
+                    .any(|p| p.content.trim() == content.trim());
+            if content.trim().is_empty() || content_exists {
+                continue;
+            }
+            let timestamp = get_unix_timestamp()?;
+            let backup_id = format!("backup-{timestamp}-{}", id.replace(SHARED_PREFIX, ""));
+            let backup_prompt = Prompt {
+                id: backup_id.clone(),
+                name: format!(
+                    "原始提示词 {}",
+                    chrono::Local::now().format("%Y-%m-%d %H:%M")
+                ),
+                content,
+                description: Some("自动备份的原始提示词".to_string()),
+                enabled: false,
+                created_at: Some(timestamp),
+                updated_at: Some(timestamp),
+                sort_index: None,
+                projects: project.map(|p| vec![p.to_string()]).unwrap_or_default(),
+            };
+            log::info!("回填 live 提示词片段，创建备份: {backup_id}");
+            state.db.save_prompt(app.as_str(), &backup_prompt)?;
+            Self::record_revision(
+                state,
+                app.as_str(),
+                &backup_id,
+                None,
+                &backup_prompt.content,
+                PromptRevisionSource::Backfill,
+            )?;
+            prompts.insert(backup_id, backup_prompt);
         }
 
-        // 启用目标提示词并写入文件
-        let mut prompts = state.db.get_prompts(app.as_str())?;
+        Ok(LiveLayout {
+            before: parsed.before,
+            after: parsed.after,
+            managed: true,
+            edited,
+        })
+    }
 
-        for prompt in prompts.values_mut() {
-            prompt.enabled = false;
+    /// 按排序将已启用片段渲染到各 live 文件（原子写入）
+    ///
+    /// `targets` 为修改前回填得到的文件列表，确保移出作用域的项目文件也会被更新。
+    /// 返回因模板片段被手动修改而未重写的文件路径，供界面提示用户。
+    fn write_live_files(
+        state: &AppState,
+        app: &AppType,
+        mut targets: Vec<LiveTarget>,
+    ) -> Result<Vec<String>, AppError> {
+        let prompts = Self::live_prompts(state, app)?;
+        let variables = state.db.get_prompt_variables()?;
+        for (project, path) in Self::live_paths(app, &prompts)? {
+            if targets.iter().all(|t| t.project != project) {
+                let layout = match std::fs::read_to_string(&path) {
+                    Ok(content) if !content.trim().is_empty() => LiveLayout {
+                        before: format!("{}\n\n", content.trim_end()),
+                        ..Default::default()
+                    },
+                    _ => LiveLayout::default(),
+                };
+                targets.push(LiveTarget {
+                    project,
+                    path,
+                    layout,
+                });
+            }
         }
 
-        if let Some(prompt) = prompts.get_mut(id) {
-            prompt.enabled = true;
-            write_text_file(&target_path, &prompt.content)?; // 原子写入
-            state.db.save_prompt(app.as_str(), prompt)?;
-        } else {
-            return Err(AppError::InvalidInput(format!("提示词 {id} 不存在")));
+        let mut skipped = Vec::new();
+        for target in &targets {
+            let project = target.project.as_deref();
+            let rendered: Vec<(&str, String)> = prompts
+                .values()
+                .filter(|p| p.enabled && belongs_to(p, project))
+                .map(|p| {
+                    (
+                        p.id.as_str(),
+                        rendered_content(app, project, &variables, &prompts, p),
+                    )
+                })
+                .collect();
+            let fragments: Vec<(&str, &str)> = rendered
+                .iter()
+                .map(|(id, content)| (*id, content.as_str()))
+                .collect();
+            // 从未托管过的文件不需要改动
+            if fragments.is_empty() && !target.layout.managed {
+                continue;
+            }
+            if let Some(dir) = project {
+                if !Path::new(dir).is_dir() {
+                    log::warn!("项目目录不存在，跳过提示词写入: {dir}");
+                    continue;
+                }
+            }
+
+            if !target.layout.edited.is_empty() {
+                log::warn!(
+                    "提示词文件中的模板片段已被手动修改，跳过写入: {} ({})；保存对应提示词可覆盖文件中的修改",
+                    target.path.display(),
+                    target.layout.edited.join(", ")
+                );
+                skipped.push(target.path.to_string_lossy().to_string());
+                continue;
+            }
+
+            let markers = BlockMarkers::for_target(app, project);
+            let file_content = render_live_file(&target.layout, &markers, &fragments);
+            if target.path.exists() {
+                let current = std::fs::read_to_string(&target.path).unwrap_or_default();
+                if current == file_content {
+                    continue;
+                }
+            } else if file_content.is_empty() {
+                continue;
+            }
+            write_text_file(&target.path, &file_content)?;
         }
+        Ok(skipped)
+    }
 
-        // Save all prompts to disable others
-        for (_, prompt) in prompts.iter() {
-            state.db.save_prompt(app.as_str(), prompt)?;
+    /// 检查各 live 文件中的托管片段是否在应用外被修改
+    pub fn check_drift(state: &AppState, app: AppType) -> Result<Vec<PromptFileDrift>, AppError> {
+        let prompts = Self::live_prompts(state, &app)?;
+        let variables = state.db.get_prompt_variables()?;
+        let mut report = Vec::new();
+
+        for (project, path) in Self::live_paths(&app, &prompts)? {
+            let enabled: Vec<&Prompt> = prompts
+                .values()
+                .filter(|p| p.enabled && belongs_to(p, project.as_deref()))
+                .collect();
+            let markers = BlockMarkers::for_target(&app, project.as_deref());
+            let live = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| parse_live_file(&content, &markers).fragments);
+
+            let (status, prompt_ids) = match live {
+                None if enabled.is_empty() => continue,
+                None => (
+                    PromptDriftStatus::Missing,
+                    enabled.iter().map(|p| p.id.clone()).collect(),
+                ),
+                Some(fragments) => {
+                    let mut ids: Vec<String> = enabled
+                        .iter()
+                        .filter(|p| {
+                            !fragments.iter().any(|(fid, fc)| {
+                                *fid == p.id
+                                    && fragment_matches(
+                                        &app,
+                                        project.as_deref(),
+                                        &variables,
+                                        &prompts,
+                                        p,
+                                        fc,
+                                    )
+                            })
+                        })
+                        .map(|p| p.id.clone())
+                        .collect();
+                    for (fid, _) in &fragments {
+                        if !enabled.iter().any(|p| p.id == *fid) && !ids.contains(fid) {
+                            ids.push(fid.clone());
+                        }
+                    }
+                    let live_order: Vec<&str> =
+                        fragments.iter().map(|(id, _)| id.as_str()).collect();
+                    let expected_order: Vec<&str> = enabled.iter().map(|p| p.id.as_str()).collect();
+                    if ids.is_empty() && live_order == expected_order {
+                        (PromptDriftStatus::InSync, ids)
+                    } else {
+                        (PromptDriftStatus::Modified, ids)
+                    }
+                }
+            };
+
+            report.push(PromptFileDrift {
+                project,
+                path: path.to_string_lossy().to_string(),
+                status,
+                prompt_ids,
+            });
         }
+        Ok(report)
+    }
 
-        Ok(())
+    /// 从用户指定的项目目录导入项目级提示词文件
+    ///
+    /// 文件中用户编写的内容会作为启用的项目级提示词接管，并原样包进托管区块；
+    /// 内容含模板语法（渲染后会改变）的文件不导入，避免改写用户文本。返回导入的数量。
+    pub fn import_from_projects(
+        state: &AppState,
+        app: AppType,
+        project_dirs: &[String],
+    ) -> Result<usize, AppError> {
+        if project_dirs.is_empty() {
+            return Err(AppError::InvalidInput(
+                "请指定要导入提示词的项目目录".to_string(),
+            ));
+        }
+        let prompts = state.db.get_prompts(app.as_str())?;
+        let live_prompts = Self::live_prompts(state, &app)?;
+        let variables = state.db.get_prompt_variables()?;
+
+        let mut targets = Self::backfill_from_live(state, &app)?;
+        let mut imported = 0;
+        for dir in project_dirs.iter().cloned() {
+            let project = Path::new(&dir);
+            let path = project_prompt_file_path(project, &app);
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let text = user_authored_text(&content);
+            if text.trim().is_empty() {
+                continue;
+            }
+            let already = prompts
+                .values()
+                .any(|p| p.projects.contains(&dir) && p.content.trim() == text.trim());
+            if already {
+                continue;
+            }
+            let verbatim = render_prompt(&app, Some(&dir), &variables, &live_prompts, None, &text)
+                .is_ok_and(|rendered| rendered.content.trim() == text.trim());
+            if !verbatim {
+                log::warn!(
+                    "项目提示词文件含模板语法，导入后内容会被改写，跳过: {}",
+                    path.display()
+                );
+                continue;
+            }
+
+            let timestamp = get_unix_timestamp()?;
+            let project_name = project
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| dir.clone());
+            let prompt = Prompt {
+                id: format!("project-{timestamp}-{imported}"),
+                name: format!("{project_name} 项目提示词"),
+                content: text,
+                description: Some(format!("从项目文件导入: {}", path.display())),
+                enabled: true,
+                created_at: Some(timestamp),
+                updated_at: Some(timestamp),
+                sort_index: None,
+                projects: vec![dir.clone()],
+            };
+            state.db.save_prompt(app.as_str(), &prompt)?;
+            Self::record_revision(
+                state,
+                app.as_str(),
+                &prompt.id,
+                None,
+                &prompt.content,
+                PromptRevisionSource::Import,
+            )?;
+            log::info!("导入项目提示词文件: {}", path.display());
+            imported += 1;
+
+            // 用户内容已由提示词接管，原样写入本应用的托管区块，其它应用的区块保持不变
+            let others =
+                other_managed_blocks(&content, &BlockMarkers::for_target(&app, Some(&dir)));
+            let layout = LiveLayout {
+                before: others.iter().map(|block| format!("{block}\n\n")).collect(),
+                managed: true,
+                ..Default::default()
+            };
+            targets.retain(|t| t.project.as_deref() != Some(dir.as_str()));
+            targets.push(LiveTarget {
+                project: Some(dir),
+                path,
+                layout,
+            });
+        }
+
+        if imported > 0 {
+            Self::write_live_files(state, &app, targets)?;
+        }
+        Ok(imported)
     }
 
     pub fn import_from_file(state: &AppState, app: AppType) -> Result<String, AppError> {
@@ -152,6 +1008,8 @@ impl PromptService {
 
         let content =
             std::fs::read_to_string(&file_path).map_err(|e| AppError::io(&file_path, e))?;
+        // 仅导入托管区块以外的用户内容，避免与已启用片段重复
+        let content = user_authored_text(&content);
         let timestamp = get_unix_timestamp()?;
 
         let id = format!("imported-{timestamp}");
@@ -166,12 +1024,408 @@ impl PromptService {
             enabled: false,
             created_at: Some(timestamp),
             updated_at: Some(timestamp),
+            sort_index: None,
+            projects: Vec::new(),
         };
 
-        Self::upsert_prompt(state, app, &id, prompt)?;
+        Self::upsert_prompt_from(state, app, &id, prompt, PromptRevisionSource::Import)?;
         Ok(id)
     }
 
+    // ========== 模板 ==========
+
+    fn validate_template(
+        state: &AppState,
+        app: &AppType,
+        prompts: &IndexMap<String, Prompt>,
+        id: &str,
+        content: &str,
+    ) -> Result<(), AppError> {
+        let variables = state.db.get_prompt_variables()?;
+        render_prompt(app, None, &variables, prompts, Some(id), content).map(|_| ())
+    }
+
+    /// 预览提示词渲染结果：传入 `content` 时预览未保存的内容，否则预览已保存的提示词
+    pub fn preview(
+        state: &AppState,
+        app: AppType,
+        id: Option<&str>,
+        content: Option<String>,
+        project: Option<&str>,
+    ) -> Result<PromptPreview, AppError> {
+        let prompts = Self::live_prompts(state, &app)?;
+        let content = match (content, id) {
+            (Some(content), _) => content,
+            (None, Some(id)) => prompts
+                .get(id)
+                .map(|p| p.content.clone())
+                .ok_or_else(|| AppError::InvalidInput(format!("提示词 {id} 不存在")))?,
+            (None, None) => {
+                return Err(AppError::InvalidInput(
+                    "缺少 id 或 content 参数".to_string(),
+                ))
+            }
+        };
+        let variables = state.db.get_prompt_variables()?;
+        let rendered = render_prompt(&app, project, &variables, &prompts, id, &content)?;
+        Ok(PromptPreview {
+            content: rendered.content,
+            unknown_variables: rendered.unknown_variables,
+            missing_includes: rendered.missing_includes,
+        })
+    }
+
+    pub fn get_variables(state: &AppState) -> Result<BTreeMap<String, String>, AppError> {
+        state.db.get_prompt_variables()
+    }
+
+    /// 保存用户自定义模板变量，并重新渲染所有应用的提示词文件
+    pub fn set_variables(
+        state: &AppState,
+        variables: BTreeMap<String, String>,
+    ) -> Result<Vec<String>, AppError> {
+        let variables: BTreeMap<String, String> = variables
+            .into_iter()
+            .map(|(k, v)| (k.trim().to_string(), v))
+            .filter(|(k, _)| !k.is_empty())
+            .collect();
+        Self::with_all_apps(state, None, || state.db.set_prompt_variables(&variables))
+    }
+
+    // ========== 版本历史 ==========
+
+    /// 记录一次内容变更（与最近一个版本相同时跳过）
+    ///
+    /// 提示词首次记录版本时，若已知修改前的内容，先将其作为初始版本保存。
+    fn record_revision(
+        state: &AppState,
+        app_key: &str,
+        prompt_id: &str,
+        previous: Option<&str>,
+        content: &str,
+        source: PromptRevisionSource,
+    ) -> Result<(), AppError> {
+        let latest = state.db.get_latest_prompt_revision(app_key, prompt_id)?;
+        let timestamp = get_unix_timestamp()?;
+        let latest_content = match (latest, previous) {
+            (Some(revision), _) => Some(revision.content),
+            (None, Some(previous)) if !previous.trim().is_empty() && previous != content => {
+                state.db.add_prompt_revision(
+                    app_key,
+                    prompt_id,
+                    previous,
+                    PromptRevisionSource::Initial,
+                    timestamp,
+                    MAX_REVISIONS_PER_PROMPT,
+                )?;
+                Some(previous.to_string())
+            }
+            (None, _) => None,
+        };
+        if latest_content.as_deref() == Some(content) {
+            return Ok(());
+        }
+        state.db.add_prompt_revision(
+            app_key,
+            prompt_id,
+            content,
+            source,
+            timestamp,
+            MAX_REVISIONS_PER_PROMPT,
+        )
+    }
+
+    /// 版本历史中的应用标识与提示词 id（共享提示词以 `shared:<id>` 传入）
+    fn revision_key<'a>(app: &'a AppType, id: &'a str) -> (&'a str, &'a str) {
+        match id.strip_prefix(SHARED_PREFIX) {
+            Some(shared_id) => (SHARED_REVISION_APP, shared_id),
+            None => (app.as_str(), id),
+        }
+    }
+
+    /// 列出提示词的版本历史（新版本在前）
+    pub fn list_revisions(
+        state: &AppState,
+        app: AppType,
+        id: &str,
+    ) -> Result<Vec<PromptRevision>, AppError> {
+        let (app_key, prompt_id) = Self::revision_key(&app, id);
+        state.db.get_prompt_revisions(app_key, prompt_id)
+    }
+
+    /// 生成两个版本之间的统一 diff
+    pub fn diff_revisions(state: &AppState, from: i64, to: i64) -> Result<String, AppError> {
+        let load = |revision_id: i64| {
+            state
+                .db
+                .get_prompt_revision(revision_id)?
+                .ok_or_else(|| AppError::InvalidInput(format!("提示词版本 {revision_id} 不存在")))
+        };
+        let (old, new) = (load(from)?, load(to)?);
+        let label = |r: &PromptRevision| {
+            let time = chrono::DateTime::from_timestamp(r.created_at, 0)
+                .map(|t| {
+                    t.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            format!("{}#{} ({}, {time})", r.prompt_id, r.id, r.source.as_str())
+        };
+        Ok(similar::TextDiff::from_lines(&old.content, &new.content)
+            .unified_diff()
+            .context_radius(3)
+            .header(&label(&old), &label(&new))
+            .to_string())
+    }
+
+    /// 恢复到指定版本：以该版本内容覆盖当前内容，并记录为新版本
+    pub fn restore_revision(
+        state: &AppState,
+        app: AppType,
+        revision_id: i64,
+    ) -> Result<Vec<String>, AppError> {
+        let revision = state
+            .db
+            .get_prompt_revision(revision_id)?
+            .ok_or_else(|| AppError::InvalidInput(format!("提示词版本 {revision_id} 不存在")))?;
+        let timestamp = get_unix_timestamp()?;
+
+        if revision.app_type == SHARED_REVISION_APP {
+            let mut prompt = state
+                .db
+                .get_shared_prompts()?
+                .shift_remove(&revision.prompt_id)
+                .ok_or_else(|| {
+                    AppError::InvalidInput(format!("提示词 {} 不存在", revision.prompt_id))
+                })?;
+            prompt.content = revision.content;
+            prompt.updated_at = Some(timestamp);
+            return Self::upsert_shared_prompt_from(state, prompt, PromptRevisionSource::Restore);
+        }
+
+        if revision.app_type != app.as_str() {
+            return Err(AppError::InvalidInput(format!(
+                "提示词版本 {revision_id} 不属于 {}",
+                app.as_str()
+            )));
+        }
+        let mut prompt = state
+            .db
+            .get_prompts(app.as_str())?
+            .shift_remove(&revision.prompt_id)
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!("提示词 {} 不存在", revision.prompt_id))
+            })?;
+        prompt.content = revision.content;
+        prompt.updated_at = Some(timestamp);
+        let id = prompt.id.clone();
+        Self::upsert_prompt_from(state, app, &id, prompt, PromptRevisionSource::Restore)
+    }
+
+    // ========== 共享提示词 ==========
+
+    pub fn get_shared_prompts(
+        state: &AppState,
+    ) -> Result<IndexMap<String, SharedPrompt>, AppError> {
+        state.db.get_shared_prompts()
+    }
+
+    /// 新增或更新共享提示词，并重新渲染所有应用的提示词文件
+    pub fn upsert_shared_prompt(
+        state: &AppState,
+        prompt: SharedPrompt,
+    ) -> Result<Vec<String>, AppError> {
+        Self::upsert_shared_prompt_from(state, prompt, PromptRevisionSource::Ui)
+    }
+
+    fn upsert_shared_prompt_from(
+        state: &AppState,
+        prompt: SharedPrompt,
+        source: PromptRevisionSource,
+    ) -> Result<Vec<String>, AppError> {
+        let id = format!("{SHARED_PREFIX}{}", prompt.id);
+        for app in ALL_APPS {
+            let content = prompt.content_for(&app);
+            let mut prompts = Self::live_prompts(state, &app)?;
+            if let Some(existing) = prompts.get_mut(&id) {
+                existing.content = content.clone();
+            }
+            Self::validate_template(state, &app, &prompts, &id, &content)?;
+        }
+        Self::with_all_apps(state, Some(&id), || {
+            let previous = state
+                .db
+                .get_shared_prompts()?
+                .get(&prompt.id)
+                .map(|p| p.content.clone());
+            state.db.save_shared_prompt(&prompt)?;
+            Self::record_revision(
+                state,
+                SHARED_REVISION_APP,
+                &prompt.id,
+                previous.as_deref(),
+                &prompt.content,
+                source,
+            )
+        })
+    }
+
+    pub fn delete_shared_prompt(state: &AppState, id: &str) -> Result<(), AppError> {
+        if let Some(prompt) = state.db.get_shared_prompts()?.get(id) {
+            if !prompt.apps.is_empty() {
+                return Err(AppError::InvalidInput("无法删除已启用的提示词".to_string()));
+            }
+        }
+        state.db.delete_shared_prompt(id)?;
+        state.db.delete_prompt_revisions(SHARED_REVISION_APP, id)
+    }
+
+    /// 切换共享提示词在指定应用中的启用状态
+    pub fn toggle_shared_prompt_app(
+        state: &AppState,
+        id: &str,
+        app: AppType,
+        enabled: bool,
+    ) -> Result<Vec<String>, AppError> {
+        let mut targets = Self::backfill_from_live(state, &app)?;
+        discard_edits(&mut targets, &format!("{SHARED_PREFIX}{id}"));
+
+        let mut shared = state.db.get_shared_prompts()?;
+        let Some(prompt) = shared.get_mut(id) else {
+            return Err(AppError::InvalidInput(format!("提示词 {id} 不存在")));
+        };
+        if prompt.apps.is_enabled_for(&app) != enabled {
+            prompt.apps.set_enabled_for(&app, enabled);
+            state.db.save_shared_prompt(prompt)?;
+        }
+
+        Self::write_live_files(state, &app, targets)
+    }
+
+    /// 按 (内容, 项目作用域) 分组各应用中的提示词，只保留出现在两个及以上应用中的组
+    fn dedup_groups(state: &AppState) -> Result<DedupGroups, AppError> {
+        let mut groups: DedupGroups = IndexMap::new();
+        for app in ALL_APPS {
+            for prompt in state.db.get_prompts(app.as_str())?.into_values() {
+                if prompt.content.trim().is_empty() {
+                    continue;
+                }
+                let mut projects = prompt.projects.clone();
+                projects.sort();
+                let group = groups
+                    .entry((prompt.content.trim().to_string(), projects))
+                    .or_default();
+                // 同一应用内的重复项不参与合并
+                if !group.iter().any(|(a, _)| *a == app) {
+                    group.push((app.clone(), prompt));
+                }
+            }
+        }
+        groups.retain(|_, group| group.len() >= 2);
+        Ok(groups)
+    }
+
+    /// 预览可合并为共享提示词的重复提示词（不做任何修改）
+    pub fn preview_dedup(state: &AppState) -> Result<Vec<PromptDedupGroup>, AppError> {
+        Ok(Self::dedup_groups(state)?
+            .into_values()
+            .map(|group| {
+                let first = &group[0].1;
+                PromptDedupGroup {
+                    name: first.name.clone(),
+                    content: first.content.clone(),
+                    projects: first.projects.clone(),
+                    members: group
+                        .iter()
+                        .map(|(app, prompt)| PromptDedupMember {
+                            app: app.as_str().to_string(),
+                            id: prompt.id.clone(),
+                            enabled: prompt.enabled,
+                        })
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+
+    /// 将各应用中内容相同的提示词合并为共享提示词（由用户在预览后手动执行）
+    ///
+    /// 内容（忽略首尾空白）与项目作用域都相同、且出现在两个及以上应用中的提示词
+    /// 会合并为一个共享提示词，原启用状态按应用保留。合并前先备份数据库。
+    /// 返回创建的共享提示词数量。
+    pub fn dedup_into_shared(state: &AppState) -> Result<usize, AppError> {
+        if Self::dedup_groups(state)?.is_empty() {
+            return Ok(0);
+        }
+        if let Some(backup) = state.db.backup_database_file()? {
+            log::info!("合并重复提示词前已备份数据库: {}", backup.display());
+        }
+
+        let mut created = 0;
+        Self::with_all_apps(state, None, || {
+            let groups = Self::dedup_groups(state)?;
+            let timestamp = get_unix_timestamp()?;
+            for group in groups.into_values() {
+                let first = &group[0].1;
+                let mut shared = SharedPrompt {
+                    id: format!("shared-{timestamp}-{created}"),
+                    name: first.name.clone(),
+                    content: first.content.clone(),
+                    description: first.description.clone(),
+                    apps: Default::default(),
+                    overrides: Default::default(),
+                    sort_index: group.iter().filter_map(|(_, p)| p.sort_index).min(),
+                    projects: first.projects.clone(),
+                    created_at: group.iter().filter_map(|(_, p)| p.created_at).min(),
+                    updated_at: Some(timestamp),
+                };
+                for (app, prompt) in &group {
+                    shared.apps.set_enabled_for(app, prompt.enabled);
+                }
+                let merged: Vec<(&str, &str)> = group
+                    .iter()
+                    .map(|(app, prompt)| (app.as_str(), prompt.id.as_str()))
+                    .collect();
+                state
+                    .db
+                    .merge_prompts_into_shared(&shared, SHARED_REVISION_APP, &merged)?;
+                log::info!(
+                    "合并 {} 个应用中的相同提示词为共享提示词: {}",
+                    group.len(),
+                    shared.id
+                );
+                created += 1;
+            }
+            Ok(())
+        })?;
+        Ok(created)
+    }
+
+    /// 在所有应用上执行「回填 → 修改 → 重新渲染」
+    ///
+    /// `overwrite` 为本次显式保存的片段 id，文件中对它的手动修改会被覆盖。
+    fn with_all_apps(
+        state: &AppState,
+        overwrite: Option<&str>,
+        mutate: impl FnOnce() -> Result<(), AppError>,
+    ) -> Result<Vec<String>, AppError> {
+        let mut targets = Vec::new();
+        for app in ALL_APPS {
+            let mut app_targets = Self::backfill_from_live(state, &app)?;
+            if let Some(id) = overwrite {
+                discard_edits(&mut app_targets, id);
+            }
+            targets.push((app.clone(), app_targets));
+        }
+        mutate()?;
+        let mut skipped = Vec::new();
+        for (app, app_targets) in targets {
+            skipped.extend(Self::write_live_files(state, &app, app_targets)?);
+        }
+        Ok(skipped)
+    }
+
     pub fn get_current_file_content(app: AppType) -> Result<Option<String>, AppError> {
         let file_path = prompt_file_path(&app)?;
         if !file_path.exists() {
@@ -231,12 +1485,192 @@ impl PromptService {
             enabled: true, // 首次导入时自动启用
             created_at: Some(timestamp),
             updated_at: Some(timestamp),
+            sort_index: None,
+            projects: Vec::new(),
         };
 
         // 保存到数据库
         state.db.save_prompt(app.as_str(), &prompt)?;
+        Self::record_revision(
+            state,
+            app.as_str(),
+            &prompt.id,
+            None,
+            &prompt.content,
+            PromptRevisionSource::Import,
+        )?;
 
         log::info!("自动导入完成: {}", app.as_str());
         Ok(1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn global_markers() -> BlockMarkers {
+        BlockMarkers::for_target(&AppType::Claude, None)
+    }
+
+    #[test]
+    fn renders_fragments_between_markers_and_keeps_user_text() {
+        let markers = global_markers();
+        let layout = LiveLayout {
+            before: "# My notes\n\n".to_string(),
+            after: "\n\nfooter\n".to_string(),
+            managed: true,
+            ..Default::default()
+        };
+        let rendered = render_live_file(&layout, &markers, &[("a", "alpha\n"), ("b", "beta")]);
+
+        assert!(rendered.starts_with("# My notes\n\n<!-- cc-switch:prompts:begin -->\n"));
+        assert!(rendered.ends_with("<!-- cc-switch:prompts:end -->\n\nfooter\n"));
+
+        let parsed = parse_live_file(&rendered, &markers);
+        assert_eq!(parsed.before, layout.before);
+        assert_eq!(parsed.after, layout.after);
+        assert_eq!(
+            parsed.fragments,
+            Some(vec![
+                ("a".to_string(), "alpha".to_string()),
+                ("b".to_string(), "beta".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_edited_fragments_back() {
+        let live = "intro\n<!-- cc-switch:prompts:begin -->\n<!-- cc-switch:prompt id=\"a\" -->\nline 1\n\nline 2 edited\n<!-- cc-switch:prompts:end -->\n";
+        let parsed = parse_live_file(live, &global_markers());
+
+        assert_eq!(parsed.before, "intro\n");
+        assert_eq!(parsed.after, "\n");
+        assert_eq!(
+            parsed.fragments,
+            Some(vec![(
+                "a".to_string(),
+                "line 1\n\nline 2 edited".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn removes_block_when_no_fragment_enabled() {
+        let markers = global_markers();
+        let live = "# Title\n\n<!-- cc-switch:prompts:begin -->\n<!-- cc-switch:prompt id=\"a\" -->\nalpha\n<!-- cc-switch:prompts:end -->\n";
+        let parsed = parse_live_file(live, &markers);
+        let layout = LiveLayout {
+            before: parsed.before,
+            after: parsed.after,
+            managed: true,
+            ..Default::default()
+        };
+
+        assert_eq!(render_live_file(&layout, &markers, &[]), "# Title\n");
+        assert_eq!(parse_live_file("plain text", &markers).fragments, None);
+    }
+
+    #[test]
+    fn shared_project_file_keeps_blocks_per_app() {
+        let codex = BlockMarkers::for_target(&AppType::Codex, Some("/repo"));
+        let opencode = BlockMarkers::for_target(&AppType::OpenCode, Some("/repo"));
+        let codex_file = render_live_file(
+            &LiveLayout {
+                before: "# Repo rules\n\n".to_string(),
+                ..Default::default()
+            },
+            &codex,
+            &[("c", "codex only")],
+        );
+
+        // OpenCode 视角下 Codex 的区块属于区块外内容，原样保留
+        let parsed = parse_live_file(&codex_file, &opencode);
+        assert_eq!(parsed.fragments, None);
+        assert_eq!(user_authored_text(&codex_file), "# Repo rules\n");
+        assert_eq!(
+            other_managed_blocks(&codex_file, &opencode),
+            vec!["<!-- cc-switch:prompts:codex:begin -->\n<!-- cc-switch:prompt id=\"c\" -->\ncodex only\n<!-- cc-switch:prompts:codex:end -->".to_string()]
+        );
+        assert!(other_managed_blocks(&codex_file, &codex).is_empty());
+    }
+
+    #[test]
+    fn shared_prompt_adapts_and_backfills_per_app() {
+        let mut shared = SharedPrompt {
+            id: "team".to_string(),
+            name: "Team".to_string(),
+            content: "Use rustfmt.".to_string(),
+            description: None,
+            apps: Default::default(),
+            overrides: Default::default(),
+            sort_index: None,
+            projects: Vec::new(),
+            created_at: None,
+            updated_at: None,
+        };
+        shared.overrides.insert(
+            "codex".to_string(),
+            crate::prompt::PromptAppOverride {
+                content: None,
+                section: Some("Run `cargo test` before answering.".to_string()),
+            },
+        );
+
+        assert_eq!(shared.content_for(&AppType::Claude), "Use rustfmt.");
+        assert_eq!(
+            shared.content_for(&AppType::Codex),
+            "Use rustfmt.\n\nRun `cargo test` before answering."
+        );
+
+        // 编辑共享部分：写回共享内容，应用专属段落保持不变
+        shared.apply_edit(
+            &AppType::Codex,
+            "Use rustfmt and clippy.\n\nRun `cargo test` before answering.",
+        );
+        assert_eq!(shared.content, "Use rustfmt and clippy.");
+        assert_eq!(
+            shared.content_for(&AppType::Claude),
+            "Use rustfmt and clippy."
+        );
+
+        // 专属段落被改动：整体作为该应用的覆盖内容
+        shared.apply_edit(&AppType::Codex, "Codex only text");
+        assert_eq!(shared.content, "Use rustfmt and clippy.");
+        assert_eq!(shared.content_for(&AppType::Codex), "Codex only text");
+    }
+
+    #[test]
+    fn volatile_variables_do_not_count_as_edits() {
+        let prompt = Prompt {
+            id: "daily".to_string(),
+            name: "Daily".to_string(),
+            content: "Today is {{date}} on {{app}}.".to_string(),
+            description: None,
+            enabled: true,
+            created_at: None,
+            updated_at: None,
+            sort_index: None,
+            projects: Vec::new(),
+        };
+        let prompts: IndexMap<String, Prompt> =
+            IndexMap::from([(prompt.id.clone(), prompt.clone())]);
+        let variables = BTreeMap::new();
+        let matches = |live: &str| {
+            fragment_matches(&AppType::Claude, None, &variables, &prompts, &prompt, live)
+        };
+
+        // 昨天渲染的文件仍视为一致
+        assert!(matches("Today is 2020-01-01 on claude."));
+        assert!(matches(&rendered_content(
+            &AppType::Claude,
+            None,
+            &variables,
+            &prompts,
+            &prompt
+        )));
+        // 非变量部分被改动才算手动修改
+        assert!(!matches("Today is 2020-01-01 on codex."));
+        assert!(!matches("Today is tomorrow on claude."));
+    }
+}
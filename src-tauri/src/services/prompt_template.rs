@@ -0,0 +1,268 @@
+//! 提示词模板渲染
+//!
+//! 提示词写入 live 文件前按模板渲染，支持的语法：
+//! - `{{name}}`：变量，内置 `project_name`、`project_dir`、`os`、`date`、`app`，其余为用户自定义变量
+//! - `{{> id}}`：引用其它提示词（按 id），被引用的内容同样按模板渲染
+//! - `{{#app claude,codex}} ... {{/app}}`：仅在列出的应用中保留的条件段落
+//!
+//! 未知变量原样保留并在结果中报告；引用成环、条件段落不配对时报错。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+
+/// 模板渲染上下文
+pub struct TemplateContext<'a> {
+    pub app: AppType,
+    pub variables: BTreeMap<String, String>,
+    /// 按 id 查找被引用提示词的原始内容
+    pub resolve_include: &'a dyn Fn(&str) -> Option<String>,
+}
+
+/// 渲染结果
+#[derive(Debug, Default, PartialEq)]
+pub struct RenderedTemplate {
+    pub content: String,
+    /// 未定义的变量（原样保留在内容中）
+    pub unknown_variables: Vec<String>,
+    /// 引用了不存在的提示词 id（渲染为空）
+    pub missing_includes: Vec<String>,
+}
+
+/// 取值随时间变化的内置变量及其取值格式（正则）
+///
+/// 比较 live 文件与渲染结果时这些变量按格式匹配，避免日期变化被当作手动修改。
+pub const VOLATILE_VARIABLES: &[(&str, &str)] = &[("date", r"\d{4}-\d{2}-\d{2}")];
+
+/// 内置变量；用户级文件的 `project_name`/`project_dir` 为空
+pub fn builtin_variables(app: &AppType, project: Option<&Path>) -> BTreeMap<String, String> {
+    let mut vars = BTreeMap::new();
+    vars.insert(
+        "project_name".to_string(),
+        project
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    );
+    vars.insert(
+        "project_dir".to_string(),
+        project
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    );
+    vars.insert("os".to_string(), std::env::consts::OS.to_string());
+    vars.insert(
+        "date".to_string(),
+        chrono::Local::now().format("%Y-%m-%d").to_string(),
+    );
+    vars.insert("app".to_string(), app.as_str().to_string());
+    vars
+}
+
+/// 渲染模板；`self_id` 为当前提示词 id，用于检测自引用
+pub fn render_template(
+    template: &str,
+    ctx: &TemplateContext,
+    self_id: Option<&str>,
+) -> Result<RenderedTemplate, AppError> {
+    let mut output = RenderedTemplate::default();
+    let mut stack: Vec<String> = self_id.map(|id| vec![id.to_string()]).unwrap_or_default();
+    output.content = render_into(template, ctx, &mut stack, &mut output)?;
+    Ok(output)
+}
+
+fn render_into(
+    template: &str,
+    ctx: &TemplateContext,
+    stack: &mut Vec<String>,
+    output: &mut RenderedTemplate,
+) -> Result<String, AppError> {
+    let mut result = String::new();
+    // 嵌套条件段落的命中状态，全部命中时才输出
+    let mut sections: Vec<bool> = Vec::new();
+    let mut pos = 0;
+
+    while let Some(offset) = template[pos..].find("{{") {
+        let start = pos + offset;
+        let active = sections.iter().all(|s| *s);
+        if active {
+            result.push_str(&template[pos..start]);
+        }
+        let Some(end_offset) = template[start..].find("}}") else {
+            pos = start;
+            break;
+        };
+        let end = start + end_offset + 2;
+        let raw = &template[start..end];
+        let tag = template[start + 2..end - 2].trim();
+        pos = end;
+
+        if let Some(list) = app_section_list(tag) {
+            let apps: Vec<&str> = list.split(',').map(str::trim).collect();
+            sections.push(apps.contains(&ctx.app.as_str()));
+            pos = skip_standalone_newline(template, start, pos);
+        } else if tag == "/app" {
+            if sections.pop().is_none() {
+                return Err(AppError::InvalidInput(
+                    "模板中存在多余的 {{/app}}".to_string(),
+                ));
+            }
+            pos = skip_standalone_newline(template, start, pos);
+        } else if !active {
+            continue;
+        } else if let Some(id) = tag.strip_prefix('>') {
+            let id = id.trim();
+            if stack.iter().any(|s| s == id) {
+                let chain = stack
+                    .iter()
+                    .map(String::as_str)
+                    .chain(std::iter::once(id))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                return Err(AppError::InvalidInput(format!("提示词引用成环: {chain}")));
+            }
+            match (ctx.resolve_include)(id) {
+                Some(content) => {
+                    stack.push(id.to_string());
+                    let rendered = render_into(&content, ctx, stack, output)?;
+                    stack.pop();
+                    result.push_str(rendered.trim_end());
+                }
+                None => {
+                    if !output.missing_includes.iter().any(|m| m == id) {
+                        output.missing_includes.push(id.to_string());
+                    }
+                }
+            }
+        } else if is_variable_name(tag) {
+            match ctx.variables.get(tag) {
+                Some(value) => result.push_str(value),
+                None => {
+                    if !output.unknown_variables.iter().any(|v| v == tag) {
+                        output.unknown_variables.push(tag.to_string());
+                    }
+                    result.push_str(raw);
+                }
+            }
+        } else {
+            result.push_str(raw);
+        }
+    }
+
+    if !sections.is_empty() {
+        return Err(AppError::InvalidInput(
+            "模板中的 {{#app}} 条件段落缺少 {{/app}}".to_string(),
+        ));
+    }
+    result.push_str(&template[pos..]);
+    Ok(result)
+}
+
+/// 解析 `{{#app ...}}` 标签中的应用列表；`#app` 后须为空白或标签结尾（排除 `{{#apple}}` 等）
+fn app_section_list(tag: &str) -> Option<&str> {
+    let list = tag.strip_prefix("#app")?;
+    (list.is_empty() || list.starts_with(char::is_whitespace)).then_some(list)
+}
+
+/// 条件标签独占一行时，连同行尾换行一起去掉，避免留下空行
+fn skip_standalone_newline(template: &str, tag_start: usize, tag_end: usize) -> usize {
+    let at_line_start = tag_start == 0 || template[..tag_start].ends_with('\n');
+    if !at_line_start {
+        return tag_end;
+    }
+    let rest = &template[tag_end..];
+    if rest.starts_with("\r\n") {
+        tag_end + 2
+    } else if rest.starts_with('\n') {
+        tag_end + 1
+    } else {
+        tag_end
+    }
+}
+
+fn is_variable_name(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(
+        template: &str,
+        app: AppType,
+        includes: &[(&str, &str)],
+    ) -> Result<RenderedTemplate, AppError> {
+        let includes: Vec<(String, String)> = includes
+            .iter()
+            .map(|(id, content)| (id.to_string(), content.to_string()))
+            .collect();
+        let resolve = |id: &str| {
+            includes
+                .iter()
+                .find(|(i, _)| i == id)
+                .map(|(_, c)| c.clone())
+        };
+        let mut variables = builtin_variables(&app, Some(Path::new("/work/cc-switch")));
+        variables.insert("team".to_string(), "Core".to_string());
+        let ctx = TemplateContext {
+            app,
+            variables,
+            resolve_include: &resolve,
+        };
+        render_template(template, &ctx, Some("root"))
+    }
+
+    #[test]
+    fn renders_variables_and_keeps_unknown() {
+        let out = render(
+            "# {{project_name}} ({{ team }}) on {{app}} {{missing}}",
+            AppType::Codex,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(out.content, "# cc-switch (Core) on codex {{missing}}");
+        assert_eq!(out.unknown_variables, vec!["missing".to_string()]);
+    }
+
+    #[test]
+    fn renders_app_sections_and_includes() {
+        let template = "Intro\n{{#app claude}}\nClaude only\n{{/app}}\n{{#app codex, gemini}}\nOthers\n{{/app}}\n{{> style}}\n{{> nope}}";
+        let out = render(
+            template,
+            AppType::Claude,
+            &[("style", "Style for {{app}}\n")],
+        )
+        .unwrap();
+        assert_eq!(out.content, "Intro\nClaude only\nStyle for claude\n");
+        assert_eq!(out.missing_includes, vec!["nope".to_string()]);
+    }
+
+    #[test]
+    fn detects_include_cycles_and_unbalanced_sections() {
+        let err = render(
+            "{{> a}}",
+            AppType::Claude,
+            &[("a", "{{> b}}"), ("b", "{{> root}}")],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("root -> a -> b -> root"));
+
+        assert!(render("{{#app claude}} open", AppType::Claude, &[]).is_err());
+        assert!(render("{{/app}}", AppType::Claude, &[]).is_err());
+    }
+
+    #[test]
+    fn app_section_tag_requires_delimiter() {
+        let out = render("{{#apple}} {{#app_name}}", AppType::Claude, &[]).unwrap();
+        assert_eq!(out.content, "{{#apple}} {{#app_name}}");
+
+        let out = render("{{#app\tclaude}}kept{{/app}}", AppType::Claude, &[]).unwrap();
+        assert_eq!(out.content, "kept");
+    }
+}
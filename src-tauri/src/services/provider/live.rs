@@ -106,6 +106,17 @@ impl LiveSnapshot {
 
 /// Write live configuration snapshot for a provider
 pub(crate) fn write_live_snapshot(app_type: &AppType, provider: &Provider) -> Result<(), AppError> {
+    // Resolve ${secret:name} references only at write time; the DB keeps the references
+    let resolved;
+    let provider = if crate::secret_store::contains_refs(&provider.settings_config) {
+        let mut copy = provider.clone();
+        copy.settings_config = crate::secret_store::resolve_value(&provider.settings_config)?;
+        resolved = copy;
+        &resolved
+    } else {
+        provider
+    };
+
     match app_type {
         AppType::Claude => {
             let path = get_claude_settings_path();
@@ -6,6 +6,7 @@ mod endpoints;
 mod gemini_auth;
 mod live;
 mod usage;
+mod usage_templates;
 
 use indexmap::IndexMap;
 use regex::Regex;
@@ -25,6 +26,8 @@ pub use live::{
     sync_current_to_live,
 };
 
+pub use usage_templates::{list_usage_templates, UsageTemplateInfo};
+
 // Internal re-exports (pub(crate))
 pub(crate) use live::sanitize_claude_settings_for_live;
 pub(crate) use live::write_live_snapshot;
@@ -385,7 +388,11 @@ impl ProviderService {
                     // Only backfill when switching to a different provider
                     if let Ok(live_config) = read_live_settings(app_type.clone()) {
                         if let Some(mut current_provider) = providers.get(&current_id).cloned() {
-                            current_provider.settings_config = live_config;
+                            // Keep ${secret:name} references instead of persisting resolved plaintext
+                            current_provider.settings_config = crate::secret_store::preserve_refs(
+                                &current_provider.settings_config,
+                                live_config,
+                            );
                             // Ignore backfill failure, don't affect switch flow
                             let _ = state.db.save_provider(app_type.as_str(), &current_provider);
                         }
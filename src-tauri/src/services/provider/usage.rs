@@ -9,6 +9,8 @@ use crate::settings;
 use crate::store::AppState;
 use crate::usage_script;
 
+use super::usage_templates::UsageTemplate;
+
 /// Execute usage script and format result (private helper method)
 pub(crate) async fn execute_and_format_usage_result(
     script_code: &str,
@@ -19,43 +21,32 @@ pub(crate) async fn execute_and_format_usage_result(
     user_id: Option<&str>,
     template_type: Option<&str>,
 ) -> Result<UsageResult, AppError> {
-    match usage_script::execute_usage_script(
-        script_code,
-        api_key,
-        base_url,
-        timeout,
-        access_token,
-        user_id,
-        template_type,
-    )
-    .await
-    {
-        Ok(data) => {
-            let usage_list: Vec<UsageData> = if data.is_array() {
-                serde_json::from_value(data).map_err(|e| {
-                    AppError::localized(
-                        "usage_script.data_format_error",
-                        format!("数据格式错误: {e}"),
-                        format!("Data format error: {e}"),
-                    )
-                })?
-            } else {
-                let single: UsageData = serde_json::from_value(data).map_err(|e| {
-                    AppError::localized(
-                        "usage_script.data_format_error",
-                        format!("数据格式错误: {e}"),
-                        format!("Data format error: {e}"),
-                    )
-                })?;
-                vec![single]
-            };
-
-            Ok(UsageResult {
-                success: true,
-                data: Some(usage_list),
-                error: None,
-            })
+    // Built-in templates are executed natively, without the JS runtime
+    let usage = match template_type.and_then(UsageTemplate::from_id) {
+        Some(template) => {
+            template
+                .query(api_key, base_url, timeout, access_token, user_id)
+                .await
         }
+        None => usage_script::execute_usage_script(
+            script_code,
+            api_key,
+            base_url,
+            timeout,
+            access_token,
+            user_id,
+            template_type,
+        )
+        .await
+        .and_then(parse_usage_value),
+    };
+
+    match usage {
+        Ok(usage_list) => Ok(UsageResult {
+            success: true,
+            data: Some(usage_list),
+            error: None,
+        }),
         Err(err) => {
             let lang = settings::get_settings()
                 .language
@@ -81,6 +72,24 @@ pub(crate) async fn execute_and_format_usage_result(
     }
 }
 
+/// Convert script output (single object or array) into usage data
+fn parse_usage_value(data: serde_json::Value) -> Result<Vec<UsageData>, AppError> {
+    let to_error = |e: serde_json::Error| {
+        AppError::localized(
+            "usage_script.data_format_error",
+            format!("数据格式错误: {e}"),
+            format!("Data format error: {e}"),
+        )
+    };
+
+    if data.is_array() {
+        serde_json::from_value(data).map_err(to_error)
+    } else {
+        let single: UsageData = serde_json::from_value(data).map_err(to_error)?;
+        Ok(vec![single])
+    }
+}
+
 /// Extract API key from provider configuration
 fn extract_api_key_from_provider(provider: &crate::provider::Provider) -> Option<String> {
     if let Some(env) = provider.settings_config.get("env") {
@@ -96,6 +105,46 @@ fn extract_api_key_from_provider(provider: &crate::provider::Provider) -> Option
     }
 }
 
+/// Credentials passed to a usage query
+#[derive(Debug, PartialEq)]
+struct UsageCredentials {
+    api_key: String,
+    base_url: String,
+    access_token: Option<String>,
+    user_id: Option<String>,
+}
+
+/// Collect usage credentials, prioritizing UsageScript values over provider config.
+///
+/// `${secret:name}` references are resolved here so the query receives the real values.
+fn usage_credentials(
+    provider: &crate::provider::Provider,
+    usage_script: &UsageScript,
+    store: &crate::secret_store::SecretStore,
+) -> Result<UsageCredentials, AppError> {
+    let resolve = |value: Option<String>| -> Result<Option<String>, AppError> {
+        value.map(|v| store.resolve_str(&v)).transpose()
+    };
+
+    let api_key = usage_script
+        .api_key
+        .clone()
+        .filter(|k| !k.is_empty())
+        .or_else(|| extract_api_key_from_provider(provider));
+    let base_url = usage_script
+        .base_url
+        .clone()
+        .filter(|u| !u.is_empty())
+        .or_else(|| extract_base_url_from_provider(provider));
+
+    Ok(UsageCredentials {
+        api_key: resolve(api_key)?.unwrap_or_default(),
+        base_url: resolve(base_url)?.unwrap_or_default(),
+        access_token: resolve(usage_script.access_token.clone())?,
+        user_id: resolve(usage_script.user_id.clone())?,
+    })
+}
+
 /// Extract base URL from provider configuration
 fn extract_base_url_from_provider(provider: &crate::provider::Provider) -> Option<String> {
     if let Some(env) = provider.settings_config.get("env") {
@@ -144,28 +193,19 @@ pub async fn query_usage(
             ));
         }
 
-        // Get credentials: prioritize UsageScript values, fallback to provider config
-        let api_key = usage_script
-            .api_key
-            .clone()
-            .filter(|k| !k.is_empty())
-            .or_else(|| extract_api_key_from_provider(provider))
-            .unwrap_or_default();
-
-        let base_url = usage_script
-            .base_url
-            .clone()
-            .filter(|u| !u.is_empty())
-            .or_else(|| extract_base_url_from_provider(provider))
-            .unwrap_or_default();
+        let credentials = usage_credentials(
+            provider,
+            usage_script,
+            &crate::secret_store::SecretStore::open_default(),
+        )?;
 
         (
             usage_script.code.clone(),
             usage_script.timeout.unwrap_or(10),
-            api_key,
-            base_url,
-            usage_script.access_token.clone(),
-            usage_script.user_id.clone(),
+            credentials.api_key,
+            credentials.base_url,
+            credentials.access_token,
+            credentials.user_id,
             usage_script.template_type.clone(),
         )
     };
@@ -196,14 +236,19 @@ pub async fn test_usage_script(
     user_id: Option<&str>,
     template_type: Option<&str>,
 ) -> Result<UsageResult, AppError> {
-    // Use provided credential parameters directly for testing
+    // Use provided credential parameters directly for testing (secret references resolved)
+    let resolve = |value: Option<&str>| value.map(crate::secret_store::resolve_str).transpose();
+    let api_key = resolve(api_key)?;
+    let base_url = resolve(base_url)?;
+    let access_token = resolve(access_token)?;
+    let user_id = resolve(user_id)?;
     execute_and_format_usage_result(
         script_code,
-        api_key.unwrap_or(""),
-        base_url.unwrap_or(""),
+        api_key.as_deref().unwrap_or(""),
+        base_url.as_deref().unwrap_or(""),
         timeout,
-        access_token,
-        user_id,
+        access_token.as_deref(),
+        user_id.as_deref(),
         template_type,
     )
     .await
@@ -226,3 +271,60 @@ pub(crate) fn validate_usage_script(script: &UsageScript) -> Result<(), AppError
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::Provider;
+    use crate::secret_store::SecretStore;
+    use serde_json::json;
+
+    fn script(value: serde_json::Value) -> UsageScript {
+        serde_json::from_value(value).expect("usage script")
+    }
+
+    #[test]
+    fn usage_credentials_resolve_secret_refs() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store = SecretStore::at(dir.path().join("secrets.json"));
+        store.init(Some("usage test passphrase")).expect("init");
+        store.set("relay", "sk-live").expect("set");
+        store.set("relay_user", "42").expect("set");
+
+        let provider = Provider::with_id(
+            "p1".into(),
+            "Relay".into(),
+            json!({"env": {
+                "ANTHROPIC_AUTH_TOKEN": "${secret:relay}",
+                "ANTHROPIC_BASE_URL": "https://relay.example.com/"
+            }}),
+            None,
+        );
+        let usage_script = script(json!({
+            "enabled": true,
+            "language": "javascript",
+            "code": "",
+            "userId": "${secret:relay_user}"
+        }));
+
+        let credentials = usage_credentials(&provider, &usage_script, &store).expect("credentials");
+        assert_eq!(
+            credentials,
+            UsageCredentials {
+                api_key: "sk-live".into(),
+                base_url: "https://relay.example.com".into(),
+                access_token: None,
+                user_id: Some("42".into()),
+            }
+        );
+
+        // 引用的密钥不存在时报错，而不是把引用原样当作 API Key 发送
+        let usage_script = script(json!({
+            "enabled": true,
+            "language": "javascript",
+            "code": "",
+            "apiKey": "${secret:missing}"
+        }));
+        assert!(usage_credentials(&provider, &usage_script, &store).is_err());
+    }
+}
@@ -0,0 +1,523 @@
+//! Built-in usage query templates
+//!
+//! Native balance adapters for common relay backends, selected via
+//! `UsageScript.template_type`. They run without the JS runtime and return
+//! `UsageData` with normalised units.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::AppError;
+use crate::provider::UsageData;
+use crate::usage_script::{
+    send_http_request, validate_base_url, validate_request_url, RequestConfig,
+};
+
+/// NewAPI / OneAPI quota units per USD
+const NEWAPI_QUOTA_PER_USD: f64 = 500_000.0;
+
+/// Built-in usage template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UsageTemplate {
+    /// NewAPI / OneAPI (`/api/user/self` with access token, or billing API with API key)
+    NewApi,
+    /// OpenRouter `/api/v1/credits`
+    OpenRouter,
+    /// DeepSeek `/user/balance`
+    DeepSeek,
+    /// SiliconFlow `/v1/user/info`
+    SiliconFlow,
+    /// Moonshot `/v1/users/me/balance`
+    Moonshot,
+}
+
+/// Template descriptor exposed to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageTemplateInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub default_base_url: &'static str,
+    /// Whether the template can use access token + user id instead of the API key
+    pub supports_access_token: bool,
+}
+
+impl UsageTemplate {
+    pub(crate) const ALL: [UsageTemplate; 5] = [
+        UsageTemplate::NewApi,
+        UsageTemplate::OpenRouter,
+        UsageTemplate::DeepSeek,
+        UsageTemplate::SiliconFlow,
+        UsageTemplate::Moonshot,
+    ];
+
+    /// Resolve a native template from `template_type`
+    ///
+    /// Ids are prefixed with `builtin:` so they never collide with the
+    /// JS presets (`custom` / `general` / `newapi`) stored by older versions.
+    pub(crate) fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.id() == id)
+    }
+
+    pub(crate) fn id(self) -> &'static str {
+        match self {
+            UsageTemplate::NewApi => "builtin:newapi",
+            UsageTemplate::OpenRouter => "builtin:openrouter",
+            UsageTemplate::DeepSeek => "builtin:deepseek",
+            UsageTemplate::SiliconFlow => "builtin:siliconflow",
+            UsageTemplate::Moonshot => "builtin:moonshot",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            UsageTemplate::NewApi => "NewAPI / OneAPI",
+            UsageTemplate::OpenRouter => "OpenRouter",
+            UsageTemplate::DeepSeek => "DeepSeek",
+            UsageTemplate::SiliconFlow => "SiliconFlow",
+            UsageTemplate::Moonshot => "Moonshot",
+        }
+    }
+
+    fn default_base_url(self) -> &'static str {
+        match self {
+            UsageTemplate::NewApi => "",
+            UsageTemplate::OpenRouter => "https://openrouter.ai",
+            UsageTemplate::DeepSeek => "https://api.deepseek.com",
+            UsageTemplate::SiliconFlow => "https://api.siliconflow.cn",
+            UsageTemplate::Moonshot => "https://api.moonshot.cn",
+        }
+    }
+
+    pub(crate) fn info(self) -> UsageTemplateInfo {
+        UsageTemplateInfo {
+            id: self.id(),
+            name: self.name(),
+            default_base_url: self.default_base_url(),
+            supports_access_token: self == UsageTemplate::NewApi,
+        }
+    }
+
+    /// Query balance using this template
+    pub(crate) async fn query(
+        self,
+        api_key: &str,
+        base_url: &str,
+        timeout: u64,
+        access_token: Option<&str>,
+        user_id: Option<&str>,
+    ) -> Result<Vec<UsageData>, AppError> {
+        let origin = resolve_origin(base_url, self.default_base_url())?;
+
+        match self {
+            UsageTemplate::NewApi => {
+                match (
+                    access_token.filter(|t| !t.is_empty()),
+                    user_id.filter(|u| !u.is_empty()),
+                ) {
+                    (Some(token), Some(uid)) => {
+                        let mut headers = bearer(token);
+                        headers.insert("New-Api-User".to_string(), uid.to_string());
+                        let body =
+                            get_json(&origin, "/api/user/self", headers, base_url, timeout).await?;
+                        Ok(parse_newapi_self(&body))
+                    }
+                    _ => {
+                        let subscription = get_json(
+                            &origin,
+                            "/v1/dashboard/billing/subscription",
+                            bearer(api_key),
+                            base_url,
+                            timeout,
+                        )
+                        .await?;
+                        let today = chrono::Local::now().date_naive();
+                        let start = today - chrono::Duration::days(99);
+                        let end = today + chrono::Duration::days(1);
+                        let usage = get_json(
+                            &origin,
+                            &format!(
+                                "/v1/dashboard/billing/usage?start_date={}&end_date={}",
+                                start.format("%Y-%m-%d"),
+                                end.format("%Y-%m-%d")
+                            ),
+                            bearer(api_key),
+                            base_url,
+                            timeout,
+                        )
+                        .await?;
+                        Ok(parse_newapi_billing(&subscription, &usage))
+                    }
+                }
+            }
+            UsageTemplate::OpenRouter => {
+                let body = get_json(
+                    &origin,
+                    "/api/v1/credits",
+                    bearer(api_key),
+                    base_url,
+                    timeout,
+                )
+                .await?;
+                Ok(parse_openrouter(&body))
+            }
+            UsageTemplate::DeepSeek => {
+                let body =
+                    get_json(&origin, "/user/balance", bearer(api_key), base_url, timeout).await?;
+                Ok(parse_deepseek(&body))
+            }
+            UsageTemplate::SiliconFlow => {
+                let body =
+                    get_json(&origin, "/v1/user/info", bearer(api_key), base_url, timeout).await?;
+                Ok(parse_siliconflow(&body))
+            }
+            UsageTemplate::Moonshot => {
+                let body = get_json(
+                    &origin,
+                    "/v1/users/me/balance",
+                    bearer(api_key),
+                    base_url,
+                    timeout,
+                )
+                .await?;
+                Ok(parse_moonshot(&body))
+            }
+        }
+    }
+}
+
+/// List all built-in templates
+pub fn list_usage_templates() -> Vec<UsageTemplateInfo> {
+    UsageTemplate::ALL
+        .into_iter()
+        .map(UsageTemplate::info)
+        .collect()
+}
+
+/// Use the origin (scheme + host + port) of the provider base URL, falling
+/// back to the template default. Provider base URLs often carry a path such as
+/// `/anthropic` that the balance endpoints do not live under.
+fn resolve_origin(base_url: &str, default_base_url: &str) -> Result<String, AppError> {
+    let base = if base_url.is_empty() {
+        default_base_url
+    } else {
+        base_url
+    };
+    if base.is_empty() {
+        return Err(AppError::localized(
+            "usage_script.base_url_empty",
+            "base_url 不能为空",
+            "base_url cannot be empty",
+        ));
+    }
+    validate_base_url(base)?;
+
+    let parsed = url::Url::parse(base).map_err(|e| {
+        AppError::localized(
+            "usage_script.base_url_invalid",
+            format!("无效的 base_url: {e}"),
+            format!("Invalid base_url: {e}"),
+        )
+    })?;
+    Ok(parsed.origin().ascii_serialization())
+}
+
+fn bearer(token: &str) -> HashMap<String, String> {
+    HashMap::from([
+        ("Authorization".to_string(), format!("Bearer {token}")),
+        ("Accept".to_string(), "application/json".to_string()),
+    ])
+}
+
+async fn get_json(
+    origin: &str,
+    path: &str,
+    headers: HashMap<String, String>,
+    base_url: &str,
+    timeout: u64,
+) -> Result<Value, AppError> {
+    let url = format!("{origin}{path}");
+    validate_request_url(&url, base_url, false)?;
+
+    let request = RequestConfig {
+        url,
+        method: "GET".to_string(),
+        headers,
+        body: None,
+    };
+    let text = send_http_request(&request, timeout, None).await?;
+    serde_json::from_str(&text).map_err(|e| {
+        AppError::localized(
+            "usage_script.response_parse_failed",
+            format!("解析响应 JSON 失败: {e}"),
+            format!("Failed to parse response JSON: {e}"),
+        )
+    })
+}
+
+/// Read a number that may be encoded as a JSON string
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Normalise currency / unit names returned by different backends
+fn normalize_unit(unit: &str) -> String {
+    match unit.trim().to_ascii_uppercase().as_str() {
+        "CNY" | "RMB" | "¥" | "元" => "CNY".to_string(),
+        "USD" | "$" | "US$" => "USD".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn invalid(message: Option<&str>) -> Vec<UsageData> {
+    vec![UsageData {
+        plan_name: None,
+        extra: None,
+        is_valid: Some(false),
+        invalid_message: Some(message.unwrap_or("query failed").to_string()),
+        total: None,
+        used: None,
+        remaining: None,
+        unit: None,
+    }]
+}
+
+fn parse_newapi_self(body: &Value) -> Vec<UsageData> {
+    let data = body
+        .get("data")
+        .filter(|_| body["success"] == Value::Bool(true));
+    let Some(data) = data else {
+        return invalid(body.get("message").and_then(Value::as_str));
+    };
+
+    let remaining = number(&data["quota"]).map(|q| q / NEWAPI_QUOTA_PER_USD);
+    let used = number(&data["used_quota"]).map(|q| q / NEWAPI_QUOTA_PER_USD);
+    vec![UsageData {
+        plan_name: data["group"].as_str().map(str::to_string),
+        extra: None,
+        is_valid: Some(true),
+        invalid_message: None,
+        total: remaining.zip(used).map(|(r, u)| r + u),
+        used,
+        remaining,
+        unit: Some("USD".to_string()),
+    }]
+}
+
+fn parse_newapi_billing(subscription: &Value, usage: &Value) -> Vec<UsageData> {
+    let Some(total) = number(&subscription["hard_limit_usd"]) else {
+        return invalid(subscription["error"]["message"].as_str());
+    };
+    // total_usage is reported in cents
+    let used = number(&usage["total_usage"]).map(|cents| cents / 100.0);
+    vec![UsageData {
+        plan_name: None,
+        extra: None,
+        is_valid: Some(true),
+        invalid_message: None,
+        total: Some(total),
+        used,
+        remaining: used.map(|u| total - u),
+        unit: Some("USD".to_string()),
+    }]
+}
+
+fn parse_openrouter(body: &Value) -> Vec<UsageData> {
+    let data = &body["data"];
+    let (Some(total), Some(used)) = (number(&data["total_credits"]), number(&data["total_usage"]))
+    else {
+        return invalid(body["error"]["message"].as_str());
+    };
+    vec![UsageData {
+        plan_name: None,
+        extra: None,
+        is_valid: Some(true),
+        invalid_message: None,
+        total: Some(total),
+        used: Some(used),
+        remaining: Some(total - used),
+        unit: Some("USD".to_string()),
+    }]
+}
+
+fn parse_deepseek(body: &Value) -> Vec<UsageData> {
+    let Some(infos) = body["balance_infos"].as_array().filter(|a| !a.is_empty()) else {
+        return invalid(body["error"]["message"].as_str());
+    };
+    let available = body["is_available"].as_bool();
+    infos
+        .iter()
+        .map(|info| {
+            let granted = number(&info["granted_balance"]);
+            let topped_up = number(&info["topped_up_balance"]);
+            UsageData {
+                plan_name: info["currency"].as_str().map(normalize_unit),
+                extra: granted
+                    .zip(topped_up)
+                    .map(|(g, t)| format!("granted {g:.2} / topped up {t:.2}")),
+                is_valid: available,
+                invalid_message: None,
+                total: None,
+                used: None,
+                remaining: number(&info["total_balance"]),
+                unit: info["currency"].as_str().map(normalize_unit),
+            }
+        })
+        .collect()
+}
+
+fn parse_siliconflow(body: &Value) -> Vec<UsageData> {
+    let data = &body["data"];
+    let Some(remaining) = number(&data["totalBalance"]) else {
+        return invalid(body["message"].as_str());
+    };
+    vec![UsageData {
+        plan_name: data["name"].as_str().map(str::to_string),
+        extra: None,
+        is_valid: Some(data["status"].as_str().is_none_or(|s| s == "normal")),
+        invalid_message: None,
+        total: None,
+        used: None,
+        remaining: Some(remaining),
+        unit: Some("CNY".to_string()),
+    }]
+}
+
+fn parse_moonshot(body: &Value) -> Vec<UsageData> {
+    let data = &body["data"];
+    let Some(remaining) = number(&data["available_balance"]) else {
+        return invalid(body["error"]["message"].as_str());
+    };
+    vec![UsageData {
+        plan_name: None,
+        extra: number(&data["voucher_balance"])
+            .zip(number(&data["cash_balance"]))
+            .map(|(v, c)| format!("voucher {v:.2} / cash {c:.2}")),
+        is_valid: Some(remaining > 0.0),
+        invalid_message: None,
+        total: None,
+        used: None,
+        remaining: Some(remaining),
+        unit: Some("CNY".to_string()),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(raw: &str) -> Value {
+        serde_json::from_str(raw).expect("fixture should be valid JSON")
+    }
+
+    #[test]
+    fn test_template_ids_roundtrip() {
+        for template in UsageTemplate::ALL {
+            assert_eq!(UsageTemplate::from_id(template.id()), Some(template));
+        }
+        // JS presets keep going through the script runtime
+        assert_eq!(UsageTemplate::from_id("newapi"), None);
+        assert_eq!(UsageTemplate::from_id("custom"), None);
+    }
+
+    #[test]
+    fn test_resolve_origin_strips_path() {
+        assert_eq!(
+            resolve_origin("https://api.deepseek.com/anthropic", "").unwrap(),
+            "https://api.deepseek.com"
+        );
+        assert_eq!(
+            resolve_origin("", "https://openrouter.ai").unwrap(),
+            "https://openrouter.ai"
+        );
+        assert!(resolve_origin("", "").is_err());
+    }
+
+    #[test]
+    fn test_parse_newapi_self() {
+        let body = fixture(
+            r#"{"success":true,"message":"","data":{"id":1,"username":"demo","quota":25000000,"used_quota":5000000,"group":"default"}}"#,
+        );
+        let data = parse_newapi_self(&body);
+        assert_eq!(data[0].remaining, Some(50.0));
+        assert_eq!(data[0].used, Some(10.0));
+        assert_eq!(data[0].total, Some(60.0));
+        assert_eq!(data[0].plan_name.as_deref(), Some("default"));
+
+        let failed = parse_newapi_self(&fixture(
+            r#"{"success":false,"message":"无权进行此操作，access token 无效"}"#,
+        ));
+        assert_eq!(failed[0].is_valid, Some(false));
+        assert!(failed[0].invalid_message.is_some());
+    }
+
+    #[test]
+    fn test_parse_newapi_billing() {
+        let subscription = fixture(
+            r#"{"object":"billing_subscription","has_payment_method":true,"soft_limit_usd":100,"hard_limit_usd":100,"system_hard_limit_usd":100,"access_until":0}"#,
+        );
+        let usage = fixture(r#"{"object":"list","total_usage":2550}"#);
+        let data = parse_newapi_billing(&subscription, &usage);
+        assert_eq!(data[0].total, Some(100.0));
+        assert_eq!(data[0].used, Some(25.5));
+        assert_eq!(data[0].remaining, Some(74.5));
+    }
+
+    #[test]
+    fn test_parse_openrouter() {
+        let data = parse_openrouter(&fixture(
+            r#"{"data":{"total_credits":100.5,"total_usage":25.75}}"#,
+        ));
+        assert_eq!(data[0].remaining, Some(74.75));
+        assert_eq!(data[0].unit.as_deref(), Some("USD"));
+
+        let failed = parse_openrouter(&fixture(
+            r#"{"error":{"code":401,"message":"No auth credentials found"}}"#,
+        ));
+        assert_eq!(failed[0].is_valid, Some(false));
+    }
+
+    #[test]
+    fn test_parse_deepseek() {
+        let data = parse_deepseek(&fixture(
+            r#"{"is_available":true,"balance_infos":[{"currency":"CNY","total_balance":"110.00","granted_balance":"10.00","topped_up_balance":"100.00"},{"currency":"USD","total_balance":"5.00","granted_balance":"0.00","topped_up_balance":"5.00"}]}"#,
+        ));
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].remaining, Some(110.0));
+        assert_eq!(data[0].unit.as_deref(), Some("CNY"));
+        assert_eq!(data[1].unit.as_deref(), Some("USD"));
+        assert_eq!(data[0].is_valid, Some(true));
+    }
+
+    #[test]
+    fn test_parse_siliconflow() {
+        let data = parse_siliconflow(&fixture(
+            r#"{"code":20000,"message":"OK","status":true,"data":{"id":"u1","name":"demo","balance":"0.88","status":"normal","chargeBalance":"88.00","totalBalance":"88.88"}}"#,
+        ));
+        assert_eq!(data[0].remaining, Some(88.88));
+        assert_eq!(data[0].unit.as_deref(), Some("CNY"));
+        assert_eq!(data[0].is_valid, Some(true));
+    }
+
+    #[test]
+    fn test_parse_moonshot() {
+        let data = parse_moonshot(&fixture(
+            r#"{"code":0,"data":{"available_balance":49.58894,"voucher_balance":46.58893,"cash_balance":3.00001},"scode":"0x0","status":true}"#,
+        ));
+        assert_eq!(data[0].remaining, Some(49.58894));
+        assert_eq!(data[0].unit.as_deref(), Some("CNY"));
+    }
+
+    #[test]
+    fn test_normalize_unit() {
+        assert_eq!(normalize_unit("rmb"), "CNY");
+        assert_eq!(normalize_unit("$"), "USD");
+        assert_eq!(normalize_unit("tokens"), "TOKENS");
+    }
+}
@@ -8,16 +8,21 @@
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
 use tokio::time::timeout;
 
-use crate::app_config::{AppType, InstalledSkill, SkillApps, UnmanagedSkill};
+use crate::app_config::{
+    AppType, InstalledSkill, SkillApps, SkillFinding, SkillFindingSeverity, UnmanagedSkill,
+};
 use crate::config::get_app_config_dir;
 use crate::database::Database;
 use crate::error::format_skill_error;
+use crate::services::skill_scan::{self, SkillScanReport};
+use crate::store::AppState;
 
 // ========== 数据结构 ==========
 
@@ -86,8 +91,30 @@ pub struct Skill {
     pub repo_branch: Option<String>,
 }
 
+/// 仓库来源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillSourceKind {
+    /// GitHub 仓库（默认）
+    #[default]
+    Github,
+    /// GitLab 仓库（含自建实例，`url` 为实例地址，默认 gitlab.com）
+    Gitlab,
+    /// Gitea / Forgejo 仓库（`url` 为实例地址）
+    Gitea,
+    /// 自定义 git 归档 URL 模板（支持 `{owner}`、`{name}`、`{branch}` 占位符）
+    Template,
+    /// 固定的 zip / tar.gz 压缩包 URL
+    Archive,
+    /// 本地目录（`url` 为目录路径，变更会自动同步到已安装的 Skills）
+    Local,
+}
+
 /// 仓库配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `owner`/`name` 同时作为来源的唯一标识；非 GitHub 来源也需要填写，
+/// 对于 archive/local 来源可以是任意便于识别的名称。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SkillRepo {
     /// GitHub 用户/组织名
     pub owner: String,
@@ -97,6 +124,18 @@ pub struct SkillRepo {
     pub branch: String,
     /// 是否启用
     pub enabled: bool,
+    /// 来源类型
+    #[serde(default)]
+    pub kind: SkillSourceKind,
+    /// 实例地址 / URL 模板 / 压缩包 URL / 本地目录（含义取决于 `kind`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// 仓库内的子路径（monorepo 中只扫描该目录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subpath: Option<String>,
+    /// 访问令牌（支持 `${secret:name}` 引用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
 }
 
 /// 技能安装状态（旧版兼容）
@@ -128,24 +167,28 @@ impl Default for SkillStore {
                     name: "skills".to_string(),
                     branch: "main".to_string(),
                     enabled: true,
+                    ..Default::default()
                 },
                 SkillRepo {
                     owner: "ComposioHQ".to_string(),
                     name: "awesome-claude-skills".to_string(),
                     branch: "master".to_string(),
                     enabled: true,
+                    ..Default::default()
                 },
                 SkillRepo {
                     owner: "cexll".to_string(),
                     name: "myclaude".to_string(),
                     branch: "master".to_string(),
                     enabled: true,
+                    ..Default::default()
                 },
                 SkillRepo {
                     owner: "JimLiu".to_string(),
                     name: "baoyu-skills".to_string(),
                     branch: "main".to_string(),
                     enabled: true,
+                    ..Default::default()
                 },
             ],
         }
@@ -159,6 +202,89 @@ pub struct SkillMetadata {
     pub description: Option<String>,
 }
 
+/// Skill 更新检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillUpdateInfo {
+    pub id: String,
+    pub name: String,
+    /// 已安装版本的提交 SHA（旧版安装记录为空）
+    pub current_commit: Option<String>,
+    /// 上游分支当前的提交 SHA（检查失败时为空）
+    pub latest_commit: Option<String>,
+    pub has_update: bool,
+    /// SSOT 中的内容与安装时不一致（更新会覆盖本地修改）
+    pub locally_modified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 被替换版本的元数据（`skill-versions/<directory>.json`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SkillVersionInfo {
+    commit_sha: Option<String>,
+    content_hash: Option<String>,
+    replaced_at: i64,
+}
+
+/// 已获取到本地的来源内容（临时目录在 drop 时删除，本地目录来源不会被删除）
+struct FetchedSource {
+    root: PathBuf,
+    /// 固定到的提交 SHA
+    commit: Option<String>,
+    temporary: bool,
+}
+
+impl FetchedSource {
+    /// 扫描根目录（应用仓库子路径）
+    fn base(&self, repo: &SkillRepo) -> PathBuf {
+        match repo.subpath.as_deref() {
+            Some(subpath) => self.root.join(subpath.trim_matches('/')),
+            None => self.root.clone(),
+        }
+    }
+
+    /// 技能目录；`directory` 为扫描根目录本身时（根目录即 Skill）返回扫描根目录
+    fn skill_dir(&self, repo: &SkillRepo, directory: &str) -> PathBuf {
+        let base = self.base(repo);
+        if directory == repo.name && base.join("SKILL.md").exists() {
+            base
+        } else {
+            base.join(directory)
+        }
+    }
+}
+
+impl Drop for FetchedSource {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+}
+
+/// 仓库访问令牌在密钥库中的条目名称前缀
+const TOKEN_SECRET_PREFIX: &str = "skill-repo.";
+
+/// 本地目录来源的检查间隔
+const LOCAL_SOURCE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 本地目录来源的监听任务（仅在存在启用的本地来源时运行）
+static LOCAL_WATCHER: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::new(None);
+
+/// 本地来源目录上次检查时的元数据指纹（Skill id → 指纹），未变化时跳过内容哈希
+static LOCAL_FINGERPRINTS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// 一轮本地来源同步的结果
+#[derive(Debug, Default)]
+pub struct LocalSourceSync {
+    /// 已同步的 Skill id
+    pub changed: Vec<String>,
+    /// 新内容出现未确认风险、需用户手动更新确认的 Skill 名称
+    pub needs_review: Vec<String>,
+}
+
 // ========== SkillService ==========
 
 pub struct SkillService;
@@ -224,6 +350,53 @@ impl SkillService {
         })
     }
 
+    /// 获取项目内的应用 Skills 目录（如 `<project>/.claude/skills`）
+    pub fn get_project_skills_dir(project: &Path, app: &AppType) -> PathBuf {
+        let config_dir = match app {
+            AppType::Claude => ".claude",
+            AppType::Codex => ".codex",
+            AppType::Gemini => ".gemini",
+            AppType::OpenCode => ".opencode",
+        };
+        project.join(config_dir).join("skills")
+    }
+
+    /// 已知项目目录：~/.claude.json 记录的项目 + 已安装 Skills 的项目作用域
+    fn known_project_dirs(installed: &[&InstalledSkill]) -> Vec<String> {
+        let mut dirs = crate::claude_mcp::list_known_project_dirs().unwrap_or_default();
+        for skill in installed {
+            for dir in &skill.projects {
+                if !dirs.contains(dir) && Path::new(dir).is_dir() {
+                    dirs.push(dir.clone());
+                }
+            }
+        }
+        dirs
+    }
+
+    /// 所有可能存放 Skills 的目录：(应用, 项目目录（用户级为 None）, Skills 目录)
+    fn skill_locations(installed: &[&InstalledSkill]) -> Vec<(AppType, Option<String>, PathBuf)> {
+        let apps = [
+            AppType::Claude,
+            AppType::Codex,
+            AppType::Gemini,
+            AppType::OpenCode,
+        ];
+        let mut locations = Vec::new();
+        for app in &apps {
+            if let Ok(dir) = Self::get_app_skills_dir(app) {
+                locations.push((app.clone(), None, dir));
+            }
+        }
+        for project in Self::known_project_dirs(installed) {
+            for app in &apps {
+                let dir = Self::get_project_skills_dir(Path::new(&project), app);
+                locations.push((app.clone(), Some(project.clone()), dir));
+            }
+        }
+        locations
+    }
+
     // ========== 统一管理方法 ==========
 
     /// 获取所有已安装的 Skills
@@ -235,14 +408,16 @@ impl SkillService {
     /// 安装 Skill
     ///
     /// 流程：
-    /// 1. 下载到 SSOT 目录
-    /// 2. 保存到数据库
-    /// 3. 同步到启用的应用目录
+    /// 1. 下载并扫描（存在阻止项时拒绝；存在风险项时需 `accept_risks` 确认）
+    /// 2. 复制到 SSOT 目录
+    /// 3. 保存到数据库（含扫描发现项）
+    /// 4. 同步到启用的应用目录
     pub async fn install(
         &self,
         db: &Arc<Database>,
         skill: &DiscoverableSkill,
         current_app: &AppType,
+        accept_risks: bool,
     ) -> Result<InstalledSkill> {
         let ssot_dir = Self::get_ssot_dir()?;
 
@@ -264,7 +439,7 @@ impl SkillService {
                     let mut updated = existing.clone();
                     updated.apps.set_enabled_for(current_app, true);
                     db.save_skill(&updated)?;
-                    Self::sync_to_app_dir(&updated.directory, current_app)?;
+                    Self::sync_skill_to_app(&updated, current_app)?;
                     log::info!(
                         "Skill {} 已存在，更新 {:?} 启用状态",
                         updated.name,
@@ -299,18 +474,18 @@ impl SkillService {
         let dest = ssot_dir.join(&install_name);
 
         // 如果已存在则跳过下载
-        if !dest.exists() {
-            let repo = SkillRepo {
-                owner: skill.repo_owner.clone(),
-                name: skill.repo_name.clone(),
-                branch: skill.repo_branch.clone(),
-                enabled: true,
-            };
+        let (commit_sha, scan_findings) = if !dest.exists() {
+            let repo = Self::lookup_repo(
+                &db.get_skill_repos()?,
+                &skill.repo_owner,
+                &skill.repo_name,
+                &skill.repo_branch,
+            );
 
-            // 下载仓库
-            let temp_dir = timeout(
+            // 获取来源内容（git 托管来源固定到分支当前的提交）
+            let fetched = timeout(
                 std::time::Duration::from_secs(60),
-                self.download_repo(&repo),
+                self.fetch_source(&repo, true),
             )
             .await
             .map_err(|_| {
@@ -326,9 +501,8 @@ impl SkillService {
             })??;
 
             // 复制到 SSOT
-            let source = temp_dir.join(&skill.directory);
+            let source = fetched.skill_dir(&repo, &skill.directory);
             if !source.exists() {
-                let _ = fs::remove_dir_all(&temp_dir);
                 return Err(anyhow!(format_skill_error(
                     "SKILL_DIR_NOT_FOUND",
                     &[("path", &source.display().to_string())],
@@ -336,9 +510,15 @@ impl SkillService {
                 )));
             }
 
+            let report = skill_scan::scan_skill_dir(&source, &skill.directory);
+            Self::ensure_scan_accepted(&report, accept_risks)?;
+
             Self::copy_dir_recursive(&source, &dest)?;
-            let _ = fs::remove_dir_all(&temp_dir);
-        }
+            (fetched.commit.clone(), report.findings)
+        } else {
+            let report = skill_scan::scan_skill_dir(&dest, &install_name);
+            (None, report.findings)
+        };
 
         // 创建 InstalledSkill 记录
         let installed_skill = InstalledSkill {
@@ -356,6 +536,10 @@ impl SkillService {
             readme_url: skill.readme_url.clone(),
             apps: SkillApps::only(current_app),
             installed_at: chrono::Utc::now().timestamp(),
+            commit_sha,
+            content_hash: compute_content_hash(&dest).ok(),
+            scan_findings,
+            projects: Vec::new(),
         };
 
         // 保存到数据库
@@ -373,6 +557,82 @@ impl SkillService {
         Ok(installed_skill)
     }
 
+    /// 下载并扫描可安装的 Skill（不写入 SSOT），供安装前展示报告
+    pub async fn scan_available(
+        &self,
+        db: &Arc<Database>,
+        skill: &DiscoverableSkill,
+    ) -> Result<SkillScanReport> {
+        let repo = Self::lookup_repo(
+            &db.get_skill_repos()?,
+            &skill.repo_owner,
+            &skill.repo_name,
+            &skill.repo_branch,
+        );
+        let fetched = timeout(
+            std::time::Duration::from_secs(60),
+            self.fetch_source(&repo, false),
+        )
+        .await
+        .map_err(|_| {
+            anyhow!(format_skill_error(
+                "DOWNLOAD_TIMEOUT",
+                &[
+                    ("owner", &repo.owner),
+                    ("name", &repo.name),
+                    ("timeout", "60")
+                ],
+                Some("checkNetwork"),
+            ))
+        })??;
+
+        let source = fetched.skill_dir(&repo, &skill.directory);
+        if !source.exists() {
+            return Err(anyhow!(format_skill_error(
+                "SKILL_DIR_NOT_FOUND",
+                &[("path", &source.display().to_string())],
+                Some("checkRepoUrl"),
+            )));
+        }
+        Ok(skill_scan::scan_skill_dir(&source, &skill.directory))
+    }
+
+    /// 校验扫描结果：存在阻止项时拒绝，存在风险项且用户未确认时要求确认
+    fn ensure_scan_accepted(report: &SkillScanReport, accept_risks: bool) -> Result<()> {
+        let codes = |severity: SkillFindingSeverity| {
+            let mut codes: Vec<&str> = report
+                .findings
+                .iter()
+                .filter(|f| f.severity == severity)
+                .map(|f| f.code.as_str())
+                .collect();
+            codes.sort_unstable();
+            codes.dedup();
+            codes.join(",")
+        };
+        if report.blocked {
+            return Err(anyhow!(format_skill_error(
+                "SKILL_SCAN_BLOCKED",
+                &[
+                    ("directory", &report.directory),
+                    ("findings", &codes(SkillFindingSeverity::Critical)),
+                ],
+                Some("reviewScanReport"),
+            )));
+        }
+        if report.requires_confirmation && !accept_risks {
+            return Err(anyhow!(format_skill_error(
+                "SKILL_SCAN_CONFIRMATION_REQUIRED",
+                &[
+                    ("directory", &report.directory),
+                    ("findings", &codes(SkillFindingSeverity::Warning)),
+                ],
+                Some("reviewScanReport"),
+            )));
+        }
+        Ok(())
+    }
+
     /// 卸载 Skill
     ///
     /// 流程：
@@ -393,6 +653,7 @@ impl SkillService {
             AppType::OpenCode,
         ] {
             let _ = Self::remove_from_app(&skill.directory, &app);
+            let _ = Self::remove_skill_from_app(&skill, &app);
         }
 
         // 从 SSOT 删除
@@ -401,6 +662,7 @@ impl SkillService {
         if skill_path.exists() {
             fs::remove_dir_all(&skill_path)?;
         }
+        Self::remove_previous_version(&skill.directory);
 
         // 从数据库删除
         db.delete_skill(id)?;
@@ -425,9 +687,9 @@ impl SkillService {
 
         // 同步文件
         if enabled {
-            Self::sync_to_app_dir(&skill.directory, app)?;
+            Self::sync_skill_to_app(&skill, app)?;
         } else {
-            Self::remove_from_app(&skill.directory, app)?;
+            Self::remove_skill_from_app(&skill, app)?;
         }
 
         // 更新数据库
@@ -440,27 +702,18 @@ impl SkillService {
 
     /// 扫描未管理的 Skills
     ///
-    /// 扫描各应用目录，找出未被 CC Switch 管理的 Skills
+    /// 扫描各应用的用户级目录与已知项目内的 Skills 目录，找出未被 CC Switch 管理的 Skills
     pub fn scan_unmanaged(db: &Arc<Database>) -> Result<Vec<UnmanagedSkill>> {
         let managed_skills = db.get_all_installed_skills()?;
         let managed_dirs: HashSet<String> = managed_skills
             .values()
             .map(|s| s.directory.clone())
             .collect();
+        let installed: Vec<&InstalledSkill> = managed_skills.values().collect();
 
         let mut unmanaged: HashMap<String, UnmanagedSkill> = HashMap::new();
 
-        for app in [
-            AppType::Claude,
-            AppType::Codex,
-            AppType::Gemini,
-            AppType::OpenCode,
-        ] {
-            let app_dir = match Self::get_app_skills_dir(&app) {
-                Ok(d) => d,
-                Err(_) => continue,
-            };
-
+        for (app, project, app_dir) in Self::skill_locations(&installed) {
             if !app_dir.exists() {
                 continue;
             }
@@ -500,22 +753,22 @@ impl SkillService {
                 };
 
                 // 添加或更新
-                let app_str = match app {
-                    AppType::Claude => "claude",
-                    AppType::Codex => "codex",
-                    AppType::Gemini => "gemini",
-                    AppType::OpenCode => "opencode",
-                };
-
-                unmanaged
-                    .entry(dir_name.clone())
-                    .and_modify(|s| s.found_in.push(app_str.to_string()))
-                    .or_insert(UnmanagedSkill {
-                        directory: dir_name,
-                        name,
-                        description,
-                        found_in: vec![app_str.to_string()],
-                    });
+                let app_str = app.as_str().to_string();
+                let skill = unmanaged.entry(dir_name.clone()).or_insert(UnmanagedSkill {
+                    directory: dir_name,
+                    name,
+                    description,
+                    found_in: Vec::new(),
+                    projects: Vec::new(),
+                });
+                if !skill.found_in.contains(&app_str) {
+                    skill.found_in.push(app_str);
+                }
+                if let Some(project) = &project {
+                    if !skill.projects.contains(project) {
+                        skill.projects.push(project.clone());
+                    }
+                }
             }
         }
 
@@ -524,38 +777,35 @@ impl SkillService {
 
     /// 从应用目录导入 Skills
     ///
-    /// 将未管理的 Skills 导入到 CC Switch 统一管理
+    /// 将未管理的 Skills 导入到 CC Switch 统一管理。
+    /// 在项目目录中发现的 Skill 导入后保留这些项目作用域。
     pub fn import_from_apps(
         db: &Arc<Database>,
         directories: Vec<String>,
     ) -> Result<Vec<InstalledSkill>> {
         let ssot_dir = Self::get_ssot_dir()?;
         let mut imported = Vec::new();
+        let managed_skills = db.get_all_installed_skills()?;
+        let locations = Self::skill_locations(&managed_skills.values().collect::<Vec<_>>());
 
         for dir_name in directories {
-            // 找到源目录（从任一应用目录复制）
+            // 找到源目录（优先用户级目录，其次项目目录）
             let mut source_path: Option<PathBuf> = None;
-            let mut found_in: Vec<String> = Vec::new();
-
-            for app in [
-                AppType::Claude,
-                AppType::Codex,
-                AppType::Gemini,
-                AppType::OpenCode,
-            ] {
-                if let Ok(app_dir) = Self::get_app_skills_dir(&app) {
-                    let skill_path = app_dir.join(&dir_name);
-                    if skill_path.exists() {
-                        if source_path.is_none() {
-                            source_path = Some(skill_path);
-                        }
-                        let app_str = match app {
-                            AppType::Claude => "claude",
-                            AppType::Codex => "codex",
-                            AppType::Gemini => "gemini",
-                            AppType::OpenCode => "opencode",
-                        };
-                        found_in.push(app_str.to_string());
+            let mut apps = SkillApps::default();
+            let mut projects: Vec<String> = Vec::new();
+
+            for (app, project, app_dir) in &locations {
+                let skill_path = app_dir.join(&dir_name);
+                if !skill_path.exists() {
+                    continue;
+                }
+                if source_path.is_none() {
+                    source_path = Some(skill_path);
+                }
+                apps.set_enabled_for(app, true);
+                if let Some(project) = project {
+                    if !projects.contains(project) {
+                        projects.push(project.clone());
                     }
                 }
             }
@@ -585,18 +835,6 @@ impl SkillService {
                 (dir_name.clone(), None)
             };
 
-            // 构建启用状态
-            let mut apps = SkillApps::default();
-            for app_str in &found_in {
-                match app_str.as_str() {
-                    "claude" => apps.claude = true,
-                    "codex" => apps.codex = true,
-                    "gemini" => apps.gemini = true,
-                    "opencode" => apps.opencode = true,
-                    _ => {}
-                }
-            }
-
             // 创建记录
             let skill = InstalledSkill {
                 id: format!("local:{dir_name}"),
@@ -609,6 +847,10 @@ impl SkillService {
                 readme_url: None,
                 apps,
                 installed_at: chrono::Utc::now().timestamp(),
+                commit_sha: None,
+                content_hash: None,
+                scan_findings: Vec::new(),
+                projects,
             };
 
             // 保存到数据库
@@ -658,6 +900,11 @@ impl SkillService {
     /// - Symlink: 仅使用 symlink
     /// - Copy: 仅使用文件复制
     pub fn sync_to_app_dir(directory: &str, app: &AppType) -> Result<()> {
+        Self::sync_into_dir(directory, &Self::get_app_skills_dir(app)?, app)
+    }
+
+    /// 同步 Skill 到指定的 Skills 目录（用户级或项目内）
+    fn sync_into_dir(directory: &str, app_dir: &Path, app: &AppType) -> Result<()> {
         let ssot_dir = Self::get_ssot_dir()?;
         let source = ssot_dir.join(directory);
 
@@ -665,8 +912,7 @@ impl SkillService {
             return Err(anyhow!("Skill 不存在于 SSOT: {directory}"));
         }
 
-        let app_dir = Self::get_app_skills_dir(app)?;
-        fs::create_dir_all(&app_dir)?;
+        fs::create_dir_all(app_dir)?;
 
         let dest = app_dir.join(directory);
 
@@ -747,13 +993,108 @@ impl SkillService {
         Ok(())
     }
 
+    /// Skill 在应用中的目标 Skills 目录：设置了项目作用域时为各项目内的目录，否则为用户级目录
+    fn scope_dirs(skill: &InstalledSkill, user_dir: PathBuf, app: &AppType) -> Vec<PathBuf> {
+        if skill.projects.is_empty() {
+            return vec![user_dir];
+        }
+        skill
+            .projects
+            .iter()
+            .map(|project| Self::get_project_skills_dir(Path::new(project), app))
+            .collect()
+    }
+
+    /// 按作用域同步 Skill 到应用（用户级目录或各项目目录）
+    pub fn sync_skill_to_app(skill: &InstalledSkill, app: &AppType) -> Result<()> {
+        for dir in Self::scope_dirs(skill, Self::get_app_skills_dir(app)?, app) {
+            Self::sync_into_dir(&skill.directory, &dir, app)?;
+        }
+        Ok(())
+    }
+
+    /// 按作用域从应用中移除 Skill（用户级目录或各项目目录）
+    pub fn remove_skill_from_app(skill: &InstalledSkill, app: &AppType) -> Result<()> {
+        for dir in Self::scope_dirs(skill, Self::get_app_skills_dir(app)?, app) {
+            Self::remove_from_dir(&skill.directory, &dir)?;
+        }
+        Ok(())
+    }
+
+    /// 从指定的 Skills 目录删除 Skill（支持 symlink 和真实目录）
+    fn remove_from_dir(directory: &str, dir: &Path) -> Result<()> {
+        let path = dir.join(directory);
+        if path.exists() || Self::is_symlink(&path) {
+            Self::remove_path(&path)?;
+            log::debug!("Skill {directory} 已从 {} 删除", dir.display());
+        }
+        Ok(())
+    }
+
+    /// 设置 Skill 的项目作用域（空列表表示用户级目录），并同步已启用应用中的文件
+    pub fn set_projects(
+        db: &Arc<Database>,
+        id: &str,
+        projects: Vec<String>,
+    ) -> Result<InstalledSkill> {
+        let mut skill = db
+            .get_installed_skill(id)?
+            .ok_or_else(|| anyhow!("Skill not found: {id}"))?;
+
+        let mut normalized: Vec<String> = Vec::new();
+        for dir in projects {
+            let dir = dir.trim().to_string();
+            if dir.is_empty() || normalized.contains(&dir) {
+                continue;
+            }
+            let path = Path::new(&dir);
+            if !path.is_absolute() || !path.is_dir() {
+                return Err(anyhow!("项目目录无效（需为已存在的绝对路径）: {dir}"));
+            }
+            normalized.push(dir);
+        }
+        if normalized == skill.projects {
+            return Ok(skill);
+        }
+
+        let apps: Vec<AppType> = [
+            AppType::Claude,
+            AppType::Codex,
+            AppType::Gemini,
+            AppType::OpenCode,
+        ]
+        .into_iter()
+        .filter(|app| skill.apps.is_enabled_for(app))
+        .collect();
+        let previous = skill.clone();
+        skill.projects = normalized;
+        for app in &apps {
+            let user_dir = Self::get_app_skills_dir(app)?;
+            let current = Self::scope_dirs(&skill, user_dir.clone(), app);
+            for dir in Self::scope_dirs(&previous, user_dir, app) {
+                if !current.contains(&dir) {
+                    Self::remove_from_dir(&skill.directory, &dir)?;
+                }
+            }
+            Self::sync_skill_to_app(&skill, app)?;
+        }
+        db.save_skill(&skill)?;
+
+        log::info!(
+            "Skill {} 的项目作用域已更新: {:?}",
+            skill.name,
+            skill.projects
+        );
+        Ok(skill)
+    }
+
     /// 同步所有已启用的 Skills 到指定应用
     pub fn sync_to_app(db: &Arc<Database>, app: &AppType) -> Result<()> {
         let skills = db.get_all_installed_skills()?;
 
         for skill in skills.values() {
             if skill.apps.is_enabled_for(app) {
-                Self::sync_to_app_dir(&skill.directory, app)?;
+                Self::sync_skill_to_app(skill, app)?;
             }
         }
 
@@ -862,27 +1203,35 @@ impl SkillService {
 
     /// 从仓库获取技能列表
     async fn fetch_repo_skills(&self, repo: &SkillRepo) -> Result<Vec<DiscoverableSkill>> {
-        let temp_dir = timeout(std::time::Duration::from_secs(60), self.download_repo(repo))
-            .await
-            .map_err(|_| {
-                anyhow!(format_skill_error(
-                    "DOWNLOAD_TIMEOUT",
-                    &[
-                        ("owner", &repo.owner),
-                        ("name", &repo.name),
-                        ("timeout", "60")
-                    ],
-                    Some("checkNetwork"),
-                ))
-            })??;
+        let fetched = timeout(
+            std::time::Duration::from_secs(60),
+            self.fetch_source(repo, false),
+        )
+        .await
+        .map_err(|_| {
+            anyhow!(format_skill_error(
+                "DOWNLOAD_TIMEOUT",
+                &[
+                    ("owner", &repo.owner),
+                    ("name", &repo.name),
+                    ("timeout", "60")
+                ],
+                Some("checkNetwork"),
+            ))
+        })??;
 
         let mut skills = Vec::new();
-        let scan_dir = temp_dir.clone();
+        let scan_dir = fetched.base(repo);
+        if !scan_dir.is_dir() {
+            return Err(anyhow!(format_skill_error(
+                "SKILL_DIR_NOT_FOUND",
+                &[("path", repo.subpath.as_deref().unwrap_or_default())],
+                Some("checkRepoUrl"),
+            )));
+        }
 
         self.scan_dir_recursive(&scan_dir, &scan_dir, repo, &mut skills)?;
 
-        let _ = fs::remove_dir_all(&temp_dir);
-
         Ok(skills)
     }
 
@@ -940,10 +1289,7 @@ impl SkillService {
             name: meta.name.unwrap_or_else(|| directory.to_string()),
             description: meta.description.unwrap_or_default(),
             directory: directory.to_string(),
-            readme_url: Some(format!(
-                "https://github.com/{}/{}/tree/{}/{}",
-                repo.owner, repo.name, repo.branch, directory
-            )),
+            readme_url: Self::readme_url(repo, directory),
             repo_owner: repo.owner.clone(),
             repo_name: repo.name.clone(),
             repo_branch: repo.branch.clone(),
@@ -993,26 +1339,90 @@ impl SkillService {
         });
     }
 
+    /// 生成技能在来源中的浏览地址（仅 git 托管来源）
+    fn readme_url(repo: &SkillRepo, directory: &str) -> Option<String> {
+        let path = match repo.subpath.as_deref() {
+            Some(subpath) => format!("{subpath}/{directory}"),
+            None => directory.to_string(),
+        };
+        let base = Self::instance_base(repo);
+        match repo.kind {
+            SkillSourceKind::Github => Some(format!(
+                "https://github.com/{}/{}/tree/{}/{path}",
+                repo.owner, repo.name, repo.branch
+            )),
+            SkillSourceKind::Gitlab => Some(format!(
+                "{base}/{}/{}/-/tree/{}/{path}",
+                repo.owner, repo.name, repo.branch
+            )),
+            SkillSourceKind::Gitea => Some(format!(
+                "{base}/{}/{}/src/branch/{}/{path}",
+                repo.owner, repo.name, repo.branch
+            )),
+            _ => None,
+        }
+    }
+
+    // ========== 仓库来源 ==========
+
+    /// 获取来源内容：git 托管来源与压缩包下载到临时目录，本地目录直接使用
+    ///
+    /// `pin` 为 true 时，支持解析提交的来源会固定到分支当前的提交。
+    async fn fetch_source(&self, repo: &SkillRepo, pin: bool) -> Result<FetchedSource> {
+        if repo.kind == SkillSourceKind::Local {
+            let root = Self::local_root(repo);
+            if !root.is_dir() {
+                return Err(anyhow!(format_skill_error(
+                    "SKILL_DIR_NOT_FOUND",
+                    &[("path", &root.display().to_string())],
+                    Some("checkRepoUrl"),
+                )));
+            }
+            return Ok(FetchedSource {
+                root,
+                commit: None,
+                temporary: false,
+            });
+        }
+
+        let (root, commit) = if pin {
+            self.download_repo_pinned(repo).await?
+        } else {
+            (self.download_repo(repo).await?, None)
+        };
+        Ok(FetchedSource {
+            root,
+            commit,
+            temporary: true,
+        })
+    }
+
     /// 下载仓库
     async fn download_repo(&self, repo: &SkillRepo) -> Result<PathBuf> {
         let temp_dir = tempfile::tempdir()?;
         let temp_path = temp_dir.path().to_path_buf();
         let _ = temp_dir.keep();
 
-        let branches = if repo.branch.is_empty() {
-            vec!["main", "master"]
+        let urls: Vec<String> = if repo.kind == SkillSourceKind::Archive {
+            repo.url.iter().cloned().collect()
         } else {
-            vec![repo.branch.as_str(), "main", "master"]
+            let branches = if repo.branch.is_empty() {
+                vec!["main", "master"]
+            } else {
+                vec![repo.branch.as_str(), "main", "master"]
+            };
+            branches
+                .into_iter()
+                .filter_map(|branch| Self::archive_url(repo, branch, false))
+                .collect()
         };
 
-        let mut last_error = None;
-        for branch in branches {
-            let url = format!(
-                "https://github.com/{}/{}/archive/refs/heads/{}.zip",
-                repo.owner, repo.name, branch
-            );
+        let token = Self::resolve_token(repo)?;
+        let auth = token.as_deref().map(|t| Self::auth_header(repo, t));
 
-            match self.download_and_extract(&url, &temp_path).await {
+        let mut last_error = None;
+        for url in urls {
+            match self.download_archive(&url, &temp_path, auth.clone()).await {
                 Ok(_) => {
                     return Ok(temp_path);
                 }
@@ -1023,72 +1433,285 @@ impl SkillService {
             }
         }
 
+        let _ = fs::remove_dir_all(&temp_path);
         Err(last_error.unwrap_or_else(|| anyhow::anyhow!("所有分支下载失败")))
     }
 
-    /// 下载并解压 ZIP
-    async fn download_and_extract(&self, url: &str, dest: &Path) -> Result<()> {
-        let client = crate::proxy::http_client::get();
-        let response = client.get(url).send().await?;
-        if !response.status().is_success() {
-            let status = response.status().as_u16().to_string();
-            return Err(anyhow::anyhow!(format_skill_error(
-                "DOWNLOAD_FAILED",
-                &[("status", &status)],
-                match status.as_str() {
-                    "403" => Some("http403"),
-                    "404" => Some("http404"),
-                    "429" => Some("http429"),
-                    _ => Some("checkNetwork"),
-                },
-            )));
+    /// 构造指定分支/提交的归档下载地址（archive/local 来源返回 None）
+    fn archive_url(repo: &SkillRepo, git_ref: &str, is_commit: bool) -> Option<String> {
+        let base = Self::instance_base(repo);
+        match repo.kind {
+            // 带令牌时走 API（支持私有仓库）
+            SkillSourceKind::Github if repo.token.is_some() => Some(format!(
+                "https://api.github.com/repos/{}/{}/zipball/{git_ref}",
+                repo.owner, repo.name
+            )),
+            SkillSourceKind::Github if is_commit => Some(format!(
+                "https://github.com/{}/{}/archive/{git_ref}.zip",
+                repo.owner, repo.name
+            )),
+            SkillSourceKind::Github => Some(format!(
+                "https://github.com/{}/{}/archive/refs/heads/{git_ref}.zip",
+                repo.owner, repo.name
+            )),
+            SkillSourceKind::Gitlab => Some(format!(
+                "{base}/api/v4/projects/{}/repository/archive.zip?sha={}",
+                Self::gitlab_project_id(repo),
+                url_encode(git_ref)
+            )),
+            SkillSourceKind::Gitea => Some(format!(
+                "{base}/api/v1/repos/{}/{}/archive/{git_ref}.zip",
+                repo.owner, repo.name
+            )),
+            SkillSourceKind::Template => repo.url.as_ref().map(|template| {
+                template
+                    .replace("{owner}", &repo.owner)
+                    .replace("{name}", &repo.name)
+                    .replace("{branch}", git_ref)
+            }),
+            SkillSourceKind::Archive | SkillSourceKind::Local => None,
         }
+    }
 
-        let bytes = response.bytes().await?;
-        let cursor = std::io::Cursor::new(bytes);
-        let mut archive = zip::ZipArchive::new(cursor)?;
-
-        let root_name = if !archive.is_empty() {
-            let first_file = archive.by_index(0)?;
-            let name = first_file.name();
-            name.split('/').next().unwrap_or("").to_string()
-        } else {
-            return Err(anyhow::anyhow!(format_skill_error(
-                "EMPTY_ARCHIVE",
-                &[],
-                Some("checkRepoUrl"),
-            )));
+    /// GitLab/Gitea 实例地址（未填写时使用公共实例）
+    fn instance_base(repo: &SkillRepo) -> String {
+        let default = match repo.kind {
+            SkillSourceKind::Gitlab => "https://gitlab.com",
+            SkillSourceKind::Gitea => "https://gitea.com",
+            _ => "https://github.com",
         };
+        repo.url
+            .as_deref()
+            .map(|url| url.trim().trim_end_matches('/'))
+            .filter(|url| !url.is_empty())
+            .unwrap_or(default)
+            .to_string()
+    }
 
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i)?;
-            let file_path = file.name();
-
-            let relative_path =
-                if let Some(stripped) = file_path.strip_prefix(&format!("{root_name}/")) {
-                    stripped
-                } else {
-                    continue;
-                };
-
-            if relative_path.is_empty() {
-                continue;
-            }
+    /// GitLab 项目 ID（URL 编码的 `group/subgroup/project` 路径）
+    fn gitlab_project_id(repo: &SkillRepo) -> String {
+        url_encode(&format!("{}/{}", repo.owner, repo.name))
+    }
 
-            let outpath = dest.join(relative_path);
+    /// 本地目录来源的根目录（支持 `~` 开头）
+    fn local_root(repo: &SkillRepo) -> PathBuf {
+        let raw = repo.url.as_deref().unwrap_or_default().trim();
+        let home_relative = raw
+            .strip_prefix("~/")
+            .or_else(|| raw.strip_prefix("~\\"))
+            .or(if raw == "~" { Some("") } else { None });
+        match (home_relative, dirs::home_dir()) {
+            (Some(stripped), Some(home)) => home.join(stripped),
+            _ => PathBuf::from(raw),
+        }
+    }
 
-            if file.is_dir() {
-                fs::create_dir_all(&outpath)?;
-            } else {
-                if let Some(parent) = outpath.parent() {
-                    fs::create_dir_all(parent)?;
+    /// 仓库访问令牌在密钥库中的条目名称
+    ///
+    /// 可读部分会被替换和截断，不同仓库可能相同，因此追加实例地址、owner、name 的哈希。
+    fn token_secret_name(repo: &SkillRepo) -> String {
+        let readable: String = format!("{}.{}", repo.owner, repo.name)
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                    c
+                } else {
+                    '_'
                 }
-                let mut outfile = fs::File::create(&outpath)?;
-                std::io::copy(&mut file, &mut outfile)?;
-            }
-        }
+            })
+            .take(40)
+            .collect();
+        let identity = format!(
+            "{}\0{}\0{}",
+            repo.url.as_deref().unwrap_or_default(),
+            repo.owner,
+            repo.name
+        );
+        let digest = ring::digest::digest(&ring::digest::SHA256, identity.as_bytes());
+        let suffix: String = digest.as_ref()[..6]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        format!("{TOKEN_SECRET_PREFIX}{readable}.{suffix}")
+    }
 
-        Ok(())
+    /// 将明文访问令牌存入密钥库，仓库配置中只保存 `${secret:name}` 引用
+    ///
+    /// 已是引用的令牌保持不变。返回是否存入了新的令牌。
+    pub fn store_repo_token(
+        repo: &mut SkillRepo,
+        store: &crate::secret_store::SecretStore,
+    ) -> Result<bool> {
+        let Some(token) = repo.token.as_deref() else {
+            return Ok(false);
+        };
+        if crate::secret_store::has_refs(token) {
+            return Ok(false);
+        }
+        let name = Self::token_secret_name(repo);
+        store.set(&name, token)?;
+        repo.token = Some(format!("${{secret:{name}}}"));
+        Ok(true)
+    }
+
+    /// 删除仓库时清理由 [`Self::store_repo_token`] 存入的令牌
+    ///
+    /// 按令牌中记录的引用删除（仓库地址修改后条目名称不再能重新推导）。
+    pub fn delete_repo_token(
+        repo: &SkillRepo,
+        store: &crate::secret_store::SecretStore,
+    ) -> Result<()> {
+        let name = repo
+            .token
+            .as_deref()
+            .and_then(|t| t.strip_prefix("${secret:"))
+            .and_then(|t| t.strip_suffix('}'))
+            .filter(|name| name.starts_with(TOKEN_SECRET_PREFIX));
+        if let Some(name) = name {
+            store.delete(name)?;
+        }
+        Ok(())
+    }
+
+    /// 将数据库中遗留的明文仓库令牌迁移到密钥库，返回迁移的数量
+    pub fn migrate_repo_tokens(db: &Arc<Database>) -> Result<usize> {
+        let store = crate::secret_store::SecretStore::open_default();
+        let mut migrated = 0;
+        for mut repo in db.get_skill_repos()? {
+            if Self::store_repo_token(&mut repo, &store)? {
+                db.save_skill_repo(&repo)?;
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+
+    /// 解析访问令牌中的 `${secret:name}` 引用
+    fn resolve_token(repo: &SkillRepo) -> Result<Option<String>> {
+        let Some(token) = repo
+            .token
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        else {
+            return Ok(None);
+        };
+        let resolved =
+            crate::secret_store::resolve_value(&serde_json::Value::String(token.to_string()))?;
+        Ok(resolved.as_str().map(str::to_string))
+    }
+
+    /// 各来源的认证请求头
+    fn auth_header(repo: &SkillRepo, token: &str) -> (&'static str, String) {
+        match repo.kind {
+            SkillSourceKind::Gitlab => ("PRIVATE-TOKEN", token.to_string()),
+            SkillSourceKind::Gitea => ("Authorization", format!("token {token}")),
+            _ => ("Authorization", format!("Bearer {token}")),
+        }
+    }
+
+    /// 校验并规范化仓库来源配置（保存前调用）
+    pub fn normalize_repo(mut repo: SkillRepo) -> Result<SkillRepo> {
+        let non_empty = |value: Option<String>| {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        repo.owner = repo.owner.trim().to_string();
+        repo.name = repo.name.trim().to_string();
+        repo.branch = repo.branch.trim().to_string();
+        repo.url = non_empty(repo.url);
+        repo.token = non_empty(repo.token);
+        repo.subpath = non_empty(repo.subpath.map(|p| p.trim().trim_matches('/').to_string()));
+
+        if repo.owner.is_empty() || repo.name.is_empty() {
+            return Err(anyhow!("仓库所有者和名称不能为空"));
+        }
+        if let Some(subpath) = &repo.subpath {
+            if !Path::new(subpath)
+                .components()
+                .all(|c| matches!(c, std::path::Component::Normal(_)))
+            {
+                return Err(anyhow!("子路径必须是仓库内的相对路径: {subpath}"));
+            }
+        }
+
+        let is_http = |url: &str| url.starts_with("https://") || url.starts_with("http://");
+        match repo.kind {
+            SkillSourceKind::Github => {}
+            SkillSourceKind::Gitlab
+            | SkillSourceKind::Gitea
+            | SkillSourceKind::Template
+            | SkillSourceKind::Archive => match repo.url.as_deref() {
+                Some(url) if is_http(url) => {}
+                Some(url) => return Err(anyhow!("URL 必须以 http:// 或 https:// 开头: {url}")),
+                None if repo.kind == SkillSourceKind::Gitlab => {}
+                None => return Err(anyhow!("该来源类型需要填写 URL")),
+            },
+            SkillSourceKind::Local => {
+                let root = Self::local_root(&repo);
+                if repo.url.is_none() || !root.is_dir() {
+                    return Err(anyhow!("本地目录不存在: {}", root.display()));
+                }
+            }
+        }
+        Ok(repo)
+    }
+
+    /// 按 owner/name 查找已配置的来源；找不到时视为 GitHub 仓库
+    ///
+    /// 安装记录只保存 owner/name/branch，来源类型与令牌以仓库配置为准。
+    fn lookup_repo(repos: &[SkillRepo], owner: &str, name: &str, branch: &str) -> SkillRepo {
+        let mut repo = repos
+            .iter()
+            .find(|r| r.owner.eq_ignore_ascii_case(owner) && r.name.eq_ignore_ascii_case(name))
+            .cloned()
+            .unwrap_or_else(|| SkillRepo {
+                owner: owner.to_string(),
+                name: name.to_string(),
+                enabled: true,
+                ..Default::default()
+            });
+        repo.branch = branch.to_string();
+        repo
+    }
+
+    /// 下载并解压归档（MCP 目录的 GitHub 来源也复用此逻辑）
+    pub(crate) async fn download_and_extract(&self, url: &str, dest: &Path) -> Result<()> {
+        self.download_archive(url, dest, None).await
+    }
+
+    /// 下载并解压 zip / tar.gz / tar 归档，可附带认证请求头
+    async fn download_archive(
+        &self,
+        url: &str,
+        dest: &Path,
+        auth: Option<(&'static str, String)>,
+    ) -> Result<()> {
+        let client = crate::proxy::http_client::get();
+        let mut request = client.get(url);
+        if let Some((name, value)) = auth {
+            request = request
+                .header(name, value)
+                .header("User-Agent", "cc-switch");
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status().as_u16().to_string();
+            return Err(anyhow::anyhow!(format_skill_error(
+                "DOWNLOAD_FAILED",
+                &[("status", &status)],
+                match status.as_str() {
+                    "403" => Some("http403"),
+                    "404" => Some("http404"),
+                    "429" => Some("http429"),
+                    _ => Some("checkNetwork"),
+                },
+            )));
+        }
+
+        let bytes = response.bytes().await?;
+        extract_archive(&bytes, dest)
     }
 
     /// 递归复制目录
@@ -1110,6 +1733,583 @@ impl SkillService {
         Ok(())
     }
 
+    // ========== 版本管理 ==========
+
+    /// 获取旧版本保留目录（~/.cc-switch/skill-versions/）
+    fn get_versions_dir() -> Result<PathBuf> {
+        let dir = get_app_config_dir().join("skill-versions");
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// 从安装记录还原仓库信息（本地 Skill 返回 None）
+    fn repo_of(repos: &[SkillRepo], skill: &InstalledSkill) -> Option<SkillRepo> {
+        Some(Self::lookup_repo(
+            repos,
+            skill.repo_owner.as_deref()?,
+            skill.repo_name.as_deref()?,
+            skill.repo_branch.as_deref().unwrap_or_default(),
+        ))
+    }
+
+    /// Skill 在来源扫描目录中的相对路径（id 格式为 "owner/repo:directory"）
+    fn repo_path(skill: &InstalledSkill) -> String {
+        skill
+            .id
+            .split_once(':')
+            .map(|(_, path)| path.to_string())
+            .unwrap_or_else(|| skill.directory.clone())
+    }
+
+    /// 检查已安装 Skills 的上游更新
+    ///
+    /// 同一仓库分支只解析一次 HEAD；本地 Skill（无仓库信息）不参与检查。
+    pub async fn check_updates(&self, db: &Arc<Database>) -> Result<Vec<SkillUpdateInfo>> {
+        let configured = db.get_skill_repos()?;
+        let repo_skills: Vec<(InstalledSkill, SkillRepo)> = db
+            .get_all_installed_skills()?
+            .into_values()
+            .filter_map(|skill| {
+                let repo = Self::repo_of(&configured, &skill)?;
+                Some((skill, repo))
+            })
+            .collect();
+
+        let repo_key = |repo: &SkillRepo| {
+            (
+                repo.owner.to_lowercase(),
+                repo.name.to_lowercase(),
+                repo.branch.clone(),
+            )
+        };
+        let mut repos: Vec<&SkillRepo> = Vec::new();
+        for (_, repo) in &repo_skills {
+            if !repos.iter().any(|r| repo_key(r) == repo_key(repo)) {
+                repos.push(repo);
+            }
+        }
+        let results =
+            futures::future::join_all(repos.iter().map(|repo| self.resolve_branch_head(repo)))
+                .await;
+        let heads: HashMap<_, std::result::Result<String, String>> = repos
+            .iter()
+            .zip(results)
+            .map(|(repo, result)| (repo_key(repo), result.map_err(|e| e.to_string())))
+            .collect();
+
+        let ssot_dir = Self::get_ssot_dir()?;
+        let mut updates = Vec::new();
+        for (skill, repo) in &repo_skills {
+            let (latest_commit, error) = match heads.get(&repo_key(repo)) {
+                Some(Ok(sha)) => (Some(sha.clone()), None),
+                Some(Err(e)) => (None, Some(e.clone())),
+                None => (None, None),
+            };
+            let locally_modified = Self::is_locally_modified(skill, &ssot_dir);
+            updates.push(SkillUpdateInfo {
+                id: skill.id.clone(),
+                name: skill.name.clone(),
+                has_update: latest_commit
+                    .as_ref()
+                    .is_some_and(|latest| skill.commit_sha.as_ref() != Some(latest)),
+                current_commit: skill.commit_sha.clone(),
+                latest_commit,
+                locally_modified,
+                error,
+            });
+        }
+        Ok(updates)
+    }
+
+    /// SSOT 中的内容是否已偏离安装时记录的哈希（没有记录时视为未修改）
+    fn is_locally_modified(skill: &InstalledSkill, ssot_dir: &Path) -> bool {
+        match &skill.content_hash {
+            Some(recorded) => compute_content_hash(&ssot_dir.join(&skill.directory))
+                .map(|current| &current != recorded)
+                .unwrap_or(false),
+            None => false,
+        }
+    }
+
+    /// 本地修改过的 Skill 需要显式强制更新，避免覆盖用户的改动
+    fn ensure_update_allowed(skill: &InstalledSkill, ssot_dir: &Path, force: bool) -> Result<()> {
+        if !force && Self::is_locally_modified(skill, ssot_dir) {
+            return Err(anyhow!(format_skill_error(
+                "SKILL_LOCALLY_MODIFIED",
+                &[("name", &skill.name)],
+                Some("forceUpdate"),
+            )));
+        }
+        Ok(())
+    }
+
+    /// 将 Skill 原地更新到上游分支的最新提交
+    ///
+    /// 新版本先完整写入暂存目录，再通过重命名替换 SSOT 目录；
+    /// 被替换的版本保留在 `skill-versions/<directory>`，可通过 `rollback` 恢复。
+    /// 本地修改过的 Skill 只有在 `force` 为 true 时才会被覆盖。
+    pub async fn update(
+        &self,
+        db: &Arc<Database>,
+        id: &str,
+        accept_risks: bool,
+        force: bool,
+    ) -> Result<InstalledSkill> {
+        let mut skill = db
+            .get_installed_skill(id)?
+            .ok_or_else(|| anyhow!("Skill not found: {id}"))?;
+        Self::ensure_update_allowed(&skill, &Self::get_ssot_dir()?, force)?;
+        let repo = Self::repo_of(&db.get_skill_repos()?, &skill)
+            .ok_or_else(|| anyhow!("Skill {} 不是从仓库安装的，无法更新", skill.name))?;
+
+        let fetched = timeout(
+            std::time::Duration::from_secs(60),
+            self.fetch_source(&repo, true),
+        )
+        .await
+        .map_err(|_| {
+            anyhow!(format_skill_error(
+                "DOWNLOAD_TIMEOUT",
+                &[
+                    ("owner", &repo.owner),
+                    ("name", &repo.name),
+                    ("timeout", "60")
+                ],
+                Some("checkNetwork"),
+            ))
+        })??;
+
+        let source = fetched.skill_dir(&repo, &Self::repo_path(&skill));
+        if !source.exists() {
+            return Err(anyhow!(format_skill_error(
+                "SKILL_DIR_NOT_FOUND",
+                &[("path", &source.display().to_string())],
+                Some("checkRepoUrl"),
+            )));
+        }
+
+        // 新版本同样需要扫描；只有相对已确认发现项新增的风险才需要再次确认
+        let report = skill_scan::scan_skill_dir(&source, &skill.directory);
+        let new_risks = skill_scan::new_risks(&skill.scan_findings, &report);
+        Self::ensure_scan_accepted(&report, accept_risks || new_risks.is_empty())?;
+
+        Self::replace_installed(
+            db,
+            &mut skill,
+            &source,
+            fetched.commit.clone(),
+            report.findings,
+        )?;
+
+        log::info!(
+            "Skill {} 已更新到 {}",
+            skill.name,
+            skill.commit_sha.as_deref().unwrap_or("最新版本")
+        );
+        Ok(skill)
+    }
+
+    /// 用来源目录的内容替换已安装的 Skill 并更新记录
+    ///
+    /// 新版本先完整写入暂存目录，再通过重命名替换 SSOT 目录。
+    fn replace_installed(
+        db: &Arc<Database>,
+        skill: &mut InstalledSkill,
+        source: &Path,
+        commit: Option<String>,
+        scan_findings: Vec<SkillFinding>,
+    ) -> Result<()> {
+        let staging = Self::get_versions_dir()?.join(format!(".staging-{}", skill.directory));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        Self::copy_dir_recursive(source, &staging)?;
+
+        let replaced = SkillVersionInfo {
+            commit_sha: skill.commit_sha.clone(),
+            content_hash: skill.content_hash.clone(),
+            replaced_at: chrono::Utc::now().timestamp(),
+        };
+        let ssot_dir = Self::get_ssot_dir()?;
+        Self::swap_in_version(
+            &ssot_dir,
+            &Self::get_versions_dir()?,
+            &staging,
+            &skill.directory,
+            &replaced,
+        )?;
+
+        skill.commit_sha = commit;
+        skill.content_hash = compute_content_hash(&ssot_dir.join(&skill.directory)).ok();
+        skill.scan_findings = scan_findings;
+        db.save_skill(skill)?;
+        Self::resync_enabled_apps(skill)
+    }
+
+    /// 回滚到更新前保留的版本（再次回滚即恢复到回滚前的版本）
+    pub fn rollback(db: &Arc<Database>, id: &str) -> Result<InstalledSkill> {
+        let mut skill = db
+            .get_installed_skill(id)?
+            .ok_or_else(|| anyhow!("Skill not found: {id}"))?;
+
+        Self::restore_previous_version(
+            &Self::get_ssot_dir()?,
+            &Self::get_versions_dir()?,
+            &mut skill,
+        )?;
+        db.save_skill(&skill)?;
+        Self::resync_enabled_apps(&skill)?;
+
+        log::info!("Skill {} 已回滚", skill.name);
+        Ok(skill)
+    }
+
+    /// 将保留的旧版本换回 SSOT，并把记录中的提交与哈希恢复为旧版本的值
+    fn restore_previous_version(
+        ssot_dir: &Path,
+        versions_dir: &Path,
+        skill: &mut InstalledSkill,
+    ) -> Result<()> {
+        let backup = versions_dir.join(&skill.directory);
+        if !backup.exists() {
+            return Err(anyhow!("Skill {} 没有可回滚的旧版本", skill.name));
+        }
+        let meta_path = versions_dir.join(format!("{}.json", skill.directory));
+        let previous: SkillVersionInfo = fs::read_to_string(&meta_path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+
+        let staging = versions_dir.join(format!(".staging-{}", skill.directory));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::rename(&backup, &staging)?;
+
+        let replaced = SkillVersionInfo {
+            commit_sha: skill.commit_sha.clone(),
+            content_hash: skill.content_hash.clone(),
+            replaced_at: chrono::Utc::now().timestamp(),
+        };
+        Self::swap_in_version(
+            ssot_dir,
+            versions_dir,
+            &staging,
+            &skill.directory,
+            &replaced,
+        )?;
+
+        skill.commit_sha = previous.commit_sha;
+        skill.content_hash = previous.content_hash;
+        Ok(())
+    }
+
+    /// 用暂存目录替换 SSOT 中的 Skill，被替换的版本移入 `versions_dir`
+    ///
+    /// 两步均为同一文件系统内的重命名；第二步失败时恢复原目录。
+    fn swap_in_version(
+        ssot_dir: &Path,
+        versions_dir: &Path,
+        staging: &Path,
+        directory: &str,
+        replaced: &SkillVersionInfo,
+    ) -> Result<()> {
+        let dest = ssot_dir.join(directory);
+        let backup = versions_dir.join(directory);
+
+        if backup.exists() {
+            fs::remove_dir_all(&backup)?;
+        }
+        let had_current = dest.exists();
+        if had_current {
+            fs::rename(&dest, &backup)?;
+        }
+        if let Err(e) = fs::rename(staging, &dest) {
+            if had_current {
+                let _ = fs::rename(&backup, &dest);
+            }
+            return Err(e.into());
+        }
+
+        if had_current {
+            fs::write(
+                versions_dir.join(format!("{directory}.json")),
+                serde_json::to_string_pretty(replaced)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 删除保留的旧版本（卸载时调用）
+    fn remove_previous_version(directory: &str) {
+        let versions_dir = get_app_config_dir().join("skill-versions");
+        let _ = fs::remove_dir_all(versions_dir.join(directory));
+        let _ = fs::remove_file(versions_dir.join(format!("{directory}.json")));
+    }
+
+    /// 重新同步到所有已启用的应用（复制模式下应用目录需要刷新）
+    fn resync_enabled_apps(skill: &InstalledSkill) -> Result<()> {
+        for app in [
+            AppType::Claude,
+            AppType::Codex,
+            AppType::Gemini,
+            AppType::OpenCode,
+        ] {
+            if skill.apps.is_enabled_for(&app) {
+                Self::sync_skill_to_app(skill, &app)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 下载仓库并固定到提交：先解析分支 HEAD，再按提交 SHA 下载
+    ///
+    /// 无法解析提交（如 GitHub API 限流）时回退到按分支下载，提交信息为空；
+    /// URL 模板与压缩包来源不支持解析提交，直接按分支下载。
+    async fn download_repo_pinned(&self, repo: &SkillRepo) -> Result<(PathBuf, Option<String>)> {
+        if !matches!(
+            repo.kind,
+            SkillSourceKind::Github | SkillSourceKind::Gitlab | SkillSourceKind::Gitea
+        ) {
+            return Ok((self.download_repo(repo).await?, None));
+        }
+
+        match self.resolve_branch_head(repo).await {
+            Ok(sha) => {
+                let temp_dir = tempfile::tempdir()?;
+                let temp_path = temp_dir.path().to_path_buf();
+                let _ = temp_dir.keep();
+
+                let url = Self::archive_url(repo, &sha, true).unwrap_or_default();
+                let token = Self::resolve_token(repo)?;
+                let auth = token.as_deref().map(|t| Self::auth_header(repo, t));
+                match self.download_archive(&url, &temp_path, auth).await {
+                    Ok(()) => return Ok((temp_path, Some(sha))),
+                    Err(e) => {
+                        let _ = fs::remove_dir_all(&temp_path);
+                        log::warn!("按提交 {sha} 下载 {}/{} 失败: {e}", repo.owner, repo.name);
+                    }
+                }
+            }
+            Err(e) => log::warn!(
+                "解析 {}/{} 的分支提交失败，回退到按分支下载: {e}",
+                repo.owner,
+                repo.name
+            ),
+        }
+        Ok((self.download_repo(repo).await?, None))
+    }
+
+    /// 解析分支当前指向的提交 SHA（分支为空时使用默认分支）
+    async fn resolve_branch_head(&self, repo: &SkillRepo) -> Result<String> {
+        let branch = if repo.branch.is_empty() {
+            "HEAD"
+        } else {
+            repo.branch.as_str()
+        };
+        let base = Self::instance_base(repo);
+        let url = match repo.kind {
+            SkillSourceKind::Github => format!(
+                "https://api.github.com/repos/{}/{}/commits/{branch}",
+                repo.owner, repo.name
+            ),
+            SkillSourceKind::Gitlab => format!(
+                "{base}/api/v4/projects/{}/repository/commits/{}",
+                Self::gitlab_project_id(repo),
+                url_encode(branch)
+            ),
+            SkillSourceKind::Gitea if repo.branch.is_empty() => format!(
+                "{base}/api/v1/repos/{}/{}/commits?limit=1",
+                repo.owner, repo.name
+            ),
+            SkillSourceKind::Gitea => format!(
+                "{base}/api/v1/repos/{}/{}/commits?limit=1&sha={}",
+                repo.owner,
+                repo.name,
+                url_encode(branch)
+            ),
+            _ => return Err(anyhow!("该来源类型不支持检查更新")),
+        };
+
+        let mut request = crate::proxy::http_client::get()
+            .get(&url)
+            .header("User-Agent", "cc-switch");
+        if repo.kind == SkillSourceKind::Github {
+            request = request.header("Accept", "application/vnd.github.sha");
+        }
+        if let Some(token) = Self::resolve_token(repo)? {
+            let (name, value) = Self::auth_header(repo, &token);
+            request = request.header(name, value);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("仓库 API 返回错误状态: {}", response.status()));
+        }
+
+        let sha = match repo.kind {
+            SkillSourceKind::Github => response.text().await?.trim().to_string(),
+            // GitLab 返回单个提交对象，Gitea 返回提交数组
+            _ => {
+                let body: serde_json::Value = response.json().await?;
+                let commit = body.get(0).unwrap_or(&body);
+                commit
+                    .get("id")
+                    .or_else(|| commit.get("sha"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            }
+        };
+        if sha.len() != 40 || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow!("无效的提交 SHA: {sha}"));
+        }
+        Ok(sha)
+    }
+
+    // ========== 本地目录来源 ==========
+
+    /// 按当前仓库配置启动本地目录来源的变更监听（定期比对内容哈希）
+    ///
+    /// 已安装 Skill 的源目录发生变化时，原地更新 SSOT（旧版本保留用于回滚）、
+    /// 重新同步到已启用的应用，并通知前端刷新。重复调用会替换已有的监听任务；
+    /// 没有启用的本地来源时只停止监听。
+    pub fn start_local_watcher(app_handle: tauri::AppHandle) {
+        Self::stop_local_watcher();
+        let has_local_source = app_handle
+            .state::<AppState>()
+            .db
+            .get_skill_repos()
+            .map(|repos| {
+                repos
+                    .iter()
+                    .any(|r| r.enabled && r.kind == SkillSourceKind::Local)
+            })
+            .unwrap_or(false);
+        if !has_local_source {
+            return;
+        }
+
+        let handle = tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(LOCAL_SOURCE_POLL_INTERVAL).await;
+
+                let db = app_handle.state::<AppState>().db.clone();
+                match tauri::async_runtime::spawn_blocking(move || Self::sync_local_sources(&db))
+                    .await
+                {
+                    Ok(Ok(sync)) => {
+                        if !sync.changed.is_empty() {
+                            log::info!("本地来源变更，已同步 {} 个 Skill", sync.changed.len());
+                            if let Err(e) = app_handle.emit("skills-changed", &sync.changed) {
+                                log::error!("发送 skills-changed 事件失败: {e}");
+                            }
+                        }
+                        if !sync.needs_review.is_empty() {
+                            if let Err(e) =
+                                app_handle.emit("skills-update-needs-review", &sync.needs_review)
+                            {
+                                log::error!("发送 skills-update-needs-review 事件失败: {e}");
+                            }
+                        }
+                    }
+                    Ok(Err(e)) => log::warn!("同步本地 Skill 来源失败: {e}"),
+                    Err(e) => log::warn!("同步本地 Skill 来源任务异常: {e}"),
+                }
+            }
+        });
+        if let Ok(mut watcher) = LOCAL_WATCHER.lock() {
+            *watcher = Some(handle);
+        }
+    }
+
+    /// 停止本地目录来源的变更监听
+    pub fn stop_local_watcher() {
+        if let Some(handle) = LOCAL_WATCHER.lock().ok().and_then(|mut w| w.take()) {
+            handle.abort();
+        }
+    }
+
+    /// 将本地目录来源的变更同步到已安装的 Skills
+    ///
+    /// 先比对目录元数据指纹，变化后才计算内容哈希。新内容出现相对已确认发现项新增的风险
+    /// （或阻止项）时不自动应用，留给用户通过手动更新确认；同一内容只提示一次。
+    /// 源目录被删除时保留已安装的副本。
+    pub fn sync_local_sources(db: &Arc<Database>) -> Result<LocalSourceSync> {
+        let local_repos: Vec<SkillRepo> = db
+            .get_skill_repos()?
+            .into_iter()
+            .filter(|r| r.enabled && r.kind == SkillSourceKind::Local)
+            .collect();
+        let mut sync = LocalSourceSync::default();
+        if local_repos.is_empty() {
+            return Ok(sync);
+        }
+
+        for mut skill in db.get_all_installed_skills()?.into_values() {
+            let Some(repo) = local_repos.iter().find(|r| {
+                skill
+                    .repo_owner
+                    .as_deref()
+                    .is_some_and(|o| o.eq_ignore_ascii_case(&r.owner))
+                    && skill
+                        .repo_name
+                        .as_deref()
+                        .is_some_and(|n| n.eq_ignore_ascii_case(&r.name))
+            }) else {
+                continue;
+            };
+
+            let source = FetchedSource {
+                root: Self::local_root(repo),
+                commit: None,
+                temporary: false,
+            }
+            .skill_dir(repo, &Self::repo_path(&skill));
+            if !source.is_dir() {
+                continue;
+            }
+            let Ok(fingerprint) = metadata_fingerprint(&source) else {
+                continue;
+            };
+            let remember = |id: &str| {
+                if let Ok(mut seen) = LOCAL_FINGERPRINTS.lock() {
+                    seen.insert(id.to_string(), fingerprint);
+                }
+            };
+            let unchanged = LOCAL_FINGERPRINTS
+                .lock()
+                .map(|seen| seen.get(&skill.id) == Some(&fingerprint))
+                .unwrap_or(false);
+            if unchanged {
+                continue;
+            }
+
+            let Ok(hash) = compute_content_hash(&source) else {
+                continue;
+            };
+            if skill.content_hash.as_deref() == Some(hash.as_str()) {
+                remember(&skill.id);
+                continue;
+            }
+
+            let report = skill_scan::scan_skill_dir(&source, &skill.directory);
+            if report.blocked || !skill_scan::new_risks(&skill.scan_findings, &report).is_empty() {
+                log::warn!(
+                    "本地 Skill {} 的新内容包含未确认的风险，需手动更新确认",
+                    skill.name
+                );
+                remember(&skill.id);
+                sync.needs_review.push(skill.name.clone());
+                continue;
+            }
+
+            Self::replace_installed(db, &mut skill, &source, None, report.findings)?;
+            remember(&skill.id);
+            sync.changed.push(skill.id.clone());
+        }
+        Ok(sync)
+    }
+
     // ========== 从 ZIP 文件安装 ==========
 
     /// 从本地 ZIP 文件安装 Skills
@@ -1117,12 +2317,14 @@ impl SkillService {
     /// 流程：
     /// 1. 解压 ZIP 到临时目录
     /// 2. 扫描目录查找包含 SKILL.md 的技能
-    /// 3. 复制到 SSOT 并保存到数据库
-    /// 4. 同步到当前应用目录
+    /// 3. 安全检查（存在阻止项时拒绝；存在风险项时需 `accept_risks` 确认）
+    /// 4. 复制到 SSOT 并保存到数据库
+    /// 5. 同步到当前应用目录
     pub fn install_from_zip(
         db: &Arc<Database>,
         zip_path: &Path,
         current_app: &AppType,
+        accept_risks: bool,
     ) -> Result<Vec<InstalledSkill>> {
         // 解压到临时目录
         let temp_dir = Self::extract_local_zip(zip_path)?;
@@ -1139,16 +2341,24 @@ impl SkillService {
             )));
         }
 
+        // 先扫描全部 Skill，任一未通过则不安装任何 Skill
+        let mut reports = Vec::new();
+        for skill_dir in &skill_dirs {
+            let report = skill_scan::scan_skill_dir(skill_dir, &Self::zip_install_name(skill_dir));
+            if let Err(e) = Self::ensure_scan_accepted(&report, accept_risks) {
+                let _ = fs::remove_dir_all(&temp_dir);
+                return Err(e);
+            }
+            reports.push(report);
+        }
+
         let ssot_dir = Self::get_ssot_dir()?;
         let mut installed = Vec::new();
         let existing_skills = db.get_all_installed_skills()?;
 
-        for skill_dir in skill_dirs {
+        for (skill_dir, report) in skill_dirs.into_iter().zip(reports) {
             // 获取目录名称作为安装名
-            let install_name = skill_dir
-                .file_name()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_else(|| "unknown".to_string());
+            let install_name = Self::zip_install_name(&skill_dir);
 
             // 检查是否已有同名 directory 的 skill
             let conflict = existing_skills
@@ -1197,6 +2407,10 @@ impl SkillService {
                 readme_url: None,
                 apps: SkillApps::only(current_app),
                 installed_at: chrono::Utc::now().timestamp(),
+                commit_sha: None,
+                content_hash: None,
+                scan_findings: report.findings,
+                projects: Vec::new(),
             };
 
             // 保存到数据库
@@ -1219,7 +2433,29 @@ impl SkillService {
         Ok(installed)
     }
 
+    /// 扫描本地 ZIP 中的 Skills（不安装），供安装前展示报告
+    pub fn scan_zip(zip_path: &Path) -> Result<Vec<SkillScanReport>> {
+        let temp_dir = Self::extract_local_zip(zip_path)?;
+        let reports = Self::scan_skills_in_dir(&temp_dir).map(|dirs| {
+            dirs.iter()
+                .map(|dir| skill_scan::scan_skill_dir(dir, &Self::zip_install_name(dir)))
+                .collect()
+        });
+        let _ = fs::remove_dir_all(&temp_dir);
+        reports
+    }
+
+    /// ZIP 中 Skill 的安装名（目录名）
+    fn zip_install_name(skill_dir: &Path) -> String {
+        skill_dir
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
     /// 解压本地 ZIP 文件到临时目录
+    ///
+    /// 含有绝对路径或 `..` 的条目（zip-slip）视为恶意归档，拒绝解压。
     fn extract_local_zip(zip_path: &Path) -> Result<PathBuf> {
         let file = fs::File::open(zip_path)
             .with_context(|| format!("Failed to open ZIP file: {}", zip_path.display()))?;
@@ -1243,7 +2479,16 @@ impl SkillService {
             let mut file = archive.by_index(i)?;
             let file_path = match file.enclosed_name() {
                 Some(path) => path.to_owned(),
-                None => continue,
+                None => {
+                    let entry = file.name().to_string();
+                    drop(file);
+                    let _ = fs::remove_dir_all(&temp_path);
+                    return Err(anyhow!(format_skill_error(
+                        "UNSAFE_ARCHIVE_ENTRY",
+                        &[("path", &entry)],
+                        Some("checkZipContent"),
+                    )));
+                }
             };
 
             let outpath = temp_path.join(&file_path);
@@ -1329,6 +2574,224 @@ impl SkillService {
     }
 }
 
+/// 计算目录内容哈希（SHA-256，覆盖相对路径与文件内容，与遍历顺序无关）
+pub(crate) fn compute_content_hash(dir: &Path) -> Result<String> {
+    let mut files = Vec::new();
+    collect_relative_files(dir, dir, &mut files)?;
+    files.sort();
+
+    let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+    for relative in files {
+        ctx.update(relative.as_bytes());
+        ctx.update(&[0]);
+        ctx.update(&fs::read(dir.join(&relative))?);
+        ctx.update(&[0]);
+    }
+    Ok(ctx
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// 目录的元数据指纹（相对路径、大小与修改时间），仅用于判断内容是否可能变化
+fn metadata_fingerprint(dir: &Path) -> Result<u64> {
+    use std::hash::{Hash, Hasher};
+
+    let mut files = Vec::new();
+    collect_relative_files(dir, dir, &mut files)?;
+    files.sort();
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for relative in files {
+        let meta = fs::metadata(dir.join(&relative))?;
+        relative.hash(&mut hasher);
+        meta.len().hash(&mut hasher);
+        meta.modified().ok().hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
+
+fn collect_relative_files(base: &Path, current: &Path, out: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(current)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_relative_files(base, &path, out)?;
+        } else {
+            let relative = path.strip_prefix(base).unwrap_or(&path);
+            let parts: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            out.push(parts.join("/"));
+        }
+    }
+    Ok(())
+}
+
+/// URL 路径组件编码（RFC 3986：除非保留字符外全部百分号编码，空格为 `%20`）
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+                (b as char).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
+/// 解压归档到目标目录（按内容识别 zip / tar.gz / tar）
+///
+/// 所有条目位于同一个顶层目录时（如 git 归档的 `repo-branch/`）去掉该层。
+fn extract_archive(bytes: &[u8], dest: &Path) -> Result<()> {
+    if bytes.starts_with(b"PK") {
+        extract_zip(bytes, dest)
+    } else if bytes.starts_with(&[0x1f, 0x8b]) {
+        extract_tar(
+            || tar::Archive::new(flate2::read::GzDecoder::new(bytes)),
+            dest,
+        )
+    } else if bytes.get(257..262) == Some(b"ustar".as_slice()) {
+        extract_tar(|| tar::Archive::new(bytes), dest)
+    } else {
+        Err(anyhow!(format_skill_error(
+            "UNSUPPORTED_ARCHIVE",
+            &[],
+            Some("checkRepoUrl"),
+        )))
+    }
+}
+
+fn extract_zip(bytes: &[u8], dest: &Path) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        entries.push((file.name().to_string(), file.is_dir()));
+    }
+    if entries.is_empty() {
+        return Err(anyhow!(format_skill_error(
+            "EMPTY_ARCHIVE",
+            &[],
+            Some("checkRepoUrl"),
+        )));
+    }
+    let root = common_root(&entries);
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let Some(relative) = archive_entry_path(file.name(), root.as_deref()) else {
+            continue;
+        };
+        let outpath = dest.join(relative);
+
+        if file.is_dir() {
+            fs::create_dir_all(&outpath)?;
+        } else {
+            if let Some(parent) = outpath.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut outfile = fs::File::create(&outpath)?;
+            std::io::copy(&mut file, &mut outfile)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 解压 tar 归档（需要读取两遍：先确定顶层目录，再写出文件）
+fn extract_tar<R: std::io::Read>(open: impl Fn() -> tar::Archive<R>, dest: &Path) -> Result<()> {
+    let mut entries = Vec::new();
+    for entry in open().entries()? {
+        let entry = entry?;
+        let kind = entry.header().entry_type();
+        // 跳过 pax 头、符号链接等特殊条目
+        if kind.is_file() || kind.is_dir() {
+            entries.push((entry.path()?.to_string_lossy().to_string(), kind.is_dir()));
+        }
+    }
+    if entries.is_empty() {
+        return Err(anyhow!(format_skill_error(
+            "EMPTY_ARCHIVE",
+            &[],
+            Some("checkRepoUrl"),
+        )));
+    }
+    let root = common_root(&entries);
+
+    for entry in open().entries()? {
+        let mut entry = entry?;
+        let kind = entry.header().entry_type();
+        let name = entry.path()?.to_string_lossy().to_string();
+        let Some(relative) = archive_entry_path(&name, root.as_deref()) else {
+            continue;
+        };
+        let outpath = dest.join(relative);
+
+        if kind.is_dir() {
+            fs::create_dir_all(&outpath)?;
+        } else if kind.is_file() {
+            if let Some(parent) = outpath.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut outfile = fs::File::create(&outpath)?;
+            std::io::copy(&mut entry, &mut outfile)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 所有条目共享的顶层目录（存在顶层文件或多个顶层目录时返回 None）
+fn common_root(entries: &[(String, bool)]) -> Option<String> {
+    let mut root: Option<&str> = None;
+    for (name, is_dir) in entries {
+        let name = name.trim_start_matches("./").trim_end_matches('/');
+        if name.is_empty() {
+            continue;
+        }
+        let first = match name.split_once('/') {
+            Some((first, _)) => first,
+            None if *is_dir => name,
+            None => return None,
+        };
+        match root {
+            None => root = Some(first),
+            Some(existing) if existing == first => {}
+            Some(_) => return None,
+        }
+    }
+    root.map(str::to_string)
+}
+
+/// 去掉顶层目录后的条目相对路径（拒绝绝对路径与 `..`，防止写出目标目录）
+fn archive_entry_path(name: &str, root: Option<&str>) -> Option<PathBuf> {
+    let name = name.trim_start_matches("./");
+    let relative = match root {
+        Some(root) => {
+            let rest = name.strip_prefix(root)?;
+            if !rest.is_empty() && !rest.starts_with('/') {
+                return None;
+            }
+            rest.trim_start_matches('/')
+        }
+        None => name,
+    };
+    let relative = relative.trim_end_matches('/');
+    if relative.is_empty() {
+        return None;
+    }
+
+    let path = PathBuf::from(relative);
+    path.components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)))
+        .then_some(path)
+}
+
 // ========== 迁移支持 ==========
 
 /// 首次启动迁移：扫描应用目录，重建数据库
@@ -1412,6 +2875,10 @@ pub fn migrate_skills_to_ssot(db: &Arc<Database>) -> Result<usize> {
             readme_url: None,
             apps,
             installed_at: chrono::Utc::now().timestamp(),
+            commit_sha: None,
+            content_hash: None,
+            scan_findings: Vec::new(),
+            projects: Vec::new(),
         };
 
         db.save_skill(&skill)?;
@@ -1422,3 +2889,454 @@ pub fn migrate_skills_to_ssot(db: &Arc<Database>) -> Result<usize> {
 
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn zip_bytes(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, content) in entries {
+            if name.ends_with('/') {
+                writer.add_directory(*name, options).unwrap();
+            } else {
+                writer.start_file(*name, options).unwrap();
+                writer.write_all(content.as_bytes()).unwrap();
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// 直接写入 tar 头中的路径，绕过 tar 库对 `..` 的校验
+    fn tar_gz_bytes(entries: &[(&str, &str)]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_cksum();
+            builder.append(&header, content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn repo(kind: SkillSourceKind) -> SkillRepo {
+        SkillRepo {
+            owner: "acme".to_string(),
+            name: "skills".to_string(),
+            branch: "main".to_string(),
+            enabled: true,
+            kind,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn archive_entry_path_rejects_escapes() {
+        assert_eq!(
+            archive_entry_path("repo-main/a/SKILL.md", Some("repo-main")),
+            Some(PathBuf::from("a/SKILL.md"))
+        );
+        assert_eq!(
+            archive_entry_path("./a/b.txt", None),
+            Some(PathBuf::from("a/b.txt"))
+        );
+        assert_eq!(archive_entry_path("repo-main/", Some("repo-main")), None);
+        // 与顶层目录同前缀但不是其子路径
+        assert_eq!(archive_entry_path("repo-mainx/a", Some("repo-main")), None);
+        assert_eq!(archive_entry_path("../evil.txt", None), None);
+        assert_eq!(
+            archive_entry_path("repo-main/../../evil", Some("repo-main")),
+            None
+        );
+        assert_eq!(archive_entry_path("a/../../evil", None), None);
+        assert_eq!(archive_entry_path("/etc/passwd", None), None);
+    }
+
+    #[test]
+    fn common_root_requires_single_top_level_dir() {
+        let entries = |items: &[(&str, bool)]| -> Vec<(String, bool)> {
+            items.iter().map(|(n, d)| (n.to_string(), *d)).collect()
+        };
+        assert_eq!(
+            common_root(&entries(&[
+                ("repo-main/", true),
+                ("repo-main/a/SKILL.md", false)
+            ])),
+            Some("repo-main".to_string())
+        );
+        assert_eq!(
+            common_root(&entries(&[
+                ("./repo/a.md", false),
+                ("./repo/b/c.md", false)
+            ])),
+            Some("repo".to_string())
+        );
+        assert_eq!(
+            common_root(&entries(&[("a/SKILL.md", false), ("b/SKILL.md", false)])),
+            None
+        );
+        assert_eq!(
+            common_root(&entries(&[("repo/a.md", false), ("README.md", false)])),
+            None
+        );
+        assert_eq!(common_root(&[]), None);
+    }
+
+    #[test]
+    fn extract_zip_strips_root_and_skips_traversal() {
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("out");
+        fs::create_dir_all(&dest).unwrap();
+        let bytes = zip_bytes(&[
+            ("repo-main/", ""),
+            ("repo-main/demo/SKILL.md", "# demo"),
+            ("repo-main/../escaped.txt", "nope"),
+        ]);
+
+        extract_archive(&bytes, &dest).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dest.join("demo/SKILL.md")).unwrap(),
+            "# demo"
+        );
+        assert!(!dir.path().join("escaped.txt").exists());
+        assert!(!dest.join("repo-main").exists());
+    }
+
+    #[test]
+    fn extract_tar_gz_strips_root_and_skips_traversal() {
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("out");
+        fs::create_dir_all(&dest).unwrap();
+        let bytes = tar_gz_bytes(&[
+            ("pkg/demo/SKILL.md", "# demo"),
+            ("pkg/../escaped.txt", "nope"),
+        ]);
+
+        extract_archive(&bytes, &dest).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dest.join("demo/SKILL.md")).unwrap(),
+            "# demo"
+        );
+        assert!(!dir.path().join("escaped.txt").exists());
+    }
+
+    #[test]
+    fn extract_archive_rejects_unknown_and_empty_archives() {
+        let dir = TempDir::new().unwrap();
+        assert!(extract_archive(b"not an archive", dir.path()).is_err());
+        assert!(extract_archive(&zip_bytes(&[]), dir.path()).is_err());
+    }
+
+    #[test]
+    fn archive_url_per_source_kind() {
+        let github = repo(SkillSourceKind::Github);
+        assert_eq!(
+            SkillService::archive_url(&github, "main", false).as_deref(),
+            Some("https://github.com/acme/skills/archive/refs/heads/main.zip")
+        );
+        assert_eq!(
+            SkillService::archive_url(&github, "abc123", true).as_deref(),
+            Some("https://github.com/acme/skills/archive/abc123.zip")
+        );
+        let private = SkillRepo {
+            token: Some("${secret:gh}".to_string()),
+            ..repo(SkillSourceKind::Github)
+        };
+        assert_eq!(
+            SkillService::archive_url(&private, "main", false).as_deref(),
+            Some("https://api.github.com/repos/acme/skills/zipball/main")
+        );
+
+        let gitlab = SkillRepo {
+            owner: "group/sub".to_string(),
+            url: Some("https://git.example.com/".to_string()),
+            ..repo(SkillSourceKind::Gitlab)
+        };
+        assert_eq!(
+            SkillService::archive_url(&gitlab, "feature/x", false).as_deref(),
+            Some("https://git.example.com/api/v4/projects/group%2Fsub%2Fskills/repository/archive.zip?sha=feature%2Fx")
+        );
+
+        let gitea = repo(SkillSourceKind::Gitea);
+        assert_eq!(
+            SkillService::archive_url(&gitea, "main", false).as_deref(),
+            Some("https://gitea.com/api/v1/repos/acme/skills/archive/main.zip")
+        );
+
+        let template = SkillRepo {
+            url: Some("https://mirror.example.com/{owner}/{name}/{branch}.tar.gz".to_string()),
+            ..repo(SkillSourceKind::Template)
+        };
+        assert_eq!(
+            SkillService::archive_url(&template, "dev", false).as_deref(),
+            Some("https://mirror.example.com/acme/skills/dev.tar.gz")
+        );
+
+        assert!(
+            SkillService::archive_url(&repo(SkillSourceKind::Archive), "main", false).is_none()
+        );
+        assert!(SkillService::archive_url(&repo(SkillSourceKind::Local), "main", false).is_none());
+    }
+
+    #[test]
+    fn normalize_repo_trims_and_validates() {
+        let normalized = SkillService::normalize_repo(SkillRepo {
+            owner: " acme ".to_string(),
+            name: "skills ".to_string(),
+            subpath: Some("/skills/core/".to_string()),
+            token: Some("  ".to_string()),
+            ..repo(SkillSourceKind::Github)
+        })
+        .unwrap();
+        assert_eq!(normalized.owner, "acme");
+        assert_eq!(normalized.name, "skills");
+        assert_eq!(normalized.subpath.as_deref(), Some("skills/core"));
+        assert!(normalized.token.is_none());
+
+        let invalid = [
+            SkillRepo {
+                owner: " ".to_string(),
+                ..repo(SkillSourceKind::Github)
+            },
+            SkillRepo {
+                subpath: Some("../outside".to_string()),
+                ..repo(SkillSourceKind::Github)
+            },
+            SkillRepo {
+                url: Some("ftp://example.com/a.zip".to_string()),
+                ..repo(SkillSourceKind::Archive)
+            },
+            repo(SkillSourceKind::Gitea),
+            SkillRepo {
+                url: Some("/definitely/not/a/dir".to_string()),
+                ..repo(SkillSourceKind::Local)
+            },
+        ];
+        for repo in invalid {
+            assert!(
+                SkillService::normalize_repo(repo.clone()).is_err(),
+                "{repo:?}"
+            );
+        }
+
+        // GitLab 未填写实例地址时使用 gitlab.com
+        assert!(SkillService::normalize_repo(repo(SkillSourceKind::Gitlab)).is_ok());
+    }
+
+    #[test]
+    fn url_encode_escapes_reserved_characters() {
+        assert_eq!(url_encode("group/sub project"), "group%2Fsub%20project");
+        assert_eq!(url_encode("v1.0-rc_1"), "v1.0-rc_1");
+        assert_eq!(url_encode("a&b=c?"), "a%26b%3Dc%3F");
+    }
+
+    fn installed(directory: &str) -> InstalledSkill {
+        InstalledSkill {
+            id: format!("acme/skills:{directory}"),
+            name: directory.to_string(),
+            description: None,
+            directory: directory.to_string(),
+            repo_owner: Some("acme".to_string()),
+            repo_name: Some("skills".to_string()),
+            repo_branch: Some("main".to_string()),
+            readme_url: None,
+            apps: SkillApps::default(),
+            installed_at: 0,
+            commit_sha: None,
+            content_hash: None,
+            scan_findings: Vec::new(),
+            projects: Vec::new(),
+        }
+    }
+
+    fn write_skill(dir: &Path, body: &str) {
+        fs::create_dir_all(dir.join("scripts")).unwrap();
+        fs::write(dir.join("SKILL.md"), body).unwrap();
+        fs::write(dir.join("scripts/run.sh"), "echo hi").unwrap();
+    }
+
+    #[test]
+    fn content_hash_covers_paths_and_content() {
+        let dir = TempDir::new().unwrap();
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        write_skill(&a, "# demo");
+        write_skill(&b, "# demo");
+        let hash = compute_content_hash(&a).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, compute_content_hash(&b).unwrap());
+
+        fs::write(b.join("SKILL.md"), "# changed").unwrap();
+        assert_ne!(hash, compute_content_hash(&b).unwrap());
+
+        // 内容相同但路径不同
+        write_skill(&b, "# demo");
+        fs::rename(b.join("scripts/run.sh"), b.join("scripts/other.sh")).unwrap();
+        assert_ne!(hash, compute_content_hash(&b).unwrap());
+    }
+
+    #[test]
+    fn metadata_fingerprint_tracks_size_and_paths() {
+        let dir = TempDir::new().unwrap();
+        write_skill(dir.path(), "# demo");
+        let fingerprint = metadata_fingerprint(dir.path()).unwrap();
+        assert_eq!(fingerprint, metadata_fingerprint(dir.path()).unwrap());
+
+        fs::write(dir.path().join("SKILL.md"), "# demo, longer").unwrap();
+        let resized = metadata_fingerprint(dir.path()).unwrap();
+        assert_ne!(fingerprint, resized);
+
+        fs::rename(
+            dir.path().join("scripts/run.sh"),
+            dir.path().join("scripts/other.sh"),
+        )
+        .unwrap();
+        assert_ne!(resized, metadata_fingerprint(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn locally_modified_skill_requires_force_to_update() {
+        let dir = TempDir::new().unwrap();
+        let ssot = dir.path();
+        write_skill(&ssot.join("demo"), "# demo");
+        let mut skill = installed("demo");
+
+        // 没有记录哈希时视为未修改
+        assert!(!SkillService::is_locally_modified(&skill, ssot));
+
+        skill.content_hash = Some(compute_content_hash(&ssot.join("demo")).unwrap());
+        assert!(!SkillService::is_locally_modified(&skill, ssot));
+        SkillService::ensure_update_allowed(&skill, ssot, false).unwrap();
+
+        fs::write(ssot.join("demo/SKILL.md"), "# edited locally").unwrap();
+        assert!(SkillService::is_locally_modified(&skill, ssot));
+        let err = SkillService::ensure_update_allowed(&skill, ssot, false).unwrap_err();
+        assert!(err.to_string().contains("SKILL_LOCALLY_MODIFIED"));
+        SkillService::ensure_update_allowed(&skill, ssot, true).unwrap();
+    }
+
+    #[test]
+    fn swap_in_version_keeps_previous_version_for_rollback() {
+        let dir = TempDir::new().unwrap();
+        let ssot = dir.path().join("skills");
+        let versions = dir.path().join("skill-versions");
+        fs::create_dir_all(&versions).unwrap();
+        write_skill(&ssot.join("demo"), "# v1");
+        let mut skill = installed("demo");
+        skill.commit_sha = Some("c1".to_string());
+        skill.content_hash = Some(compute_content_hash(&ssot.join("demo")).unwrap());
+        let v1_hash = skill.content_hash.clone();
+
+        // 更新：暂存目录换入 SSOT，旧版本移入 versions
+        let staging = versions.join(".staging-demo");
+        write_skill(&staging, "# v2");
+        let replaced = SkillVersionInfo {
+            commit_sha: skill.commit_sha.clone(),
+            content_hash: skill.content_hash.clone(),
+            replaced_at: 1,
+        };
+        SkillService::swap_in_version(&ssot, &versions, &staging, "demo", &replaced).unwrap();
+        skill.commit_sha = Some("c2".to_string());
+        skill.content_hash = Some(compute_content_hash(&ssot.join("demo")).unwrap());
+
+        assert!(!staging.exists());
+        assert_eq!(
+            fs::read_to_string(ssot.join("demo/SKILL.md")).unwrap(),
+            "# v2"
+        );
+        assert_eq!(
+            fs::read_to_string(versions.join("demo/SKILL.md")).unwrap(),
+            "# v1"
+        );
+        assert!(versions.join("demo.json").exists());
+
+        // 回滚：恢复 v1 的内容与记录
+        SkillService::restore_previous_version(&ssot, &versions, &mut skill).unwrap();
+        assert_eq!(
+            fs::read_to_string(ssot.join("demo/SKILL.md")).unwrap(),
+            "# v1"
+        );
+        assert_eq!(skill.commit_sha.as_deref(), Some("c1"));
+        assert_eq!(skill.content_hash, v1_hash);
+        assert!(!SkillService::is_locally_modified(&skill, &ssot));
+
+        // 再次回滚即回到 v2
+        SkillService::restore_previous_version(&ssot, &versions, &mut skill).unwrap();
+        assert_eq!(
+            fs::read_to_string(ssot.join("demo/SKILL.md")).unwrap(),
+            "# v2"
+        );
+        assert_eq!(skill.commit_sha.as_deref(), Some("c2"));
+    }
+
+    #[test]
+    fn rollback_without_previous_version_fails() {
+        let dir = TempDir::new().unwrap();
+        let ssot = dir.path().join("skills");
+        write_skill(&ssot.join("demo"), "# v1");
+        let mut skill = installed("demo");
+        assert!(SkillService::restore_previous_version(&ssot, dir.path(), &mut skill).is_err());
+        assert_eq!(
+            fs::read_to_string(ssot.join("demo/SKILL.md")).unwrap(),
+            "# v1"
+        );
+    }
+
+    #[test]
+    fn repo_token_is_stored_as_secret_reference() {
+        let dir = TempDir::new().unwrap();
+        let store = crate::secret_store::SecretStore::at(dir.path().join("secrets.json"));
+        store.init(Some("passphrase")).unwrap();
+
+        let mut repo = SkillRepo {
+            token: Some("glpat-123".to_string()),
+            ..repo(SkillSourceKind::Gitlab)
+        };
+        let name = SkillService::token_secret_name(&repo);
+        assert!(name.starts_with("skill-repo.acme.skills."));
+        assert!(SkillService::store_repo_token(&mut repo, &store).unwrap());
+        assert_eq!(repo.token, Some(format!("${{secret:{name}}}")));
+        assert_eq!(store.get(&name).unwrap().as_deref(), Some("glpat-123"));
+
+        // 已是引用时不再重复存储
+        assert!(!SkillService::store_repo_token(&mut repo, &store).unwrap());
+
+        SkillService::delete_repo_token(&repo, &store).unwrap();
+        assert!(store.get(&name).unwrap().is_none());
+    }
+
+    #[test]
+    fn repo_token_names_do_not_collide() {
+        let named = |owner: &str, name: &str, url: Option<&str>| {
+            SkillService::token_secret_name(&SkillRepo {
+                owner: owner.to_string(),
+                name: name.to_string(),
+                url: url.map(str::to_string),
+                ..repo(SkillSourceKind::Gitlab)
+            })
+        };
+        let a = named("a.b", "c", None);
+        let b = named("a", "b.c", None);
+        assert_ne!(a, b);
+        assert_ne!(named("a b", "c", None), named("a_b", "c", None));
+        assert_ne!(
+            named("acme", "skills", Some("https://gitlab.com")),
+            named("acme", "skills", Some("https://git.example.com"))
+        );
+
+        let long = "x".repeat(80);
+        let name = named(&long, &long, None);
+        assert!(name.len() <= 64);
+        assert!(crate::secret_store::has_refs(&format!(
+            "${{secret:{name}}}"
+        )));
+    }
+}
@@ -187,7 +187,8 @@ impl StreamCheckService {
             .map_err(|e| AppError::Message(format!("Failed to extract base_url: {e}")))?;
 
         let auth = adapter
-            .extract_auth(provider)
+            .extract_resolved_auth(provider)
+            .map_err(|e| AppError::Message(e.to_string()))?
             .ok_or_else(|| AppError::Message("API Key not found".to_string()))?;
 
         // 获取 HTTP 客户端：优先使用供应商单独代理配置，否则使用全局客户端
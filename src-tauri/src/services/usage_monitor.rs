@@ -0,0 +1,379 @@
+//! 用量脚本调度服务
+//!
+//! 按供应商配置的 `autoQueryInterval` 在后台定时执行用量脚本（前端不再自行轮询，
+//! 通过 `usage-query-updated` 事件获取结果），将每次查询结果写入历史表，
+//! 并在剩余额度跌破阈值时发出低余额告警，可选地将该供应商移至故障转移队列末尾；
+//! 余额回升到阈值以上后恢复其队列位置。
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::error::AppError;
+use crate::provider::{UsageResult, UsageScript};
+use crate::proxy::log_codes::usg as log_usg;
+use crate::services::ProviderService;
+use crate::store::AppState;
+
+/// 调度器检查间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+/// 历史清理间隔
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// 历史保留天数
+const HISTORY_RETENTION_DAYS: i64 = 90;
+
+/// 用量查询结果更新事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageQueryUpdatedEvent {
+    pub app_type: String,
+    pub provider_id: String,
+    pub result: UsageResult,
+    pub queried_at: i64,
+}
+
+/// 低余额告警
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LowBalanceAlert {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub remaining: f64,
+    pub threshold: f64,
+    pub unit: Option<String>,
+    /// 是否已将供应商移至故障转移队列末尾
+    pub demoted: bool,
+}
+
+pub struct UsageMonitorService;
+
+impl UsageMonitorService {
+    /// 启动后台调度任务
+    pub fn start(app_handle: tauri::AppHandle) {
+        tauri::async_runtime::spawn(async move {
+            let mut last_run: HashMap<(String, String), i64> = HashMap::new();
+            let mut last_prune: Option<Instant> = None;
+
+            loop {
+                tokio::time::sleep(TICK_INTERVAL).await;
+
+                let state = app_handle.state::<AppState>();
+
+                if last_prune.is_none_or(|t| t.elapsed() >= PRUNE_INTERVAL) {
+                    let before = chrono::Utc::now().timestamp() - HISTORY_RETENTION_DAYS * 86400;
+                    match state.db.prune_usage_query_history(before) {
+                        Ok(n) if n > 0 => log::info!("已清理 {n} 条过期用量查询记录"),
+                        Ok(_) => {}
+                        Err(e) => log::warn!("清理用量查询历史失败: {e}"),
+                    }
+                    last_prune = Some(Instant::now());
+                }
+
+                Self::tick(&app_handle, &state, &mut last_run).await;
+            }
+        });
+    }
+
+    /// 执行一轮调度：查询所有到期的供应商
+    async fn tick(
+        app_handle: &tauri::AppHandle,
+        state: &AppState,
+        last_run: &mut HashMap<(String, String), i64>,
+    ) {
+        for app_type in AppType::all() {
+            let providers = match state.db.get_all_providers(app_type.as_str()) {
+                Ok(p) => p,
+                Err(e) => {
+                    log::warn!(
+                        "[{}] 读取 {} 供应商失败: {e}",
+                        log_usg::USAGE_QUERY_FAILED,
+                        app_type.as_str()
+                    );
+                    continue;
+                }
+            };
+
+            for (provider_id, provider) in providers {
+                let Some(interval) = provider
+                    .meta
+                    .as_ref()
+                    .and_then(|m| m.usage_script.as_ref())
+                    .and_then(scheduled_interval_secs)
+                else {
+                    continue;
+                };
+
+                // 手动查询同样写入历史，取两者中较晚的时间，避免紧接着重复查询
+                let key = (app_type.as_str().to_string(), provider_id.clone());
+                let now = chrono::Utc::now().timestamp();
+                let recorded = state
+                    .db
+                    .get_last_usage_query_at(&provider_id, app_type.as_str())
+                    .unwrap_or(None);
+                let last = last_run.get(&key).copied().max(recorded);
+                if last.is_some_and(|ts| now - ts < interval) {
+                    continue;
+                }
+                last_run.insert(key, now);
+
+                match ProviderService::query_usage(state, app_type.clone(), &provider_id).await {
+                    Ok(result) => {
+                        if let Err(e) = Self::record_result(
+                            &state.db,
+                            Some(app_handle),
+                            app_type.as_str(),
+                            &provider_id,
+                            &result,
+                        ) {
+                            log::warn!(
+                                "[{}] 记录用量查询结果失败: {e}",
+                                log_usg::USAGE_QUERY_FAILED
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "[{}] 定时用量查询失败: app={}, provider={provider_id}, error={e}",
+                            log_usg::USAGE_QUERY_FAILED,
+                            app_type.as_str()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// 记录一次查询结果，并在剩余额度跌破阈值时发出告警
+    ///
+    /// 手动查询与定时查询共用此入口。仅在从“高于阈值”跌至“低于阈值”时告警，
+    /// 避免每次查询重复通知；余额回升到阈值以上时撤销降级并清除托盘提示。
+    pub fn record_result(
+        db: &Database,
+        app_handle: Option<&tauri::AppHandle>,
+        app_type: &str,
+        provider_id: &str,
+        result: &UsageResult,
+    ) -> Result<Option<LowBalanceAlert>, AppError> {
+        let previous = db.get_latest_min_remaining(provider_id, app_type)?;
+        let queried_at = chrono::Utc::now().timestamp();
+        db.save_usage_query_result(provider_id, app_type, result, queried_at)?;
+
+        if let Some(app) = app_handle {
+            let event = UsageQueryUpdatedEvent {
+                app_type: app_type.to_string(),
+                provider_id: provider_id.to_string(),
+                result: result.clone(),
+                queried_at,
+            };
+            if let Err(e) = app.emit("usage-query-updated", event) {
+                log::warn!(
+                    "[{}] 发射用量查询事件失败: {e}",
+                    log_usg::USAGE_QUERY_FAILED
+                );
+            }
+        }
+
+        let Some(provider) = db.get_provider_by_id(provider_id, app_type)? else {
+            return Ok(None);
+        };
+        let Some(script) = provider.meta.as_ref().and_then(|m| m.usage_script.as_ref()) else {
+            return Ok(None);
+        };
+        let Some(threshold) = script.low_balance_threshold else {
+            return Ok(None);
+        };
+        let notice_key = format!("usage:{app_type}:{provider_id}");
+
+        if recovered_balance(result, threshold) {
+            if db.restore_failover_position(app_type, provider_id)? {
+                log::info!(
+                    "供应商余额已恢复，恢复故障转移队列位置: app={app_type}, provider={}",
+                    provider.name
+                );
+            }
+            if let Some(app) = app_handle {
+                crate::tray::set_tray_notice(app, &notice_key, None);
+            }
+            return Ok(None);
+        }
+
+        let Some((remaining, unit)) = crossed_low_balance(result, threshold, previous) else {
+            return Ok(None);
+        };
+
+        let demoted = if script.demote_on_low_balance.unwrap_or(false) {
+            db.move_to_failover_queue_end(app_type, provider_id)?
+        } else {
+            false
+        };
+
+        let alert = LowBalanceAlert {
+            app_type: app_type.to_string(),
+            provider_id: provider_id.to_string(),
+            provider_name: provider.name.clone(),
+            remaining,
+            threshold,
+            unit,
+            demoted,
+        };
+
+        log::warn!(
+            "[{}] 供应商余额不足: app={app_type}, provider={}, remaining={remaining}, threshold={threshold}, demoted={demoted}",
+            log_usg::LOW_BALANCE,
+            provider.name
+        );
+
+        if let Some(app) = app_handle {
+            if let Err(e) = app.emit("usage-low-balance", &alert) {
+                log::warn!("[{}] 发射低余额事件失败: {e}", log_usg::LOW_BALANCE);
+            }
+            let notice = format!(
+                "{} 余额不足: {remaining}{}",
+                provider.name,
+                alert.unit.as_deref().unwrap_or("")
+            );
+            crate::tray::set_tray_notice(app, &notice_key, Some(notice));
+        }
+
+        Ok(Some(alert))
+    }
+}
+
+/// 计算定时查询间隔（秒），未启用或间隔为 0 时返回 None
+fn scheduled_interval_secs(script: &UsageScript) -> Option<i64> {
+    if !script.enabled {
+        return None;
+    }
+    match script.auto_query_interval {
+        Some(minutes) if minutes > 0 => Some(minutes as i64 * 60),
+        _ => None,
+    }
+}
+
+/// 本次成功查询的最小剩余额度
+fn min_remaining(result: &UsageResult) -> Option<(f64, Option<String>)> {
+    if !result.success {
+        return None;
+    }
+    result
+        .data
+        .as_deref()?
+        .iter()
+        .filter_map(|d| d.remaining.map(|r| (r, d.unit.clone())))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+/// 判断本次结果的余额是否不低于阈值（用于撤销低余额降级）
+fn recovered_balance(result: &UsageResult, threshold: f64) -> bool {
+    min_remaining(result).is_some_and(|(remaining, _)| remaining >= threshold)
+}
+
+/// 判断本次结果是否从阈值之上跌破阈值
+///
+/// 返回本次最小剩余额度及其单位；`previous` 为上一次成功查询的最小剩余额度。
+fn crossed_low_balance(
+    result: &UsageResult,
+    threshold: f64,
+    previous: Option<f64>,
+) -> Option<(f64, Option<String>)> {
+    let (remaining, unit) = min_remaining(result)?;
+
+    if remaining >= threshold {
+        return None;
+    }
+    if previous.is_some_and(|p| p < threshold) {
+        return None;
+    }
+    Some((remaining, unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::UsageData;
+
+    fn result_with(remaining: f64) -> UsageResult {
+        UsageResult {
+            success: true,
+            data: Some(vec![UsageData {
+                plan_name: Some("default".to_string()),
+                extra: None,
+                is_valid: Some(true),
+                invalid_message: None,
+                total: Some(100.0),
+                used: Some(100.0 - remaining),
+                remaining: Some(remaining),
+                unit: Some("USD".to_string()),
+            }]),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_crossed_low_balance_only_on_transition() {
+        // 首次查询即低于阈值
+        assert!(crossed_low_balance(&result_with(3.0), 5.0, None).is_some());
+        // 从阈值之上跌破
+        assert!(crossed_low_balance(&result_with(3.0), 5.0, Some(10.0)).is_some());
+        // 已处于低余额状态，不重复告警
+        assert!(crossed_low_balance(&result_with(2.0), 5.0, Some(3.0)).is_none());
+        // 高于阈值
+        assert!(crossed_low_balance(&result_with(8.0), 5.0, Some(3.0)).is_none());
+
+        let failed = UsageResult {
+            success: false,
+            data: None,
+            error: Some("timeout".to_string()),
+        };
+        assert!(crossed_low_balance(&failed, 5.0, None).is_none());
+        assert!(!recovered_balance(&failed, 5.0));
+    }
+
+    #[test]
+    fn test_recovered_balance() {
+        assert!(recovered_balance(&result_with(5.0), 5.0));
+        assert!(recovered_balance(&result_with(20.0), 5.0));
+        assert!(!recovered_balance(&result_with(4.9), 5.0));
+    }
+
+    #[test]
+    fn test_usage_history_roundtrip() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.save_usage_query_result("p1", "claude", &result_with(40.0), 1_000)?;
+        db.save_usage_query_result(
+            "p1",
+            "claude",
+            &UsageResult {
+                success: false,
+                data: None,
+                error: Some("boom".to_string()),
+            },
+            2_000,
+        )?;
+        db.save_usage_query_result("p1", "claude", &result_with(10.0), 3_000)?;
+
+        let history = db.get_usage_query_history("p1", "claude", None, None)?;
+        assert_eq!(history.len(), 3);
+        assert!(!history[1].success);
+        assert_eq!(history[1].error.as_deref(), Some("boom"));
+
+        assert_eq!(db.get_latest_min_remaining("p1", "claude")?, Some(10.0));
+        assert_eq!(db.get_last_usage_query_at("p1", "claude")?, Some(3_000));
+
+        let ranged = db.get_usage_query_history("p1", "claude", Some(1_500), None)?;
+        assert_eq!(ranged.len(), 2);
+
+        assert_eq!(db.prune_usage_query_history(2_500)?, 2);
+        assert_eq!(
+            db.get_usage_query_history("p1", "claude", None, None)?
+                .len(),
+            1
+        );
+        Ok(())
+    }
+}
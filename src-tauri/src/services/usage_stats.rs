@@ -4,6 +4,7 @@
 
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
+use crate::session_manager::SessionMeta;
 use chrono::{Local, TimeZone};
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
@@ -62,6 +63,35 @@ pub struct ModelStats {
     pub avg_cost_per_request: String,
 }
 
+/// 会话统计（按 proxy_request_logs.session_id 聚合）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUsageStats {
+    pub session_id: String,
+    pub app_type: String,
+    pub request_count: u64,
+    pub total_tokens: u64,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    pub total_cost: String,
+    pub first_request_at: i64,
+    pub last_request_at: i64,
+}
+
+/// 项目统计（会话统计与本地会话元数据关联后按 project_dir 聚合）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectUsageStats {
+    /// 项目目录，无法关联到本地会话时为 None
+    pub project_dir: Option<String>,
+    pub project_name: Option<String>,
+    pub session_count: u64,
+    pub request_count: u64,
+    pub total_tokens: u64,
+    pub total_cost: String,
+    pub last_request_at: i64,
+}
+
 /// 请求日志过滤器
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -382,6 +412,68 @@ impl Database {
         Ok(stats)
     }
 
+    /// 获取会话统计
+    ///
+    /// 仅统计带 session_id 的请求，按总成本降序排列。
+    pub fn get_session_stats(
+        &self,
+        start_date: Option<i64>,
+        end_date: Option<i64>,
+    ) -> Result<Vec<SessionUsageStats>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let mut conditions = vec!["session_id IS NOT NULL", "session_id != ''"];
+        let mut params = Vec::new();
+        if let Some(start) = start_date {
+            conditions.push("created_at >= ?");
+            params.push(start);
+        }
+        if let Some(end) = end_date {
+            conditions.push("created_at <= ?");
+            params.push(end);
+        }
+
+        let sql = format!(
+            "SELECT
+                session_id,
+                app_type,
+                COUNT(*) as request_count,
+                COALESCE(SUM(input_tokens + output_tokens), 0) as total_tokens,
+                COALESCE(SUM(input_tokens), 0) as total_input_tokens,
+                COALESCE(SUM(output_tokens), 0) as total_output_tokens,
+                COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0) as total_cost,
+                MIN(created_at) as first_request_at,
+                MAX(created_at) as last_request_at
+             FROM proxy_request_logs
+             WHERE {}
+             GROUP BY session_id, app_type
+             ORDER BY total_cost DESC",
+            conditions.join(" AND ")
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+            Ok(SessionUsageStats {
+                session_id: row.get(0)?,
+                app_type: row.get(1)?,
+                request_count: row.get::<_, i64>(2)? as u64,
+                total_tokens: row.get::<_, i64>(3)? as u64,
+                total_input_tokens: row.get::<_, i64>(4)? as u64,
+                total_output_tokens: row.get::<_, i64>(5)? as u64,
+                total_cost: format!("{:.6}", row.get::<_, f64>(6)?),
+                first_request_at: row.get(7)?,
+                last_request_at: row.get(8)?,
+            })
+        })?;
+
+        let mut stats = Vec::new();
+        for row in rows {
+            stats.push(row?);
+        }
+
+        Ok(stats)
+    }
+
     /// 获取请求日志列表（分页）
     pub fn get_request_logs(
         &self,
@@ -814,6 +906,125 @@ impl Database {
     }
 }
 
+/// 将会话统计按项目目录聚合
+///
+/// 通过 session_id 关联本地扫描到的会话元数据（`SessionMeta.project_dir`），
+/// 无法关联的会话统一归入 `project_dir = None`。按总成本降序排列。
+pub fn aggregate_project_stats(
+    session_stats: &[SessionUsageStats],
+    sessions: &[SessionMeta],
+) -> Vec<ProjectUsageStats> {
+    let session_stats = merge_stats_by_local_session(session_stats);
+    let project_by_session: HashMap<&str, &str> = sessions
+        .iter()
+        .filter_map(|s| {
+            s.project_dir
+                .as_deref()
+                .map(|dir| (s.session_id.as_str(), dir))
+        })
+        .collect();
+
+    struct Acc {
+        session_count: u64,
+        request_count: u64,
+        total_tokens: u64,
+        total_cost: f64,
+        last_request_at: i64,
+    }
+
+    let mut grouped: HashMap<Option<&str>, Acc> = HashMap::new();
+    for stat in &session_stats {
+        let project = project_by_session.get(stat.session_id.as_str()).copied();
+        let acc = grouped.entry(project).or_insert(Acc {
+            session_count: 0,
+            request_count: 0,
+            total_tokens: 0,
+            total_cost: 0.0,
+            last_request_at: 0,
+        });
+        acc.session_count += 1;
+        acc.request_count += stat.request_count;
+        acc.total_tokens += stat.total_tokens;
+        acc.total_cost += stat.total_cost.parse::<f64>().unwrap_or(0.0);
+        acc.last_request_at = acc.last_request_at.max(stat.last_request_at);
+    }
+
+    let mut stats: Vec<(f64, ProjectUsageStats)> = grouped
+        .into_iter()
+        .map(|(project, acc)| {
+            let project_name = project.and_then(|dir| {
+                dir.trim_end_matches(['/', '\\'])
+                    .rsplit(['/', '\\'])
+                    .next()
+                    .filter(|name| !name.is_empty())
+                    .map(|name| name.to_string())
+            });
+            (
+                acc.total_cost,
+                ProjectUsageStats {
+                    project_dir: project.map(|dir| dir.to_string()),
+                    project_name,
+                    session_count: acc.session_count,
+                    request_count: acc.request_count,
+                    total_tokens: acc.total_tokens,
+                    total_cost: format!("{:.6}", acc.total_cost),
+                    last_request_at: acc.last_request_at,
+                },
+            )
+        })
+        .collect();
+
+    stats.sort_by(|a, b| b.0.total_cmp(&a.0));
+    stats.into_iter().map(|(_, stat)| stat).collect()
+}
+
+/// 本地会话 id：Codex 请求记录的 session_id 带 `codex_` 前缀
+pub fn local_session_id(session_id: &str) -> &str {
+    session_id.strip_prefix("codex_").unwrap_or(session_id)
+}
+
+/// 按本地会话 id 合并会话统计
+///
+/// 同一会话可能以多个 app_type 记录（如经不同入口代理），合并后与本地扫描到的会话一一对应。
+/// 合并结果保留首条记录的 app_type。
+pub fn merge_stats_by_local_session(stats: &[SessionUsageStats]) -> Vec<SessionUsageStats> {
+    let mut merged: Vec<(SessionUsageStats, f64)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for stat in stats {
+        let cost = stat.total_cost.parse::<f64>().unwrap_or(0.0);
+        let session_id = local_session_id(&stat.session_id);
+        match index.get(session_id) {
+            Some(&i) => {
+                let (acc, total_cost) = &mut merged[i];
+                acc.request_count += stat.request_count;
+                acc.total_tokens += stat.total_tokens;
+                acc.total_input_tokens += stat.total_input_tokens;
+                acc.total_output_tokens += stat.total_output_tokens;
+                acc.first_request_at = acc.first_request_at.min(stat.first_request_at);
+                acc.last_request_at = acc.last_request_at.max(stat.last_request_at);
+                *total_cost += cost;
+            }
+            None => {
+                index.insert(session_id, merged.len());
+                merged.push((
+                    SessionUsageStats {
+                        session_id: session_id.to_string(),
+                        ..stat.clone()
+                    },
+                    cost,
+                ));
+            }
+        }
+    }
+    merged
+        .into_iter()
+        .map(|(stat, total_cost)| SessionUsageStats {
+            total_cost: format!("{total_cost:.6}"),
+            ..stat
+        })
+        .collect()
+}
+
 pub(crate) fn find_model_pricing_row(
     conn: &Connection,
     model_id: &str,
@@ -928,6 +1139,129 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_session_and_project_stats() -> Result<(), AppError> {
+        let db = Database::memory()?;
+
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, session, cost, ts) in [
+                ("req1", Some("s1"), "0.10", 1000),
+                ("req2", Some("s1"), "0.20", 2000),
+                ("req3", Some("s2"), "0.05", 3000),
+                ("req4", Some("s3"), "1.00", 4000),
+                ("req5", None, "9.00", 5000),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, session_id, created_at
+                    ) VALUES (?, 'p1', 'claude', 'claude-3', 100, 50, ?, 100, 200, ?, ?)",
+                    params![id, cost, session, ts],
+                )?;
+            }
+        }
+
+        let session_stats = db.get_session_stats(None, None)?;
+        assert_eq!(session_stats.len(), 3);
+        assert_eq!(session_stats[0].session_id, "s3");
+        let s1 = session_stats.iter().find(|s| s.session_id == "s1").unwrap();
+        assert_eq!(s1.request_count, 2);
+        assert_eq!(s1.total_cost, "0.300000");
+        assert_eq!(s1.first_request_at, 1000);
+        assert_eq!(s1.last_request_at, 2000);
+
+        let windowed = db.get_session_stats(Some(2500), None)?;
+        assert_eq!(windowed.len(), 2);
+
+        let meta = |session_id: &str, project_dir: Option<&str>| SessionMeta {
+            provider_id: "claude".to_string(),
+            session_id: session_id.to_string(),
+            title: None,
+            summary: None,
+            project_dir: project_dir.map(|d| d.to_string()),
+            created_at: None,
+            last_active_at: None,
+            source_path: None,
+            resume_command: None,
+            total_cost: None,
+            request_count: None,
+        };
+        let sessions = vec![
+            meta("s1", Some("/work/repo-x")),
+            meta("s2", Some("/work/repo-x")),
+        ];
+
+        let projects = aggregate_project_stats(&session_stats, &sessions);
+        assert_eq!(projects.len(), 2);
+        // s3 无法关联本地会话，归入未知项目
+        assert_eq!(projects[0].project_dir, None);
+        assert_eq!(projects[0].total_cost, "1.000000");
+        assert_eq!(projects[1].project_dir.as_deref(), Some("/work/repo-x"));
+        assert_eq!(projects[1].project_name.as_deref(), Some("repo-x"));
+        assert_eq!(projects[1].session_count, 2);
+        assert_eq!(projects[1].request_count, 3);
+        assert_eq!(projects[1].total_cost, "0.350000");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_stats_match_codex_sessions() -> Result<(), AppError> {
+        let db = Database::memory()?;
+
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, app_type, session, cost, ts) in [
+                ("req1", "codex", "codex_019a-c1", "0.10", 1000),
+                ("req2", "codex", "codex_019a-c1", "0.20", 2000),
+                // 同一会话经另一入口记录
+                ("req3", "openai", "codex_019a-c1", "0.05", 3000),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, session_id, created_at
+                    ) VALUES (?, 'p1', ?, 'gpt-5-codex', 100, 50, ?, 100, 200, ?, ?)",
+                    params![id, app_type, cost, session, ts],
+                )?;
+            }
+        }
+
+        let session_stats = db.get_session_stats(None, None)?;
+        assert_eq!(session_stats.len(), 2);
+        let merged = merge_stats_by_local_session(&session_stats);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].session_id, "019a-c1");
+        assert_eq!(merged[0].request_count, 3);
+        assert_eq!(merged[0].total_cost, "0.350000");
+        assert_eq!(merged[0].first_request_at, 1000);
+        assert_eq!(merged[0].last_request_at, 3000);
+
+        let sessions = vec![SessionMeta {
+            provider_id: "codex".to_string(),
+            session_id: "019a-c1".to_string(),
+            title: None,
+            summary: None,
+            project_dir: Some("/work/repo-y".to_string()),
+            created_at: None,
+            last_active_at: None,
+            source_path: None,
+            resume_command: None,
+            total_cost: None,
+            request_count: None,
+        }];
+        let projects = aggregate_project_stats(&session_stats, &sessions);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].project_dir.as_deref(), Some("/work/repo-y"));
+        assert_eq!(projects[0].session_count, 1);
+        assert_eq!(projects[0].request_count, 3);
+
+        Ok(())
+    }
+
     #[test]
     fn test_model_pricing_matching() -> Result<(), AppError> {
         let db = Database::memory()?;
@@ -0,0 +1,630 @@
+//! 会话使用统计
+//!
+//! 基于各应用解析出的完整会话记录（含工具调用与结果）计算单个会话及汇总的使用情况：
+//! 对话轮数、各工具的调用次数与失败次数、涉及的文件、持续时长以及所用模型。
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{load_transcript, scan_sessions, SessionBlock, SessionMessage, SessionMeta};
+
+/// 应用注入而非用户输入的消息（如 Codex 的环境信息与项目指令、Claude 的斜杠命令及其输出），
+/// 不计入对话轮数
+const INJECTED_CONTEXT_PREFIXES: [&str; 6] = [
+    "<environment_context>",
+    "<user_instructions>",
+    "<command-name>",
+    "<command-message>",
+    "<local-command-stdout>",
+    "<local-command-caveat>",
+];
+
+/// 统计范围
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAnalyticsQuery {
+    #[serde(default)]
+    pub provider_id: Option<String>,
+    #[serde(default)]
+    pub project_dir: Option<String>,
+    /// 仅统计单个会话
+    #[serde(default)]
+    pub source_path: Option<String>,
+    /// 仅统计该时间之后仍有活动的会话（毫秒时间戳）
+    #[serde(default)]
+    pub since: Option<i64>,
+}
+
+/// 单个工具的使用情况
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolUsage {
+    pub name: String,
+    pub calls: u64,
+    pub errors: u64,
+}
+
+/// 单个模型的使用情况
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelUsage {
+    pub model: String,
+    /// 该模型生成的助手消息数
+    pub messages: u64,
+    /// 使用过该模型的会话数
+    pub sessions: u64,
+}
+
+/// 单个会话的统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAnalytics {
+    pub provider_id: String,
+    pub session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<i64>,
+    /// 首条与末条消息之间的时长（毫秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    /// 用户发起的对话轮数
+    pub turns: u64,
+    /// 含文本内容的消息数
+    pub messages: u64,
+    pub tool_calls: u64,
+    pub failed_tool_calls: u64,
+    /// 按调用次数降序
+    pub tools: Vec<ToolUsage>,
+    /// 工具调用涉及的文件（保持首次出现顺序）
+    pub files_touched: Vec<String>,
+    pub models: Vec<ModelUsage>,
+}
+
+/// 单个应用的汇总
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderAnalytics {
+    pub provider_id: String,
+    pub sessions: u64,
+    pub turns: u64,
+    pub tool_calls: u64,
+    pub failed_tool_calls: u64,
+    pub duration_ms: i64,
+}
+
+/// 统计范围内全部会话的汇总
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAnalyticsSummary {
+    pub sessions: u64,
+    pub turns: u64,
+    pub messages: u64,
+    pub tool_calls: u64,
+    pub failed_tool_calls: u64,
+    pub duration_ms: i64,
+    /// 涉及的不同文件数（按项目目录区分）
+    pub files_touched: u64,
+    pub tools: Vec<ToolUsage>,
+    pub models: Vec<ModelUsage>,
+    pub providers: Vec<ProviderAnalytics>,
+    /// 无法解析而跳过的会话数
+    pub failed: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAnalyticsReport {
+    pub summary: SessionAnalyticsSummary,
+    /// 按开始时间倒序
+    pub sessions: Vec<SessionAnalytics>,
+}
+
+/// 统计符合条件的会话
+pub fn analyze_sessions(query: &SessionAnalyticsQuery) -> SessionAnalyticsReport {
+    let mut sessions = Vec::new();
+    let mut failed = 0;
+
+    for meta in scan_sessions() {
+        if !matches_query(&meta, query) {
+            continue;
+        }
+        let Some(source_path) = meta.source_path.as_deref() else {
+            continue;
+        };
+        match load_transcript(&meta.provider_id, source_path) {
+            Ok(messages) => sessions.push(analyze_session(&meta, &messages)),
+            Err(e) => {
+                log::warn!("Failed to analyze session {source_path}: {e}");
+                failed += 1;
+            }
+        }
+    }
+
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.started_at));
+    let mut summary = summarize(&sessions);
+    summary.failed = failed;
+
+    SessionAnalyticsReport { summary, sessions }
+}
+
+/// 统计单个会话
+pub fn analyze_session(meta: &SessionMeta, messages: &[SessionMessage]) -> SessionAnalytics {
+    let mut turns = 0;
+    let mut text_messages = 0;
+    let mut tools: Vec<ToolUsage> = Vec::new();
+    let mut tool_names: HashMap<&str, usize> = HashMap::new();
+    let mut failed_tool_calls = 0;
+    let mut models: Vec<ModelUsage> = Vec::new();
+
+    for message in messages {
+        // Claude 的工具结果以 user 角色的消息返回，content 中是展开后的结果文本，不算文本消息
+        let is_text = message
+            .blocks
+            .iter()
+            .any(|block| matches!(block, SessionBlock::Text { .. }))
+            && !message
+                .blocks
+                .iter()
+                .any(|block| matches!(block, SessionBlock::ToolResult { .. }));
+        let text = message.content.trim();
+        if is_text && !text.is_empty() {
+            text_messages += 1;
+            if message.role == "user"
+                && !INJECTED_CONTEXT_PREFIXES
+                    .iter()
+                    .any(|prefix| text.starts_with(prefix))
+            {
+                turns += 1;
+            }
+        }
+
+        if message.role == "assistant" {
+            if let Some(model) = message.model.as_deref() {
+                match models.iter_mut().find(|usage| usage.model == model) {
+                    Some(usage) => usage.messages += 1,
+                    None => models.push(ModelUsage {
+                        model: model.to_string(),
+                        messages: 1,
+                        sessions: 1,
+                    }),
+                }
+            }
+        }
+
+        for block in &message.blocks {
+            match block {
+                SessionBlock::ToolUse { id, name, .. } => {
+                    let index = match tools.iter().position(|usage| &usage.name == name) {
+                        Some(index) => index,
+                        None => {
+                            tools.push(ToolUsage {
+                                name: name.clone(),
+                                calls: 0,
+                                errors: 0,
+                            });
+                            tools.len() - 1
+                        }
+                    };
+                    tools[index].calls += 1;
+                    if let Some(id) = id {
+                        tool_names.insert(id, index);
+                    }
+                }
+                SessionBlock::ToolResult {
+                    tool_use_id,
+                    is_error: true,
+                    ..
+                } => {
+                    failed_tool_calls += 1;
+                    if let Some(index) = tool_use_id.as_deref().and_then(|id| tool_names.get(id)) {
+                        tools[*index].errors += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut timestamps = messages.iter().filter_map(|message| message.ts);
+    let first = timestamps.next();
+    let (started_at, ended_at) = match first {
+        Some(first) => {
+            let (min, max) =
+                timestamps.fold((first, first), |(min, max), ts| (min.min(ts), max.max(ts)));
+            (Some(min), Some(max))
+        }
+        None => (meta.created_at, meta.last_active_at),
+    };
+    let duration_ms = started_at
+        .zip(ended_at)
+        .map(|(start, end)| (end - start).max(0));
+
+    sort_tools(&mut tools);
+    models.sort_by(|a, b| b.messages.cmp(&a.messages).then(a.model.cmp(&b.model)));
+
+    SessionAnalytics {
+        provider_id: meta.provider_id.clone(),
+        session_id: meta.session_id.clone(),
+        source_path: meta.source_path.clone(),
+        title: meta.title.clone(),
+        project_dir: meta.project_dir.clone(),
+        started_at,
+        ended_at,
+        duration_ms,
+        turns,
+        messages: text_messages,
+        tool_calls: tools.iter().map(|usage| usage.calls).sum(),
+        failed_tool_calls,
+        tools,
+        files_touched: touched_files(messages),
+        models,
+    }
+}
+
+/// 从工具调用参数中收集涉及的文件路径（保持首次出现顺序）
+pub(crate) fn touched_files(messages: &[SessionMessage]) -> Vec<String> {
+    const PATH_KEYS: [&str; 5] = [
+        "file_path",
+        "filePath",
+        "notebook_path",
+        "absolute_path",
+        "path",
+    ];
+    const PATCH_MARKERS: [&str; 3] = ["*** Update File: ", "*** Add File: ", "*** Delete File: "];
+
+    let mut files: Vec<String> = Vec::new();
+    let mut push = |file: &str| {
+        let file = file.trim();
+        if !file.is_empty() && !files.iter().any(|f| f == file) {
+            files.push(file.to_string());
+        }
+    };
+
+    for block in messages.iter().flat_map(|message| &message.blocks) {
+        let SessionBlock::ToolUse { input, .. } = block else {
+            continue;
+        };
+        for key in PATH_KEYS {
+            if let Some(path) = input.get(key).and_then(Value::as_str) {
+                push(path);
+            }
+        }
+        // Codex 的 apply_patch 以补丁文本作为输入
+        let patch = input
+            .as_str()
+            .or_else(|| input.get("input").and_then(Value::as_str));
+        for line in patch.into_iter().flat_map(str::lines) {
+            for marker in PATCH_MARKERS {
+                if let Some(path) = line.strip_prefix(marker) {
+                    push(path);
+                }
+            }
+        }
+    }
+
+    files
+}
+
+fn matches_query(meta: &SessionMeta, query: &SessionAnalyticsQuery) -> bool {
+    if query
+        .provider_id
+        .as_deref()
+        .is_some_and(|provider| provider != meta.provider_id)
+    {
+        return false;
+    }
+    if query
+        .project_dir
+        .as_deref()
+        .is_some_and(|dir| meta.project_dir.as_deref() != Some(dir))
+    {
+        return false;
+    }
+    if query
+        .source_path
+        .as_deref()
+        .is_some_and(|path| meta.source_path.as_deref() != Some(path))
+    {
+        return false;
+    }
+    if let Some(since) = query.since {
+        if meta.last_active_at.or(meta.created_at).unwrap_or(0) < since {
+            return false;
+        }
+    }
+    true
+}
+
+fn summarize(sessions: &[SessionAnalytics]) -> SessionAnalyticsSummary {
+    let mut summary = SessionAnalyticsSummary::default();
+    let mut tools: Vec<ToolUsage> = Vec::new();
+    let mut models: Vec<ModelUsage> = Vec::new();
+    let mut files: HashSet<(Option<&str>, &str)> = HashSet::new();
+
+    for session in sessions {
+        summary.sessions += 1;
+        summary.turns += session.turns;
+        summary.messages += session.messages;
+        summary.tool_calls += session.tool_calls;
+        summary.failed_tool_calls += session.failed_tool_calls;
+        summary.duration_ms += session.duration_ms.unwrap_or(0);
+
+        for usage in &session.tools {
+            match tools.iter_mut().find(|total| total.name == usage.name) {
+                Some(total) => {
+                    total.calls += usage.calls;
+                    total.errors += usage.errors;
+                }
+                None => tools.push(usage.clone()),
+            }
+        }
+        for usage in &session.models {
+            match models.iter_mut().find(|total| total.model == usage.model) {
+                Some(total) => {
+                    total.messages += usage.messages;
+                    total.sessions += 1;
+                }
+                None => models.push(usage.clone()),
+            }
+        }
+        for file in &session.files_touched {
+            files.insert((session.project_dir.as_deref(), file));
+        }
+
+        let provider = match summary
+            .providers
+            .iter_mut()
+            .find(|total| total.provider_id == session.provider_id)
+        {
+            Some(provider) => provider,
+            None => {
+                summary.providers.push(ProviderAnalytics {
+                    provider_id: session.provider_id.clone(),
+                    ..Default::default()
+                });
+                summary.providers.last_mut().expect("just pushed")
+            }
+        };
+        provider.sessions += 1;
+        provider.turns += session.turns;
+        provider.tool_calls += session.tool_calls;
+        provider.failed_tool_calls += session.failed_tool_calls;
+        provider.duration_ms += session.duration_ms.unwrap_or(0);
+    }
+
+    sort_tools(&mut tools);
+    models.sort_by(|a, b| b.sessions.cmp(&a.sessions).then(a.model.cmp(&b.model)));
+    summary.providers.sort_by(|a, b| {
+        b.sessions
+            .cmp(&a.sessions)
+            .then(a.provider_id.cmp(&b.provider_id))
+    });
+    summary.files_touched = files.len() as u64;
+    summary.tools = tools;
+    summary.models = models;
+    summary
+}
+
+fn sort_tools(tools: &mut [ToolUsage]) {
+    tools.sort_by(|a, b| b.calls.cmp(&a.calls).then(a.name.cmp(&b.name)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn meta(provider_id: &str, session_id: &str) -> SessionMeta {
+        SessionMeta {
+            provider_id: provider_id.to_string(),
+            session_id: session_id.to_string(),
+            title: None,
+            summary: None,
+            project_dir: Some("/repo".to_string()),
+            created_at: None,
+            last_active_at: None,
+            source_path: None,
+            resume_command: None,
+            total_cost: None,
+            request_count: None,
+        }
+    }
+
+    fn message(
+        role: &str,
+        content: &str,
+        ts: i64,
+        mut blocks: Vec<SessionBlock>,
+    ) -> SessionMessage {
+        if !content.is_empty() {
+            blocks.insert(
+                0,
+                SessionBlock::Text {
+                    text: content.to_string(),
+                },
+            );
+        }
+        SessionMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            ts: Some(ts),
+            blocks,
+            model: (role == "assistant").then(|| "gpt-5-codex".to_string()),
+        }
+    }
+
+    fn tool_use(id: &str, name: &str, input: Value) -> SessionBlock {
+        SessionBlock::ToolUse {
+            id: Some(id.to_string()),
+            name: name.to_string(),
+            input,
+        }
+    }
+
+    fn tool_result(id: &str, is_error: bool) -> SessionBlock {
+        SessionBlock::ToolResult {
+            tool_use_id: Some(id.to_string()),
+            content: String::new(),
+            is_error,
+        }
+    }
+
+    fn transcript() -> Vec<SessionMessage> {
+        vec![
+            message(
+                "user",
+                "<environment_context>cwd</environment_context>",
+                1_000,
+                vec![],
+            ),
+            message("user", "Fix the build", 2_000, vec![]),
+            message(
+                "assistant",
+                "",
+                3_000,
+                vec![tool_use(
+                    "a",
+                    "shell",
+                    json!({ "command": ["cargo", "build"] }),
+                )],
+            ),
+            message("tool", "", 4_000, vec![tool_result("a", true)]),
+            message(
+                "assistant",
+                "",
+                5_000,
+                vec![tool_use(
+                    "b",
+                    "apply_patch",
+                    json!("*** Begin Patch\n*** Update File: src/lib.rs\n*** End Patch"),
+                )],
+            ),
+            message("tool", "", 6_000, vec![tool_result("b", false)]),
+            message(
+                "assistant",
+                "",
+                7_000,
+                vec![tool_use(
+                    "c",
+                    "shell",
+                    json!({ "command": ["cargo", "test"] }),
+                )],
+            ),
+            message("assistant", "Build fixed.", 9_000, vec![]),
+            message("user", "Thanks", 10_000, vec![]),
+        ]
+    }
+
+    #[test]
+    fn session_counts_turns_tools_errors_and_duration() {
+        let analytics = analyze_session(&meta("codex", "s1"), &transcript());
+
+        assert_eq!(analytics.turns, 2);
+        assert_eq!(analytics.messages, 4);
+        assert_eq!(analytics.tool_calls, 3);
+        assert_eq!(analytics.failed_tool_calls, 1);
+        assert_eq!(
+            analytics.tools,
+            vec![
+                ToolUsage {
+                    name: "shell".to_string(),
+                    calls: 2,
+                    errors: 1,
+                },
+                ToolUsage {
+                    name: "apply_patch".to_string(),
+                    calls: 1,
+                    errors: 0,
+                },
+            ]
+        );
+        assert_eq!(analytics.files_touched, vec!["src/lib.rs".to_string()]);
+        assert_eq!(analytics.started_at, Some(1_000));
+        assert_eq!(analytics.duration_ms, Some(9_000));
+        assert_eq!(analytics.models.len(), 1);
+        assert_eq!(analytics.models[0].messages, 4);
+    }
+
+    #[test]
+    fn summary_merges_sessions_by_tool_model_and_provider() {
+        let sessions = vec![
+            analyze_session(&meta("codex", "s1"), &transcript()),
+            analyze_session(&meta("codex", "s2"), &transcript()),
+            analyze_session(
+                &meta("claude", "s3"),
+                &[message("user", "Hello", 0, vec![])],
+            ),
+        ];
+        let summary = summarize(&sessions);
+
+        assert_eq!(summary.sessions, 3);
+        assert_eq!(summary.turns, 5);
+        assert_eq!(summary.tool_calls, 6);
+        assert_eq!(summary.failed_tool_calls, 2);
+        assert_eq!(summary.duration_ms, 18_000);
+        assert_eq!(summary.files_touched, 1);
+        assert_eq!(summary.tools[0].name, "shell");
+        assert_eq!(summary.tools[0].calls, 4);
+        assert_eq!(summary.models[0].sessions, 2);
+        assert_eq!(summary.providers[0].provider_id, "codex");
+        assert_eq!(summary.providers[0].sessions, 2);
+        assert_eq!(summary.providers[1].turns, 1);
+    }
+
+    #[test]
+    fn claude_tool_results_and_commands_are_not_turns() {
+        let tool_result = SessionMessage {
+            role: "user".to_string(),
+            content: "Compiling cc-switch\nerror: build failed".to_string(),
+            ts: Some(3_000),
+            blocks: vec![SessionBlock::ToolResult {
+                tool_use_id: Some("toolu_1".to_string()),
+                content: "Compiling cc-switch\nerror: build failed".to_string(),
+                is_error: true,
+            }],
+            model: None,
+        };
+        let messages = vec![
+            message("user", "Fix the build", 1_000, vec![]),
+            message(
+                "assistant",
+                "",
+                2_000,
+                vec![tool_use(
+                    "toolu_1",
+                    "Bash",
+                    json!({ "command": "cargo build" }),
+                )],
+            ),
+            tool_result,
+            message("assistant", "The build is fixed.", 4_000, vec![]),
+            message(
+                "user",
+                "<command-name>/cost</command-name>\n<command-message>cost</command-message>",
+                5_000,
+                vec![],
+            ),
+            message(
+                "user",
+                "<local-command-stdout>Total cost: $0.01</local-command-stdout>",
+                6_000,
+                vec![],
+            ),
+        ];
+        let analytics = analyze_session(&meta("claude", "s4"), &messages);
+
+        assert_eq!(analytics.turns, 1);
+        assert_eq!(analytics.messages, 4);
+        assert_eq!(analytics.tool_calls, 1);
+        assert_eq!(analytics.failed_tool_calls, 1);
+    }
+}
@@ -0,0 +1,694 @@
+//! 会话导出
+//!
+//! 将会话记录导出为 Markdown、自包含 HTML，或打包为分享包（zip：Markdown + HTML + 结构化 JSON），
+//! 便于附加到 PR 或事故报告。可选脱敏：替换常见密钥 / 令牌，以及项目目录与用户主目录等绝对路径。
+
+use std::io::Write;
+
+use chrono::{Local, TimeZone};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{SessionBlock, SessionMessage, SessionMeta};
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionExportFormat {
+    Markdown,
+    Html,
+    /// zip 分享包
+    Bundle,
+}
+
+/// 导出选项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionExportOptions {
+    /// 脱敏密钥与绝对路径
+    pub redact: bool,
+    /// 包含思考过程
+    pub include_thinking: bool,
+    /// 包含工具调用与结果
+    pub include_tools: bool,
+}
+
+impl Default for SessionExportOptions {
+    fn default() -> Self {
+        Self {
+            redact: false,
+            include_thinking: true,
+            include_tools: true,
+        }
+    }
+}
+
+/// 分享包中的结构化会话数据
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionBundleData<'a> {
+    session: &'a SessionMeta,
+    messages: &'a [SessionMessage],
+}
+
+/// 导出会话，返回文件内容
+pub fn export_session(
+    meta: &SessionMeta,
+    messages: &[SessionMessage],
+    format: SessionExportFormat,
+    options: &SessionExportOptions,
+) -> Result<Vec<u8>, String> {
+    let (meta, messages) = prepare(meta, messages, options);
+    match format {
+        SessionExportFormat::Markdown => Ok(render_markdown(&meta, &messages).into_bytes()),
+        SessionExportFormat::Html => Ok(render_html(&meta, &messages).into_bytes()),
+        SessionExportFormat::Bundle => render_bundle(&meta, &messages),
+    }
+}
+
+/// 按选项过滤内容块并脱敏
+fn prepare(
+    meta: &SessionMeta,
+    messages: &[SessionMessage],
+    options: &SessionExportOptions,
+) -> (SessionMeta, Vec<SessionMessage>) {
+    let redactor = options
+        .redact
+        .then(|| Redactor::new(meta.project_dir.as_deref()));
+    let redact = |text: &str| match &redactor {
+        Some(redactor) => redactor.apply(text),
+        None => text.to_string(),
+    };
+
+    let mut meta = meta.clone();
+    if redactor.is_some() {
+        meta.title = meta.title.as_deref().map(redact);
+        meta.summary = meta.summary.as_deref().map(redact);
+        meta.project_dir = meta.project_dir.as_deref().map(redact);
+        meta.source_path = None;
+    }
+
+    let messages = messages
+        .iter()
+        .filter_map(|message| {
+            let blocks: Vec<SessionBlock> = message_blocks(message)
+                .into_iter()
+                .filter(|block| match block {
+                    SessionBlock::Thinking { .. } => options.include_thinking,
+                    SessionBlock::ToolUse { .. } | SessionBlock::ToolResult { .. } => {
+                        options.include_tools
+                    }
+                    _ => true,
+                })
+                .map(|block| match (&redactor, block) {
+                    (None, block) => block,
+                    // 图片可能包含敏感信息，脱敏时不导出
+                    (Some(_), SessionBlock::Image { .. }) => SessionBlock::Text {
+                        text: "[image omitted]".to_string(),
+                    },
+                    (Some(_), SessionBlock::Text { text }) => SessionBlock::Text {
+                        text: redact(&text),
+                    },
+                    (Some(_), SessionBlock::Thinking { text }) => SessionBlock::Thinking {
+                        text: redact(&text),
+                    },
+                    (Some(_), SessionBlock::ToolUse { id, name, input }) => SessionBlock::ToolUse {
+                        id,
+                        name,
+                        input: redact_value(&input, &redact),
+                    },
+                    (
+                        Some(_),
+                        SessionBlock::ToolResult {
+                            tool_use_id,
+                            content,
+                            is_error,
+                        },
+                    ) => SessionBlock::ToolResult {
+                        tool_use_id,
+                        content: redact(&content),
+                        is_error,
+                    },
+                })
+                .collect();
+            if blocks.is_empty() {
+                return None;
+            }
+            // 纯文本内容按保留的文本块重建，原 content 可能包含已过滤的工具输出
+            let content = blocks
+                .iter()
+                .filter_map(|block| match block {
+                    SessionBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some(SessionMessage {
+                role: message.role.clone(),
+                content,
+                ts: message.ts,
+                blocks,
+                model: message.model.clone(),
+            })
+        })
+        .collect();
+
+    (meta, messages)
+}
+
+/// 未提供结构化内容块的消息以纯文本作为唯一内容块
+fn message_blocks(message: &SessionMessage) -> Vec<SessionBlock> {
+    if !message.blocks.is_empty() {
+        return message.blocks.clone();
+    }
+    if message.content.trim().is_empty() {
+        return Vec::new();
+    }
+    vec![SessionBlock::Text {
+        text: message.content.clone(),
+    }]
+}
+
+fn redact_value(value: &Value, redact: &dyn Fn(&str) -> String) -> Value {
+    match value {
+        Value::String(text) => Value::String(redact(text)),
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|v| redact_value(v, redact)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), redact_value(v, redact)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+// ============================================================================
+// 脱敏
+// ============================================================================
+
+static SECRET_RULES: Lazy<Vec<(Regex, &'static str)>> = Lazy::new(|| {
+    let rule = |pattern: &str, replacement| {
+        (
+            Regex::new(pattern).expect("invalid redaction pattern"),
+            replacement,
+        )
+    };
+    vec![
+        rule(
+            r"(?s)-----BEGIN [A-Z ]*PRIVATE KEY-----.*?-----END [A-Z ]*PRIVATE KEY-----",
+            "[REDACTED PRIVATE KEY]",
+        ),
+        rule(r"\bsk-[A-Za-z0-9_-]{20,}", "[REDACTED]"),
+        rule(r"\bAKIA[0-9A-Z]{16}\b", "[REDACTED]"),
+        rule(
+            r"\b(gh[pousr]_[A-Za-z0-9]{30,}|github_pat_[A-Za-z0-9_]{30,})",
+            "[REDACTED]",
+        ),
+        rule(r"\bxox[abprs]-[A-Za-z0-9-]{10,}", "[REDACTED]"),
+        rule(r"\bAIza[0-9A-Za-z_-]{35}", "[REDACTED]"),
+        rule(
+            r"\beyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}",
+            "[REDACTED]",
+        ),
+        rule(
+            r"(?i)(\bbearer\s+)[A-Za-z0-9._~+/=-]{16,}",
+            "${1}[REDACTED]",
+        ),
+        rule(
+            r#"(?i)(\b[A-Z0-9_]*(?:API_?KEY|SECRET|TOKEN|PASSWORD|PASSWD)[A-Z0-9_]*["']?\s*[:=]\s*["']?)[^\s"',]{6,}"#,
+            "${1}[REDACTED]",
+        ),
+    ]
+});
+
+static HOME_PATH: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(/Users/[^/\s]+|/home/[^/\s]+|[A-Za-z]:\\Users\\[^\\\s]+)")
+        .expect("invalid home path pattern")
+});
+
+struct Redactor {
+    /// 按长度降序替换的绝对路径前缀
+    paths: Vec<(String, &'static str)>,
+}
+
+impl Redactor {
+    fn new(project_dir: Option<&str>) -> Self {
+        let mut paths = Vec::new();
+        if let Some(project) = project_dir.filter(|p| !p.trim().is_empty()) {
+            paths.push((
+                project.trim_end_matches(['/', '\\']).to_string(),
+                "<project>",
+            ));
+        }
+        if let Some(home) = dirs::home_dir() {
+            let home = home.to_string_lossy().to_string();
+            if !home.is_empty() && home != "/" {
+                paths.push((home, "~"));
+            }
+        }
+        paths.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+        Self { paths }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut result = text.to_string();
+        for (regex, replacement) in SECRET_RULES.iter() {
+            result = regex.replace_all(&result, *replacement).into_owned();
+        }
+        for (path, replacement) in &self.paths {
+            result = result.replace(path.as_str(), replacement);
+        }
+        HOME_PATH.replace_all(&result, "~").into_owned()
+    }
+}
+
+// ============================================================================
+// Markdown
+// ============================================================================
+
+fn render_markdown(meta: &SessionMeta, messages: &[SessionMessage]) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("# {}\n\n", session_title(meta)));
+    for (label, value) in header_fields(meta) {
+        out.push_str(&format!("- **{label}**: {value}\n"));
+    }
+    out.push_str("\n---\n");
+
+    for message in messages {
+        out.push_str(&format!("\n## {}", role_label(&message.role)));
+        if let Some(ts) = message.ts {
+            out.push_str(&format!(" · {}", format_ts(ts)));
+        }
+        out.push_str("\n\n");
+
+        for block in &message.blocks {
+            match block {
+                SessionBlock::Text { text } => {
+                    out.push_str(text.trim_end());
+                    out.push_str("\n\n");
+                }
+                SessionBlock::Thinking { text } => {
+                    out.push_str("<details><summary>Thinking</summary>\n\n");
+                    out.push_str(&fenced(text, ""));
+                    out.push_str("</details>\n\n");
+                }
+                SessionBlock::ToolUse { name, input, .. } => {
+                    out.push_str(&format!(
+                        "<details><summary>Tool call: {name}</summary>\n\n"
+                    ));
+                    out.push_str(&fenced(&format_input(input), "json"));
+                    out.push_str("</details>\n\n");
+                }
+                SessionBlock::ToolResult {
+                    content, is_error, ..
+                } => {
+                    let summary = if *is_error {
+                        "Tool error"
+                    } else {
+                        "Tool result"
+                    };
+                    out.push_str(&format!("<details><summary>{summary}</summary>\n\n"));
+                    out.push_str(&fenced(content, ""));
+                    out.push_str("</details>\n\n");
+                }
+                SessionBlock::Image { media_type, source } => {
+                    out.push_str(&format!("![image]({})\n\n", image_src(media_type, source)));
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// 生成不会被内容中反引号截断的代码块
+fn fenced(content: &str, lang: &str) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in content.chars() {
+        if c == '`' {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{lang}\n{}\n{fence}\n", content.trim_end())
+}
+
+// ============================================================================
+// HTML
+// ============================================================================
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;max-width:960px;margin:2rem auto;padding:0 1rem;color:#1f2328;line-height:1.5}\
+header dl{display:grid;grid-template-columns:max-content 1fr;gap:.25rem 1rem;color:#59636e}\
+header dt{font-weight:600}\
+section.message{border:1px solid #d1d9e0;border-radius:8px;margin:1rem 0;padding:.75rem 1rem}\
+section.user{background:#f6f8fa}\
+.meta{font-size:.85rem;color:#59636e;margin-bottom:.5rem}\
+.role{font-weight:600;text-transform:capitalize}\
+pre{white-space:pre-wrap;word-break:break-word;margin:.25rem 0}\
+pre.code{background:#f6f8fa;border-radius:6px;padding:.5rem .75rem;font-size:.85rem}\
+details{margin:.5rem 0}summary{cursor:pointer;color:#59636e}\
+details.error summary{color:#d1242f}\
+img{max-width:100%}";
+
+fn render_html(meta: &SessionMeta, messages: &[SessionMessage]) -> String {
+    let title = escape_html(&session_title(meta));
+    let mut out = String::new();
+    out.push_str(&format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<header>\n<h1>{title}</h1>\n<dl>\n"
+    ));
+    for (label, value) in header_fields(meta) {
+        out.push_str(&format!(
+            "<dt>{}</dt><dd>{}</dd>\n",
+            escape_html(label),
+            escape_html(&value)
+        ));
+    }
+    out.push_str("</dl>\n</header>\n<main>\n");
+
+    for message in messages {
+        let role_class = escape_html(&message.role.to_lowercase());
+        out.push_str(&format!(
+            "<section class=\"message {role_class}\">\n<div class=\"meta\"><span class=\"role\">{}</span>",
+            escape_html(role_label(&message.role))
+        ));
+        if let Some(ts) = message.ts {
+            out.push_str(&format!(" · {}", escape_html(&format_ts(ts))));
+        }
+        out.push_str("</div>\n");
+
+        for block in &message.blocks {
+            match block {
+                SessionBlock::Text { text } => {
+                    out.push_str(&format!("<pre>{}</pre>\n", escape_html(text.trim_end())));
+                }
+                SessionBlock::Thinking { text } => {
+                    out.push_str(&format!(
+                        "<details><summary>Thinking</summary><pre class=\"code\">{}</pre></details>\n",
+                        escape_html(text.trim_end())
+                    ));
+                }
+                SessionBlock::ToolUse { name, input, .. } => {
+                    out.push_str(&format!(
+                        "<details><summary>Tool call: {}</summary><pre class=\"code\">{}</pre></details>\n",
+                        escape_html(name),
+                        escape_html(&format_input(input))
+                    ));
+                }
+                SessionBlock::ToolResult {
+                    content, is_error, ..
+                } => {
+                    let (class, summary) = if *is_error {
+                        (" class=\"error\"", "Tool error")
+                    } else {
+                        ("", "Tool result")
+                    };
+                    out.push_str(&format!(
+                        "<details{class}><summary>{summary}</summary><pre class=\"code\">{}</pre></details>\n",
+                        escape_html(content.trim_end())
+                    ));
+                }
+                SessionBlock::Image { media_type, source } => {
+                    out.push_str(&format!(
+                        "<img src=\"{}\" alt=\"image\">\n",
+                        escape_html(&image_src(media_type, source))
+                    ));
+                }
+            }
+        }
+        out.push_str("</section>\n");
+    }
+
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// 分享包
+// ============================================================================
+
+fn render_bundle(meta: &SessionMeta, messages: &[SessionMessage]) -> Result<Vec<u8>, String> {
+    let data = serde_json::to_string_pretty(&SessionBundleData {
+        session: meta,
+        messages,
+    })
+    .map_err(|e| format!("Failed to serialize session: {e}"))?;
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    for (name, content) in [
+        ("transcript.md", render_markdown(meta, messages)),
+        ("transcript.html", render_html(meta, messages)),
+        ("session.json", data),
+    ] {
+        writer
+            .start_file(name, options)
+            .map_err(|e| format!("Failed to write bundle: {e}"))?;
+        writer
+            .write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to write bundle: {e}"))?;
+    }
+    let cursor = writer
+        .finish()
+        .map_err(|e| format!("Failed to write bundle: {e}"))?;
+    Ok(cursor.into_inner())
+}
+
+// ============================================================================
+// 公共
+// ============================================================================
+
+fn session_title(meta: &SessionMeta) -> String {
+    match &meta.title {
+        Some(title) => format!("{title} ({})", meta.session_id),
+        None => meta.session_id.clone(),
+    }
+}
+
+fn header_fields(meta: &SessionMeta) -> Vec<(&'static str, String)> {
+    let mut fields = vec![
+        ("Provider", meta.provider_id.clone()),
+        ("Session", meta.session_id.clone()),
+    ];
+    if let Some(project) = &meta.project_dir {
+        fields.push(("Project", project.clone()));
+    }
+    if let Some(ts) = meta.created_at {
+        fields.push(("Started", format_ts(ts)));
+    }
+    if let Some(ts) = meta.last_active_at {
+        fields.push(("Last active", format_ts(ts)));
+    }
+    if let Some(command) = &meta.resume_command {
+        fields.push(("Resume", command.clone()));
+    }
+    fields
+}
+
+fn role_label(role: &str) -> &str {
+    match role {
+        "user" => "User",
+        "assistant" => "Assistant",
+        "tool" => "Tool",
+        "system" => "System",
+        other => other,
+    }
+}
+
+fn format_ts(ts: i64) -> String {
+    Local
+        .timestamp_millis_opt(ts)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+fn format_input(input: &Value) -> String {
+    match input {
+        Value::String(text) => text.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string()),
+    }
+}
+
+/// 图片来源：URL / data URL 原样使用，裸 base64 补全为 data URL
+fn image_src(media_type: &Option<String>, source: &str) -> String {
+    if source.starts_with("data:") || source.contains("://") {
+        return source.to_string();
+    }
+    let media_type = media_type.as_deref().unwrap_or("image/png");
+    format!("data:{media_type};base64,{source}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn meta() -> SessionMeta {
+        SessionMeta {
+            provider_id: "claude".to_string(),
+            session_id: "abc".to_string(),
+            title: Some("app".to_string()),
+            summary: None,
+            project_dir: Some("/Users/alice/work/app".to_string()),
+            created_at: None,
+            last_active_at: None,
+            source_path: Some("/Users/alice/.claude/projects/x.jsonl".to_string()),
+            resume_command: Some("claude --resume abc".to_string()),
+            total_cost: None,
+            request_count: None,
+        }
+    }
+
+    fn messages() -> Vec<SessionMessage> {
+        vec![
+            SessionMessage {
+                role: "user".to_string(),
+                content:
+                    "Use key sk-ant-REDACTED in /Users/alice/work/app/src"
+                        .to_string(),
+                ts: None,
+                blocks: Vec::new(),
+                model: None,
+            },
+            SessionMessage {
+                role: "assistant".to_string(),
+                content: String::new(),
+                ts: None,
+                blocks: vec![
+                    SessionBlock::Thinking {
+                        text: "plan".to_string(),
+                    },
+                    SessionBlock::ToolUse {
+                        id: Some("t1".to_string()),
+                        name: "Bash".to_string(),
+                        input: json!({ "command": "cat /Users/bob/.env", "note": "```" }),
+                    },
+                    SessionBlock::ToolResult {
+                        tool_use_id: Some("t1".to_string()),
+                        content: "API_KEY=supersecretvalue <b>".to_string(),
+                        is_error: true,
+                    },
+                ],
+                model: Some("claude-sonnet-4".to_string()),
+            },
+        ]
+    }
+
+    fn export(format: SessionExportFormat, options: &SessionExportOptions) -> String {
+        String::from_utf8(export_session(&meta(), &messages(), format, options).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn markdown_includes_blocks_and_safe_fences() {
+        let md = export(
+            SessionExportFormat::Markdown,
+            &SessionExportOptions::default(),
+        );
+        assert!(md.starts_with("# app (abc)\n"));
+        assert!(md.contains("<summary>Tool call: Bash</summary>"));
+        assert!(md.contains("````json\n"));
+        assert!(md.contains("<summary>Tool error</summary>"));
+        assert!(md.contains("<summary>Thinking</summary>"));
+
+        let md = export(
+            SessionExportFormat::Markdown,
+            &SessionExportOptions {
+                include_thinking: false,
+                include_tools: false,
+                ..Default::default()
+            },
+        );
+        assert!(!md.contains("Thinking"));
+        assert!(!md.contains("Tool call"));
+        assert!(!md.contains("## Assistant"));
+    }
+
+    #[test]
+    fn redaction_replaces_secrets_and_paths() {
+        let md = export(
+            SessionExportFormat::Markdown,
+            &SessionExportOptions {
+                redact: true,
+                ..Default::default()
+            },
+        );
+        assert!(!md.contains("sk-ant-api03"));
+        assert!(!md.contains("supersecretvalue"));
+        assert!(md.contains("API_KEY=[REDACTED]"));
+        assert!(md.contains("<project>/src"));
+        assert!(md.contains("cat ~/.env"));
+        assert!(!md.contains("/Users/"));
+    }
+
+    #[test]
+    fn html_escapes_content() {
+        let html = export(SessionExportFormat::Html, &SessionExportOptions::default());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("&lt;b&gt;"));
+        assert!(!html.contains("<b>"));
+        assert!(html.contains("<details class=\"error\">"));
+    }
+
+    #[test]
+    fn bundle_omits_filtered_tool_output_from_session_json() {
+        // Claude 的工具结果消息：content 为展开后的工具输出
+        let mut messages = messages();
+        messages.push(SessionMessage {
+            role: "user".to_string(),
+            content: "API_KEY=supersecretvalue".to_string(),
+            ts: None,
+            blocks: vec![SessionBlock::ToolResult {
+                tool_use_id: Some("t1".to_string()),
+                content: "API_KEY=supersecretvalue".to_string(),
+                is_error: false,
+            }],
+            model: None,
+        });
+        let bundle = export_session(
+            &meta(),
+            &messages,
+            SessionExportFormat::Bundle,
+            &SessionExportOptions {
+                include_tools: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bundle)).unwrap();
+        let mut data = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("session.json").unwrap(), &mut data)
+            .unwrap();
+        let value: Value = serde_json::from_str(&data).unwrap();
+        let messages = value["messages"].as_array().unwrap();
+        assert!(!data.contains("supersecretvalue"));
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0]["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("Use key"));
+    }
+}
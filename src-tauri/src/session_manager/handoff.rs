@@ -0,0 +1,704 @@
+//! 跨应用会话接力
+//!
+//! 把某个应用的会话转换为 Claude Code / Codex 可直接恢复的原生会话文件，便于某个服务不可用时
+//! 换用另一个工具继续当前任务。各应用的工具集与思考签名互不兼容，转换时工具调用及结果压缩为
+//! 文本说明、思考过程丢弃；无法转换为原生会话时（缺少项目目录、没有对话文本或历史过长），
+//! 退化为一份精简的接力提示词，由目标应用读取后继续。
+
+use std::path::Path;
+
+use chrono::{Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::codex_config::get_codex_config_dir;
+use crate::config::{atomic_write, get_app_config_dir, get_claude_config_dir};
+
+use super::analytics::touched_files;
+use super::terminal::shell_escape;
+use super::{find_session, load_transcript, SessionBlock, SessionMessage, SessionMeta};
+
+/// 原生会话允许的最大文本量（字符），超出后目标应用恢复时大概率直接超出上下文窗口
+const MAX_NATIVE_CHARS: usize = 400_000;
+/// 工具调用参数摘要的最大长度
+const TOOL_INPUT_PREVIEW: usize = 200;
+/// 工具结果保留的最大长度
+const TOOL_RESULT_PREVIEW: usize = 600;
+/// 接力提示词中原始需求的最大长度
+const PROMPT_REQUEST_CHARS: usize = 4_000;
+/// 接力提示词中近期对话的字符预算
+const PROMPT_RECENT_CHARS: usize = 16_000;
+/// 接力提示词中列出的文件数量上限
+const PROMPT_MAX_FILES: usize = 50;
+
+/// 接力方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HandoffMode {
+    /// 优先转换为原生会话，无法转换时退化为接力提示词
+    #[default]
+    Auto,
+    /// 仅转换为原生会话
+    Native,
+    /// 仅生成接力提示词
+    Prompt,
+}
+
+/// 接力结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffResult {
+    pub target_app: String,
+    /// 实际采用的方式（native / prompt）
+    pub mode: HandoffMode,
+    /// 新会话 ID（仅原生会话）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// 生成的会话文件或提示词文件
+    pub file_path: String,
+    /// 在目标应用中继续任务的命令
+    pub command: String,
+    /// 启动目录（原会话的项目目录）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// 未能转换为原生会话的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    User,
+    Assistant,
+}
+
+/// 压缩后的对话轮次，相邻同角色的消息合并为一轮
+#[derive(Debug, Clone, PartialEq)]
+struct Turn {
+    role: Role,
+    text: String,
+    ts: Option<i64>,
+}
+
+/// 将会话接力到目标应用（`claude` / `codex`），返回生成的文件与启动命令
+pub fn handoff_session(
+    provider_id: &str,
+    source_path: &str,
+    target_app: &str,
+    mode: HandoffMode,
+) -> Result<HandoffResult, String> {
+    if !matches!(target_app, "claude" | "codex") {
+        return Err(format!("Unsupported handoff target: {target_app}"));
+    }
+    if provider_id == target_app {
+        return Err(format!(
+            "Session already belongs to {target_app}, use its resume command instead"
+        ));
+    }
+
+    let meta = find_session(provider_id, source_path)
+        .ok_or_else(|| format!("Session not found: {source_path}"))?;
+    let messages = load_transcript(provider_id, source_path)?;
+    let turns = build_turns(&messages, app_label(provider_id));
+    let cwd = meta
+        .project_dir
+        .clone()
+        .filter(|dir| Path::new(dir).is_dir());
+
+    let mut fallback_reason = None;
+    if mode != HandoffMode::Prompt {
+        match native_blocker(&messages, &turns, cwd.as_deref()) {
+            None => {
+                let cwd = cwd.unwrap_or_default();
+                return write_native_session(target_app, &turns, &cwd);
+            }
+            Some(reason) if mode == HandoffMode::Native => return Err(reason),
+            Some(reason) => fallback_reason = Some(reason),
+        }
+    }
+
+    let mut result = write_handoff_prompt(target_app, &meta, &messages, cwd)?;
+    result.fallback_reason = fallback_reason;
+    Ok(result)
+}
+
+/// 无法转换为原生会话的原因
+fn native_blocker(
+    messages: &[SessionMessage],
+    turns: &[Turn],
+    cwd: Option<&str>,
+) -> Option<String> {
+    if cwd.is_none() {
+        return Some("Project directory of the session is unknown or no longer exists".into());
+    }
+    if !messages
+        .iter()
+        .any(|message| message.role == "user" && !message.content.trim().is_empty())
+    {
+        return Some("Session has no user messages to convert".into());
+    }
+    let total: usize = turns.iter().map(|turn| turn.text.chars().count()).sum();
+    if total > MAX_NATIVE_CHARS {
+        return Some(format!(
+            "Session history is too large to replay ({total} characters)"
+        ));
+    }
+    None
+}
+
+fn write_native_session(
+    target_app: &str,
+    turns: &[Turn],
+    cwd: &str,
+) -> Result<HandoffResult, String> {
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp_millis();
+
+    let (path, lines, command) = if target_app == "codex" {
+        let started = Local
+            .timestamp_millis_opt(now)
+            .single()
+            .unwrap_or_else(Local::now);
+        let path = get_codex_config_dir()
+            .join("sessions")
+            .join(started.format("%Y").to_string())
+            .join(started.format("%m").to_string())
+            .join(started.format("%d").to_string())
+            .join(format!(
+                "rollout-{}-{session_id}.jsonl",
+                started.format("%Y-%m-%dT%H-%M-%S")
+            ));
+        (
+            path,
+            codex_rollout_lines(turns, &session_id, cwd, now),
+            format!("codex resume {session_id}"),
+        )
+    } else {
+        let path = get_claude_config_dir()
+            .join("projects")
+            .join(claude_project_key(cwd))
+            .join(format!("{session_id}.jsonl"));
+        (
+            path,
+            claude_session_lines(turns, &session_id, cwd, now),
+            format!("claude --resume {session_id}"),
+        )
+    };
+
+    write_lines(&path, &lines)?;
+    Ok(HandoffResult {
+        target_app: target_app.to_string(),
+        mode: HandoffMode::Native,
+        session_id: Some(session_id),
+        file_path: path.to_string_lossy().to_string(),
+        command,
+        cwd: Some(cwd.to_string()),
+        fallback_reason: None,
+    })
+}
+
+fn write_handoff_prompt(
+    target_app: &str,
+    meta: &SessionMeta,
+    messages: &[SessionMessage],
+    cwd: Option<String>,
+) -> Result<HandoffResult, String> {
+    let file_name = format!(
+        "{}-{}-{}.md",
+        meta.provider_id,
+        sanitize_file_stem(&meta.session_id),
+        Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = get_app_config_dir().join("handoff").join(file_name);
+    let prompt = build_handoff_prompt(meta, messages);
+    atomic_write(&path, prompt.as_bytes()).map_err(|e| e.to_string())?;
+
+    let instruction = shell_escape(&format!(
+        "Read the handoff notes at {} and continue the task from where it stopped.",
+        path.display()
+    ));
+    Ok(HandoffResult {
+        target_app: target_app.to_string(),
+        mode: HandoffMode::Prompt,
+        session_id: None,
+        file_path: path.to_string_lossy().to_string(),
+        command: format!("{target_app} {instruction}"),
+        cwd,
+        fallback_reason: None,
+    })
+}
+
+/// 将消息压缩为交替的用户 / 助手轮次：
+/// 文本原样保留，工具调用与结果折叠为简短说明并归入助手一侧，思考过程丢弃
+fn build_turns(messages: &[SessionMessage], source_label: &str) -> Vec<Turn> {
+    let mut turns: Vec<Turn> = Vec::new();
+
+    for message in messages {
+        let side = match message.role.as_str() {
+            "user" => Role::User,
+            "system" | "developer" => continue,
+            _ => Role::Assistant,
+        };
+
+        let mut pieces: Vec<(Role, String)> = Vec::new();
+        if message.blocks.is_empty() {
+            pieces.push((side, message.content.trim().to_string()));
+        }
+        for block in &message.blocks {
+            match block {
+                SessionBlock::Text { text } => pieces.push((side, text.trim().to_string())),
+                SessionBlock::Thinking { .. } => {}
+                SessionBlock::ToolUse { name, input, .. } => pieces.push((
+                    Role::Assistant,
+                    format!("[Tool call] {name}: {}", tool_input_preview(input)),
+                )),
+                SessionBlock::ToolResult {
+                    content, is_error, ..
+                } => {
+                    let label = if *is_error {
+                        "[Tool error]"
+                    } else {
+                        "[Tool result]"
+                    };
+                    pieces.push((
+                        Role::Assistant,
+                        format!("{label} {}", clip(content.trim(), TOOL_RESULT_PREVIEW)),
+                    ));
+                }
+                SessionBlock::Image { .. } => pieces.push((side, "[Image omitted]".to_string())),
+            }
+        }
+
+        for (role, text) in pieces {
+            if text.is_empty() {
+                continue;
+            }
+            match turns.last_mut() {
+                Some(last) if last.role == role => {
+                    last.text.push_str("\n\n");
+                    last.text.push_str(&text);
+                }
+                _ => turns.push(Turn {
+                    role,
+                    text,
+                    ts: message.ts,
+                }),
+            }
+        }
+    }
+
+    // 说明会话来源；两个目标应用都要求会话以用户消息开头
+    let note = format!(
+        "[Handoff] This conversation was started in {source_label} and imported by CC Switch. \
+         Tool calls and their results are summarized as text; re-read files before editing them."
+    );
+    match turns.first_mut() {
+        Some(first) if first.role == Role::User => first.text = format!("{note}\n\n{}", first.text),
+        _ => {
+            let ts = turns.first().and_then(|turn| turn.ts);
+            turns.insert(
+                0,
+                Turn {
+                    role: Role::User,
+                    text: note,
+                    ts,
+                },
+            );
+        }
+    }
+
+    turns
+}
+
+/// Claude Code 会话：`~/.claude/projects/<项目目录编码>/<session-id>.jsonl`，每行一条消息并通过 parentUuid 串联
+fn claude_session_lines(turns: &[Turn], session_id: &str, cwd: &str, now: i64) -> Vec<Value> {
+    let mut parent: Option<String> = None;
+    let mut lines = Vec::with_capacity(turns.len());
+
+    for (index, turn) in turns.iter().enumerate() {
+        let uuid = uuid::Uuid::new_v4().to_string();
+        let message = match turn.role {
+            Role::User => json!({ "role": "user", "content": turn.text }),
+            Role::Assistant => json!({
+                "id": format!("msg_handoff_{index}"),
+                "type": "message",
+                "role": "assistant",
+                "model": "<synthetic>",
+                "content": [{ "type": "text", "text": turn.text }],
+                "stop_reason": "end_turn",
+                "stop_sequence": null,
+                "usage": { "input_tokens": 0, "output_tokens": 0 },
+            }),
+        };
+        lines.push(json!({
+            "parentUuid": parent,
+            "isSidechain": false,
+            "userType": "external",
+            "cwd": cwd,
+            "sessionId": session_id,
+            "version": env!("CARGO_PKG_VERSION"),
+            "type": role_name(turn.role),
+            "message": message,
+            "uuid": uuid,
+            "timestamp": format_timestamp(turn.ts.unwrap_or(now)),
+        }));
+        parent = Some(uuid);
+    }
+
+    lines
+}
+
+/// Codex rollout：首行 `session_meta`，随后每轮一条 `response_item` 消息
+fn codex_rollout_lines(turns: &[Turn], session_id: &str, cwd: &str, now: i64) -> Vec<Value> {
+    let started = format_timestamp(now);
+    let mut lines = vec![json!({
+        "timestamp": started,
+        "type": "session_meta",
+        "payload": {
+            "id": session_id,
+            "timestamp": started,
+            "cwd": cwd,
+            "originator": "cc_switch_handoff",
+            "cli_version": env!("CARGO_PKG_VERSION"),
+            "instructions": null,
+        },
+    })];
+
+    for turn in turns {
+        let content_type = match turn.role {
+            Role::User => "input_text",
+            Role::Assistant => "output_text",
+        };
+        lines.push(json!({
+            "timestamp": format_timestamp(turn.ts.unwrap_or(now)),
+            "type": "response_item",
+            "payload": {
+                "type": "message",
+                "role": role_name(turn.role),
+                "content": [{ "type": content_type, "text": turn.text }],
+            },
+        }));
+    }
+
+    lines
+}
+
+/// 精简的接力提示词：原始需求、涉及的文件与最近的对话
+fn build_handoff_prompt(meta: &SessionMeta, messages: &[SessionMessage]) -> String {
+    let mut out = String::from("# Task handoff\n\n");
+    out.push_str(&format!(
+        "This task was started in {} (session `{}`) and could not be resumed there. \
+         Continue it in this session.\n\n",
+        app_label(&meta.provider_id),
+        meta.session_id
+    ));
+    if let Some(dir) = &meta.project_dir {
+        out.push_str(&format!("- Project directory: `{dir}`\n"));
+    }
+    if let Some(summary) = meta.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        out.push_str(&format!("- Summary: {}\n", summary.trim()));
+    }
+    out.push('\n');
+
+    let texts: Vec<(&str, String)> = messages
+        .iter()
+        .filter(|message| matches!(message.role.as_str(), "user" | "assistant"))
+        .map(|message| (message.role.as_str(), message.content.trim().to_string()))
+        .filter(|(_, text)| !text.is_empty())
+        .collect();
+
+    if let Some((_, request)) = texts.iter().find(|(role, _)| *role == "user") {
+        out.push_str("## Original request\n\n");
+        out.push_str(&clip(request, PROMPT_REQUEST_CHARS));
+        out.push_str("\n\n");
+    }
+
+    let files = touched_files(messages);
+    if !files.is_empty() {
+        out.push_str("## Files touched so far\n\n");
+        for file in files.iter().take(PROMPT_MAX_FILES) {
+            out.push_str(&format!("- `{file}`\n"));
+        }
+        out.push('\n');
+    }
+
+    // 从末尾向前取最近的对话，直到用完字符预算
+    let mut budget = PROMPT_RECENT_CHARS;
+    let mut recent = Vec::new();
+    for (role, text) in texts.iter().rev() {
+        if budget == 0 {
+            break;
+        }
+        let excerpt = clip(text, budget);
+        budget = budget.saturating_sub(excerpt.chars().count());
+        recent.push((*role, excerpt));
+    }
+    if !recent.is_empty() {
+        out.push_str("## Recent conversation\n\n");
+        for (role, text) in recent.into_iter().rev() {
+            let speaker = if role == "user" { "User" } else { "Assistant" };
+            out.push_str(&format!("### {speaker}\n\n{text}\n\n"));
+        }
+    }
+
+    out.push_str(
+        "## Next steps\n\n\
+         1. Inspect the current state of the files above; changes may be partially applied.\n\
+         2. Pick up from the last assistant message and finish the original request.\n",
+    );
+    out
+}
+
+/// 工具参数摘要：优先取命令、路径等关键字段，否则使用紧凑 JSON
+fn tool_input_preview(input: &Value) -> String {
+    const KEYS: [&str; 7] = [
+        "command",
+        "cmd",
+        "file_path",
+        "filePath",
+        "path",
+        "pattern",
+        "url",
+    ];
+    let preview = KEYS
+        .iter()
+        .find_map(|key| match input.get(key) {
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(Value::Array(items)) => Some(
+                items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            _ => None,
+        })
+        .unwrap_or_else(|| match input {
+            Value::Null => String::new(),
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        });
+    clip(&preview.replace('\n', " "), TOOL_INPUT_PREVIEW)
+}
+
+/// Claude Code 以项目路径中非字母数字字符替换为 `-` 作为会话目录名
+fn claude_project_key(cwd: &str) -> String {
+    cwd.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+fn write_lines(path: &Path, lines: &[Value]) -> Result<(), String> {
+    let mut content = String::new();
+    for line in lines {
+        content.push_str(&line.to_string());
+        content.push('\n');
+    }
+    atomic_write(path, content.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+    }
+}
+
+fn app_label(provider_id: &str) -> &str {
+    match provider_id {
+        "claude" => "Claude Code",
+        "codex" => "Codex",
+        "gemini" => "Gemini CLI",
+        "opencode" => "OpenCode",
+        other => other,
+    }
+}
+
+fn format_timestamp(ms: i64) -> String {
+    Utc.timestamp_millis_opt(ms)
+        .single()
+        .unwrap_or_else(Utc::now)
+        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        .to_string()
+}
+
+fn sanitize_file_stem(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn clip(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut clipped: String = text.chars().take(max_chars).collect();
+    clipped.push('…');
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, blocks: Vec<SessionBlock>) -> SessionMessage {
+        let content = blocks
+            .iter()
+            .filter_map(|block| match block {
+                SessionBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        SessionMessage {
+            role: role.to_string(),
+            content,
+            ts: Some(1_700_000_000_000),
+            blocks,
+            model: None,
+        }
+    }
+
+    fn text(value: &str) -> SessionBlock {
+        SessionBlock::Text {
+            text: value.to_string(),
+        }
+    }
+
+    fn sample_messages() -> Vec<SessionMessage> {
+        vec![
+            message("user", vec![text("Fix the failing migration")]),
+            message(
+                "assistant",
+                vec![
+                    SessionBlock::Thinking {
+                        text: "secret plan".to_string(),
+                    },
+                    text("Looking at schema.rs"),
+                    SessionBlock::ToolUse {
+                        id: Some("t1".to_string()),
+                        name: "Read".to_string(),
+                        input: json!({ "file_path": "/repo/src/schema.rs" }),
+                    },
+                ],
+            ),
+            message(
+                "user",
+                vec![SessionBlock::ToolResult {
+                    tool_use_id: Some("t1".to_string()),
+                    content: "fn migrate() {}".to_string(),
+                    is_error: false,
+                }],
+            ),
+            message("assistant", vec![text("Found the bug.")]),
+            message("user", vec![text("Great, apply it")]),
+        ]
+    }
+
+    #[test]
+    fn turns_alternate_and_fold_tools_into_assistant_side() {
+        let turns = build_turns(&sample_messages(), "Gemini CLI");
+        let roles: Vec<Role> = turns.iter().map(|turn| turn.role).collect();
+        assert_eq!(roles, vec![Role::User, Role::Assistant, Role::User]);
+
+        assert!(turns[0].text.starts_with("[Handoff]"));
+        assert!(turns[0].text.contains("Gemini CLI"));
+        assert!(turns[0].text.ends_with("Fix the failing migration"));
+
+        let assistant = &turns[1].text;
+        assert!(assistant.contains("[Tool call] Read: /repo/src/schema.rs"));
+        assert!(assistant.contains("[Tool result] fn migrate() {}"));
+        assert!(assistant.ends_with("Found the bug."));
+        assert!(!assistant.contains("secret plan"));
+
+        let turns = build_turns(&[message("assistant", vec![text("Hi")])], "Codex");
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].role, Role::User);
+        assert!(turns[0].text.starts_with("[Handoff]"));
+    }
+
+    #[test]
+    fn native_lines_follow_target_formats() {
+        let turns = build_turns(&sample_messages(), "OpenCode");
+
+        let claude = claude_session_lines(&turns, "sid", "/repo", 0);
+        assert_eq!(claude.len(), 3);
+        assert!(claude[0]["parentUuid"].is_null());
+        assert_eq!(claude[1]["parentUuid"], claude[0]["uuid"]);
+        assert_eq!(claude[1]["type"], "assistant");
+        assert_eq!(claude[1]["message"]["content"][0]["type"], "text");
+        assert_eq!(claude[2]["sessionId"], "sid");
+        assert_eq!(
+            claude_project_key("/Users/me/my_app.v2"),
+            "-Users-me-my-app-v2"
+        );
+
+        let codex = codex_rollout_lines(&turns, "sid", "/repo", 0);
+        assert_eq!(codex.len(), 4);
+        assert_eq!(codex[0]["type"], "session_meta");
+        assert_eq!(codex[0]["payload"]["cwd"], "/repo");
+        assert_eq!(codex[1]["payload"]["content"][0]["type"], "input_text");
+        assert_eq!(codex[2]["payload"]["content"][0]["type"], "output_text");
+    }
+
+    #[test]
+    fn native_conversion_requires_project_dir_and_user_text() {
+        let messages = sample_messages();
+        let turns = build_turns(&messages, "Codex");
+        assert!(native_blocker(&messages, &turns, Some("/repo")).is_none());
+        assert!(native_blocker(&messages, &turns, None).is_some());
+
+        let assistant_only = vec![message("assistant", vec![text("Hi")])];
+        let turns = build_turns(&assistant_only, "Codex");
+        assert!(native_blocker(&assistant_only, &turns, Some("/repo")).is_some());
+
+        let huge = vec![Turn {
+            role: Role::User,
+            text: "x".repeat(MAX_NATIVE_CHARS + 1),
+            ts: None,
+        }];
+        assert!(native_blocker(&messages, &huge, Some("/repo")).is_some());
+    }
+
+    #[test]
+    fn handoff_prompt_lists_request_files_and_recent_messages() {
+        let meta = SessionMeta {
+            provider_id: "claude".to_string(),
+            session_id: "abc".to_string(),
+            title: None,
+            summary: None,
+            project_dir: Some("/repo".to_string()),
+            created_at: None,
+            last_active_at: None,
+            source_path: None,
+            resume_command: None,
+            total_cost: None,
+            request_count: None,
+        };
+        let mut messages = sample_messages();
+        messages.push(message(
+            "assistant",
+            vec![SessionBlock::ToolUse {
+                id: None,
+                name: "apply_patch".to_string(),
+                input: Value::String(
+                    "*** Begin Patch\n*** Update File: src/lib.rs\n*** End Patch".to_string(),
+                ),
+            }],
+        ));
+
+        let prompt = build_handoff_prompt(&meta, &messages);
+        assert!(prompt.contains("Claude Code"));
+        assert!(prompt.contains("## Original request\n\nFix the failing migration"));
+        assert!(prompt.contains("- `/repo/src/schema.rs`"));
+        assert!(prompt.contains("- `src/lib.rs`"));
+        assert!(prompt.contains("### User\n\nGreat, apply it"));
+    }
+}
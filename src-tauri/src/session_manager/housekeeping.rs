@@ -0,0 +1,677 @@
+//! 会话清理
+//!
+//! - 按应用 / 项目统计会话占用的磁盘空间
+//! - 将超过指定天数未活动的会话归档为压缩包（`~/.cc-switch/session-archive/`）
+//! - 删除会话时先打包移入回收站（`~/.cc-switch/session-trash/`）
+//! - 从归档或回收站恢复会话到原路径
+//!
+//! 归档包与回收站条目格式相同：tar.gz 内含 `manifest.json` 与 `files/` 下的原始文件。
+//! 最近几分钟内修改过的会话文件可能仍在被 CLI 写入，任何操作都不会触碰它们。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::get_app_config_dir;
+
+use super::{scan_provider_sessions, scan_sessions, session_files, SessionMeta};
+
+/// 最近修改保护时间窗口
+const RECENT_MODIFICATION_GUARD_MS: i64 = 10 * 60 * 1000;
+const MANIFEST_NAME: &str = "manifest.json";
+const BUNDLE_EXTENSION: &str = ".tar.gz";
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// 会话存放位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStore {
+    Archive,
+    Trash,
+}
+
+impl SessionStore {
+    fn dir(&self) -> PathBuf {
+        match self {
+            Self::Archive => get_app_config_dir().join("session-archive"),
+            Self::Trash => get_app_config_dir().join("session-trash"),
+        }
+    }
+}
+
+/// 前端选择的会话
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRef {
+    pub provider_id: String,
+    pub source_path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDiskUsage {
+    pub provider_id: String,
+    pub session_count: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDiskUsage {
+    pub provider_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+    pub session_count: usize,
+    pub total_bytes: u64,
+}
+
+/// 磁盘占用报告（项目按占用降序）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDiskUsage {
+    pub apps: Vec<AppDiskUsage>,
+    pub projects: Vec<ProjectDiskUsage>,
+    pub archive_bytes: u64,
+    pub trash_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedSession {
+    pub source_path: String,
+    pub reason: String,
+}
+
+/// 归档 / 删除结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HousekeepingResult {
+    /// 已处理会话的打包路径
+    pub bundles: Vec<String>,
+    pub skipped: Vec<SkippedSession>,
+    /// 释放的原始文件大小
+    pub freed_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredFile {
+    /// 包内路径
+    pub name: String,
+    pub original_path: String,
+}
+
+/// 打包清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredSession {
+    pub provider_id: String,
+    pub session_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_active_at: Option<i64>,
+    pub stored_at: i64,
+    pub files: Vec<StoredFile>,
+}
+
+/// 归档或回收站中的条目
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredSessionEntry {
+    pub store: SessionStore,
+    pub bundle_path: String,
+    pub size_bytes: u64,
+    #[serde(flatten)]
+    pub manifest: StoredSession,
+}
+
+/// 统计各应用、各项目的会话磁盘占用
+pub fn disk_usage() -> SessionDiskUsage {
+    let mut apps: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    let mut projects: HashMap<(String, Option<String>), (usize, u64)> = HashMap::new();
+
+    for session in scan_sessions() {
+        let Some(source_path) = session.source_path.as_deref() else {
+            continue;
+        };
+        let bytes: u64 = session_files(&session.provider_id, source_path)
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|meta| meta.len())
+            .sum();
+
+        let app = apps.entry(session.provider_id.clone()).or_default();
+        app.0 += 1;
+        app.1 += bytes;
+        let project = projects
+            .entry((session.provider_id, session.project_dir))
+            .or_default();
+        project.0 += 1;
+        project.1 += bytes;
+    }
+
+    let mut projects: Vec<ProjectDiskUsage> = projects
+        .into_iter()
+        .map(
+            |((provider_id, project_dir), (session_count, total_bytes))| ProjectDiskUsage {
+                provider_id,
+                project_dir,
+                session_count,
+                total_bytes,
+            },
+        )
+        .collect();
+    projects.sort_by_key(|p| std::cmp::Reverse(p.total_bytes));
+
+    SessionDiskUsage {
+        apps: apps
+            .into_iter()
+            .map(|(provider_id, (session_count, total_bytes))| AppDiskUsage {
+                provider_id,
+                session_count,
+                total_bytes,
+            })
+            .collect(),
+        projects,
+        archive_bytes: dir_size(&SessionStore::Archive.dir()),
+        trash_bytes: dir_size(&SessionStore::Trash.dir()),
+    }
+}
+
+/// 归档超过 `older_than_days` 天未活动的会话，可按应用过滤
+pub fn archive_sessions(
+    older_than_days: u32,
+    provider_id: Option<&str>,
+) -> Result<HousekeepingResult, String> {
+    if older_than_days == 0 {
+        return Err("older_than_days must be at least 1".to_string());
+    }
+
+    let now = now_ms();
+    let cutoff = now - i64::from(older_than_days) * DAY_MS;
+    let sessions = match provider_id {
+        Some(provider_id) => scan_provider_sessions(provider_id),
+        None => scan_sessions(),
+    };
+
+    let store_dir = SessionStore::Archive.dir();
+    let mut result = HousekeepingResult::default();
+    for session in sessions {
+        let active_at = session.last_active_at.or(session.created_at);
+        if active_at.is_some_and(|ts| ts >= cutoff) {
+            continue;
+        }
+        store_or_skip(&session, &store_dir, now, &mut result);
+    }
+
+    Ok(result)
+}
+
+/// 删除选中的会话（打包移入回收站）
+pub fn delete_sessions(refs: &[SessionRef]) -> Result<HousekeepingResult, String> {
+    let now = now_ms();
+    let store_dir = SessionStore::Trash.dir();
+    let mut by_provider: HashMap<&str, Vec<SessionMeta>> = HashMap::new();
+    let mut result = HousekeepingResult::default();
+
+    for session_ref in refs {
+        let sessions = by_provider
+            .entry(session_ref.provider_id.as_str())
+            .or_insert_with(|| scan_provider_sessions(&session_ref.provider_id));
+        match sessions
+            .iter()
+            .find(|s| s.source_path.as_deref() == Some(session_ref.source_path.as_str()))
+        {
+            Some(session) => store_or_skip(session, &store_dir, now, &mut result),
+            None => result.skipped.push(SkippedSession {
+                source_path: session_ref.source_path.clone(),
+                reason: "session not found".to_string(),
+            }),
+        }
+    }
+
+    Ok(result)
+}
+
+/// 列出归档或回收站中的会话（新的在前）
+pub fn list_stored_sessions(store: SessionStore) -> Vec<StoredSessionEntry> {
+    let mut entries: Vec<StoredSessionEntry> = collect_bundles(&store.dir())
+        .into_iter()
+        .filter_map(|path| {
+            let manifest = match read_manifest(&path) {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    log::warn!("Skipping unreadable session bundle {}: {e}", path.display());
+                    return None;
+                }
+            };
+            Some(StoredSessionEntry {
+                store,
+                size_bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+                bundle_path: path.to_string_lossy().to_string(),
+                manifest,
+            })
+        })
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.manifest.stored_at));
+    entries
+}
+
+/// 从归档或回收站恢复会话到原路径；任一原路径已存在时拒绝恢复
+pub fn restore_session(bundle_path: &str) -> Result<StoredSession, String> {
+    // 规范化后再比较，避免 `..` 或符号链接绕过存放目录检查
+    let bundle = PathBuf::from(bundle_path)
+        .canonicalize()
+        .map_err(|_| format!("Not a session bundle: {bundle_path}"))?;
+    let inside_store = [SessionStore::Archive, SessionStore::Trash]
+        .iter()
+        .filter_map(|store| store.dir().canonicalize().ok())
+        .any(|dir| bundle.starts_with(dir));
+    if !inside_store || !bundle.is_file() {
+        return Err(format!("Not a session bundle: {bundle_path}"));
+    }
+
+    let manifest = read_manifest(&bundle)?;
+    if let Some(existing) = manifest
+        .files
+        .iter()
+        .find(|file| Path::new(&file.original_path).exists())
+    {
+        return Err(format!(
+            "Refusing to overwrite existing file: {}",
+            existing.original_path
+        ));
+    }
+
+    let targets: HashMap<&str, &str> = manifest
+        .files
+        .iter()
+        .map(|file| (file.name.as_str(), file.original_path.as_str()))
+        .collect();
+    let mut archive = open_bundle(&bundle)?;
+    for entry in archive
+        .entries()
+        .map_err(|e| format!("Failed to read bundle: {e}"))?
+    {
+        let mut entry = entry.map_err(|e| format!("Failed to read bundle: {e}"))?;
+        let name = entry
+            .path()
+            .map_err(|e| format!("Failed to read bundle: {e}"))?
+            .to_string_lossy()
+            .to_string();
+        let Some(target) = targets.get(name.as_str()) else {
+            continue;
+        };
+        let target = Path::new(target);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Failed to read bundle: {e}"))?;
+        fs::write(target, data)
+            .map_err(|e| format!("Failed to restore {}: {e}", target.display()))?;
+    }
+
+    fs::remove_file(&bundle).map_err(|e| format!("Failed to remove bundle: {e}"))?;
+    Ok(manifest)
+}
+
+/// 清空回收站；指定天数时仅删除早于该天数的条目，返回删除数量
+pub fn empty_trash(older_than_days: Option<u32>) -> Result<usize, String> {
+    let cutoff = older_than_days.map(|days| now_ms() - i64::from(days) * DAY_MS);
+    let mut removed = 0;
+    for entry in list_stored_sessions(SessionStore::Trash) {
+        if cutoff.is_some_and(|cutoff| entry.manifest.stored_at >= cutoff) {
+            continue;
+        }
+        fs::remove_file(&entry.bundle_path)
+            .map_err(|e| format!("Failed to remove {}: {e}", entry.bundle_path))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// 打包会话；失败时记入 `skipped`，不中断整批操作
+fn store_or_skip(
+    session: &SessionMeta,
+    store_dir: &Path,
+    now: i64,
+    result: &mut HousekeepingResult,
+) {
+    if let Err(reason) = store_session(session, store_dir, now, result) {
+        log::warn!("Failed to store session {}: {reason}", session.session_id);
+        result.skipped.push(SkippedSession {
+            source_path: session.source_path.clone().unwrap_or_default(),
+            reason,
+        });
+    }
+}
+
+/// 将会话打包到存放目录（`store_dir/<provider>/`）并删除原文件
+fn store_session(
+    session: &SessionMeta,
+    store_dir: &Path,
+    now: i64,
+    result: &mut HousekeepingResult,
+) -> Result<(), String> {
+    let Some(source_path) = session.source_path.as_deref() else {
+        return Ok(());
+    };
+    let skip = |result: &mut HousekeepingResult, reason: &str| {
+        result.skipped.push(SkippedSession {
+            source_path: source_path.to_string(),
+            reason: reason.to_string(),
+        })
+    };
+
+    let files: Vec<PathBuf> = session_files(&session.provider_id, source_path)
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect();
+    if files.is_empty() {
+        skip(result, "session file not found");
+        return Ok(());
+    }
+    if files
+        .iter()
+        .any(|path| modified_ms(path).is_none_or(|ts| now - ts < RECENT_MODIFICATION_GUARD_MS))
+    {
+        skip(result, "modified recently");
+        return Ok(());
+    }
+
+    let manifest = StoredSession {
+        provider_id: session.provider_id.clone(),
+        session_id: session.session_id.clone(),
+        title: session.title.clone(),
+        project_dir: session.project_dir.clone(),
+        last_active_at: session.last_active_at,
+        stored_at: now,
+        files: files
+            .iter()
+            .enumerate()
+            .map(|(index, path)| StoredFile {
+                name: format!(
+                    "files/{index}-{}",
+                    path.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                ),
+                original_path: path.to_string_lossy().to_string(),
+            })
+            .collect(),
+    };
+
+    let dir = store_dir.join(&session.provider_id);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let bundle = unique_bundle_path(&dir, &session.session_id, now);
+    write_bundle(&bundle, &manifest)?;
+
+    let mut freed = 0;
+    for path in &files {
+        freed += fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+        // 移除因此变空的目录（如 OpenCode 的消息目录），非空目录保持不动
+        if let Some(parent) = path.parent() {
+            let _ = fs::remove_dir(parent);
+        }
+    }
+
+    result.bundles.push(bundle.to_string_lossy().to_string());
+    result.freed_bytes += freed;
+    Ok(())
+}
+
+fn write_bundle(bundle: &Path, manifest: &StoredSession) -> Result<(), String> {
+    let tmp = bundle.with_extension("tmp");
+    let write = || -> std::io::Result<()> {
+        let file = File::create(&tmp)?;
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+
+        let manifest_json = serde_json::to_vec_pretty(manifest)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest_json.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime((manifest.stored_at / 1000) as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, MANIFEST_NAME, manifest_json.as_slice())?;
+
+        for file in &manifest.files {
+            builder.append_path_with_name(&file.original_path, &file.name)?;
+        }
+        builder.into_inner()?.finish()?.sync_all()
+    };
+
+    if let Err(e) = write() {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("Failed to write {}: {e}", bundle.display()));
+    }
+    fs::rename(&tmp, bundle).map_err(|e| format!("Failed to write {}: {e}", bundle.display()))
+}
+
+fn open_bundle(path: &Path) -> Result<tar::Archive<flate2::read::GzDecoder<File>>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    Ok(tar::Archive::new(flate2::read::GzDecoder::new(file)))
+}
+
+fn read_manifest(path: &Path) -> Result<StoredSession, String> {
+    let mut archive = open_bundle(path)?;
+    for entry in archive
+        .entries()
+        .map_err(|e| format!("Failed to read bundle: {e}"))?
+    {
+        let mut entry = entry.map_err(|e| format!("Failed to read bundle: {e}"))?;
+        let is_manifest = entry
+            .path()
+            .map(|p| p.as_os_str() == MANIFEST_NAME)
+            .unwrap_or(false);
+        if is_manifest {
+            let mut data = String::new();
+            entry
+                .read_to_string(&mut data)
+                .map_err(|e| format!("Failed to read manifest: {e}"))?;
+            return serde_json::from_str(&data).map_err(|e| format!("Invalid manifest: {e}"));
+        }
+    }
+    Err("Bundle has no manifest".to_string())
+}
+
+fn unique_bundle_path(dir: &Path, session_id: &str, now: i64) -> PathBuf {
+    let safe_id: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut path = dir.join(format!("{safe_id}-{now}{BUNDLE_EXTENSION}"));
+    let mut counter = 1;
+    while path.exists() {
+        path = dir.join(format!("{safe_id}-{now}-{counter}{BUNDLE_EXTENSION}"));
+        counter += 1;
+    }
+    path
+}
+
+fn collect_bundles(root: &Path) -> Vec<PathBuf> {
+    let mut bundles = Vec::new();
+    let Ok(providers) = fs::read_dir(root) else {
+        return bundles;
+    };
+    for provider_dir in providers.flatten().map(|e| e.path()) {
+        let Ok(entries) = fs::read_dir(&provider_dir) else {
+            continue;
+        };
+        bundles.extend(
+            entries.flatten().map(|e| e.path()).filter(|path| {
+                path.is_file() && path.to_string_lossy().ends_with(BUNDLE_EXTENSION)
+            }),
+        );
+    }
+    bundles
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                dir_size(&path)
+            } else {
+                entry.metadata().map(|m| m.len()).unwrap_or(0)
+            }
+        })
+        .sum()
+}
+
+fn modified_ms(path: &Path) -> Option<i64> {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as i64)
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn bundle_roundtrip_preserves_files_and_manifest() {
+        let dir = tempdir().unwrap();
+        let original = dir.path().join("project").join("session.jsonl");
+        fs::create_dir_all(original.parent().unwrap()).unwrap();
+        fs::write(&original, "{\"type\":\"user\"}\n").unwrap();
+
+        let manifest = StoredSession {
+            provider_id: "claude".to_string(),
+            session_id: "checkpoint:tag".to_string(),
+            title: None,
+            project_dir: None,
+            last_active_at: Some(1),
+            stored_at: 1_700_000_000_000,
+            files: vec![StoredFile {
+                name: "files/0-session.jsonl".to_string(),
+                original_path: original.to_string_lossy().to_string(),
+            }],
+        };
+        let bundle = unique_bundle_path(dir.path(), &manifest.session_id, manifest.stored_at);
+        assert!(bundle
+            .to_string_lossy()
+            .ends_with("checkpoint_tag-1700000000000.tar.gz"));
+        write_bundle(&bundle, &manifest).unwrap();
+
+        let read = read_manifest(&bundle).unwrap();
+        assert_eq!(read.session_id, "checkpoint:tag");
+        assert_eq!(read.files.len(), 1);
+
+        let mut archive = open_bundle(&bundle).unwrap();
+        let mut names = Vec::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().to_string();
+            if name == "files/0-session.jsonl" {
+                let mut data = String::new();
+                entry.read_to_string(&mut data).unwrap();
+                assert_eq!(data, "{\"type\":\"user\"}\n");
+            }
+            names.push(name);
+        }
+        assert_eq!(names, vec![MANIFEST_NAME, "files/0-session.jsonl"]);
+    }
+
+    fn session(source_path: &Path, last_active_at: i64) -> SessionMeta {
+        SessionMeta {
+            provider_id: "claude".to_string(),
+            session_id: "abc".to_string(),
+            title: None,
+            summary: None,
+            project_dir: None,
+            created_at: None,
+            last_active_at: Some(last_active_at),
+            source_path: Some(source_path.to_string_lossy().to_string()),
+            resume_command: None,
+            total_cost: None,
+            request_count: None,
+        }
+    }
+
+    #[test]
+    fn store_session_skips_recently_modified_files() {
+        let dir = tempdir().unwrap();
+        let store_dir = dir.path().join("store");
+        let source = dir.path().join("session.jsonl");
+        fs::write(&source, "{}\n").unwrap();
+        let now = now_ms();
+
+        let mut result = HousekeepingResult::default();
+        store_session(&session(&source, now), &store_dir, now, &mut result).unwrap();
+        assert!(result.bundles.is_empty());
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].reason, "modified recently");
+        assert!(source.exists());
+        assert!(!store_dir.exists());
+
+        // 超过保护窗口后才会打包并删除原文件
+        let later = now + RECENT_MODIFICATION_GUARD_MS + 1000;
+        let mut result = HousekeepingResult::default();
+        store_session(&session(&source, now), &store_dir, later, &mut result).unwrap();
+        assert!(result.skipped.is_empty());
+        assert_eq!(result.bundles.len(), 1);
+        assert!(!source.exists());
+        assert!(Path::new(&result.bundles[0]).starts_with(store_dir.join("claude")));
+        assert_eq!(
+            read_manifest(Path::new(&result.bundles[0]))
+                .unwrap()
+                .session_id,
+            "abc"
+        );
+    }
+
+    #[test]
+    fn store_failure_is_recorded_and_batch_continues() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("session.jsonl");
+        fs::write(&source, "{}\n").unwrap();
+        // 存放目录的位置被普通文件占用，无法创建
+        let blocked = dir.path().join("blocked");
+        fs::write(&blocked, "").unwrap();
+        let later = now_ms() + RECENT_MODIFICATION_GUARD_MS + 1000;
+
+        let mut result = HousekeepingResult::default();
+        store_or_skip(&session(&source, 0), &blocked, later, &mut result);
+        assert_eq!(result.skipped.len(), 1);
+        assert!(result.skipped[0].reason.starts_with("Failed to create"));
+        assert!(source.exists());
+
+        store_or_skip(
+            &session(&source, 0),
+            &dir.path().join("store"),
+            later,
+            &mut result,
+        );
+        assert_eq!(result.bundles.len(), 1);
+    }
+}
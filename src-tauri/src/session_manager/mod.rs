@@ -1,10 +1,16 @@
+pub mod analytics;
+pub mod export;
+pub mod handoff;
+pub mod housekeeping;
 pub mod providers;
+pub mod search;
 pub mod terminal;
 
 use serde::Serialize;
+use serde_json::Value;
 use std::path::Path;
 
-use providers::{claude, codex};
+use providers::{claude, codex, gemini, opencode};
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,21 +31,70 @@ pub struct SessionMeta {
     pub source_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resume_command: Option<String>,
+    /// 经代理记录的累计成本（USD），未经过代理的会话为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_cost: Option<String>,
+    /// 经代理记录的请求数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_count: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionMessage {
     pub role: String,
+    /// 消息的纯文本内容（会话列表与检索使用）
     pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ts: Option<i64>,
+    /// 结构化内容块（文本、工具调用及结果、思考过程、图片），供导出使用
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<SessionBlock>,
+    /// 生成该消息的模型（仅助手消息，记录中缺失时为 None）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// 消息内容块
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum SessionBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    ToolUse {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tool_use_id: Option<String>,
+        content: String,
+        is_error: bool,
+    },
+    Image {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        media_type: Option<String>,
+        /// base64 数据或 URL（含 data URL）
+        source: String,
+    },
 }
 
 pub fn scan_sessions() -> Vec<SessionMeta> {
     let mut sessions = Vec::new();
     sessions.extend(codex::scan_sessions());
     sessions.extend(claude::scan_sessions());
+    sessions.extend(gemini::scan_sessions());
+    sessions.extend(opencode::scan_sessions());
 
     sessions.sort_by(|a, b| {
         let a_ts = a.last_active_at.or(a.created_at).unwrap_or(0);
@@ -50,11 +105,50 @@ pub fn scan_sessions() -> Vec<SessionMeta> {
     sessions
 }
 
+/// 扫描单个应用的会话
+pub fn scan_provider_sessions(provider_id: &str) -> Vec<SessionMeta> {
+    match provider_id {
+        "codex" => codex::scan_sessions(),
+        "claude" => claude::scan_sessions(),
+        "gemini" => gemini::scan_sessions(),
+        "opencode" => opencode::scan_sessions(),
+        _ => Vec::new(),
+    }
+}
+
+/// 按来源文件查找单个会话的元数据
+pub fn find_session(provider_id: &str, source_path: &str) -> Option<SessionMeta> {
+    scan_provider_sessions(provider_id)
+        .into_iter()
+        .find(|session| session.source_path.as_deref() == Some(source_path))
+}
+
+/// 会话在磁盘上对应的全部文件（多数应用为单个文件）
+pub fn session_files(provider_id: &str, source_path: &str) -> Vec<std::path::PathBuf> {
+    match provider_id {
+        "opencode" => opencode::session_files(Path::new(source_path)),
+        _ => vec![Path::new(source_path).to_path_buf()],
+    }
+}
+
+/// 加载会话中含文本内容的消息
 pub fn load_messages(provider_id: &str, source_path: &str) -> Result<Vec<SessionMessage>, String> {
+    let mut messages = load_transcript(provider_id, source_path)?;
+    messages.retain(|message| !message.content.trim().is_empty());
+    Ok(messages)
+}
+
+/// 加载完整会话记录，包括仅含工具调用、工具结果或思考过程的消息
+pub fn load_transcript(
+    provider_id: &str,
+    source_path: &str,
+) -> Result<Vec<SessionMessage>, String> {
     let path = Path::new(source_path);
     match provider_id {
         "codex" => codex::load_messages(path),
         "claude" => claude::load_messages(path),
+        "gemini" => gemini::load_messages(path),
+        "opencode" => opencode::load_messages(path),
         _ => Err(format!("Unsupported provider: {provider_id}")),
     }
 }
@@ -7,7 +7,9 @@ use serde_json::Value;
 use crate::config::get_claude_config_dir;
 use crate::session_manager::{SessionMessage, SessionMeta};
 
-use super::utils::{extract_text, parse_timestamp_to_ms, path_basename, truncate_summary};
+use super::utils::{
+    extract_blocks, extract_text, parse_timestamp_to_ms, path_basename, truncate_summary,
+};
 
 const PROVIDER_ID: &str = "claude";
 
@@ -56,13 +58,29 @@ pub fn load_messages(path: &Path) -> Result<Vec<SessionMessage>, String> {
             .unwrap_or("unknown")
             .to_string();
         let content = message.get("content").map(extract_text).unwrap_or_default();
-        if content.trim().is_empty() {
+        let blocks = message
+            .get("content")
+            .map(extract_blocks)
+            .unwrap_or_default();
+        if content.trim().is_empty() && blocks.is_empty() {
             continue;
         }
 
         let ts = value.get("timestamp").and_then(parse_timestamp_to_ms);
-
-        messages.push(SessionMessage { role, content, ts });
+        // Claude Code 自行插入的消息（如中断提示）模型记为 `<synthetic>`
+        let model = message
+            .get("model")
+            .and_then(Value::as_str)
+            .filter(|model| !model.starts_with('<'))
+            .map(str::to_string);
+
+        messages.push(SessionMessage {
+            role,
+            content,
+            ts,
+            blocks,
+            model,
+        });
     }
 
     Ok(messages)
@@ -149,6 +167,8 @@ fn parse_session(path: &Path) -> Option<SessionMeta> {
         last_active_at,
         source_path: Some(path.to_string_lossy().to_string()),
         resume_command: Some(format!("claude --resume {session_id}")),
+        total_cost: None,
+        request_count: None,
     })
 }
 
@@ -6,9 +6,11 @@ use regex::Regex;
 use serde_json::Value;
 
 use crate::codex_config::get_codex_config_dir;
-use crate::session_manager::{SessionMessage, SessionMeta};
+use crate::session_manager::{SessionBlock, SessionMessage, SessionMeta};
 
-use super::utils::{extract_text, parse_timestamp_to_ms, path_basename, truncate_summary};
+use super::utils::{
+    extract_blocks, extract_text, parse_timestamp_to_ms, path_basename, truncate_summary,
+};
 
 const PROVIDER_ID: &str = "codex";
 
@@ -31,6 +33,8 @@ pub fn load_messages(path: &Path) -> Result<Vec<SessionMessage>, String> {
     let file = File::open(path).map_err(|e| format!("Failed to open session file: {e}"))?;
     let reader = BufReader::new(file);
     let mut messages = Vec::new();
+    // 模型记录在每轮的 turn_context 中，作用于其后的助手输出
+    let mut model: Option<String> = None;
 
     for line in reader.lines() {
         let line = match line {
@@ -42,7 +46,18 @@ pub fn load_messages(path: &Path) -> Result<Vec<SessionMessage>, String> {
             Err(_) => continue,
         };
 
-        if value.get("type").and_then(Value::as_str) != Some("response_item") {
+        let line_type = value.get("type").and_then(Value::as_str);
+        if line_type == Some("turn_context") {
+            if let Some(current) = value
+                .get("payload")
+                .and_then(|payload| payload.get("model"))
+                .and_then(Value::as_str)
+            {
+                model = Some(current.to_string());
+            }
+            continue;
+        }
+        if line_type != Some("response_item") {
             continue;
         }
 
@@ -51,7 +66,18 @@ pub fn load_messages(path: &Path) -> Result<Vec<SessionMessage>, String> {
             None => continue,
         };
 
+        let ts = value.get("timestamp").and_then(parse_timestamp_to_ms);
+
         if payload.get("type").and_then(Value::as_str) != Some("message") {
+            if let Some((role, block)) = parse_non_message_item(payload) {
+                messages.push(SessionMessage {
+                    role: role.to_string(),
+                    content: String::new(),
+                    ts,
+                    blocks: vec![block],
+                    model: (role == "assistant").then(|| model.clone()).flatten(),
+                });
+            }
             continue;
         }
 
@@ -61,18 +87,113 @@ pub fn load_messages(path: &Path) -> Result<Vec<SessionMessage>, String> {
             .unwrap_or("unknown")
             .to_string();
         let content = payload.get("content").map(extract_text).unwrap_or_default();
-        if content.trim().is_empty() {
+        let blocks = payload
+            .get("content")
+            .map(extract_blocks)
+            .unwrap_or_default();
+        if content.trim().is_empty() && blocks.is_empty() {
             continue;
         }
 
-        let ts = value.get("timestamp").and_then(parse_timestamp_to_ms);
-
-        messages.push(SessionMessage { role, content, ts });
+        messages.push(SessionMessage {
+            model: (role == "assistant").then(|| model.clone()).flatten(),
+            role,
+            content,
+            ts,
+            blocks,
+        });
     }
 
     Ok(messages)
 }
 
+/// 解析工具调用、工具输出与推理摘要等非消息条目
+fn parse_non_message_item(payload: &Value) -> Option<(&'static str, SessionBlock)> {
+    let str_field = |key: &str| payload.get(key).and_then(Value::as_str);
+    let call_id = str_field("call_id").map(str::to_string);
+
+    match str_field("type")? {
+        "function_call" | "custom_tool_call" => {
+            let input = payload
+                .get("arguments")
+                .or_else(|| payload.get("input"))
+                .map(|raw| match raw {
+                    // arguments 为 JSON 字符串，尽量还原为对象
+                    Value::String(text) => {
+                        serde_json::from_str(text).unwrap_or_else(|_| raw.clone())
+                    }
+                    other => other.clone(),
+                })
+                .unwrap_or(Value::Null);
+            Some((
+                "assistant",
+                SessionBlock::ToolUse {
+                    id: call_id,
+                    name: str_field("name").unwrap_or("tool").to_string(),
+                    input,
+                },
+            ))
+        }
+        "local_shell_call" => Some((
+            "assistant",
+            SessionBlock::ToolUse {
+                id: call_id,
+                name: "shell".to_string(),
+                input: payload.get("action").cloned().unwrap_or(Value::Null),
+            },
+        )),
+        "function_call_output" | "custom_tool_call_output" => {
+            let output = payload.get("output")?;
+            let content = match output {
+                Value::String(text) => text.clone(),
+                other => other
+                    .get("content")
+                    .or_else(|| other.get("output"))
+                    .map(extract_text)
+                    .unwrap_or_else(|| other.to_string()),
+            };
+            let is_error = output
+                .get("success")
+                .and_then(Value::as_bool)
+                .map(|ok| !ok)
+                .unwrap_or_else(|| output.as_str().is_some_and(shell_output_failed));
+            Some((
+                "tool",
+                SessionBlock::ToolResult {
+                    tool_use_id: call_id,
+                    content,
+                    is_error,
+                },
+            ))
+        }
+        "reasoning" => {
+            let text = payload.get("summary").map(extract_text).unwrap_or_default();
+            if text.trim().is_empty() {
+                return None;
+            }
+            Some(("assistant", SessionBlock::Thinking { text }))
+        }
+        _ => None,
+    }
+}
+
+/// 命令类工具的输出不带 success 字段，从退出码判断是否失败：
+/// JSON 形式 `{"output": ..., "metadata": {"exit_code": N}}` 或文本形式 `Exit code: N`
+fn shell_output_failed(output: &str) -> bool {
+    let exit_code = match serde_json::from_str::<Value>(output) {
+        Ok(value) => value
+            .get("metadata")
+            .and_then(|metadata| metadata.get("exit_code"))
+            .and_then(Value::as_i64),
+        Err(_) => output
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("Exit code: "))
+            .and_then(|code| code.trim().parse().ok()),
+    };
+    exit_code.is_some_and(|code| code != 0)
+}
+
 fn parse_session(path: &Path) -> Option<SessionMeta> {
     let file = File::open(path).ok()?;
     let reader = BufReader::new(file);
@@ -161,6 +282,8 @@ fn parse_session(path: &Path) -> Option<SessionMeta> {
         last_active_at,
         source_path: Some(path.to_string_lossy().to_string()),
         resume_command: Some(format!("codex resume {session_id}")),
+        total_cost: None,
+        request_count: None,
     })
 }
 
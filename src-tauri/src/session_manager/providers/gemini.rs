@@ -0,0 +1,559 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::gemini_config::get_gemini_dir;
+use crate::session_manager::{SessionBlock, SessionMessage, SessionMeta};
+
+use super::utils::{extract_text, parse_timestamp_to_ms, path_basename, truncate_summary};
+
+const PROVIDER_ID: &str = "gemini";
+
+/// 扫描 `~/.gemini/tmp/<project-hash>/` 下的会话记录：
+/// - `chats/session-*.json`：自动保存的完整会话，可通过 `gemini --resume` 恢复
+/// - `checkpoint-<tag>.json`：`/chat save <tag>` 保存的检查点，需在 CLI 内 `/chat resume <tag>`
+pub fn scan_sessions() -> Vec<SessionMeta> {
+    let root = get_gemini_dir().join("tmp");
+    let entries = match std::fs::read_dir(&root) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let project_dirs = known_project_dirs_by_hash();
+    let mut sessions = Vec::new();
+
+    for entry in entries.flatten() {
+        let project_root = entry.path();
+        if !project_root.is_dir() {
+            continue;
+        }
+        let hash = entry.file_name().to_string_lossy().to_string();
+        let project_dir = resolve_project_dir(&project_root, &hash, &project_dirs);
+
+        for path in list_json_files(&project_root.join("chats"), "session-") {
+            if let Some(meta) = parse_chat_session(&path, project_dir.clone()) {
+                sessions.push(meta);
+            }
+        }
+
+        for path in list_json_files(&project_root, "checkpoint-") {
+            if let Some(meta) = parse_checkpoint(&path, project_dir.clone()) {
+                sessions.push(meta);
+            }
+        }
+    }
+
+    sessions
+}
+
+pub fn load_messages(path: &Path) -> Result<Vec<SessionMessage>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open session file: {e}"))?;
+    let value: Value =
+        serde_json::from_str(&data).map_err(|e| format!("Failed to parse session file: {e}"))?;
+
+    let messages = match &value {
+        // 检查点：`[{ role, parts: [{ text } | { functionCall } | { functionResponse }] }]`
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| {
+                let role = normalize_role(item.get("role").and_then(Value::as_str)?)?;
+                let parts = item.get("parts").and_then(Value::as_array);
+                let blocks: Vec<SessionBlock> = parts
+                    .map(|parts| parts.iter().filter_map(block_from_part).collect())
+                    .unwrap_or_default();
+                let content = text_of(&blocks);
+                if blocks.is_empty() {
+                    return None;
+                }
+                Some(SessionMessage {
+                    role,
+                    content,
+                    ts: None,
+                    blocks,
+                    model: None,
+                })
+            })
+            .collect(),
+        // 会话记录：`{ sessionId, messages: [{ type, content, thoughts, toolCalls, timestamp }] }`
+        _ => value
+            .get("messages")
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| {
+                        let role = normalize_role(item.get("type").and_then(Value::as_str)?)?;
+                        let content = item.get("content").map(extract_text).unwrap_or_default();
+                        let blocks = chat_message_blocks(item, &content);
+                        if content.trim().is_empty() && blocks.is_empty() {
+                            return None;
+                        }
+                        let ts = item.get("timestamp").and_then(parse_timestamp_to_ms);
+                        let model = item
+                            .get("model")
+                            .and_then(Value::as_str)
+                            .map(str::to_string);
+                        Some(SessionMessage {
+                            role,
+                            content,
+                            ts,
+                            blocks,
+                            model,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    Ok(messages)
+}
+
+fn parse_chat_session(path: &Path, project_dir: Option<String>) -> Option<SessionMeta> {
+    let data = std::fs::read_to_string(path).ok()?;
+    let value: Value = serde_json::from_str(&data).ok()?;
+
+    let session_id = value
+        .get("sessionId")
+        .and_then(Value::as_str)
+        .map(|s| s.to_string())?;
+    let created_at = value.get("startTime").and_then(parse_timestamp_to_ms);
+    let last_active_at = value
+        .get("lastUpdated")
+        .and_then(parse_timestamp_to_ms)
+        .or(created_at);
+
+    let summary = value
+        .get("messages")
+        .and_then(Value::as_array)
+        .and_then(|items| {
+            items.iter().rev().find_map(|item| {
+                normalize_role(item.get("type").and_then(Value::as_str)?)?;
+                let text = item.get("content").map(extract_text).unwrap_or_default();
+                (!text.trim().is_empty()).then_some(text)
+            })
+        })
+        .map(|text| truncate_summary(&text, 160));
+
+    // 空会话（仅启动未对话）不展示
+    summary.as_ref()?;
+
+    Some(SessionMeta {
+        provider_id: PROVIDER_ID.to_string(),
+        session_id: session_id.clone(),
+        title: project_dir.as_deref().and_then(path_basename),
+        summary,
+        project_dir,
+        created_at,
+        last_active_at,
+        source_path: Some(path.to_string_lossy().to_string()),
+        resume_command: Some(format!("gemini --resume {session_id}")),
+        total_cost: None,
+        request_count: None,
+    })
+}
+
+fn parse_checkpoint(path: &Path, project_dir: Option<String>) -> Option<SessionMeta> {
+    let tag = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.strip_prefix("checkpoint-"))
+        .map(|tag| tag.to_string())?;
+
+    let messages = load_messages(path).ok()?;
+    let summary = messages
+        .iter()
+        .rev()
+        .find(|message| !message.content.trim().is_empty())
+        .map(|message| truncate_summary(&message.content, 160))?;
+
+    // 检查点文件不含时间戳，以文件修改时间近似
+    let modified_at = std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as i64);
+
+    let title = project_dir
+        .as_deref()
+        .and_then(path_basename)
+        .map(|name| format!("{name} · {tag}"))
+        .or_else(|| Some(tag.clone()));
+
+    Some(SessionMeta {
+        provider_id: PROVIDER_ID.to_string(),
+        session_id: format!("checkpoint:{tag}"),
+        title,
+        summary: Some(summary),
+        project_dir,
+        created_at: modified_at,
+        last_active_at: modified_at,
+        source_path: Some(path.to_string_lossy().to_string()),
+        resume_command: None,
+        total_cost: None,
+        request_count: None,
+    })
+}
+
+fn chat_message_blocks(item: &Value, content: &str) -> Vec<SessionBlock> {
+    let mut blocks = Vec::new();
+
+    for thought in item
+        .get("thoughts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let subject = thought.get("subject").and_then(Value::as_str);
+        let description = thought
+            .get("description")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let text = match subject {
+            Some(subject) if !subject.is_empty() => format!("{subject}: {description}"),
+            _ => description.to_string(),
+        };
+        if !text.trim().is_empty() {
+            blocks.push(SessionBlock::Thinking { text });
+        }
+    }
+
+    if !content.trim().is_empty() {
+        blocks.push(SessionBlock::Text {
+            text: content.to_string(),
+        });
+    }
+
+    for call in item
+        .get("toolCalls")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let id = call.get("id").and_then(Value::as_str).map(str::to_string);
+        blocks.push(SessionBlock::ToolUse {
+            id: id.clone(),
+            name: call
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("tool")
+                .to_string(),
+            input: call.get("args").cloned().unwrap_or(Value::Null),
+        });
+        let result = call
+            .get("resultDisplay")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| call.get("result").map(extract_text));
+        if let Some(result) = result.filter(|r| !r.trim().is_empty()) {
+            blocks.push(SessionBlock::ToolResult {
+                tool_use_id: id,
+                content: result,
+                is_error: call.get("status").and_then(Value::as_str) == Some("error"),
+            });
+        }
+    }
+
+    blocks
+}
+
+fn block_from_part(part: &Value) -> Option<SessionBlock> {
+    if let Some(call) = part.get("functionCall") {
+        return Some(SessionBlock::ToolUse {
+            id: call.get("id").and_then(Value::as_str).map(str::to_string),
+            name: call
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("tool")
+                .to_string(),
+            input: call.get("args").cloned().unwrap_or(Value::Null),
+        });
+    }
+    if let Some(response) = part.get("functionResponse") {
+        let output = response.get("response").cloned().unwrap_or(Value::Null);
+        let content = output
+            .get("output")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| output.to_string());
+        return Some(SessionBlock::ToolResult {
+            tool_use_id: response
+                .get("id")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            content,
+            is_error: output.get("error").is_some(),
+        });
+    }
+    if let Some(data) = part.get("inlineData") {
+        return Some(SessionBlock::Image {
+            media_type: data
+                .get("mimeType")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            source: data.get("data").and_then(Value::as_str)?.to_string(),
+        });
+    }
+
+    let text = part.get("text").and_then(Value::as_str)?;
+    if text.trim().is_empty() {
+        return None;
+    }
+    if part.get("thought").and_then(Value::as_bool) == Some(true) {
+        return Some(SessionBlock::Thinking {
+            text: text.to_string(),
+        });
+    }
+    Some(SessionBlock::Text {
+        text: text.to_string(),
+    })
+}
+
+fn text_of(blocks: &[SessionBlock]) -> String {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            SessionBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Gemini 的消息类型：`user` / `gemini`（会话记录）、`user` / `model`（检查点）
+fn normalize_role(raw: &str) -> Option<String> {
+    match raw {
+        "user" => Some("user".to_string()),
+        "gemini" | "model" => Some("assistant".to_string()),
+        _ => None,
+    }
+}
+
+/// 解析 tmp 子目录对应的项目目录：
+/// - 旧版以项目根目录路径的 SHA-256 作为子目录名，用已知项目目录反查
+/// - 新版子目录中的 `.project_root` 文件直接记录了项目根目录
+///
+/// 均无法确定时 project_dir 为空
+fn resolve_project_dir(
+    project_root: &Path,
+    hash: &str,
+    known: &HashMap<String, String>,
+) -> Option<String> {
+    if let Some(dir) = known.get(hash) {
+        return Some(dir.clone());
+    }
+    std::fs::read_to_string(project_root.join(".project_root"))
+        .ok()
+        .map(|dir| dir.trim().to_string())
+        .filter(|dir| !dir.is_empty())
+}
+
+/// 已知项目目录（Claude 记录过的项目与 Codex 配置中的项目）按路径哈希索引
+fn known_project_dirs_by_hash() -> HashMap<String, String> {
+    let mut dirs = match crate::claude_mcp::list_known_project_dirs() {
+        Ok(dirs) => dirs,
+        Err(e) => {
+            log::debug!("Failed to list known project dirs: {e}");
+            Vec::new()
+        }
+    };
+    dirs.extend(codex_project_dirs());
+
+    dirs.into_iter()
+        .map(|dir| (project_hash(&dir), dir))
+        .collect()
+}
+
+/// `~/.codex/config.toml` 中 `[projects."<dir>"]` 记录的项目目录（仅返回仍存在的目录）
+fn codex_project_dirs() -> Vec<String> {
+    let Ok(text) = std::fs::read_to_string(crate::codex_config::get_codex_config_path()) else {
+        return Vec::new();
+    };
+    let Ok(config) = text.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    config
+        .get("projects")
+        .and_then(toml::Value::as_table)
+        .map(|projects| {
+            projects
+                .keys()
+                .filter(|dir| Path::new(dir).is_dir())
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn project_hash(dir: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, dir.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn list_json_files(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path.extension().and_then(|ext| ext.to_str()) == Some("json")
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| name.starts_with(prefix))
+                    .unwrap_or(false)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAT_SESSION: &str = r#"{
+        "sessionId": "5f1c2a7e-demo",
+        "projectHash": "abc",
+        "startTime": "2025-09-01T10:00:00.000Z",
+        "lastUpdated": "2025-09-01T10:05:00.000Z",
+        "messages": [
+            { "type": "info", "content": "Update available" },
+            { "type": "user", "content": "List the files", "timestamp": "2025-09-01T10:00:01.000Z" },
+            {
+                "type": "gemini",
+                "content": "Here are the files.",
+                "model": "gemini-2.5-pro",
+                "timestamp": "2025-09-01T10:00:05.000Z",
+                "thoughts": [{ "subject": "Planning", "description": "Use ls" }],
+                "toolCalls": [{
+                    "id": "call-1",
+                    "name": "list_directory",
+                    "args": { "path": "." },
+                    "status": "success",
+                    "resultDisplay": "Cargo.toml\nsrc"
+                }]
+            }
+        ]
+    }"#;
+
+    const CHECKPOINT: &str = r#"[
+        { "role": "user", "parts": [{ "text": "Read main.rs" }] },
+        {
+            "role": "model",
+            "parts": [
+                { "text": "Thinking about it", "thought": true },
+                { "functionCall": { "id": "fc-1", "name": "read_file", "args": { "path": "main.rs" } } }
+            ]
+        },
+        {
+            "role": "user",
+            "parts": [{ "functionResponse": { "id": "fc-1", "name": "read_file", "response": { "output": "fn main() {}" } } }]
+        },
+        { "role": "model", "parts": [{ "text": "It is an empty main." }] }
+    ]"#;
+
+    #[test]
+    fn parses_chat_session_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session-2025-09-01T10-00-demo.json");
+        std::fs::write(&path, CHAT_SESSION).unwrap();
+
+        let meta = parse_chat_session(&path, Some("/work/demo".to_string())).unwrap();
+        assert_eq!(meta.session_id, "5f1c2a7e-demo");
+        assert_eq!(meta.title.as_deref(), Some("demo"));
+        assert_eq!(meta.summary.as_deref(), Some("Here are the files."));
+        assert_eq!(
+            meta.resume_command.as_deref(),
+            Some("gemini --resume 5f1c2a7e-demo")
+        );
+        assert!(meta.last_active_at > meta.created_at);
+
+        let messages = load_messages(&path).unwrap();
+        assert_eq!(messages.len(), 2, "info messages are skipped");
+        assert_eq!(messages[0].role, "user");
+        assert_eq!(messages[1].role, "assistant");
+        assert_eq!(messages[1].model.as_deref(), Some("gemini-2.5-pro"));
+        assert_eq!(
+            messages[1].blocks,
+            vec![
+                SessionBlock::Thinking {
+                    text: "Planning: Use ls".to_string()
+                },
+                SessionBlock::Text {
+                    text: "Here are the files.".to_string()
+                },
+                SessionBlock::ToolUse {
+                    id: Some("call-1".to_string()),
+                    name: "list_directory".to_string(),
+                    input: serde_json::json!({ "path": "." }),
+                },
+                SessionBlock::ToolResult {
+                    tool_use_id: Some("call-1".to_string()),
+                    content: "Cargo.toml\nsrc".to_string(),
+                    is_error: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_checkpoint_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint-refactor.json");
+        std::fs::write(&path, CHECKPOINT).unwrap();
+
+        let meta = parse_checkpoint(&path, Some("/work/demo".to_string())).unwrap();
+        assert_eq!(meta.session_id, "checkpoint:refactor");
+        assert_eq!(meta.title.as_deref(), Some("demo · refactor"));
+        assert_eq!(meta.summary.as_deref(), Some("It is an empty main."));
+        assert!(meta.resume_command.is_none());
+
+        let messages = load_messages(&path).unwrap();
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["user", "assistant", "user", "assistant"]);
+        assert!(matches!(
+            &messages[1].blocks[..],
+            [SessionBlock::Thinking { .. }, SessionBlock::ToolUse { name, .. }] if name == "read_file"
+        ));
+        assert_eq!(
+            messages[2].blocks,
+            vec![SessionBlock::ToolResult {
+                tool_use_id: Some("fc-1".to_string()),
+                content: "fn main() {}".to_string(),
+                is_error: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn resolves_project_dir_by_hash_then_marker_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let known: HashMap<String, String> =
+            [(project_hash("/work/demo"), "/work/demo".to_string())].into();
+
+        let hashed = dir.path().join(project_hash("/work/demo"));
+        std::fs::create_dir_all(&hashed).unwrap();
+        assert_eq!(
+            resolve_project_dir(&hashed, &project_hash("/work/demo"), &known).as_deref(),
+            Some("/work/demo")
+        );
+
+        let named = dir.path().join("other");
+        std::fs::create_dir_all(&named).unwrap();
+        assert_eq!(resolve_project_dir(&named, "other", &known), None);
+        std::fs::write(named.join(".project_root"), "/work/other\n").unwrap();
+        assert_eq!(
+            resolve_project_dir(&named, "other", &known).as_deref(),
+            Some("/work/other")
+        );
+    }
+}
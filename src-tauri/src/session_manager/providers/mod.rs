@@ -1,3 +1,5 @@
 pub mod claude;
 pub mod codex;
+pub mod gemini;
+pub mod opencode;
 mod utils;
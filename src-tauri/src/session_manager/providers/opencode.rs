@@ -0,0 +1,446 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::session_manager::{SessionBlock, SessionMessage, SessionMeta};
+use crate::settings::get_opencode_override_dir;
+
+use super::utils::{path_basename, truncate_summary};
+
+const PROVIDER_ID: &str = "opencode";
+
+/// OpenCode 会话存储目录：
+/// - `session/<project-id>/<session-id>.json`：会话信息（标题、目录、时间）
+/// - `message/<session-id>/<message-id>.json`：消息元数据（角色、时间）
+/// - `part/<message-id>/<part-id>.json`：消息内容片段
+///
+/// 会话位于数据目录（`$XDG_DATA_HOME/opencode`，默认 `~/.local/share/opencode`）而非配置目录；
+/// 若设置了配置目录覆盖且其下存在 `storage`，优先使用覆盖目录。
+fn storage_dir() -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(custom) = get_opencode_override_dir() {
+        candidates.push(custom.join("storage"));
+    }
+    if let Some(data_home) = std::env::var_os("XDG_DATA_HOME").filter(|v| !v.is_empty()) {
+        candidates.push(PathBuf::from(data_home).join("opencode").join("storage"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        candidates.push(
+            home.join(".local")
+                .join("share")
+                .join("opencode")
+                .join("storage"),
+        );
+    }
+
+    candidates.into_iter().find(|dir| dir.is_dir())
+}
+
+pub fn scan_sessions() -> Vec<SessionMeta> {
+    let Some(storage) = storage_dir() else {
+        return Vec::new();
+    };
+
+    let mut sessions = Vec::new();
+    for project_dir in list_dirs(&storage.join("session")) {
+        for path in list_json_files(&project_dir) {
+            if let Some(meta) = parse_session(&path) {
+                sessions.push(meta);
+            }
+        }
+    }
+
+    sessions
+}
+
+pub fn load_messages(path: &Path) -> Result<Vec<SessionMessage>, String> {
+    let info = read_json(path).ok_or_else(|| "Failed to read session file".to_string())?;
+    let session_id = info
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "Session file is missing id".to_string())?;
+    // source_path 形如 <storage>/session/<project-id>/<session-id>.json
+    let storage = path
+        .ancestors()
+        .nth(3)
+        .ok_or_else(|| "Invalid session path".to_string())?;
+
+    let mut entries: Vec<(i64, String, String, Option<String>)> = Vec::new();
+    for message_path in list_json_files(&storage.join("message").join(session_id)) {
+        let Some(message) = read_json(&message_path) else {
+            continue;
+        };
+        let (Some(message_id), Some(role)) = (
+            message.get("id").and_then(Value::as_str),
+            message.get("role").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        let created = message
+            .get("time")
+            .and_then(|t| t.get("created"))
+            .and_then(Value::as_i64)
+            .unwrap_or(0);
+        let model = message
+            .get("modelID")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        entries.push((created, message_id.to_string(), role.to_string(), model));
+    }
+    entries.sort();
+
+    let mut messages = Vec::new();
+    for (created, message_id, role, model) in entries {
+        let mut parts = list_json_files(&storage.join("part").join(&message_id));
+        parts.sort();
+        let blocks: Vec<SessionBlock> = parts
+            .iter()
+            .filter_map(|part_path| read_json(part_path))
+            .flat_map(|part| blocks_from_part(&part))
+            .collect();
+        let content = blocks
+            .iter()
+            .filter_map(|block| match block {
+                SessionBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if blocks.is_empty() {
+            continue;
+        }
+
+        messages.push(SessionMessage {
+            role,
+            content,
+            ts: (created > 0).then_some(created),
+            blocks,
+            model,
+        });
+    }
+
+    Ok(messages)
+}
+
+/// 会话涉及的全部文件：会话信息、消息元数据及各消息的内容片段
+pub fn session_files(path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![path.to_path_buf()];
+    let (Some(info), Some(storage)) = (read_json(path), path.ancestors().nth(3)) else {
+        return files;
+    };
+    let Some(session_id) = info.get("id").and_then(Value::as_str) else {
+        return files;
+    };
+
+    for message_path in list_json_files(&storage.join("message").join(session_id)) {
+        if let Some(message_id) = read_json(&message_path)
+            .as_ref()
+            .and_then(|message| message.get("id"))
+            .and_then(Value::as_str)
+        {
+            files.extend(list_json_files(&storage.join("part").join(message_id)));
+        }
+        files.push(message_path);
+    }
+
+    files
+}
+
+fn parse_session(path: &Path) -> Option<SessionMeta> {
+    let value = read_json(path)?;
+
+    // 子代理派生的会话不单独展示
+    if value.get("parentID").and_then(Value::as_str).is_some() {
+        return None;
+    }
+
+    let session_id = value.get("id").and_then(Value::as_str)?.to_string();
+    let project_dir = value
+        .get("directory")
+        .and_then(Value::as_str)
+        .map(|s| s.to_string());
+    let time = value.get("time");
+    let created_at = time.and_then(|t| t.get("created")).and_then(Value::as_i64);
+    let last_active_at = time
+        .and_then(|t| t.get("updated"))
+        .and_then(Value::as_i64)
+        .or(created_at);
+
+    // OpenCode 会自动为会话生成标题，直接作为摘要
+    let summary = value
+        .get("title")
+        .and_then(Value::as_str)
+        .filter(|title| !title.trim().is_empty())
+        .map(|title| truncate_summary(title, 160));
+
+    Some(SessionMeta {
+        provider_id: PROVIDER_ID.to_string(),
+        session_id: session_id.clone(),
+        title: project_dir.as_deref().and_then(path_basename),
+        summary,
+        project_dir,
+        created_at,
+        last_active_at,
+        source_path: Some(path.to_string_lossy().to_string()),
+        resume_command: Some(format!("opencode --session {session_id}")),
+        total_cost: None,
+        request_count: None,
+    })
+}
+
+/// 消息片段：`text` / `reasoning` / `tool` / `file`，其余（步骤标记、快照等）忽略
+fn blocks_from_part(part: &Value) -> Vec<SessionBlock> {
+    let str_field = |key: &str| part.get(key).and_then(Value::as_str);
+    let non_empty = |key: &str| str_field(key).filter(|text| !text.trim().is_empty());
+
+    match str_field("type").unwrap_or_default() {
+        "text" if part.get("synthetic").and_then(Value::as_bool) != Some(true) => non_empty("text")
+            .map(|text| SessionBlock::Text {
+                text: text.to_string(),
+            })
+            .into_iter()
+            .collect(),
+        "reasoning" => non_empty("text")
+            .map(|text| SessionBlock::Thinking {
+                text: text.to_string(),
+            })
+            .into_iter()
+            .collect(),
+        "tool" => {
+            let state = part.get("state");
+            let state_field = |key: &str| state.and_then(|s| s.get(key));
+            let call_id = str_field("callID").map(str::to_string);
+            let mut blocks = vec![SessionBlock::ToolUse {
+                id: call_id.clone(),
+                name: str_field("tool").unwrap_or("tool").to_string(),
+                input: state_field("input").cloned().unwrap_or(Value::Null),
+            }];
+            if let Some(output) = state_field("output")
+                .or_else(|| state_field("error"))
+                .and_then(Value::as_str)
+            {
+                blocks.push(SessionBlock::ToolResult {
+                    tool_use_id: call_id,
+                    content: output.to_string(),
+                    is_error: state_field("status").and_then(Value::as_str) == Some("error"),
+                });
+            }
+            blocks
+        }
+        "file" => {
+            let Some(url) = str_field("url") else {
+                return Vec::new();
+            };
+            let mime = str_field("mime");
+            if mime.is_some_and(|mime| mime.starts_with("image/")) {
+                return vec![SessionBlock::Image {
+                    media_type: mime.map(str::to_string),
+                    source: url.to_string(),
+                }];
+            }
+            // 非图片附件（源码、PDF 等）以文件名与链接呈现
+            let name = str_field("filename").unwrap_or(url);
+            let text = if url.starts_with("data:") || name == url {
+                format!("[file: {name}]")
+            } else {
+                format!("[file: {name}]({url})")
+            };
+            vec![SessionBlock::Text { text }]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let data = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn list_dirs(root: &Path) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+fn list_json_files(dir: &Path) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在临时目录中按 OpenCode 的存储布局写入 JSON 文件
+    fn write(storage: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = storage.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn fixture(storage: &Path) -> PathBuf {
+        let session = write(
+            storage,
+            "session/proj_1/ses_1.json",
+            r#"{"id":"ses_1","projectID":"proj_1","directory":"/work/demo","title":"Fix the build","time":{"created":1000,"updated":5000}}"#,
+        );
+        write(
+            storage,
+            "session/proj_1/ses_child.json",
+            r#"{"id":"ses_child","parentID":"ses_1","directory":"/work/demo","title":"Subtask","time":{"created":2000}}"#,
+        );
+
+        // 消息文件名与时间顺序不一致，需按 time.created 排序
+        write(
+            storage,
+            "message/ses_1/msg_b.json",
+            r#"{"id":"msg_b","sessionID":"ses_1","role":"assistant","modelID":"claude-sonnet-4","time":{"created":3000}}"#,
+        );
+        write(
+            storage,
+            "message/ses_1/msg_a.json",
+            r#"{"id":"msg_a","sessionID":"ses_1","role":"user","time":{"created":2000}}"#,
+        );
+
+        write(
+            storage,
+            "part/msg_a/prt_1.json",
+            r#"{"id":"prt_1","type":"text","text":"Why does cargo fail?"}"#,
+        );
+        write(
+            storage,
+            "part/msg_a/prt_2.json",
+            r#"{"id":"prt_2","type":"text","synthetic":true,"text":"<system reminder>"}"#,
+        );
+        write(
+            storage,
+            "part/msg_a/prt_3.json",
+            r#"{"id":"prt_3","type":"file","mime":"image/png","filename":"shot.png","url":"data:image/png;base64,AAAA"}"#,
+        );
+        write(
+            storage,
+            "part/msg_b/prt_1.json",
+            r#"{"id":"prt_1","type":"step-start"}"#,
+        );
+        write(
+            storage,
+            "part/msg_b/prt_2.json",
+            r#"{"id":"prt_2","type":"reasoning","text":"Check the manifest"}"#,
+        );
+        write(
+            storage,
+            "part/msg_b/prt_3.json",
+            r#"{"id":"prt_3","type":"tool","callID":"call_1","tool":"bash","state":{"status":"error","input":{"command":"cargo build"},"error":"exit 101"}}"#,
+        );
+        write(
+            storage,
+            "part/msg_b/prt_4.json",
+            r#"{"id":"prt_4","type":"text","text":"The manifest is missing."}"#,
+        );
+
+        session
+    }
+
+    #[test]
+    fn parses_session_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = fixture(dir.path());
+
+        let meta = parse_session(&session).unwrap();
+        assert_eq!(meta.session_id, "ses_1");
+        assert_eq!(meta.title.as_deref(), Some("demo"));
+        assert_eq!(meta.summary.as_deref(), Some("Fix the build"));
+        assert_eq!(meta.project_dir.as_deref(), Some("/work/demo"));
+        assert_eq!(meta.created_at, Some(1000));
+        assert_eq!(meta.last_active_at, Some(5000));
+        assert_eq!(
+            meta.resume_command.as_deref(),
+            Some("opencode --session ses_1")
+        );
+
+        let child = dir.path().join("session/proj_1/ses_child.json");
+        assert!(
+            parse_session(&child).is_none(),
+            "subagent sessions are hidden"
+        );
+    }
+
+    #[test]
+    fn loads_messages_from_parts_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = fixture(dir.path());
+
+        let messages = load_messages(&session).unwrap();
+        assert_eq!(messages.len(), 2);
+
+        assert_eq!(messages[0].role, "user");
+        assert_eq!(messages[0].ts, Some(2000));
+        assert_eq!(messages[0].content, "Why does cargo fail?");
+        assert_eq!(
+            messages[0].blocks,
+            vec![
+                SessionBlock::Text {
+                    text: "Why does cargo fail?".to_string()
+                },
+                SessionBlock::Image {
+                    media_type: Some("image/png".to_string()),
+                    source: "data:image/png;base64,AAAA".to_string(),
+                },
+            ]
+        );
+
+        assert_eq!(messages[1].role, "assistant");
+        assert_eq!(messages[1].model.as_deref(), Some("claude-sonnet-4"));
+        assert_eq!(messages[1].content, "The manifest is missing.");
+        assert_eq!(
+            messages[1].blocks,
+            vec![
+                SessionBlock::Thinking {
+                    text: "Check the manifest".to_string()
+                },
+                SessionBlock::ToolUse {
+                    id: Some("call_1".to_string()),
+                    name: "bash".to_string(),
+                    input: serde_json::json!({ "command": "cargo build" }),
+                },
+                SessionBlock::ToolResult {
+                    tool_use_id: Some("call_1".to_string()),
+                    content: "exit 101".to_string(),
+                    is_error: true,
+                },
+                SessionBlock::Text {
+                    text: "The manifest is missing.".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn session_files_cover_messages_and_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = fixture(dir.path());
+
+        let files = session_files(&session);
+        // 会话信息 + 2 条消息 + 7 个片段
+        assert_eq!(files.len(), 10);
+        assert_eq!(files[0], session);
+        assert!(files.iter().all(|file| file.exists()));
+        assert!(!files.iter().any(|file| file.ends_with("ses_child.json")));
+    }
+}
@@ -1,6 +1,8 @@
 use chrono::{DateTime, FixedOffset};
 use serde_json::Value;
 
+use crate::session_manager::SessionBlock;
+
 pub fn parse_timestamp_to_ms(value: &Value) -> Option<i64> {
     let raw = value.as_str()?;
     DateTime::parse_from_rfc3339(raw)
@@ -49,6 +51,68 @@ fn extract_text_from_item(item: &Value) -> Option<String> {
     None
 }
 
+/// 将 Claude / Codex 风格的 content（字符串或内容块数组）解析为结构化内容块
+pub fn extract_blocks(content: &Value) -> Vec<SessionBlock> {
+    match content {
+        Value::String(text) => text_block(text).into_iter().collect(),
+        Value::Array(items) => items.iter().filter_map(block_from_item).collect(),
+        Value::Object(_) => block_from_item(content).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn block_from_item(item: &Value) -> Option<SessionBlock> {
+    let str_field = |key: &str| item.get(key).and_then(Value::as_str);
+
+    match str_field("type").unwrap_or_default() {
+        "thinking" => str_field("thinking")
+            .or_else(|| str_field("text"))
+            .filter(|text| !text.trim().is_empty())
+            .map(|text| SessionBlock::Thinking {
+                text: text.to_string(),
+            }),
+        "redacted_thinking" => None,
+        "tool_use" | "server_tool_use" => Some(SessionBlock::ToolUse {
+            id: str_field("id").map(str::to_string),
+            name: str_field("name").unwrap_or("tool").to_string(),
+            input: item.get("input").cloned().unwrap_or(Value::Null),
+        }),
+        "tool_result" => Some(SessionBlock::ToolResult {
+            tool_use_id: str_field("tool_use_id").map(str::to_string),
+            content: item.get("content").map(extract_text).unwrap_or_default(),
+            is_error: item.get("is_error").and_then(Value::as_bool) == Some(true),
+        }),
+        "image" => {
+            let source = item.get("source")?;
+            let data = source
+                .get("data")
+                .or_else(|| source.get("url"))
+                .and_then(Value::as_str)?;
+            Some(SessionBlock::Image {
+                media_type: source
+                    .get("media_type")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                source: data.to_string(),
+            })
+        }
+        "input_image" => Some(SessionBlock::Image {
+            media_type: None,
+            source: str_field("image_url")?.to_string(),
+        }),
+        _ => extract_text_from_item(item).and_then(|text| text_block(&text)),
+    }
+}
+
+fn text_block(text: &str) -> Option<SessionBlock> {
+    if text.trim().is_empty() {
+        return None;
+    }
+    Some(SessionBlock::Text {
+        text: text.to_string(),
+    })
+}
+
 pub fn truncate_summary(text: &str, max_chars: usize) -> String {
     let trimmed = text.trim();
     if trimmed.is_empty() {
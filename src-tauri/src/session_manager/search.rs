@@ -0,0 +1,397 @@
+//! 会话全文检索
+//!
+//! 基于数据库中的 FTS5 索引（见 `database::dao::session_index`）。索引按会话文件增量更新：
+//! 文件大小或修改时间变化时重新解析并替换该文件的消息，已删除的文件同步移出索引。
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::database::Database;
+use crate::error::AppError;
+
+use super::{load_messages, scan_sessions, SessionMeta};
+
+/// 默认返回的消息命中数上限
+const DEFAULT_HIT_LIMIT: usize = 200;
+/// 片段中关键词前后保留的字符数
+const SNIPPET_RADIUS: usize = 60;
+/// 检索前自动增量更新索引的最短间隔，避免连续检索时反复扫描会话目录
+const AUTO_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 上次增量更新索引的时间
+static LAST_REFRESH: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// 检索条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchQuery {
+    /// 关键词，按空白分隔，需全部命中
+    pub text: String,
+    #[serde(default)]
+    pub provider_id: Option<String>,
+    #[serde(default)]
+    pub project_dir: Option<String>,
+    /// 起始时间（毫秒时间戳，含）
+    #[serde(default)]
+    pub start_date: Option<i64>,
+    /// 结束时间（毫秒时间戳，含）
+    #[serde(default)]
+    pub end_date: Option<i64>,
+    /// 仅检索指定角色的消息（user / assistant 等）
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// 已索引的会话文件
+#[derive(Debug, Clone)]
+pub struct IndexedSessionFile {
+    pub source_path: String,
+    pub provider_id: String,
+    pub session_id: String,
+    pub title: Option<String>,
+    pub project_dir: Option<String>,
+    pub resume_command: Option<String>,
+    pub created_at: Option<i64>,
+    pub last_active_at: Option<i64>,
+    pub file_size: i64,
+    pub file_mtime: i64,
+    pub indexed_at: i64,
+}
+
+/// 数据库返回的单条命中消息
+#[derive(Debug, Clone)]
+pub struct SessionSearchRow {
+    pub provider_id: String,
+    pub session_id: String,
+    pub source_path: String,
+    pub title: Option<String>,
+    pub project_dir: Option<String>,
+    pub resume_command: Option<String>,
+    pub last_active_at: Option<i64>,
+    pub role: String,
+    pub content: String,
+    pub seq: i64,
+    pub ts: Option<i64>,
+}
+
+/// 会话内命中的消息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMessageHit {
+    pub role: String,
+    /// 消息在会话中的序号（与 `load_messages` 返回顺序一致）
+    pub seq: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ts: Option<i64>,
+    /// 命中片段，关键词以 `<mark>` 包裹
+    pub snippet: String,
+}
+
+/// 按会话分组的检索结果（按最佳命中排序）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchResult {
+    pub provider_id: String,
+    pub session_id: String,
+    pub source_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resume_command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_active_at: Option<i64>,
+    pub hits: Vec<SessionMessageHit>,
+}
+
+/// 索引更新统计
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionIndexStats {
+    pub indexed: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    pub failed: usize,
+}
+
+/// 增量更新索引；`rebuild` 为 true 时先清空再全量索引
+pub fn refresh_index(db: &Database, rebuild: bool) -> Result<SessionIndexStats, AppError> {
+    if rebuild {
+        db.clear_session_index()?;
+    }
+
+    let indexed = db.get_session_index_files()?;
+    let mut stats = SessionIndexStats::default();
+    let mut seen = HashSet::new();
+    let now = chrono::Utc::now().timestamp_millis();
+
+    for session in scan_sessions() {
+        let Some(source_path) = session.source_path.clone() else {
+            continue;
+        };
+        if !seen.insert(source_path.clone()) {
+            continue;
+        }
+        let Some((file_size, file_mtime)) = file_stamp(Path::new(&source_path)) else {
+            continue;
+        };
+        if indexed.get(&source_path) == Some(&(file_size, file_mtime)) {
+            stats.unchanged += 1;
+            continue;
+        }
+
+        let messages = match load_messages(&session.provider_id, &source_path) {
+            Ok(messages) => messages,
+            Err(e) => {
+                log::warn!("Failed to index session {source_path}: {e}");
+                stats.failed += 1;
+                continue;
+            }
+        };
+        let file = indexed_file(session, source_path, file_size, file_mtime, now);
+        db.replace_session_index(&file, &messages)?;
+        stats.indexed += 1;
+    }
+
+    for source_path in indexed.keys() {
+        if !seen.contains(source_path) {
+            db.remove_session_index(source_path)?;
+            stats.removed += 1;
+        }
+    }
+
+    if let Ok(mut last) = LAST_REFRESH.lock() {
+        *last = Some(Instant::now());
+    }
+    Ok(stats)
+}
+
+/// 检索前确保索引覆盖新会话：距上次更新超过间隔时做一次增量更新
+///
+/// 增量更新只重新解析大小或修改时间变化的文件，未变化的会话仅比较文件时间戳。
+pub fn ensure_index(db: &Database) -> Result<(), AppError> {
+    let stale = LAST_REFRESH
+        .lock()
+        .map(|last| last.is_none_or(|at| at.elapsed() >= AUTO_REFRESH_INTERVAL))
+        .unwrap_or(true);
+    if stale {
+        refresh_index(db, false)?;
+    }
+    Ok(())
+}
+
+/// 检索会话消息，结果按会话分组
+pub fn search(
+    db: &Database,
+    query: &SessionSearchQuery,
+) -> Result<Vec<SessionSearchResult>, AppError> {
+    let terms = split_terms(&query.text);
+    if terms.is_empty() {
+        return Err(AppError::InvalidInput("检索关键词不能为空".to_string()));
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_HIT_LIMIT).max(1);
+    let rows = db.search_session_index(query, &terms, limit)?;
+
+    let mut results: Vec<SessionSearchResult> = Vec::new();
+    for row in rows {
+        let hit = SessionMessageHit {
+            role: row.role,
+            seq: row.seq,
+            ts: row.ts,
+            snippet: build_snippet(&row.content, &terms, SNIPPET_RADIUS),
+        };
+        match results
+            .iter_mut()
+            .find(|r| r.source_path == row.source_path)
+        {
+            Some(result) => result.hits.push(hit),
+            None => results.push(SessionSearchResult {
+                provider_id: row.provider_id,
+                session_id: row.session_id,
+                source_path: row.source_path,
+                title: row.title,
+                project_dir: row.project_dir,
+                resume_command: row.resume_command,
+                last_active_at: row.last_active_at,
+                hits: vec![hit],
+            }),
+        }
+    }
+
+    for result in &mut results {
+        result.hits.sort_by_key(|hit| hit.seq);
+    }
+
+    Ok(results)
+}
+
+fn indexed_file(
+    session: SessionMeta,
+    source_path: String,
+    file_size: i64,
+    file_mtime: i64,
+    indexed_at: i64,
+) -> IndexedSessionFile {
+    IndexedSessionFile {
+        source_path,
+        provider_id: session.provider_id,
+        session_id: session.session_id,
+        title: session.title,
+        project_dir: session.project_dir,
+        resume_command: session.resume_command,
+        created_at: session.created_at,
+        last_active_at: session.last_active_at,
+        file_size,
+        file_mtime,
+        indexed_at,
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(i64, i64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0);
+    Some((meta.len() as i64, mtime))
+}
+
+fn split_terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in text.split_whitespace() {
+        if !terms.iter().any(|t| t == term) {
+            terms.push(term.to_string());
+        }
+    }
+    terms
+}
+
+/// 截取首个命中关键词附近的片段，并用 `<mark>` 标记所有命中（不区分大小写）
+///
+/// 消息内容先做 HTML 转义，片段中只有 `<mark>` 是标签。
+fn build_snippet(content: &str, terms: &[String], radius: usize) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let needles: Vec<Vec<char>> = terms
+        .iter()
+        .map(|t| {
+            t.chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .collect()
+        })
+        .filter(|n: &Vec<char>| !n.is_empty())
+        .collect();
+
+    // 命中区间（按字符下标）
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut i = 0;
+    while i < lower.len() {
+        let matched = needles
+            .iter()
+            .filter(|n| lower[i..].starts_with(n))
+            .map(|n| n.len())
+            .max();
+        match matched {
+            Some(len) => {
+                ranges.push((i, i + len));
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+
+    let first = ranges.first().map(|r| r.0).unwrap_or(0);
+    let start = first.saturating_sub(radius);
+    let first_end = ranges.first().map(|r| r.1).unwrap_or(0);
+    let end = (first + radius).max(first_end).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    let mut pos = start;
+    for (range_start, range_end) in ranges.iter().copied() {
+        if range_end <= start || range_start >= end {
+            continue;
+        }
+        let range_start = range_start.max(start);
+        let range_end = range_end.min(end);
+        push_escaped(&mut snippet, &chars[pos..range_start]);
+        snippet.push_str("<mark>");
+        push_escaped(&mut snippet, &chars[range_start..range_end]);
+        snippet.push_str("</mark>");
+        pos = range_end;
+    }
+    push_escaped(&mut snippet, &chars[pos..end]);
+    if end < chars.len() {
+        snippet.push('…');
+    }
+
+    snippet.replace(['\n', '\r'], " ")
+}
+
+fn push_escaped(out: &mut String, chars: &[char]) {
+    for c in chars {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(*c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_terms_dedups_and_ignores_whitespace() {
+        assert_eq!(
+            split_terms("  migration  bug\tmigration "),
+            vec!["migration".to_string(), "bug".to_string()]
+        );
+        assert!(split_terms("   ").is_empty());
+    }
+
+    #[test]
+    fn snippet_marks_terms_around_first_hit() {
+        let content = format!("{}Fixed the Migration bug\nin schema.rs", "x".repeat(100));
+        let terms = vec!["migration".to_string(), "bug".to_string()];
+        let snippet = build_snippet(&content, &terms, 20);
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.contains("<mark>Migration</mark> <mark>bug</mark>"));
+        assert!(!snippet.contains('\n'));
+
+        let short = build_snippet("迁移脚本出错", &["脚本".to_string()], 20);
+        assert_eq!(short, "迁移<mark>脚本</mark>出错");
+    }
+
+    #[test]
+    fn snippet_escapes_html_in_content() {
+        let snippet = build_snippet(
+            "<img src=x onerror=\"alert('x')\"> & <b>bold</b>",
+            &["<b>".to_string()],
+            80,
+        );
+        assert_eq!(
+            snippet,
+            "&lt;img src=x onerror=&quot;alert(&#39;x&#39;)&quot;&gt; &amp; <mark>&lt;b&gt;</mark>bold&lt;/b&gt;"
+        );
+    }
+}
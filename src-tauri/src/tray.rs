@@ -2,6 +2,9 @@
 //!
 //! 负责系统托盘图标和菜单的创建、更新和事件处理。
 
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
 use tauri::menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem};
 use tauri::{Emitter, Manager};
 
@@ -9,6 +12,34 @@ use crate::app_config::AppType;
 use crate::error::AppError;
 use crate::store::AppState;
 
+/// 托盘提示中的告警行（来源 → 文本）；多个来源共用同一个提示，互不覆盖
+static TRAY_NOTICES: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// 设置或清除某个来源的托盘提示行，并刷新托盘提示（全部清除后恢复为无提示）
+pub fn set_tray_notice(app: &tauri::AppHandle, key: &str, notice: Option<String>) {
+    let tooltip = {
+        let Ok(mut notices) = TRAY_NOTICES.lock() else {
+            return;
+        };
+        let changed = match notice {
+            Some(text) => notices.insert(key.to_string(), text.clone()).as_ref() != Some(&text),
+            None => notices.remove(key).is_some(),
+        };
+        if !changed {
+            return;
+        }
+        (!notices.is_empty()).then(|| {
+            let lines: Vec<&str> = notices.values().map(String::as_str).collect();
+            format!("CC Switch\n{}", lines.join("\n"))
+        })
+    };
+    if let Some(tray) = app.tray_by_id("main") {
+        if let Err(e) = tray.set_tooltip(tooltip) {
+            log::warn!("更新托盘提示失败: {e}");
+        }
+    }
+}
+
 /// 托盘菜单文本（国际化）
 #[derive(Clone, Copy)]
 pub struct TrayTexts {
@@ -1,6 +1,10 @@
-use rquickjs::{Context, Function, Runtime};
+use rquickjs::{Context, Ctx, Exception, Function, Runtime};
 use serde_json::Value;
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 use url::{Host, Url};
 
 use crate::error::AppError;
@@ -30,21 +34,10 @@ pub async fn execute_usage_script(
     }
 
     // 3. 在独立作用域中提取 request 配置（确保 Runtime/Context 在 await 前释放）
-    let request_config = {
-        let runtime = Runtime::new().map_err(|e| {
-            AppError::localized(
-                "usage_script.runtime_create_failed",
-                format!("创建 JS 运行时失败: {e}"),
-                format!("Failed to create JS runtime: {e}"),
-            )
-        })?;
-        let context = Context::full(&runtime).map_err(|e| {
-            AppError::localized(
-                "usage_script.context_create_failed",
-                format!("创建 JS 上下文失败: {e}"),
-                format!("Failed to create JS context: {e}"),
-            )
-        })?;
+    // request 为可选：脚本可以不声明 request，完全通过 fetch 自行发起请求
+    let request_config: Option<String> = {
+        let runtime = create_runtime()?;
+        let context = create_context(&runtime)?;
 
         context.with(|ctx| {
             // 执行用户代码，获取配置对象
@@ -57,13 +50,16 @@ pub async fn execute_usage_script(
             })?;
 
             // 提取 request 配置
-            let request: rquickjs::Object = config.get("request").map_err(|e| {
+            let request: Option<rquickjs::Object> = config.get("request").map_err(|e| {
                 AppError::localized(
                     "usage_script.request_missing",
                     format!("缺少 request 配置: {e}"),
                     format!("Missing request config: {e}"),
                 )
             })?;
+            let Some(request) = request else {
+                return Ok::<_, AppError>(None);
+            };
 
             // 将 request 转换为 JSON 字符串
             let request_json: String = ctx
@@ -91,141 +87,404 @@ pub async fn execute_usage_script(
                     )
                 })?;
 
-            Ok::<_, AppError>(request_json)
+            Ok::<_, AppError>(Some(request_json))
         })?
     }; // Runtime 和 Context 在这里被 drop
 
-    // 4. 解析 request 配置
-    let request: RequestConfig = serde_json::from_str(&request_config).map_err(|e| {
+    let response_data = match request_config {
+        Some(request_config) => {
+            // 4. 解析 request 配置
+            let request: RequestConfig = serde_json::from_str(&request_config).map_err(|e| {
+                AppError::localized(
+                    "usage_script.request_format_invalid",
+                    format!("request 配置格式错误: {e}"),
+                    format!("Invalid request config format: {e}"),
+                )
+            })?;
+
+            // 5. 验证请求 URL 是否安全（防止 SSRF）
+            // 如果提供了 base_url，则验证同源；否则只做基本安全检查
+            validate_request_url(&request.url, base_url, is_custom_template)?;
+            let pinned = {
+                let (url, base_url) = (request.url.clone(), base_url.to_string());
+                tokio::task::spawn_blocking(move || {
+                    resolve_request_host(&url, &base_url, is_custom_template)
+                })
+                .await
+                .map_err(|e| {
+                    AppError::localized(
+                        "usage_script.dns_resolve_failed",
+                        format!("解析域名失败: {e}"),
+                        format!("Failed to resolve host: {e}"),
+                    )
+                })??
+            };
+
+            // 6. 发送 HTTP 请求
+            Some(send_http_request(&request, timeout_secs, pinned.as_ref()).await?)
+        }
+        None => None,
+    };
+
+    // 7. 在阻塞线程中执行 extractor（extractor 内可通过 fetch 发起后续请求）
+    let sandbox = FetchSandbox {
+        runtime: tokio::runtime::Handle::current(),
+        base_url: base_url.to_string(),
+        is_custom_template,
+        timeout_secs,
+        deadline: Instant::now() + request_timeout(timeout_secs),
+        initial_requests: u32::from(response_data.is_some()),
+    };
+    let result: Value = tokio::task::spawn_blocking(move || {
+        run_extractor(&script_with_vars, response_data.as_deref(), sandbox)
+    })
+    .await
+    .map_err(|e| {
         AppError::localized(
-            "usage_script.request_format_invalid",
-            format!("request 配置格式错误: {e}"),
-            format!("Invalid request config format: {e}"),
+            "usage_script.extractor_exec_failed",
+            format!("执行 extractor 失败: {e}"),
+            format!("Failed to execute extractor: {e}"),
         )
-    })?;
+    })??;
+
+    // 8. 验证返回值格式
+    validate_result(&result)?;
 
-    // 5. 验证请求 URL 是否安全（防止 SSRF）
-    // 如果提供了 base_url，则验证同源；否则只做基本安全检查
-    validate_request_url(&request.url, base_url, is_custom_template)?;
+    Ok(result)
+}
 
-    // 6. 发送 HTTP 请求
-    let response_data = send_http_request(&request, timeout_secs).await?;
+/// 单次脚本执行允许的最大 HTTP 请求数（包含 request 配置发起的请求）
+const MAX_SCRIPT_REQUESTS: u32 = 10;
+
+/// 注入到 JS 运行时的 fetch 包装，底层调用 Rust 实现的 `__ccSwitchFetch`
+///
+/// 返回值遵循 Web fetch 的常用子集：`status`、`ok`、`headers`、`text()`、`json()`。
+const FETCH_PRELUDE: &str = r#"
+globalThis.fetch = function (url, options) {
+    try {
+        const raw = JSON.parse(__ccSwitchFetch(String(url), JSON.stringify(options || {})));
+        return Promise.resolve({
+            status: raw.status,
+            ok: raw.status >= 200 && raw.status < 300,
+            headers: raw.headers,
+            text: () => Promise.resolve(raw.body),
+            json: () => Promise.resolve(JSON.parse(raw.body)),
+        });
+    } catch (e) {
+        return Promise.reject(e);
+    }
+};
+"#;
 
-    // 7. 在独立作用域中执行 extractor（确保 Runtime/Context 在函数结束前释放）
-    let result: Value = {
-        let runtime = Runtime::new().map_err(|e| {
+/// fetch 沙箱参数
+struct FetchSandbox {
+    runtime: tokio::runtime::Handle,
+    base_url: String,
+    is_custom_template: bool,
+    timeout_secs: u64,
+    /// 整个 extractor 阶段（含所有 fetch）的截止时间
+    deadline: Instant,
+    /// 进入 extractor 前已发起的请求数
+    initial_requests: u32,
+}
+
+/// fetch 调用参数（与 Web fetch 的 init 对象对应）
+#[derive(Debug, Default, serde::Deserialize)]
+struct FetchOptions {
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+fn create_runtime() -> Result<Runtime, AppError> {
+    Runtime::new().map_err(|e| {
+        AppError::localized(
+            "usage_script.runtime_create_failed",
+            format!("创建 JS 运行时失败: {e}"),
+            format!("Failed to create JS runtime: {e}"),
+        )
+    })
+}
+
+fn create_context(runtime: &Runtime) -> Result<Context, AppError> {
+    Context::full(runtime).map_err(|e| {
+        AppError::localized(
+            "usage_script.context_create_failed",
+            format!("创建 JS 上下文失败: {e}"),
+            format!("Failed to create JS context: {e}"),
+        )
+    })
+}
+
+/// 约束超时范围，防止异常配置导致长时间阻塞（最小 2 秒，最大 30 秒）
+fn request_timeout(timeout_secs: u64) -> Duration {
+    Duration::from_secs(timeout_secs.clamp(2, 30))
+}
+
+/// 将 rquickjs 错误转换为可读文本（异常时取出 JS 抛出的消息）
+fn describe_js_error(ctx: &Ctx<'_>, err: rquickjs::Error) -> String {
+    if !matches!(err, rquickjs::Error::Exception) {
+        return err.to_string();
+    }
+    let thrown = ctx.catch();
+    if let Some(exception) = thrown.as_exception() {
+        return exception.message().unwrap_or_else(|| err.to_string());
+    }
+    if let Some(text) = thrown.as_string().and_then(|s| s.to_string().ok()) {
+        return text;
+    }
+    format!("{thrown:?}")
+}
+
+/// 执行 extractor（在阻塞线程中运行，fetch 通过 tokio Handle 同步等待响应）
+fn run_extractor(
+    script_with_vars: &str,
+    response_data: Option<&str>,
+    sandbox: FetchSandbox,
+) -> Result<Value, AppError> {
+    let runtime = create_runtime()?;
+    // 超过截止时间后中断脚本执行，防止死循环
+    let deadline = sandbox.deadline;
+    runtime.set_interrupt_handler(Some(Box::new(move || Instant::now() > deadline)));
+    let context = create_context(&runtime)?;
+    let sandbox = Rc::new(sandbox);
+    let used = Rc::new(Cell::new(sandbox.initial_requests));
+
+    context.with(|ctx| {
+        install_fetch(&ctx, sandbox.clone(), used.clone()).map_err(|e| {
             AppError::localized(
-                "usage_script.runtime_create_failed",
-                format!("创建 JS 运行时失败: {e}"),
-                format!("Failed to create JS runtime: {e}"),
+                "usage_script.fetch_install_failed",
+                format!("注入 fetch 失败: {e}"),
+                format!("Failed to install fetch: {e}"),
             )
         })?;
-        let context = Context::full(&runtime).map_err(|e| {
+
+        // 重新 eval 获取配置对象
+        let config: rquickjs::Object = ctx.eval(script_with_vars).map_err(|e| {
+            let message = describe_js_error(&ctx, e);
             AppError::localized(
-                "usage_script.context_create_failed",
-                format!("创建 JS 上下文失败: {e}"),
-                format!("Failed to create JS context: {e}"),
+                "usage_script.config_reparse_failed",
+                format!("重新解析配置失败: {message}"),
+                format!("Failed to re-parse config: {message}"),
             )
         })?;
 
-        context.with(|ctx| {
-            // 重新 eval 获取配置对象
-            let config: rquickjs::Object = ctx.eval(script_with_vars.clone()).map_err(|e| {
-                AppError::localized(
-                    "usage_script.config_reparse_failed",
-                    format!("重新解析配置失败: {e}"),
-                    format!("Failed to re-parse config: {e}"),
-                )
-            })?;
-
-            // 提取 extractor 函数
-            let extractor: Function = config.get("extractor").map_err(|e| {
-                AppError::localized(
-                    "usage_script.extractor_missing",
-                    format!("缺少 extractor 函数: {e}"),
-                    format!("Missing extractor function: {e}"),
-                )
-            })?;
+        // 提取 extractor 函数
+        let extractor: Function = config.get("extractor").map_err(|e| {
+            AppError::localized(
+                "usage_script.extractor_missing",
+                format!("缺少 extractor 函数: {e}"),
+                format!("Missing extractor function: {e}"),
+            )
+        })?;
 
-            // 将响应数据转换为 JS 值
-            let response_js: rquickjs::Value =
-                ctx.json_parse(response_data.as_str()).map_err(|e| {
+        // 调用 extractor(response)；未配置 request 时不传参数
+        let called = match response_data {
+            Some(data) => {
+                // 将响应数据转换为 JS 值
+                let response_js: rquickjs::Value = ctx.json_parse(data).map_err(|e| {
                     AppError::localized(
                         "usage_script.response_parse_failed",
                         format!("解析响应 JSON 失败: {e}"),
                         format!("Failed to parse response JSON: {e}"),
                     )
                 })?;
+                extractor.call::<_, rquickjs::Value>((response_js,))
+            }
+            None => extractor.call::<_, rquickjs::Value>(()),
+        };
 
-            // 调用 extractor(response)
-            let result_js: rquickjs::Value = extractor.call((response_js,)).map_err(|e| {
+        // async extractor 返回 Promise，驱动任务队列直到完成
+        let result_js = called.and_then(|value| match value.as_promise() {
+            Some(promise) => promise.finish::<rquickjs::Value>(),
+            None => Ok(value),
+        });
+        let result_js = result_js.map_err(|e| {
+            let message = describe_js_error(&ctx, e);
+            AppError::localized(
+                "usage_script.extractor_exec_failed",
+                format!("执行 extractor 失败: {message}"),
+                format!("Failed to execute extractor: {message}"),
+            )
+        })?;
+
+        // 转换为 JSON 字符串
+        let result_json: String = ctx
+            .json_stringify(result_js)
+            .map_err(|e| {
+                AppError::localized(
+                    "usage_script.result_serialize_failed",
+                    format!("序列化结果失败: {e}"),
+                    format!("Failed to serialize result: {e}"),
+                )
+            })?
+            .ok_or_else(|| {
                 AppError::localized(
-                    "usage_script.extractor_exec_failed",
-                    format!("执行 extractor 失败: {e}"),
-                    format!("Failed to execute extractor: {e}"),
+                    "usage_script.serialize_none",
+                    "序列化返回 None",
+                    "Serialization returned None",
+                )
+            })?
+            .get()
+            .map_err(|e| {
+                AppError::localized(
+                    "usage_script.get_string_failed",
+                    format!("获取字符串失败: {e}"),
+                    format!("Failed to get string: {e}"),
                 )
             })?;
 
-            // 转换为 JSON 字符串
-            let result_json: String = ctx
-                .json_stringify(result_js)
-                .map_err(|e| {
-                    AppError::localized(
-                        "usage_script.result_serialize_failed",
-                        format!("序列化结果失败: {e}"),
-                        format!("Failed to serialize result: {e}"),
-                    )
-                })?
-                .ok_or_else(|| {
-                    AppError::localized(
-                        "usage_script.serialize_none",
-                        "序列化返回 None",
-                        "Serialization returned None",
-                    )
-                })?
-                .get()
-                .map_err(|e| {
-                    AppError::localized(
-                        "usage_script.get_string_failed",
-                        format!("获取字符串失败: {e}"),
-                        format!("Failed to get string: {e}"),
-                    )
-                })?;
+        // 解析为 serde_json::Value
+        serde_json::from_str(&result_json).map_err(|e| {
+            AppError::localized(
+                "usage_script.json_parse_failed",
+                format!("JSON 解析失败: {e}"),
+                format!("JSON parse failed: {e}"),
+            )
+        })
+    })
+}
 
-            // 解析为 serde_json::Value
-            serde_json::from_str(&result_json).map_err(|e| {
-                AppError::localized(
-                    "usage_script.json_parse_failed",
-                    format!("JSON 解析失败: {e}"),
-                    format!("JSON parse failed: {e}"),
-                )
-            })
-        })?
-    }; // Runtime 和 Context 在这里被 drop
+/// 向 JS 上下文注入受限的 fetch
+fn install_fetch<'js>(
+    ctx: &Ctx<'js>,
+    sandbox: Rc<FetchSandbox>,
+    used: Rc<Cell<u32>>,
+) -> rquickjs::Result<()> {
+    let native = Function::new(
+        ctx.clone(),
+        move |ctx: Ctx<'js>, url: String, options: String| -> rquickjs::Result<String> {
+            sandboxed_fetch(&sandbox, &used, &url, &options)
+                .map_err(|e| Exception::throw_message(&ctx, &e.to_string()))
+        },
+    )?;
+    ctx.globals().set("__ccSwitchFetch", native)?;
+    ctx.eval::<(), _>(FETCH_PRELUDE)?;
+    Ok(())
+}
 
-    // 8. 验证返回值格式
-    validate_result(&result)?;
+/// fetch 的 Rust 实现：校验预算与 URL 后同步发送请求
+fn sandboxed_fetch(
+    sandbox: &FetchSandbox,
+    used: &Cell<u32>,
+    url: &str,
+    options: &str,
+) -> Result<String, AppError> {
+    if used.get() >= MAX_SCRIPT_REQUESTS {
+        return Err(AppError::localized(
+            "usage_script.fetch_budget_exceeded",
+            format!("请求次数超过上限（{MAX_SCRIPT_REQUESTS}）"),
+            format!("Request budget exceeded ({MAX_SCRIPT_REQUESTS})"),
+        ));
+    }
 
-    Ok(result)
+    let remaining = sandbox.deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+        return Err(AppError::localized(
+            "usage_script.fetch_timeout",
+            "脚本执行超时",
+            "Script execution timed out",
+        ));
+    }
+
+    let options: FetchOptions = serde_json::from_str(options).map_err(|e| {
+        AppError::localized(
+            "usage_script.fetch_options_invalid",
+            format!("fetch 参数格式错误: {e}"),
+            format!("Invalid fetch options: {e}"),
+        )
+    })?;
+
+    validate_request_url(url, &sandbox.base_url, sandbox.is_custom_template)?;
+    let pinned = resolve_request_host(url, &sandbox.base_url, sandbox.is_custom_template)?;
+    used.set(used.get() + 1);
+
+    let config = RequestConfig {
+        url: url.to_string(),
+        method: options.method.unwrap_or_else(|| "GET".to_string()),
+        headers: options.headers,
+        body: options.body,
+    };
+    let timeout = request_timeout(sandbox.timeout_secs).min(remaining);
+    let (status, headers, body) =
+        sandbox
+            .runtime
+            .block_on(send_request(&config, timeout, pinned.as_ref()))?;
+
+    Ok(serde_json::json!({
+        "status": status.as_u16(),
+        "headers": headers,
+        "body": body,
+    })
+    .to_string())
 }
 
 /// 请求配置结构
 #[derive(Debug, serde::Deserialize)]
-struct RequestConfig {
-    url: String,
-    method: String,
+pub(crate) struct RequestConfig {
+    pub(crate) url: String,
+    pub(crate) method: String,
     #[serde(default)]
-    headers: HashMap<String, String>,
+    pub(crate) headers: HashMap<String, String>,
     #[serde(default)]
-    body: Option<String>,
+    pub(crate) body: Option<String>,
 }
 
-/// 发送 HTTP 请求
-async fn send_http_request(config: &RequestConfig, timeout_secs: u64) -> Result<String, AppError> {
-    // 使用全局 HTTP 客户端（已包含代理配置）
-    let client = crate::proxy::http_client::get();
-    // 约束超时范围，防止异常配置导致长时间阻塞（最小 2 秒，最大 30 秒）
-    let request_timeout = std::time::Duration::from_secs(timeout_secs.clamp(2, 30));
+/// 已校验的请求域名及其解析地址，发送请求时固定使用
+pub(crate) struct PinnedHost {
+    host: String,
+    addrs: Vec<SocketAddr>,
+}
+
+/// 发送 HTTP 请求，非 2xx 响应视为错误
+pub(crate) async fn send_http_request(
+    config: &RequestConfig,
+    timeout_secs: u64,
+    pinned: Option<&PinnedHost>,
+) -> Result<String, AppError> {
+    let (status, _, text) = send_request(config, request_timeout(timeout_secs), pinned).await?;
+
+    if !status.is_success() {
+        let preview = if text.len() > 200 {
+            let mut safe_cut = 200usize;
+            while !text.is_char_boundary(safe_cut) {
+                safe_cut = safe_cut.saturating_sub(1);
+            }
+            format!("{}...", &text[..safe_cut])
+        } else {
+            text.clone()
+        };
+        return Err(AppError::localized(
+            "usage_script.http_error",
+            format!("HTTP {status} : {preview}"),
+            format!("HTTP {status} : {preview}"),
+        ));
+    }
+
+    Ok(text)
+}
+
+/// 发送 HTTP 请求并返回状态码、响应头与响应体
+///
+/// 不跟随重定向：3xx 响应原样返回，脚本需再次 fetch 新地址，从而重新经过 URL 校验。
+async fn send_request(
+    config: &RequestConfig,
+    request_timeout: Duration,
+    pinned: Option<&PinnedHost>,
+) -> Result<(reqwest::StatusCode, HashMap<String, String>, String), AppError> {
+    let client = crate::proxy::http_client::build_no_redirect_client(
+        pinned.map(|p| (p.host.as_str(), p.addrs.as_slice())),
+    )
+    .map_err(|e| {
+        AppError::localized(
+            "usage_script.request_failed",
+            format!("请求失败: {e}"),
+            format!("Request failed: {e}"),
+        )
+    })?;
 
     // 严格校验 HTTP 方法，非法值不回退为 GET
     let method: reqwest::Method = config.method.parse().map_err(|_| {
@@ -260,6 +519,11 @@ async fn send_http_request(config: &RequestConfig, timeout_secs: u64) -> Result<
     })?;
 
     let status = resp.status();
+    let headers = resp
+        .headers()
+        .iter()
+        .filter_map(|(k, v)| Some((k.as_str().to_string(), v.to_str().ok()?.to_string())))
+        .collect();
     let text = resp.text().await.map_err(|e| {
         AppError::localized(
             "usage_script.read_response_failed",
@@ -268,24 +532,7 @@ async fn send_http_request(config: &RequestConfig, timeout_secs: u64) -> Result<
         )
     })?;
 
-    if !status.is_success() {
-        let preview = if text.len() > 200 {
-            let mut safe_cut = 200usize;
-            while !text.is_char_boundary(safe_cut) {
-                safe_cut = safe_cut.saturating_sub(1);
-            }
-            format!("{}...", &text[..safe_cut])
-        } else {
-            text.clone()
-        };
-        return Err(AppError::localized(
-            "usage_script.http_error",
-            format!("HTTP {status} : {preview}"),
-            format!("HTTP {status} : {preview}"),
-        ));
-    }
-
-    Ok(text)
+    Ok((status, headers, text))
 }
 
 /// 验证脚本返回值（支持单对象或数组）
@@ -421,7 +668,7 @@ fn build_script_with_vars(
 }
 
 /// 验证 base_url 的基本安全性
-fn validate_base_url(base_url: &str) -> Result<(), AppError> {
+pub(crate) fn validate_base_url(base_url: &str) -> Result<(), AppError> {
     if base_url.is_empty() {
         return Err(AppError::localized(
             "usage_script.base_url_empty",
@@ -481,7 +728,7 @@ fn validate_base_url(base_url: &str) -> Result<(), AppError> {
 }
 
 /// 验证请求 URL 是否安全（防止 SSRF）
-fn validate_request_url(
+pub(crate) fn validate_request_url(
     request_url: &str,
     base_url: &str,
     is_custom_template: bool,
@@ -592,6 +839,73 @@ fn validate_request_url(
     Ok(())
 }
 
+/// 解析请求域名并校验解析结果（防止域名指向内网地址）
+///
+/// 仅在没有同源限制时需要（自定义模板或未提供 base_url）；IP 字面量与 localhost
+/// 已由 [`validate_request_url`] 校验，返回 None。
+fn resolve_request_host(
+    request_url: &str,
+    base_url: &str,
+    is_custom_template: bool,
+) -> Result<Option<PinnedHost>, AppError> {
+    if !base_url.is_empty() && !is_custom_template {
+        return Ok(None);
+    }
+    let parsed = Url::parse(request_url).map_err(|e| {
+        AppError::localized(
+            "usage_script.request_url_invalid",
+            format!("无效的请求 URL: {e}"),
+            format!("Invalid request URL: {e}"),
+        )
+    })?;
+    let Some(Host::Domain(domain)) = parsed.host() else {
+        return Ok(None);
+    };
+    if domain.eq_ignore_ascii_case("localhost") {
+        return Ok(None);
+    }
+
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<SocketAddr> = (domain, port)
+        .to_socket_addrs()
+        .map_err(|e| {
+            AppError::localized(
+                "usage_script.dns_resolve_failed",
+                format!("解析域名 {domain} 失败: {e}"),
+                format!("Failed to resolve host {domain}: {e}"),
+            )
+        })?
+        .collect();
+    ensure_public_addrs(domain, &addrs)?;
+
+    Ok(Some(PinnedHost {
+        host: domain.to_string(),
+        addrs,
+    }))
+}
+
+/// 域名的解析结果必须非空且全部为公网地址（含 IPv4 映射的 IPv6 地址）
+fn ensure_public_addrs(domain: &str, addrs: &[SocketAddr]) -> Result<(), AppError> {
+    if addrs.is_empty() {
+        return Err(AppError::localized(
+            "usage_script.dns_resolve_failed",
+            format!("域名 {domain} 没有解析结果"),
+            format!("Host {domain} did not resolve to any address"),
+        ));
+    }
+    if addrs
+        .iter()
+        .any(|addr| is_private_ip_addr(addr.ip().to_canonical()))
+    {
+        return Err(AppError::localized(
+            "usage_script.private_ip_blocked",
+            format!("域名 {domain} 解析到私有 IP 地址，禁止访问"),
+            format!("Host {domain} resolves to a private IP address and is blocked"),
+        ));
+    }
+    Ok(())
+}
+
 /// 检查是否为私有 IP 地址
 fn is_private_ip(host: &str) -> bool {
     // localhost 检查
@@ -893,4 +1207,171 @@ mod tests {
             }
         }
     }
+
+    fn test_sandbox(runtime: &tokio::runtime::Runtime, initial_requests: u32) -> FetchSandbox {
+        FetchSandbox {
+            runtime: runtime.handle().clone(),
+            base_url: String::new(),
+            is_custom_template: true,
+            timeout_secs: 5,
+            deadline: Instant::now() + Duration::from_secs(5),
+            initial_requests,
+        }
+    }
+
+    #[test]
+    fn test_async_extractor_without_request() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let script = r#"({
+            extractor: async function () {
+                const value = await Promise.resolve(42);
+                return { remaining: value, unit: "USD" };
+            }
+        })"#;
+
+        let result = run_extractor(script, None, test_sandbox(&runtime, 0)).unwrap();
+        assert_eq!(result["remaining"], 42);
+        assert_eq!(result["unit"], "USD");
+    }
+
+    #[test]
+    fn test_fetch_budget_exceeded() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let script = r#"({
+            extractor: async function () {
+                const resp = await fetch("https://api.example.com/balance");
+                return { remaining: (await resp.json()).balance };
+            }
+        })"#;
+
+        let err = run_extractor(script, None, test_sandbox(&runtime, MAX_SCRIPT_REQUESTS))
+            .expect_err("超出请求预算时应拒绝 fetch");
+        assert!(
+            err.to_string()
+                .contains(&format!("Request budget exceeded ({MAX_SCRIPT_REQUESTS})")),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_fetch_blocks_private_ip() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let script = r#"({
+            extractor: async function () {
+                await fetch("http://10.0.0.1/admin");
+                return { remaining: 1 };
+            }
+        })"#;
+
+        let err = run_extractor(script, None, test_sandbox(&runtime, 0))
+            .expect_err("fetch 不应允许访问私有 IP");
+        assert!(
+            err.to_string()
+                .contains("Access to private IP addresses is blocked"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_resolved_private_addrs_blocked() {
+        let public: SocketAddr = "93.184.216.34:443".parse().unwrap();
+        assert!(ensure_public_addrs("api.example.com", &[public]).is_ok());
+
+        for private in ["10.0.0.1:443", "127.0.0.1:443", "[::ffff:192.168.1.1]:443"] {
+            let addr: SocketAddr = private.parse().unwrap();
+            let err = ensure_public_addrs("evil.example.com", &[public, addr]).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("Host evil.example.com resolves to a private IP address"),
+                "unexpected error for {private}: {err}"
+            );
+        }
+        let err = ensure_public_addrs("empty.example.com", &[]).unwrap_err();
+        assert!(err.to_string().contains("did not resolve to any address"));
+
+        // IP 字面量、localhost 与同源模式不做解析
+        assert!(resolve_request_host("http://localhost:8080/a", "", true)
+            .unwrap()
+            .is_none());
+        assert!(resolve_request_host("http://127.0.0.1:8080/a", "", true)
+            .unwrap()
+            .is_none());
+        assert!(resolve_request_host(
+            "https://api.example.com/a",
+            "https://api.example.com",
+            false
+        )
+        .unwrap()
+        .is_none());
+    }
+
+    /// 本地 HTTP 服务：依次应答给定的响应体，返回地址与收到的请求行/请求头
+    fn serve_json(bodies: Vec<&'static str>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for body in bodies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    head.push_str(&line);
+                }
+                requests.push(head);
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+            requests
+        });
+        (addr, handle)
+    }
+
+    #[test]
+    fn test_chained_fetch() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (addr, server) = serve_json(vec![r#"{"token":"t-1"}"#, r#"{"balance":12.5}"#]);
+        let script = format!(
+            r#"({{
+            extractor: async function () {{
+                const login = await (await fetch("{addr}/login", {{ method: "POST" }})).json();
+                const resp = await fetch("{addr}/balance", {{
+                    headers: {{ Authorization: "Bearer " + login.token }}
+                }});
+                return {{ remaining: (await resp.json()).balance, unit: "USD" }};
+            }}
+        }})"#
+        );
+
+        let result = run_extractor(&script, None, test_sandbox(&runtime, 0)).unwrap();
+        assert_eq!(result["remaining"], 12.5);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /login "));
+        assert!(requests[1].starts_with("GET /balance "));
+        assert!(requests[1]
+            .to_ascii_lowercase()
+            .contains("authorization: bearer t-1"));
+    }
+
+    #[test]
+    fn test_script_interrupted_after_deadline() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut sandbox = test_sandbox(&runtime, 0);
+        sandbox.deadline = Instant::now() + Duration::from_millis(100);
+        let script = r#"({ extractor: function () { while (true) {} } })"#;
+
+        assert!(run_extractor(script, None, sandbox).is_err());
+    }
 }
@@ -559,6 +559,9 @@ command = "echo"
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     );
 
@@ -687,6 +690,9 @@ fn import_from_claude_merges_into_config() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     );
 
@@ -4,8 +4,9 @@ use std::fs;
 use serde_json::json;
 
 use cc_switch_lib::{
-    get_claude_mcp_path, get_claude_settings_path, import_default_config_test_hook, AppError,
-    AppType, McpApps, McpServer, McpService, MultiAppConfig,
+    get_claude_mcp_path, get_claude_settings_path, import_default_config_test_hook,
+    init_secret_store, set_secret, AppError, AppType, McpApps, McpServer, McpService,
+    MultiAppConfig,
 };
 
 #[path = "support.rs"]
@@ -220,6 +221,9 @@ fn set_mcp_enabled_for_codex_writes_live_config() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     );
 
@@ -284,6 +288,9 @@ fn enabling_codex_mcp_skips_when_codex_dir_missing() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     )
     .expect("insert server without syncing");
@@ -328,6 +335,9 @@ fn upsert_mcp_server_disabling_app_removes_from_claude_live_config() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     )
     .expect("upsert should sync to Claude live config");
@@ -361,6 +371,9 @@ fn upsert_mcp_server_disabling_app_removes_from_claude_live_config() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     )
     .expect("upsert disabling app should remove from Claude live config");
@@ -493,6 +506,9 @@ fn enabling_gemini_mcp_skips_when_gemini_dir_missing() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     )
     .expect("insert server without syncing");
@@ -547,6 +563,9 @@ fn enabling_claude_mcp_skips_when_claude_config_absent() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     )
     .expect("insert server without syncing");
@@ -560,3 +579,49 @@ fn enabling_claude_mcp_skips_when_claude_config_absent() {
         "~/.claude.json should still not exist after skipped sync"
     );
 }
+
+#[test]
+fn project_scoped_mcp_server_resolves_secrets_in_project_files() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let project = tempfile::tempdir().expect("project dir");
+    let project_dir = project.path().to_string_lossy().to_string();
+
+    // 应用可能由用户直接启动，项目配置中的引用需解析为明文
+    init_secret_store(Some("correct horse battery".to_string())).expect("init secret store");
+    set_secret("github_token".to_string(), "ghp_project".to_string()).expect("set secret");
+    let state = support::create_test_state().expect("create test state");
+    McpService::upsert_server(
+        &state,
+        McpServer {
+            id: "github".to_string(),
+            name: "github".to_string(),
+            server: json!({
+                "type": "stdio",
+                "command": "npx",
+                "env": { "GITHUB_TOKEN": "${secret:github_token}" }
+            }),
+            apps: McpApps {
+                claude: true,
+                codex: false,
+                gemini: false,
+                opencode: false,
+            },
+            description: None,
+            homepage: None,
+            docs: None,
+            tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: vec![project_dir],
+        },
+    )
+    .expect("project-scoped upsert should resolve secrets");
+
+    let text = fs::read_to_string(project.path().join(".mcp.json")).expect("read .mcp.json");
+    let v: serde_json::Value = serde_json::from_str(&text).expect("parse .mcp.json");
+    assert_eq!(
+        v.pointer("/mcpServers/github/env/GITHUB_TOKEN"),
+        Some(&json!("ghp_project"))
+    );
+}
@@ -0,0 +1,103 @@
+use serde_json::json;
+
+use cc_switch_lib::{AppType, Prompt, PromptService};
+
+#[path = "support.rs"]
+mod support;
+use support::{create_test_state, ensure_test_home, reset_test_fs, test_mutex};
+
+/// 编辑表单提交的提示词（不含项目作用域与排序）
+fn form_payload(content: &str) -> Prompt {
+    serde_json::from_value(json!({
+        "id": "rules",
+        "name": "Rules",
+        "content": content,
+        "enabled": false,
+    }))
+    .expect("parse prompt payload")
+}
+
+#[test]
+fn upsert_without_sort_index_keeps_stored_order() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    ensure_test_home();
+
+    let state = create_test_state().expect("create test state");
+    let mut other = form_payload("other");
+    other.id = "other".to_string();
+    PromptService::upsert_prompt(&state, AppType::Claude, "other", other).expect("create other");
+    PromptService::upsert_prompt(&state, AppType::Claude, "rules", form_payload("v1"))
+        .expect("create prompt");
+    PromptService::reorder_prompts(
+        &state,
+        AppType::Claude,
+        &["rules".to_string(), "other".to_string()],
+    )
+    .expect("reorder prompts");
+
+    PromptService::upsert_prompt(&state, AppType::Claude, "rules", form_payload("v2"))
+        .expect("edit prompt");
+
+    let prompts = PromptService::get_prompts(&state, AppType::Claude).expect("load prompts");
+    assert_eq!(prompts["rules"].sort_index, Some(0));
+    assert_eq!(prompts["other"].sort_index, Some(1));
+}
+
+#[test]
+fn upsert_without_scope_fields_keeps_stored_projects() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+    let project = home.join("workspace").join("repo");
+    std::fs::create_dir_all(&project).expect("create project dir");
+    let project = project.to_string_lossy().to_string();
+
+    let state = create_test_state().expect("create test state");
+    PromptService::upsert_prompt(&state, AppType::Claude, "rules", form_payload("v1"))
+        .expect("create prompt");
+    PromptService::set_projects(&state, AppType::Claude, "rules", vec![project.clone()])
+        .expect("scope prompt to project");
+
+    PromptService::upsert_prompt(&state, AppType::Claude, "rules", form_payload("v2"))
+        .expect("edit prompt");
+
+    let prompts = PromptService::get_prompts(&state, AppType::Claude).expect("load prompts");
+    let stored = prompts.get("rules").expect("prompt exists");
+    assert_eq!(stored.content, "v2");
+    assert_eq!(stored.projects, vec![project]);
+}
+
+#[test]
+fn enable_reports_files_with_hand_edited_template_sections() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+    let live_file = home.join(".claude").join("CLAUDE.md");
+
+    let state = create_test_state().expect("create test state");
+    PromptService::upsert_prompt(
+        &state,
+        AppType::Claude,
+        "rules",
+        form_payload("Running on {{os}}"),
+    )
+    .expect("create templated prompt");
+    let skipped =
+        PromptService::enable_prompt(&state, AppType::Claude, "rules").expect("enable prompt");
+    assert!(skipped.is_empty());
+
+    let live = std::fs::read_to_string(&live_file).expect("read live file");
+    std::fs::write(&live_file, live.replace("Running on", "Running at"))
+        .expect("edit live file by hand");
+
+    let mut other = form_payload("other");
+    other.id = "other".to_string();
+    PromptService::upsert_prompt(&state, AppType::Claude, "other", other).expect("create other");
+    let skipped =
+        PromptService::enable_prompt(&state, AppType::Claude, "other").expect("enable other");
+    assert_eq!(skipped, vec![live_file.to_string_lossy().to_string()]);
+
+    let live = std::fs::read_to_string(&live_file).expect("read live file");
+    assert!(live.contains("Running at"), "hand edits must be preserved");
+}
@@ -80,6 +80,9 @@ command = "say"
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     );
 
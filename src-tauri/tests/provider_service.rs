@@ -94,6 +94,9 @@ command = "say"
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            tools: Vec::new(),
+            disabled_tools: Default::default(),
+            projects: Vec::new(),
         },
     );
 
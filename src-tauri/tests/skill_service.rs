@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::Path;
+
+use cc_switch_lib::{AppType, InstalledSkill, SkillApps, SkillService};
+
+#[path = "support.rs"]
+mod support;
+use support::{create_test_state, ensure_test_home, reset_test_fs, test_mutex};
+
+/// 在 SSOT 目录中准备一个已安装的 Skill
+fn installed_skill(home: &Path, projects: Vec<String>) -> InstalledSkill {
+    let ssot = home.join(".cc-switch").join("skills").join("demo");
+    fs::create_dir_all(&ssot).expect("create ssot skill dir");
+    fs::write(ssot.join("SKILL.md"), "# demo").expect("write SKILL.md");
+
+    InstalledSkill {
+        id: "local:demo".to_string(),
+        name: "demo".to_string(),
+        description: None,
+        directory: "demo".to_string(),
+        repo_owner: None,
+        repo_name: None,
+        repo_branch: None,
+        readme_url: None,
+        apps: SkillApps {
+            claude: true,
+            ..Default::default()
+        },
+        installed_at: 0,
+        commit_sha: None,
+        content_hash: None,
+        scan_findings: Vec::new(),
+        projects,
+    }
+}
+
+#[test]
+fn project_scoped_skill_syncs_only_to_project_dirs() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+    let project = home.join("workspace").join("app");
+    fs::create_dir_all(&project).expect("create project dir");
+
+    let skill = installed_skill(home, vec![project.to_string_lossy().to_string()]);
+    SkillService::sync_skill_to_app(&skill, &AppType::Claude).expect("sync skill");
+
+    assert!(project.join(".claude/skills/demo/SKILL.md").exists());
+    assert!(
+        !home.join(".claude/skills/demo").exists(),
+        "project-scoped skill must not be written to the user-level dir"
+    );
+
+    SkillService::remove_skill_from_app(&skill, &AppType::Claude).expect("remove skill");
+    assert!(!project.join(".claude/skills/demo").exists());
+}
+
+#[test]
+fn set_projects_moves_skill_between_user_and_project_dirs() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+    let project = home.join("workspace").join("app");
+    fs::create_dir_all(&project).expect("create project dir");
+    let user_copy = home.join(".claude/skills/demo");
+    let project_copy = project.join(".claude/skills/demo");
+
+    let state = create_test_state().expect("create test state");
+    let skill = installed_skill(home, Vec::new());
+    state.db.save_skill(&skill).expect("save skill");
+    SkillService::sync_skill_to_app(&skill, &AppType::Claude).expect("sync skill");
+    assert!(user_copy.exists());
+
+    SkillService::set_projects(
+        &state.db,
+        &skill.id,
+        vec![project.to_string_lossy().to_string()],
+    )
+    .expect("scope skill to project");
+    assert!(project_copy.exists());
+    assert!(!user_copy.exists());
+
+    SkillService::set_projects(&state.db, &skill.id, Vec::new()).expect("clear project scope");
+    assert!(user_copy.exists());
+    assert!(!project_copy.exists());
+}
@@ -17,7 +17,7 @@ import {
   BarChart2,
   FolderArchive,
 } from "lucide-react";
-import type { Provider, VisibleApps } from "@/types";
+import type { Provider, UsageResult, VisibleApps } from "@/types";
 import type { EnvConflict } from "@/types/env";
 import { useProvidersQuery, useSettingsQuery } from "@/lib/query";
 import {
@@ -215,6 +215,69 @@ function App() {
     };
   }, [queryClient]);
 
+  // 后端定时用量查询的结果写入对应供应商的用量缓存
+  useEffect(() => {
+    let unsubscribe: (() => void) | undefined;
+
+    const setupListener = async () => {
+      try {
+        const { listen } = await import("@tauri-apps/api/event");
+        unsubscribe = await listen<{
+          appType: string;
+          providerId: string;
+          result: UsageResult;
+        }>("usage-query-updated", (event) => {
+          const { appType, providerId, result } = event.payload;
+          queryClient.setQueryData(["usage", providerId, appType], result);
+        });
+      } catch (error) {
+        console.error(
+          "[App] Failed to subscribe usage-query-updated event",
+          error,
+        );
+      }
+    };
+
+    setupListener();
+    return () => {
+      unsubscribe?.();
+    };
+  }, [queryClient]);
+
+  // 监听本地 Skill 来源的同步结果：刷新列表，新增风险时提示用户手动更新确认
+  useEffect(() => {
+    const unsubscribers: Array<() => void> = [];
+
+    const setupListeners = async () => {
+      try {
+        const { listen } = await import("@tauri-apps/api/event");
+        unsubscribers.push(
+          await listen("skills-changed", async () => {
+            await queryClient.invalidateQueries({
+              queryKey: ["skills", "installed"],
+            });
+          }),
+        );
+        unsubscribers.push(
+          await listen<string[]>("skills-update-needs-review", (event) => {
+            toast.warning(
+              t("skills.localUpdateNeedsReview", {
+                names: event.payload.join(", "),
+              }),
+            );
+          }),
+        );
+      } catch (error) {
+        console.error("[App] Failed to subscribe skills events", error);
+      }
+    };
+
+    setupListeners();
+    return () => {
+      unsubscribers.forEach((unsubscribe) => unsubscribe());
+    };
+  }, [queryClient, t]);
+
   // 应用启动时检测所有应用的环境变量冲突
   useEffect(() => {
     const checkEnvOnStartup = async () => {
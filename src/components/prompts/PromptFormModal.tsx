@@ -85,6 +85,8 @@ const PromptFormModal: React.FC<PromptFormModalProps> = ({
         enabled: initialData?.enabled || false,
         createdAt: initialData?.createdAt || timestamp,
         updatedAt: timestamp,
+        sortIndex: initialData?.sortIndex,
+        projects: initialData?.projects,
       };
       await onSave(id, prompt);
       onClose();
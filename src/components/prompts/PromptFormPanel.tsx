@@ -77,6 +77,8 @@ const PromptFormPanel: React.FC<PromptFormPanelProps> = ({
         enabled: initialData?.enabled || false,
         createdAt: initialData?.createdAt || timestamp,
         updatedAt: timestamp,
+        sortIndex: initialData?.sortIndex,
+        projects: initialData?.projects,
       };
       await onSave(id, prompt);
       onClose();
@@ -23,7 +23,10 @@ import {
   type AppType,
 } from "@/hooks/useSkills";
 import type { DiscoverableSkill, SkillRepo } from "@/lib/api/skills";
-import { formatSkillError } from "@/lib/errors/skillErrorParser";
+import {
+  formatSkillError,
+  parseSkillError,
+} from "@/lib/errors/skillErrorParser";
 
 interface SkillsPageProps {
   initialApp?: AppType;
@@ -120,7 +123,7 @@ export const SkillsPage = forwardRef<SkillsPageHandle, SkillsPageProps>(
       openRepoManager: () => setRepoManagerOpen(true),
     }));
 
-    const handleInstall = async (directory: string) => {
+    const handleInstall = async (directory: string, acceptRisks = false) => {
       // 找到对应的 DiscoverableSkill
       const skill = discoverableSkills?.find(
         (s) =>
@@ -136,6 +139,7 @@ export const SkillsPage = forwardRef<SkillsPageHandle, SkillsPageProps>(
         await installMutation.mutateAsync({
           skill,
           currentApp,
+          acceptRisks,
         });
         toast.success(t("skills.installSuccess", { name: skill.name }), {
           closeButton: true,
@@ -148,6 +152,21 @@ export const SkillsPage = forwardRef<SkillsPageHandle, SkillsPageProps>(
           t,
           "skills.installFailed",
         );
+        // 扫描发现风险项：用户确认后带 acceptRisks 重新安装
+        if (
+          parseSkillError(errorMessage)?.code ===
+          "SKILL_SCAN_CONFIRMATION_REQUIRED"
+        ) {
+          toast.warning(title, {
+            description,
+            duration: 15000,
+            action: {
+              label: t("skills.installAnyway"),
+              onClick: () => void handleInstall(directory, true),
+            },
+          });
+          return;
+        }
         toast.error(title, {
           description,
           duration: 10000,
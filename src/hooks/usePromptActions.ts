@@ -31,18 +31,31 @@ export function usePromptActions(appId: AppId) {
     }
   }, [appId, t]);
 
+  // 含手动修改的模板片段不会被覆盖，提示用户对应文件未更新
+  const warnSkippedFiles = useCallback(
+    (files: string[]) => {
+      if (files.length === 0) return;
+      toast.warning(t("prompts.liveFilesSkipped"), {
+        description: files.join("\n"),
+        duration: 10000,
+      });
+    },
+    [t],
+  );
+
   const savePrompt = useCallback(
     async (id: string, prompt: Prompt) => {
       try {
-        await promptsApi.upsertPrompt(appId, id, prompt);
+        const skipped = await promptsApi.upsertPrompt(appId, id, prompt);
         await reload();
         toast.success(t("prompts.saveSuccess"), { closeButton: true });
+        warnSkippedFiles(skipped);
       } catch (error) {
         toast.error(t("prompts.saveFailed"));
         throw error;
       }
     },
-    [appId, reload, t],
+    [appId, reload, t, warnSkippedFiles],
   );
 
   const deletePrompt = useCallback(
@@ -62,57 +75,40 @@ export function usePromptActions(appId: AppId) {
   const enablePrompt = useCallback(
     async (id: string) => {
       try {
-        await promptsApi.enablePrompt(appId, id);
+        const skipped = await promptsApi.enablePrompt(appId, id);
         await reload();
         toast.success(t("prompts.enableSuccess"), { closeButton: true });
+        warnSkippedFiles(skipped);
       } catch (error) {
         toast.error(t("prompts.enableFailed"));
         throw error;
       }
     },
-    [appId, reload, t],
+    [appId, reload, t, warnSkippedFiles],
   );
 
   const toggleEnabled = useCallback(
     async (id: string, enabled: boolean) => {
-      // Optimistic update
+      // Optimistic update（可同时启用多个提示词，只更新当前这一项）
       const previousPrompts = prompts;
-
-      // 如果要启用当前提示词，先禁用其他所有提示词
-      if (enabled) {
-        const updatedPrompts = Object.keys(prompts).reduce(
-          (acc, key) => {
-            acc[key] = {
-              ...prompts[key],
-              enabled: key === id,
-            };
-            return acc;
-          },
-          {} as Record<string, Prompt>,
-        );
-        setPrompts(updatedPrompts);
-      } else {
-        setPrompts((prev) => ({
-          ...prev,
-          [id]: {
-            ...prev[id],
-            enabled: false,
-          },
-        }));
-      }
+      setPrompts((prev) => ({
+        ...prev,
+        [id]: {
+          ...prev[id],
+          enabled,
+        },
+      }));
 
       try {
+        let skipped: string[];
         if (enabled) {
-          await promptsApi.enablePrompt(appId, id);
+          skipped = await promptsApi.enablePrompt(appId, id);
           toast.success(t("prompts.enableSuccess"), { closeButton: true });
         } else {
-          // 禁用提示词 - 需要后端支持
-          await promptsApi.upsertPrompt(appId, id, {
-            ...prompts[id],
-            enabled: false,
-          });
+          skipped = await promptsApi.disablePrompt(appId, id);
           toast.success(t("prompts.disableSuccess"), { closeButton: true });
         }
+        warnSkippedFiles(skipped);
         await reload();
       } catch (error) {
         // Rollback on failure
@@ -123,7 +119,7 @@ export function usePromptActions(appId: AppId) {
         throw error;
       }
     },
-    [appId, prompts, reload, t],
+    [appId, prompts, reload, t, warnSkippedFiles],
   );
 
   const importFromFile = useCallback(async () => {
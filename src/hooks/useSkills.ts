@@ -36,10 +36,12 @@ export function useInstallSkill() {
     mutationFn: ({
       skill,
       currentApp,
+      acceptRisks,
     }: {
       skill: DiscoverableSkill;
       currentApp: AppType;
-    }) => skillsApi.installUnified(skill, currentApp),
+      acceptRisks?: boolean;
+    }) => skillsApi.installUnified(skill, currentApp, acceptRisks),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ["skills", "installed"] });
       queryClient.invalidateQueries({ queryKey: ["skills", "discoverable"] });
@@ -156,10 +158,12 @@ export function useInstallSkillsFromZip() {
     mutationFn: ({
       filePath,
       currentApp,
+      acceptRisks,
     }: {
       filePath: string;
       currentApp: AppType;
-    }) => skillsApi.installFromZip(filePath, currentApp),
+      acceptRisks?: boolean;
+    }) => skillsApi.installFromZip(filePath, currentApp, acceptRisks),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ["skills", "installed"] });
       queryClient.invalidateQueries({ queryKey: ["skills", "unmanaged"] });
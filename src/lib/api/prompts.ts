@@ -9,14 +9,23 @@ export interface Prompt {
   enabled: boolean;
   createdAt?: number;
   updatedAt?: number;
+  /** 片段在托管区块中的渲染顺序 */
+  sortIndex?: number;
+  /** 项目作用域（为空表示用户级） */
+  projects?: string[];
 }
 
+/** 写入类命令返回因模板片段被手动修改而未重写的提示词文件路径 */
 export const promptsApi = {
   async getPrompts(app: AppId): Promise<Record<string, Prompt>> {
     return await invoke("get_prompts", { app });
   },
 
-  async upsertPrompt(app: AppId, id: string, prompt: Prompt): Promise<void> {
+  async upsertPrompt(
+    app: AppId,
+    id: string,
+    prompt: Prompt,
+  ): Promise<string[]> {
     return await invoke("upsert_prompt", { app, id, prompt });
   },
 
@@ -24,10 +33,14 @@ export const promptsApi = {
     return await invoke("delete_prompt", { app, id });
   },
 
-  async enablePrompt(app: AppId, id: string): Promise<void> {
+  async enablePrompt(app: AppId, id: string): Promise<string[]> {
     return await invoke("enable_prompt", { app, id });
   },
 
+  async disablePrompt(app: AppId, id: string): Promise<string[]> {
+    return await invoke("disable_prompt", { app, id });
+  },
+
   async importFromFile(app: AppId): Promise<string> {
     return await invoke("import_prompt_from_file", { app });
   },
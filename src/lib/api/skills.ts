@@ -81,8 +81,13 @@ export const skillsApi = {
   async installUnified(
     skill: DiscoverableSkill,
     currentApp: AppType,
+    acceptRisks = false,
   ): Promise<InstalledSkill> {
-    return await invoke("install_skill_unified", { skill, currentApp });
+    return await invoke("install_skill_unified", {
+      skill,
+      currentApp,
+      acceptRisks,
+    });
   },
 
   /** 卸载 Skill（统一卸载） */
@@ -125,11 +130,19 @@ export const skillsApi = {
   },
 
   /** 安装技能（兼容旧 API） */
-  async install(directory: string, app: AppType = "claude"): Promise<boolean> {
+  async install(
+    directory: string,
+    app: AppType = "claude",
+    acceptRisks = false,
+  ): Promise<boolean> {
     if (app === "claude") {
-      return await invoke("install_skill", { directory });
+      return await invoke("install_skill", { directory, acceptRisks });
     }
-    return await invoke("install_skill_for_app", { app, directory });
+    return await invoke("install_skill_for_app", {
+      app,
+      directory,
+      acceptRisks,
+    });
   },
 
   /** 卸载技能（兼容旧 API） */
@@ -171,7 +184,12 @@ export const skillsApi = {
   async installFromZip(
     filePath: string,
     currentApp: AppType,
+    acceptRisks = false,
   ): Promise<InstalledSkill[]> {
-    return await invoke("install_skills_from_zip", { filePath, currentApp });
+    return await invoke("install_skills_from_zip", {
+      filePath,
+      currentApp,
+      acceptRisks,
+    });
   },
 };
@@ -112,15 +112,12 @@ export const useUsageQuery = (
       ? autoQueryInterval * 60 * 1000 // 与刷新间隔保持一致
       : 5 * 60 * 1000; // 默认 5 分钟
 
+  // 定时查询由后端调度器执行（结果经 usage-query-updated 事件写入缓存），
+  // 这里不再轮询，避免重复查询和重复写入历史
   const query = useQuery<UsageResult>({
     queryKey: ["usage", providerId, appId],
     queryFn: async () => usageApi.query(providerId, appId),
     enabled: enabled && !!providerId,
-    refetchInterval:
-      autoQueryInterval > 0
-        ? Math.max(autoQueryInterval, 1) * 60 * 1000 // 最小1分钟
-        : false,
-    refetchIntervalInBackground: true, // 后台也继续定时查询
     refetchOnWindowFocus: false,
     retry: false,
     staleTime, // 使用动态计算的缓存时间